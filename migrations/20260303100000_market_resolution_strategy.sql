@@ -0,0 +1,2 @@
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS resolution JSONB NOT NULL DEFAULT '{"kind": "SPREAD"}';
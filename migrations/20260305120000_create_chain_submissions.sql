@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS chain_submissions (
+  id UUID PRIMARY KEY,
+  outbox_id UUID NOT NULL REFERENCES outbox(id) ON DELETE CASCADE,
+  market_id UUID NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+  tx_hash TEXT NOT NULL UNIQUE,
+  block_number BIGINT,
+  gas_used BIGINT,
+  submitter TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_chain_submissions_market
+  ON chain_submissions (market_id, created_at);
@@ -0,0 +1,14 @@
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS category TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_markets_category
+  ON markets (category);
+
+CREATE TABLE IF NOT EXISTS market_tags (
+  market_id UUID NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+  tag TEXT NOT NULL,
+  PRIMARY KEY (market_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_tags_tag
+  ON market_tags (tag);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS metrics_history (
+  id BIGSERIAL PRIMARY KEY,
+  captured_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  reports_24h BIGINT NOT NULL,
+  settlements_24h BIGINT NOT NULL,
+  batches_24h BIGINT NOT NULL,
+  avg_batch_size DOUBLE PRECISION,
+  db_size_bytes BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_metrics_history_captured
+  ON metrics_history (captured_at);
@@ -0,0 +1,5 @@
+ALTER TABLE outbox
+  ADD COLUMN IF NOT EXISTS intent_tx_hash TEXT,
+  ADD COLUMN IF NOT EXISTS intent_raw_tx TEXT,
+  ADD COLUMN IF NOT EXISTS intent_submitter TEXT,
+  ADD COLUMN IF NOT EXISTS intent_at TIMESTAMPTZ;
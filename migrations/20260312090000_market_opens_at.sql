@@ -0,0 +1,5 @@
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS opens_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_markets_status_opens
+  ON markets (status, opens_at);
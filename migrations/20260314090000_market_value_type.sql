@@ -0,0 +1,2 @@
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS value_type TEXT NOT NULL DEFAULT 'NUMBER';
@@ -0,0 +1,5 @@
+ALTER TABLE reports
+  ADD COLUMN IF NOT EXISTS self_reported BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS self_report_policy JSONB NOT NULL DEFAULT '{"mode": "INCLUDE"}';
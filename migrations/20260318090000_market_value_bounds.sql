@@ -0,0 +1,3 @@
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS min_value DOUBLE PRECISION,
+  ADD COLUMN IF NOT EXISTS max_value DOUBLE PRECISION;
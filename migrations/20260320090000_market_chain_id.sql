@@ -0,0 +1,3 @@
+-- NULL means "whatever the default chain is when the settlement is queued"
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS chain_id BIGINT;
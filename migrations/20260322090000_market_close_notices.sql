@@ -0,0 +1,11 @@
+-- lead times (seconds before closes_at) for "closing soon" notices; NULL uses
+-- the server default
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS close_notice_secs INTEGER[];
+
+CREATE TABLE IF NOT EXISTS market_close_notices (
+  market_id UUID NOT NULL REFERENCES markets(id),
+  lead_secs INTEGER NOT NULL,
+  sent_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  PRIMARY KEY (market_id, lead_secs)
+);
@@ -0,0 +1,6 @@
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS idempotency_key TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS markets_idempotency_key_idx
+  ON markets (idempotency_key)
+  WHERE idempotency_key IS NOT NULL;
@@ -0,0 +1,3 @@
+-- where a reported value came from: {source_url, http_status, response_sha256, fetched_at}
+ALTER TABLE reports
+  ADD COLUMN IF NOT EXISTS provenance JSONB;
@@ -0,0 +1,4 @@
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS close_conditions JSONB NOT NULL DEFAULT '[]',
+  -- the condition that closed the market early; NULL when it closed on time
+  ADD COLUMN IF NOT EXISTS close_trigger JSONB;
@@ -0,0 +1,14 @@
+-- markets that must settle together in one transaction
+CREATE TABLE IF NOT EXISTS market_groups (
+  id UUID PRIMARY KEY,
+  invariant JSONB NOT NULL,
+  -- ACTIVE -> SETTLED, or BLOCKED when the invariant fails and needs review
+  status TEXT NOT NULL DEFAULT 'ACTIVE',
+  blocked_reason TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS group_id UUID REFERENCES market_groups(id);
+
+CREATE INDEX IF NOT EXISTS markets_group_id_idx ON markets (group_id);
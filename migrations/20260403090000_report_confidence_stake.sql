@@ -0,0 +1,3 @@
+ALTER TABLE reports
+  ADD COLUMN IF NOT EXISTS confidence DOUBLE PRECISION,
+  ADD COLUMN IF NOT EXISTS stake DOUBLE PRECISION;
@@ -0,0 +1,5 @@
+-- a worker owns a job from claim until it writes the outcome; claims older
+-- than the worker's TTL are treated as abandoned and can be taken over
+ALTER TABLE outbox
+  ADD COLUMN IF NOT EXISTS claimed_by TEXT,
+  ADD COLUMN IF NOT EXISTS claimed_at TIMESTAMPTZ;
@@ -0,0 +1,7 @@
+-- failed attempts push this out with exponential backoff; the worker skips
+-- jobs until it has passed
+ALTER TABLE outbox
+  ADD COLUMN IF NOT EXISTS next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE INDEX IF NOT EXISTS idx_outbox_status_next_attempt
+  ON outbox (status, next_attempt_at);
@@ -0,0 +1,15 @@
+-- external sources polled by the feeds loop; each fetch lands in reports as
+-- a self-reported row
+CREATE TABLE IF NOT EXISTS market_feeds (
+  id UUID PRIMARY KEY,
+  market_id UUID NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+  source JSONB NOT NULL,
+  interval_secs INT NOT NULL,
+  last_fetched_at TIMESTAMPTZ,
+  last_value DOUBLE PRECISION,
+  last_error TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_feeds_market
+  ON market_feeds (market_id);
@@ -0,0 +1,11 @@
+-- exact outcome as a fixed-point integer (8 decimals); `outcome` is kept as
+-- its float rendering for existing readers
+ALTER TABLE settlements
+  ADD COLUMN IF NOT EXISTS outcome_e8 BIGINT;
+
+UPDATE settlements
+SET outcome_e8 = round(outcome * 100000000)::BIGINT
+WHERE outcome_e8 IS NULL;
+
+ALTER TABLE settlements
+  ALTER COLUMN outcome_e8 SET NOT NULL;
@@ -0,0 +1,13 @@
+-- a CLOSED market still unresolved at this point becomes UNRESOLVED
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS resolve_deadline TIMESTAMPTZ;
+
+UPDATE markets
+SET resolve_deadline = closes_at + INTERVAL '24 hours'
+WHERE resolve_deadline IS NULL;
+
+ALTER TABLE markets
+  ALTER COLUMN resolve_deadline SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_markets_status_resolve_deadline
+  ON markets (status, resolve_deadline);
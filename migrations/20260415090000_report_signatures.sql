@@ -0,0 +1,7 @@
+-- optional EIP-191 signature by the reporter's wallet; `verified` is set when
+-- it recovered to reporter_address at submission
+ALTER TABLE reports
+  ADD COLUMN IF NOT EXISTS reporter_address TEXT,
+  ADD COLUMN IF NOT EXISTS signature TEXT,
+  ADD COLUMN IF NOT EXISTS signed_at TIMESTAMPTZ,
+  ADD COLUMN IF NOT EXISTS verified BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,5 @@
+-- SENT jobs move to CONFIRMED once the reconciler has seen the settlement
+-- on-chain with enough blocks on top of it
+ALTER TABLE outbox
+  ADD COLUMN IF NOT EXISTS confirmed_at TIMESTAMPTZ,
+  ADD COLUMN IF NOT EXISTS confirmed_block BIGINT;
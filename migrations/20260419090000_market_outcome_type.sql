@@ -0,0 +1,3 @@
+-- BINARY/CATEGORICAL markets settle to an option index by majority vote
+ALTER TABLE markets
+  ADD COLUMN IF NOT EXISTS outcome_type JSONB NOT NULL DEFAULT '{"kind": "NUMERIC"}';
@@ -0,0 +1,8 @@
+-- per-market preconditions on top of the resolution strategy: every listed
+-- source must have reported, and at least min_reports reports must count
+CREATE TABLE IF NOT EXISTS market_requirements (
+  market_id UUID PRIMARY KEY REFERENCES markets(id) ON DELETE CASCADE,
+  required_sources TEXT[] NOT NULL DEFAULT '{}',
+  min_reports INT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,28 @@
+-- cold storage for old resolved markets and their reports. Rows are moved
+-- by column name, so a column added to markets/reports should be added here
+-- too or it is dropped on archival.
+CREATE TABLE IF NOT EXISTS markets_archive (
+  LIKE markets INCLUDING DEFAULTS,
+  tags TEXT[] NOT NULL DEFAULT '{}',
+  required_sources TEXT[],
+  min_reports INT,
+  -- set when archived through DELETE /markets/:id rather than by age
+  deleted_at TIMESTAMPTZ,
+  archived_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  PRIMARY KEY (id)
+);
+
+CREATE TABLE IF NOT EXISTS reports_archive (
+  LIKE reports INCLUDING DEFAULTS,
+  archived_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS reports_archive_market_id_idx ON reports_archive (market_id);
+
+-- settlement records outlive the market row so proofs and verification keep
+-- working after archival
+ALTER TABLE settlements DROP CONSTRAINT IF EXISTS settlements_market_id_fkey;
+ALTER TABLE batch_items DROP CONSTRAINT IF EXISTS batch_items_market_id_fkey;
+ALTER TABLE outbox DROP CONSTRAINT IF EXISTS outbox_market_id_fkey;
+ALTER TABLE chain_submissions DROP CONSTRAINT IF EXISTS chain_submissions_market_id_fkey;
@@ -0,0 +1,11 @@
+-- API accounts exchanged for JWTs at POST /auth/token. Roles nest:
+-- admin > reporter > reader.
+CREATE TABLE IF NOT EXISTS users (
+  id UUID PRIMARY KEY,
+  username TEXT NOT NULL UNIQUE,
+  -- argon2 PHC string
+  password_hash TEXT NOT NULL,
+  role TEXT NOT NULL CHECK (role IN ('admin', 'reporter', 'reader')),
+  disabled BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,3 @@
+-- per-market SPREAD tolerance in basis points; NULL uses the server default
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS consensus_bps INT;
+ALTER TABLE markets_archive ADD COLUMN IF NOT EXISTS consensus_bps INT;
@@ -0,0 +1,12 @@
+-- reports the resolver left out of a settlement, e.g. OUTLIER. No FK so the
+-- flags survive reports moving to reports_archive.
+CREATE TABLE IF NOT EXISTS report_flags (
+  report_id UUID NOT NULL,
+  market_id UUID NOT NULL,
+  flag TEXT NOT NULL,
+  details JSONB NOT NULL DEFAULT '{}',
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  PRIMARY KEY (report_id, flag)
+);
+
+CREATE INDEX IF NOT EXISTS report_flags_market_id_idx ON report_flags (market_id);
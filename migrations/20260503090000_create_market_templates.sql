@@ -0,0 +1,24 @@
+-- recurring markets: one market per slot at starts_at + k * cadence_secs,
+-- open for duration_secs; settings holds the MarketSettings every instance
+-- is created with
+CREATE TABLE IF NOT EXISTS market_templates (
+  id UUID PRIMARY KEY,
+  name TEXT NOT NULL,
+  question_pattern TEXT NOT NULL,
+  starts_at TIMESTAMPTZ NOT NULL,
+  cadence_secs INT NOT NULL CHECK (cadence_secs > 0),
+  duration_secs INT NOT NULL CHECK (duration_secs > 0),
+  -- resolve_deadline offset from closes_at; NULL uses RESOLVE_WINDOW_SECS
+  resolve_window_secs INT,
+  -- how long before its slot an instance is created (as SCHEDULED)
+  lead_secs INT NOT NULL DEFAULT 0,
+  settings JSONB NOT NULL,
+  -- the next slot to instantiate
+  next_run_at TIMESTAMPTZ NOT NULL,
+  active BOOLEAN NOT NULL DEFAULT true,
+  last_market_id UUID,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS market_templates_due_idx
+  ON market_templates (next_run_at) WHERE active;
@@ -0,0 +1,8 @@
+-- the exact leaf each settlement went into its batch with, so proofs don't
+-- depend on the current leaf encoding. Rows from before this are filled in
+-- by the batcher on startup.
+ALTER TABLE batch_items ADD COLUMN IF NOT EXISTS leaf_index INT;
+ALTER TABLE batch_items ADD COLUMN IF NOT EXISTS leaf_hash TEXT;
+ALTER TABLE batch_items ADD COLUMN IF NOT EXISTS leaf_data TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS batch_items_batch_leaf_idx ON batch_items (batch_id, leaf_index);
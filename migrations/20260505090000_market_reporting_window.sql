@@ -0,0 +1,3 @@
+-- reports are only accepted from here until closes_at; NULL means from opens_at
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS reporting_opens_at TIMESTAMPTZ;
+ALTER TABLE markets_archive ADD COLUMN IF NOT EXISTS reporting_opens_at TIMESTAMPTZ;
@@ -0,0 +1,7 @@
+-- reports accepted after closes_at, during the close grace period
+ALTER TABLE reports ADD COLUMN IF NOT EXISTS late BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE reports_archive ADD COLUMN IF NOT EXISTS late BOOLEAN NOT NULL DEFAULT false;
+
+-- whether late reports count towards the outcome
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS late_report_policy JSONB NOT NULL DEFAULT '{"mode":"INCLUDE"}';
+ALTER TABLE markets_archive ADD COLUMN IF NOT EXISTS late_report_policy JSONB NOT NULL DEFAULT '{"mode":"INCLUDE"}';
@@ -0,0 +1,16 @@
+-- settlements an operator override replaced. The live settlement stays in
+-- settlements; no FK so history survives archival like settlements do.
+ALTER TABLE settlements ADD COLUMN IF NOT EXISTS revision INT NOT NULL DEFAULT 1;
+
+CREATE TABLE IF NOT EXISTS settlement_revisions (
+  id UUID PRIMARY KEY,
+  market_id UUID NOT NULL,
+  revision INT NOT NULL,
+  outcome DOUBLE PRECISION NOT NULL,
+  outcome_e8 BIGINT NOT NULL,
+  decided_at TIMESTAMPTZ NOT NULL,
+  superseded_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  superseded_by TEXT NOT NULL,
+  reason TEXT,
+  UNIQUE (market_id, revision)
+);
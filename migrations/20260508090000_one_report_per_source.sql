@@ -0,0 +1,26 @@
+-- each source gets one report per market, updated in place via PUT.
+-- Older duplicates from before the rule move to reports_archive so the
+-- settlement hashes that listed them are unaffected.
+ALTER TABLE reports ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ;
+ALTER TABLE reports_archive ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ;
+
+WITH ranked AS (
+  SELECT id, ROW_NUMBER() OVER (
+           PARTITION BY market_id, source ORDER BY created_at DESC, id DESC
+         ) AS n
+  FROM reports
+),
+moved AS (
+  DELETE FROM reports r
+  USING ranked
+  WHERE ranked.id = r.id AND ranked.n > 1
+  RETURNING r.*
+)
+INSERT INTO reports_archive
+SELECT (jsonb_populate_record(
+          NULL::reports_archive,
+          to_jsonb(moved) || jsonb_build_object('archived_at', now())
+       )).*
+FROM moved;
+
+CREATE UNIQUE INDEX IF NOT EXISTS reports_market_source_key ON reports (market_id, source);
@@ -0,0 +1,12 @@
+-- settlements headed for a chain start PROPOSED and become FINAL once their
+-- transaction has enough confirmations; the market waits in PROPOSED until
+-- then. Everything settled before this was final when decided.
+ALTER TABLE settlements ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'FINAL';
+ALTER TABLE settlements ADD COLUMN IF NOT EXISTS finalized_at TIMESTAMPTZ;
+ALTER TABLE settlement_revisions ADD COLUMN IF NOT EXISTS finalized_at TIMESTAMPTZ;
+
+UPDATE settlements SET finalized_at = decided_at WHERE status = 'FINAL' AND finalized_at IS NULL;
+UPDATE settlement_revisions SET finalized_at = decided_at WHERE finalized_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_settlements_proposed
+  ON settlements (decided_at) WHERE status = 'PROPOSED';
@@ -0,0 +1,5 @@
+-- the hash behind each settlement's hash and leaf, and each batch's tree:
+-- sha256 (everything before this) or keccak256
+ALTER TABLE settlements ADD COLUMN IF NOT EXISTS hash_algorithm TEXT NOT NULL DEFAULT 'sha256';
+ALTER TABLE settlement_revisions ADD COLUMN IF NOT EXISTS hash_algorithm TEXT NOT NULL DEFAULT 'sha256';
+ALTER TABLE batches ADD COLUMN IF NOT EXISTS hash_algorithm TEXT NOT NULL DEFAULT 'sha256';
@@ -0,0 +1,17 @@
+ALTER TABLE events ADD COLUMN IF NOT EXISTS dispatched_at TIMESTAMPTZ;
+
+-- everything already here was written by the old recorder, which fanned it out on insert
+UPDATE events SET dispatched_at = created_at WHERE dispatched_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS events_undispatched_idx ON events (id) WHERE dispatched_at IS NULL;
+
+CREATE OR REPLACE FUNCTION events_notify() RETURNS trigger AS $$
+BEGIN
+  PERFORM pg_notify('oraclesettle_events', '');
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS events_notify ON events;
+CREATE TRIGGER events_notify AFTER INSERT ON events
+  FOR EACH STATEMENT EXECUTE FUNCTION events_notify();
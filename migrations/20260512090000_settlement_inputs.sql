@@ -0,0 +1,3 @@
+-- what the resolver decided from; NULL for operator outcomes and settlements predating it
+ALTER TABLE settlements ADD COLUMN IF NOT EXISTS inputs JSONB;
+ALTER TABLE settlement_revisions ADD COLUMN IF NOT EXISTS inputs JSONB;
@@ -0,0 +1,8 @@
+-- PENDING until a member's transaction is sent, SUBMITTED until all are
+-- confirmed (CONFIRMED), or FAILED once one can't be anchored
+ALTER TABLE batches ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'PENDING';
+ALTER TABLE batches ADD COLUMN IF NOT EXISTS status_changed_at TIMESTAMPTZ;
+-- a voided batch no longer claims its settlements, which go into a new one
+ALTER TABLE batches ADD COLUMN IF NOT EXISTS voided_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS batches_open_idx ON batches (created_at) WHERE status IN ('PENDING', 'SUBMITTED');
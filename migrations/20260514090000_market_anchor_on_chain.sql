@@ -0,0 +1,3 @@
+-- false keeps the settlement off chain: no outbox job and never batched
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS anchor_on_chain BOOLEAN NOT NULL DEFAULT true;
+ALTER TABLE markets_archive ADD COLUMN IF NOT EXISTS anchor_on_chain BOOLEAN NOT NULL DEFAULT true;
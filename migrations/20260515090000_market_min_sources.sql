@@ -0,0 +1,3 @@
+-- quorum of distinct sources; feed sources of one provider count once
+ALTER TABLE market_requirements ADD COLUMN IF NOT EXISTS min_sources INT;
+ALTER TABLE markets_archive ADD COLUMN IF NOT EXISTS min_sources INT;
@@ -0,0 +1,32 @@
+-- every value a report has held, for time-weighted resolution. Filled by
+-- trigger so feed upserts and PUTs alike are covered; rows outlive the
+-- report's move to reports_archive.
+CREATE TABLE IF NOT EXISTS report_values (
+  id BIGSERIAL PRIMARY KEY,
+  report_id UUID NOT NULL,
+  market_id UUID NOT NULL,
+  value DOUBLE PRECISION NOT NULL,
+  recorded_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS report_values_market_idx ON report_values (market_id, report_id, recorded_at);
+
+-- only the current value of existing reports is known
+INSERT INTO report_values (report_id, market_id, value, recorded_at)
+SELECT r.id, r.market_id, r.value, COALESCE(r.updated_at, r.created_at)
+FROM reports r
+WHERE NOT EXISTS (SELECT 1 FROM report_values v WHERE v.report_id = r.id);
+
+CREATE OR REPLACE FUNCTION reports_record_value() RETURNS trigger AS $$
+BEGIN
+  IF TG_OP = 'INSERT' OR NEW.value IS DISTINCT FROM OLD.value THEN
+    INSERT INTO report_values (report_id, market_id, value, recorded_at)
+    VALUES (NEW.id, NEW.market_id, NEW.value, COALESCE(NEW.updated_at, NEW.created_at));
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS reports_record_value ON reports;
+CREATE TRIGGER reports_record_value AFTER INSERT OR UPDATE OF value ON reports
+  FOR EACH ROW EXECUTE FUNCTION reports_record_value();
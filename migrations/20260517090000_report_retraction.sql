@@ -0,0 +1,16 @@
+-- a reporter may retract its own report while the market is open; the row
+-- stays for audit but no longer counts, and the source may report again
+ALTER TABLE reports ADD COLUMN IF NOT EXISTS submitted_by TEXT;
+ALTER TABLE reports ADD COLUMN IF NOT EXISTS retracted_at TIMESTAMPTZ;
+ALTER TABLE reports_archive ADD COLUMN IF NOT EXISTS submitted_by TEXT;
+ALTER TABLE reports_archive ADD COLUMN IF NOT EXISTS retracted_at TIMESTAMPTZ;
+
+UPDATE reports r
+SET submitted_by = a.actor
+FROM audit_log a
+WHERE a.entity_type = 'report' AND a.entity_id = r.id AND a.action = 'accepted'
+  AND r.submitted_by IS NULL;
+
+DROP INDEX IF EXISTS reports_market_source_key;
+CREATE UNIQUE INDEX IF NOT EXISTS reports_market_source_key ON reports (market_id, source)
+  WHERE retracted_at IS NULL;
@@ -0,0 +1,9 @@
+-- admin boost: CLOSED markets resolve highest priority first, then oldest
+-- closes_at first
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE markets_archive ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0;
+
+-- the resolver's queue in the order it pages through it
+CREATE INDEX IF NOT EXISTS markets_resolution_queue_idx
+    ON markets (priority DESC, closes_at, id)
+    WHERE status = 'CLOSED' AND group_id IS NULL;
@@ -0,0 +1,15 @@
+-- the worker claims the highest priority first, then the oldest; jobs
+-- inherit their market's priority when queued
+ALTER TABLE outbox ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0;
+
+-- the chain the payload is pinned to, so each chain's queue is claimed on its
+-- own; NULL on payloads from before multi-chain support (the default chain)
+ALTER TABLE outbox ADD COLUMN IF NOT EXISTS chain_id BIGINT;
+
+UPDATE outbox
+SET chain_id = (payload->>'chain_id')::BIGINT
+WHERE chain_id IS NULL AND payload->>'chain_id' IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_outbox_chain_queue
+  ON outbox (chain_id, priority DESC, created_at)
+  WHERE status IN ('PENDING', 'INTENT');
@@ -0,0 +1,9 @@
+-- places a market's outcome is rounded to and anchored at; NULL uses its
+-- value type's (PRICE_USD 2, TEMPERATURE_C 1, ...)
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS decimals SMALLINT;
+ALTER TABLE markets_archive ADD COLUMN IF NOT EXISTS decimals SMALLINT;
+
+-- the precision a settlement was decided at; NULL on settlements from before
+-- it was recorded, which were anchored at their value type's
+ALTER TABLE settlements ADD COLUMN IF NOT EXISTS decimals SMALLINT;
+ALTER TABLE settlement_revisions ADD COLUMN IF NOT EXISTS decimals SMALLINT;
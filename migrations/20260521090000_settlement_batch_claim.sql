@@ -0,0 +1,15 @@
+-- the non-voided batch covering the market's settlement, claimed by the
+-- batcher in the transaction that cuts the batch and released when it is
+-- voided; NULL while unbatched. A revision replacing a batched settlement
+-- stays covered by the batch holding its predecessor.
+ALTER TABLE settlements ADD COLUMN IF NOT EXISTS batch_id UUID;
+
+UPDATE settlements s
+SET batch_id = bi.batch_id
+FROM batch_items bi
+JOIN batches b ON b.id = bi.batch_id
+WHERE bi.market_id = s.market_id AND b.voided_at IS NULL AND s.batch_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_settlements_unbatched
+  ON settlements (decided_at, market_id)
+  WHERE batch_id IS NULL;
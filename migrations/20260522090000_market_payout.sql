@@ -0,0 +1,4 @@
+-- formula splitting the settled outcome into per-side payout fractions;
+-- NULL when the market doesn't define one
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS payout JSONB;
+ALTER TABLE markets_archive ADD COLUMN IF NOT EXISTS payout JSONB;
@@ -0,0 +1,3 @@
+-- GET /settlements pages through every settlement in this order
+CREATE INDEX IF NOT EXISTS idx_settlements_decided
+  ON settlements (decided_at, market_id);
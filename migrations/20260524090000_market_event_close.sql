@@ -0,0 +1,11 @@
+-- markets that close on an event rather than a timestamp have no closes_at
+-- until POST /markets/{id}/close sets it, and no resolve_deadline unless
+-- one was given; closing fills it in from RESOLVE_WINDOW_SECS
+ALTER TABLE markets ALTER COLUMN closes_at DROP NOT NULL;
+ALTER TABLE markets ALTER COLUMN resolve_deadline DROP NOT NULL;
+ALTER TABLE markets_archive ALTER COLUMN closes_at DROP NOT NULL;
+ALTER TABLE markets_archive ALTER COLUMN resolve_deadline DROP NOT NULL;
+
+-- why an operator closed the market through POST /markets/{id}/close
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS closed_reason TEXT;
+ALTER TABLE markets_archive ADD COLUMN IF NOT EXISTS closed_reason TEXT;
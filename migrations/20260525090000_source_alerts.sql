@@ -0,0 +1,22 @@
+-- suspicious reporting patterns found by the anomaly pass, one row per
+-- (source, kind, related source). A pattern that stops showing up is
+-- cleared rather than deleted; if it comes back it is raised again.
+CREATE TABLE IF NOT EXISTS source_alerts (
+  id UUID PRIMARY KEY,
+  source TEXT NOT NULL,
+  -- ALWAYS_LAST, COPYING or BIASED
+  kind TEXT NOT NULL,
+  -- the source being copied, for COPYING; '' otherwise
+  related_source TEXT NOT NULL DEFAULT '',
+  -- markets the finding is based on
+  markets INT NOT NULL,
+  details JSONB NOT NULL DEFAULT '{}'::jsonb,
+  detected_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  last_detected_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  cleared_at TIMESTAMPTZ,
+
+  UNIQUE (source, kind, related_source)
+);
+
+CREATE INDEX IF NOT EXISTS idx_source_alerts_source
+  ON source_alerts (source, detected_at DESC);
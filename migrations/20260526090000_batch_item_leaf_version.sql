@@ -0,0 +1,5 @@
+-- the encoding each stored leaf was built with: 1 for the legacy string
+-- leaves of batches cut before leaves were stored, 2 for `encode_leaf`.
+-- Leaves recorded so far were all built by the current batcher.
+ALTER TABLE batch_items ADD COLUMN IF NOT EXISTS leaf_version SMALLINT;
+UPDATE batch_items SET leaf_version = 2 WHERE leaf_hash IS NOT NULL AND leaf_version IS NULL;
@@ -0,0 +1,10 @@
+-- the sources each reporter account may report for. A source belongs to one
+-- account, so nobody else can submit (or squat) its one report per market.
+-- Admins may report for any source.
+CREATE TABLE IF NOT EXISTS user_sources (
+  username TEXT NOT NULL REFERENCES users (username) ON DELETE CASCADE,
+  source TEXT NOT NULL,
+  PRIMARY KEY (username, source)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS user_sources_source_idx ON user_sources (source);
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::config::AnomalyConfig;
+use crate::events::{self, Event};
+use crate::state::AppState;
+
+pub async fn anomaly_loop(state: AppState) {
+    let interval = state.config.anomaly.interval();
+
+    loop {
+        state.leader.wait().await;
+
+        let run = state.loops.start("anomaly", interval);
+
+        match detect_anomalies(&state).await {
+            Ok(n) => run.finish(n),
+            Err(e) => {
+                tracing::error!("anomaly pass failed: {}", e);
+                run.fail(e);
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// A settled market's live reports, in the order they were first made.
+struct SettledMarket {
+    outcome: f64,
+    numeric: bool,
+    reports: Vec<(String, f64)>,
+}
+
+struct Finding {
+    source: String,
+    kind: &'static str,
+    // '' unless the pattern involves a second source
+    related_source: String,
+    markets: usize,
+    details: Value,
+}
+
+/// Looks over the reports of markets settled within `window_secs` for
+/// sources that keep reporting last, repeat another source's values, or
+/// lean the same way off the outcome. Findings are upserted into
+/// `source_alerts`; active alerts no longer found are cleared. Returns the
+/// number of active alerts.
+async fn detect_anomalies(state: &AppState) -> Result<usize, sqlx::Error> {
+    let config = &state.config.anomaly;
+
+    // archived markets keep their settlement but move their reports
+    let rows = sqlx::query!(
+        r#"
+        SELECT r.market_id AS "market_id!", r.source AS "source!", r.value AS "value!",
+               s.outcome,
+               COALESCE(m.outcome_type, a.outcome_type, '{}'::jsonb)->>'kind' = 'NUMERIC' AS "numeric!"
+        FROM settlements s
+        JOIN (
+            SELECT id, market_id, source, value, created_at FROM reports WHERE retracted_at IS NULL
+            UNION ALL
+            SELECT id, market_id, source, value, created_at FROM reports_archive WHERE retracted_at IS NULL
+        ) r ON r.market_id = s.market_id
+        LEFT JOIN markets m ON m.id = s.market_id
+        LEFT JOIN markets_archive a ON a.id = s.market_id
+        WHERE s.status = 'FINAL'
+          AND s.decided_at >= now() - make_interval(secs => $1)
+        ORDER BY r.market_id, r.created_at, r.id
+        "#,
+        config.window_secs as f64
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let mut markets: Vec<SettledMarket> = Vec::new();
+    let mut current = None;
+
+    for row in rows {
+        if current != Some(row.market_id) {
+            current = Some(row.market_id);
+            markets.push(SettledMarket {
+                outcome: row.outcome,
+                numeric: row.numeric,
+                reports: Vec::new(),
+            });
+        }
+
+        let market = markets.last_mut().expect("pushed above");
+        // duplicates archived from before one report per source: the first counts
+        if !market
+            .reports
+            .iter()
+            .any(|(source, _)| *source == row.source)
+        {
+            market.reports.push((row.source, row.value));
+        }
+    }
+
+    let mut findings = always_last(&markets, config);
+    findings.extend(copying(&markets, config));
+    findings.extend(biased(&markets, config));
+
+    let mut tx = state.db.begin().await?;
+    let mut active = Vec::with_capacity(findings.len());
+
+    for finding in &findings {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO source_alerts (id, source, kind, related_source, markets, details)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (source, kind, related_source) DO UPDATE
+            SET markets = EXCLUDED.markets,
+                details = EXCLUDED.details,
+                detected_at = CASE
+                    WHEN source_alerts.cleared_at IS NULL THEN source_alerts.detected_at
+                    ELSE now()
+                END,
+                last_detected_at = now(),
+                cleared_at = NULL
+            RETURNING id, detected_at = last_detected_at AS "raised!"
+            "#,
+            Uuid::new_v4(),
+            finding.source,
+            finding.kind,
+            finding.related_source,
+            finding.markets as i32,
+            finding.details
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        active.push(row.id);
+
+        if row.raised {
+            tracing::warn!(
+                "Source {} flagged {}{}",
+                finding.source,
+                finding.kind,
+                if finding.related_source.is_empty() {
+                    String::new()
+                } else {
+                    format!(" of {}", finding.related_source)
+                }
+            );
+
+            let event = Event::SourceAlertRaised {
+                source: finding.source.clone(),
+                alert: finding.kind.to_string(),
+                related_source: Some(finding.related_source.clone()).filter(|s| !s.is_empty()),
+            };
+            events::append(&mut *tx, &event).await?;
+        }
+    }
+
+    sqlx::query!(
+        "UPDATE source_alerts SET cleared_at = now() WHERE cleared_at IS NULL AND NOT (id = ANY($1))",
+        &active
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(active.len())
+}
+
+/// Sources whose report came in last on most markets they shared with
+/// another source.
+fn always_last(markets: &[SettledMarket], config: &AnomalyConfig) -> Vec<Finding> {
+    // source -> (markets shared with another source, times last)
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+
+    for market in markets.iter().filter(|m| m.reports.len() >= 2) {
+        let last = market.reports.len() - 1;
+        for (i, (source, _)) in market.reports.iter().enumerate() {
+            let entry = counts.entry(source).or_default();
+            entry.0 += 1;
+            if i == last {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    counts
+        .into_iter()
+        .filter(|(_, (shared, last))| {
+            *shared >= config.min_markets && share(*last, *shared) >= config.min_share
+        })
+        .map(|(source, (shared, last))| Finding {
+            source: source.to_string(),
+            kind: "ALWAYS_LAST",
+            related_source: String::new(),
+            markets: shared,
+            details: json!({ "last": last, "share": share(last, shared) }),
+        })
+        .collect()
+}
+
+/// Sources that, on markets where reports disagreed, keep reporting exactly
+/// the value another source reported before them.
+fn copying(markets: &[SettledMarket], config: &AnomalyConfig) -> Vec<Finding> {
+    // (later source, earlier source) -> (markets both reported, times matched)
+    let mut counts: HashMap<(&str, &str), (usize, usize)> = HashMap::new();
+
+    // where every report agrees, matching values say nothing
+    let disputed = markets
+        .iter()
+        .filter(|m| m.numeric && m.reports.iter().any(|(_, v)| *v != m.reports[0].1));
+
+    for market in disputed {
+        for (j, (later, value)) in market.reports.iter().enumerate() {
+            for (earlier, earlier_value) in &market.reports[..j] {
+                let entry = counts.entry((later, earlier)).or_default();
+                entry.0 += 1;
+                if value == earlier_value {
+                    entry.1 += 1;
+                }
+            }
+        }
+    }
+
+    counts
+        .into_iter()
+        .filter(|(_, (shared, matched))| {
+            *matched >= config.min_markets && share(*matched, *shared) >= config.min_share
+        })
+        .map(|((later, earlier), (shared, matched))| Finding {
+            source: later.to_string(),
+            kind: "COPYING",
+            related_source: earlier.to_string(),
+            markets: shared,
+            details: json!({ "matched": matched, "share": share(matched, shared) }),
+        })
+        .collect()
+}
+
+/// Sources whose values sit off the outcome in the same direction on most
+/// NUMERIC markets, by more than `bias_threshold` on average.
+fn biased(markets: &[SettledMarket], config: &AnomalyConfig) -> Vec<Finding> {
+    // source -> (markets, sum of relative deviations, above, below)
+    let mut counts: HashMap<&str, (usize, f64, usize, usize)> = HashMap::new();
+
+    for market in markets.iter().filter(|m| m.numeric && m.outcome != 0.0) {
+        for (source, value) in &market.reports {
+            let deviation = (value - market.outcome) / market.outcome.abs();
+            let entry = counts.entry(source).or_default();
+            entry.0 += 1;
+            entry.1 += deviation;
+            if deviation > 0.0 {
+                entry.2 += 1;
+            } else if deviation < 0.0 {
+                entry.3 += 1;
+            }
+        }
+    }
+
+    counts
+        .into_iter()
+        .filter_map(|(source, (n, sum, above, below))| {
+            let mean = sum / n as f64;
+            let same_side = if mean > 0.0 { above } else { below };
+
+            (n >= config.min_markets
+                && mean.abs() >= config.bias_threshold
+                && share(same_side, n) >= config.min_share)
+                .then(|| Finding {
+                    source: source.to_string(),
+                    kind: "BIASED",
+                    related_source: String::new(),
+                    markets: n,
+                    details: json!({
+                        "mean_deviation": mean,
+                        "direction": if mean > 0.0 { "HIGH" } else { "LOW" },
+                        "share": share(same_side, n),
+                    }),
+                })
+        })
+        .collect()
+}
+
+fn share(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::state::AppState;
+
+/// Runs on the leader replica only, so two replicas never archive the same
+/// markets.
+pub async fn archive_loop(state: AppState) {
+    let interval = state.config.archiver.interval();
+
+    loop {
+        state.leader.wait().await;
+
+        let run = state.loops.start("archiver", interval);
+
+        match archive_resolved(&state).await {
+            Ok(n) => run.finish(n),
+            Err(e) => {
+                tracing::error!("archival failed: {}", e);
+                run.fail(e);
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Moves RESOLVED markets decided longer than `archive_after_secs` ago into
+/// the archive tables. A market is only picked up once its settlement is in a
+/// batch (unless it isn't anchored on chain), no outbox job for it is still in flight and its group (if any) has
+/// settled. Returns the number of markets archived.
+async fn archive_resolved(state: &AppState) -> Result<usize, sqlx::Error> {
+    let config = &state.config.archiver;
+
+    let mut tx = state.db.begin().await?;
+
+    let ids: Vec<Uuid> = sqlx::query_scalar!(
+        r#"
+        SELECT m.id
+        FROM markets m
+        JOIN settlements s ON s.market_id = m.id
+        LEFT JOIN market_groups g ON g.id = m.group_id
+        WHERE m.status = 'RESOLVED'
+          AND s.decided_at < now() - make_interval(secs => $1)
+          AND (NOT m.anchor_on_chain OR s.batch_id IS NOT NULL)
+          AND NOT EXISTS (
+                SELECT 1 FROM outbox o
+                WHERE o.market_id = m.id AND o.status IN ('PENDING', 'INTENT', 'SENT')
+              )
+          AND (m.group_id IS NULL OR g.status = 'SETTLED')
+        ORDER BY s.decided_at ASC
+        LIMIT $2
+        FOR UPDATE OF m SKIP LOCKED
+        "#,
+        config.archive_after_secs as f64,
+        config.batch_size
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    archive_markets(&mut tx, &ids, None, "archiver").await?;
+    tx.commit().await?;
+    state.cache.invalidate_markets();
+
+    tracing::info!("Archived {} market(s)", ids.len());
+
+    Ok(ids.len())
+}
+
+/// Copies the markets (with their tags and requirements) and their reports
+/// into the archive tables, then removes them from the hot tables.
+/// `deleted_at` marks markets removed through the API rather than by age.
+pub(crate) async fn archive_markets(
+    tx: &mut Transaction<'_, Postgres>,
+    ids: &[Uuid],
+    deleted_at: Option<DateTime<Utc>>,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO markets_archive
+        SELECT (jsonb_populate_record(
+                  NULL::markets_archive,
+                  to_jsonb(m) || jsonb_build_object(
+                    'tags', ARRAY(SELECT t.tag FROM market_tags t WHERE t.market_id = m.id ORDER BY t.tag),
+                    'required_sources', r.required_sources,
+                    'min_reports', r.min_reports,
+                    'min_sources', r.min_sources,
+                    'deleted_at', $2::TIMESTAMPTZ,
+                    'archived_at', now()
+                  )
+               )).*
+        FROM markets m
+        LEFT JOIN market_requirements r ON r.market_id = m.id
+        WHERE m.id = ANY($1)
+        "#,
+        ids,
+        deleted_at
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO reports_archive
+        SELECT (jsonb_populate_record(
+                  NULL::reports_archive,
+                  to_jsonb(r) || jsonb_build_object('archived_at', now())
+               )).*
+        FROM reports r
+        WHERE r.market_id = ANY($1)
+        "#,
+        ids
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!("DELETE FROM reports WHERE market_id = ANY($1)", ids)
+        .execute(&mut **tx)
+        .await?;
+
+    // the only reference to markets without ON DELETE CASCADE
+    sqlx::query!(
+        "DELETE FROM market_close_notices WHERE market_id = ANY($1)",
+        ids
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    // tags, requirements and feeds cascade
+    sqlx::query!("DELETE FROM markets WHERE id = ANY($1)", ids)
+        .execute(&mut **tx)
+        .await?;
+
+    let action = if deleted_at.is_some() {
+        "deleted"
+    } else {
+        "archived"
+    };
+
+    for id in ids {
+        audit::record(&mut **tx, AuditEntry::new("market", *id, action, actor)).await?;
+    }
+
+    Ok(())
+}
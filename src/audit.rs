@@ -0,0 +1,66 @@
+use serde_json::Value;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+/// One state change. `before`/`after` are the entity's status on either side
+/// of the transition; creations have no `before`.
+pub struct AuditEntry<'a> {
+    pub entity_type: &'a str,
+    pub entity_id: Uuid,
+    pub action: &'a str,
+    pub actor: &'a str,
+    pub before: Option<&'a str>,
+    pub after: Option<&'a str>,
+    pub details: Value,
+}
+
+impl<'a> AuditEntry<'a> {
+    pub fn new(entity_type: &'a str, entity_id: Uuid, action: &'a str, actor: &'a str) -> Self {
+        Self {
+            entity_type,
+            entity_id,
+            action,
+            actor,
+            before: None,
+            after: None,
+            details: Value::Null,
+        }
+    }
+
+    pub fn transition(mut self, before: Option<&'a str>, after: Option<&'a str>) -> Self {
+        self.before = before;
+        self.after = after;
+        self
+    }
+
+    pub fn details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Appends to `audit_log`. Pass the open transaction when there is one so the
+/// entry commits (or rolls back) with the change it describes.
+pub async fn record<'e, E: PgExecutor<'e>>(
+    db: E,
+    entry: AuditEntry<'_>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_log
+        (entity_type, entity_id, action, actor, before_state, after_state, details)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(entry.entity_type)
+    .bind(entry.entity_id)
+    .bind(entry.action)
+    .bind(entry.actor)
+    .bind(entry.before)
+    .bind(entry.after)
+    .bind(entry.details)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
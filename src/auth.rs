@@ -0,0 +1,306 @@
+use std::sync::OnceLock;
+
+use argon2::{
+    Argon2,
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
+};
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{StatusCode, header::AUTHORIZATION, request::Parts},
+};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+use crate::state::AppState;
+
+const DEFAULT_TOKEN_TTL_SECS: i64 = 3600;
+
+/// What a caller may do. Roles nest, so the derived ordering is the check:
+/// an admin can do anything a reporter can, a reporter anything a reader can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Reader,
+    Reporter,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Reader => "reader",
+            Role::Reporter => "reporter",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "reader" => Some(Role::Reader),
+            "reporter" => Some(Role::Reporter),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// JWT body. `sub` is the username and becomes the audit actor.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub role: Role,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Credentials for protected routes: HS256 JWTs signed with `JWT_SECRET`,
+/// plus the static `ADMIN_TOKEN`, which acts as an admin and is how the
+/// first users get created. With neither configured every protected request
+/// is refused rather than left open.
+#[derive(Clone)]
+pub struct AuthConfig {
+    admin_token: Option<String>,
+    jwt: Option<JwtKeys>,
+    token_ttl: Duration,
+}
+
+#[derive(Clone)]
+struct JwtKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Self {
+        let jwt = std::env::var("JWT_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|secret| JwtKeys {
+                encoding: EncodingKey::from_secret(secret.as_bytes()),
+                decoding: DecodingKey::from_secret(secret.as_bytes()),
+            });
+
+        let ttl_secs = std::env::var("JWT_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&secs: &i64| secs > 0)
+            .unwrap_or(DEFAULT_TOKEN_TTL_SECS);
+
+        Self {
+            admin_token: std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()),
+            jwt,
+            token_ttl: Duration::seconds(ttl_secs),
+        }
+    }
+
+    /// Signs a token for `username`. Returns it with its expiry.
+    pub fn issue(&self, username: &str, role: Role) -> Result<(String, DateTime<Utc>), AppError> {
+        let keys = self.jwt.as_ref().ok_or_else(|| {
+            AppError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "AUTH_DISABLED",
+                "token issuance is disabled (JWT_SECRET not set)",
+            )
+        })?;
+
+        let now = Utc::now();
+        let expires_at = now + self.token_ttl;
+
+        let claims = Claims {
+            sub: username.to_string(),
+            role,
+            iat: now.timestamp(),
+            exp: expires_at.timestamp(),
+        };
+
+        let token = jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &keys.encoding)
+            .map_err(|e| AppError::internal(format!("failed to sign token: {}", e)))?;
+
+        Ok((token, expires_at))
+    }
+
+    /// Resolves the bearer token on a request to a caller holding at least
+    /// `required`.
+    fn authorize(&self, parts: &Parts, required: Role) -> Result<Caller, AppError> {
+        if self.admin_token.is_none() && self.jwt.is_none() {
+            return Err(AppError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "AUTH_DISABLED",
+                "protected routes are disabled (set JWT_SECRET or ADMIN_TOKEN)",
+            ));
+        }
+
+        let presented = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                AppError::new(
+                    StatusCode::UNAUTHORIZED,
+                    "MISSING_TOKEN",
+                    "missing bearer token",
+                )
+            })?;
+
+        let caller = if self
+            .admin_token
+            .as_ref()
+            .is_some_and(|expected| constant_time_eq(presented.as_bytes(), expected.as_bytes()))
+        {
+            Caller {
+                actor: "admin".to_string(),
+                role: Role::Admin,
+            }
+        } else {
+            let claims = self
+                .jwt
+                .as_ref()
+                .and_then(|keys| {
+                    jsonwebtoken::decode::<Claims>(
+                        presented,
+                        &keys.decoding,
+                        &Validation::new(Algorithm::HS256),
+                    )
+                    .ok()
+                })
+                .ok_or_else(|| {
+                    AppError::new(
+                        StatusCode::UNAUTHORIZED,
+                        "INVALID_TOKEN",
+                        "invalid or expired token",
+                    )
+                })?
+                .claims;
+
+            Caller {
+                actor: claims.sub,
+                role: claims.role,
+            }
+        };
+
+        if caller.role < required {
+            return Err(AppError::new(
+                StatusCode::FORBIDDEN,
+                "INSUFFICIENT_ROLE",
+                format!("requires the {} role", required.as_str()),
+            ));
+        }
+
+        Ok(caller)
+    }
+}
+
+struct Caller {
+    actor: String,
+    role: Role,
+}
+
+/// Extractor for admin-only handlers.
+pub struct RequireAdmin {
+    // recorded as the audit actor
+    pub actor: String,
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for RequireAdmin {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let caller = state.auth.authorize(parts, Role::Admin)?;
+
+        Ok(RequireAdmin {
+            actor: caller.actor,
+        })
+    }
+}
+
+/// Extractor for handlers that submit data: reporters and admins.
+pub struct RequireReporter {
+    // recorded as the audit actor
+    pub actor: String,
+    pub role: Role,
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for RequireReporter {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let caller = state.auth.authorize(parts, Role::Reporter)?;
+
+        Ok(RequireReporter {
+            actor: caller.actor,
+            role: caller.role,
+        })
+    }
+}
+
+impl RequireReporter {
+    /// Refuses unless the caller may report for `source`: one of the
+    /// sources assigned to them in `user_sources`. Admins may report for
+    /// any.
+    pub async fn check_source(&self, db: &PgPool, source: &str) -> Result<(), AppError> {
+        if self.role >= Role::Admin {
+            return Ok(());
+        }
+
+        let assigned = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM user_sources WHERE username = $1 AND source = $2) AS "assigned!""#,
+            self.actor,
+            source
+        )
+        .fetch_one(db)
+        .await?;
+
+        if !assigned {
+            return Err(AppError::new(
+                StatusCode::FORBIDDEN,
+                "SOURCE_NOT_ASSIGNED",
+                format!("{} may not report for source {}", self.actor, source),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Argon2id PHC string for storing in `users.password_hash`.
+pub fn hash_password(password: &str) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::internal(format!("failed to hash password: {}", e)))
+}
+
+/// A hash to verify against when there is no user, so the miss costs the
+/// same argon2 work as a wrong password. Hashed once, on first use.
+pub fn dummy_password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| hash_password("not the password of any user").unwrap_or_default())
+}
+
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|parsed| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok()
+    })
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,266 @@
+use chrono::{SubsecRound, Utc};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::events::{self, Event};
+use crate::proof::{
+    HashAlgorithm, LEGACY_LEAF_VERSION, build_merkle_root, hash_leaf, legacy_leaf_data,
+    settlement_leaf, settlement_leaf_data,
+};
+use crate::repo::{BatchRepo, NewLeaf, UnrecordedLeaf};
+use crate::state::AppState;
+use crate::types::BatchSummary;
+
+/// Runs on the leader replica only, so two replicas never cut batches over
+/// the same settlements.
+pub async fn batcher_loop(state: AppState) {
+    let interval = state.config.batcher.interval();
+    let mut backfilled = false;
+
+    loop {
+        state.leader.wait().await;
+
+        if !backfilled {
+            backfilled = true;
+            match backfill_leaves(&state).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!(
+                    "Recorded leaves for {} batch item(s) batched before leaves were stored",
+                    n
+                ),
+                Err(e) => tracing::error!("leaf backfill failed: {}", e),
+            }
+        }
+
+        let run = state.loops.start("batcher", interval);
+
+        match flush(&state, false, "batcher").await {
+            Ok(batches) => run.finish(batches.iter().map(|b| b.size as usize).sum()),
+            Err(e) => {
+                tracing::error!("batching failed: {}", e);
+                run.fail(e);
+            }
+        }
+
+        if let Err(e) = sync_statuses(&state).await {
+            tracing::error!("batch status update failed: {}", e);
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Cuts batches of unbatched settlements, oldest first. Full batches of
+/// `max_batch_size` are always cut; the remainder waits until it reaches
+/// `min_batch_size` or its oldest settlement has waited `max_wait_secs`.
+/// `force` batches the remainder regardless.
+pub async fn flush(
+    state: &AppState,
+    force: bool,
+    actor: &str,
+) -> Result<Vec<BatchSummary>, sqlx::Error> {
+    let max = state.config.batcher.max_batch_size.max(1);
+    let mut batches = Vec::new();
+
+    while let Some(batch) = create_batch(state, force, actor).await? {
+        let full = batch.size >= max;
+        batches.push(batch);
+        if !full {
+            break;
+        }
+    }
+
+    Ok(batches)
+}
+
+/// Follows open batches through their items' chain submissions. A FAILED
+/// batch keeps its settlements until an operator voids it.
+async fn sync_statuses(state: &AppState) -> Result<(), sqlx::Error> {
+    let mut tx = state.db.begin().await?;
+
+    for batch in BatchRepo::sync_statuses(&mut *tx).await? {
+        audit::record(
+            &mut *tx,
+            AuditEntry::new("batch", batch.id, "status_changed", "batcher")
+                .transition(Some(&batch.from), Some(&batch.to)),
+        )
+        .await?;
+
+        if batch.to == "FAILED" {
+            tracing::warn!(
+                "Batch {} failed to anchor; void it to re-batch its settlements",
+                batch.id
+            );
+            events::append(&mut *tx, &Event::BatchFailed { batch_id: batch.id }).await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// One batch, or `None` when nothing is pending or the policy says wait.
+async fn create_batch(
+    state: &AppState,
+    force: bool,
+    actor: &str,
+) -> Result<Option<BatchSummary>, sqlx::Error> {
+    let policy = &state.config.batcher;
+    let max = policy.max_batch_size.max(1);
+
+    let batch_id = Uuid::new_v4();
+
+    let mut tx = state.db.begin().await?;
+
+    let rows = BatchRepo::claim_unbatched(&mut *tx, batch_id, max).await?;
+
+    let Some(oldest) = rows.first() else {
+        return Ok(None);
+    };
+
+    let now = Utc::now().trunc_subsecs(6);
+    let waited = (now - oldest.decided_at).num_seconds();
+    let size = rows.len() as i64;
+
+    // dropping the transaction releases the claim
+    if !force && size < max && size < policy.min_batch_size && waited < policy.max_wait_secs as i64
+    {
+        return Ok(None);
+    }
+
+    let data: Vec<String> = rows
+        .iter()
+        .map(|r| settlement_leaf_data(r.market_id, r.outcome_e8, r.decided_at))
+        .collect();
+    // each leaf is the settlement's own; the tree above it uses the batch's
+    // algorithm
+    let hashes: Vec<[u8; 32]> = rows
+        .iter()
+        .map(|r| {
+            settlement_leaf(
+                HashAlgorithm::parse(&r.hash_algorithm).unwrap_or_default(),
+                r.market_id,
+                r.outcome_e8,
+                r.decided_at,
+            )
+        })
+        .collect();
+
+    let hash_algorithm = state.config.proof.hash_algorithm;
+    let root = build_merkle_root(hash_algorithm, hashes.clone());
+    let root_hex = hex::encode(root);
+
+    let leaves: Vec<NewLeaf> = rows
+        .iter()
+        .zip(data)
+        .zip(&hashes)
+        .map(|((r, leaf_data), hash)| NewLeaf {
+            market_id: r.market_id,
+            leaf_hash: hex::encode(hash),
+            leaf_data,
+        })
+        .collect();
+
+    BatchRepo::insert(&mut tx, batch_id, &root_hex, hash_algorithm, &leaves, now).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("batch", batch_id, "created", actor).details(serde_json::json!({
+            "merkle_root": root_hex,
+            "hash_algorithm": hash_algorithm,
+            "market_ids": rows.iter().map(|r| r.market_id).collect::<Vec<_>>(),
+            "forced": force,
+        })),
+    )
+    .await?;
+
+    let event = Event::BatchCreated {
+        batch_id,
+        merkle_root: root_hex.clone(),
+        size: rows.len(),
+    };
+    events::append(&mut *tx, &event).await?;
+
+    tx.commit().await?;
+    state
+        .cache
+        .invalidate_settlements(&rows.iter().map(|r| r.market_id).collect::<Vec<_>>())
+        .await;
+
+    tracing::info!("Created batch {} root={} size={}", batch_id, root_hex, size);
+
+    Ok(Some(BatchSummary {
+        id: batch_id,
+        merkle_root: root_hex,
+        hash_algorithm,
+        size,
+        status: "PENDING".to_string(),
+        voided_at: None,
+        created_at: now,
+    }))
+}
+
+/// Records the leaves of batches cut before leaves were stored. Those were
+/// built with the legacy string encoding, so they're rebuilt with it and
+/// stored as `LEGACY_LEAF_VERSION`. The legacy batcher didn't order its
+/// leaves, so the items' insertion order is tried first, then the
+/// (decided_at, market_id) order batches use now. A batch whose rebuilt
+/// leaves don't give its stored root in either order is left unrecorded.
+async fn backfill_leaves(state: &AppState) -> Result<usize, sqlx::Error> {
+    let mut total = 0;
+
+    for batch in BatchRepo::unrecorded(&state.db).await? {
+        let mut items = BatchRepo::legacy_items(&state.db, batch.id).await?;
+        let alg = batch.hash_algorithm();
+
+        let Some(leaves) = legacy_leaves(alg, &batch.merkle_root, &items).or_else(|| {
+            items.sort_by_key(|i| (i.decided_at, i.market_id));
+            legacy_leaves(alg, &batch.merkle_root, &items)
+        }) else {
+            tracing::error!(
+                "legacy leaves of batch {} don't rebuild its merkle root; leaving them unrecorded",
+                batch.id
+            );
+            continue;
+        };
+
+        let mut tx = state.db.begin().await?;
+        for (index, (item, (leaf, data))) in items.iter().zip(&leaves).enumerate() {
+            BatchRepo::record_leaf(
+                &mut *tx,
+                batch.id,
+                item.market_id,
+                index as i32,
+                &hex::encode(leaf),
+                data,
+                LEGACY_LEAF_VERSION,
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        total += leaves.len();
+    }
+
+    Ok(total)
+}
+
+// the items' legacy leaves and the strings they hash, if in this order they
+// rebuild `root`
+fn legacy_leaves(
+    alg: HashAlgorithm,
+    root: &str,
+    items: &[UnrecordedLeaf],
+) -> Option<Vec<([u8; 32], String)>> {
+    let leaves: Vec<([u8; 32], String)> = items
+        .iter()
+        .map(|i| {
+            let data = legacy_leaf_data(i.market_id, i.outcome, i.decided_at);
+            (hash_leaf(alg, &data), data)
+        })
+        .collect();
+
+    let rebuilt = build_merkle_root(alg, leaves.iter().map(|(leaf, _)| *leaf).collect());
+    (!leaves.is_empty() && hex::encode(rebuilt) == root).then_some(leaves)
+}
@@ -0,0 +1,297 @@
+//! Operator commands. Reads go straight to the database; anything that
+//! changes state goes through the HTTP API so it is authorized, audited and
+//! published like any other request.
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{Parser, Subcommand};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use oraclesettle_backend::config::AppConfig;
+use oraclesettle_backend::proof::{settlement_leaf, versioned_leaf};
+use oraclesettle_backend::repo::{BatchRepo, MarketFilter, MarketRepo, SettlementRepo};
+use oraclesettle_backend::verify::inclusion_proof;
+
+#[derive(Parser)]
+#[command(
+    name = "oraclesettle-cli",
+    about = "Operational tasks for oraclesettle"
+)]
+struct Cli {
+    /// Base URL of the API; defaults to the configured listen address.
+    #[arg(long, env = "ORACLESETTLE_URL", global = true)]
+    url: Option<String>,
+
+    /// Bearer token for admin commands.
+    #[arg(long, env = "ADMIN_TOKEN", global = true, hide_env_values = true)]
+    token: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Market listings.
+    Markets {
+        #[command(subcommand)]
+        command: MarketsCommand,
+    },
+    /// Operator actions on one market.
+    Market {
+        #[command(subcommand)]
+        command: MarketCommand,
+    },
+    /// Outbox jobs.
+    Outbox {
+        #[command(subcommand)]
+        command: OutboxCommand,
+    },
+    /// Settlement batches.
+    Batch {
+        #[command(subcommand)]
+        command: BatchCommand,
+    },
+    /// A settlement's leaf and its Merkle path to the batch root.
+    Proof { market_id: Uuid },
+}
+
+#[derive(Subcommand)]
+enum MarketsCommand {
+    List {
+        #[arg(long)]
+        status: Option<String>,
+        #[arg(long)]
+        category: Option<String>,
+        #[arg(long)]
+        tag: Option<String>,
+        #[arg(long)]
+        include_archived: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum MarketCommand {
+    /// Settles (or overrides) the market with the given outcome.
+    Resolve {
+        id: Uuid,
+        #[arg(long)]
+        outcome: f64,
+        #[arg(long)]
+        reason: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum OutboxCommand {
+    /// Sends a FAILED or ABANDONED job back to the worker.
+    Retry { id: Uuid },
+}
+
+#[derive(Subcommand)]
+enum BatchCommand {
+    /// Batches every unbatched settlement now.
+    Flush,
+    /// Voids a FAILED batch so its settlements are batched again.
+    Void { id: Uuid },
+}
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("error: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let Cli {
+        url,
+        token,
+        command,
+    } = cli;
+    let config = AppConfig::load()?;
+    let api = || Api::new(url.as_deref(), token.as_deref(), &config);
+
+    match command {
+        Command::Markets {
+            command:
+                MarketsCommand::List {
+                    status,
+                    category,
+                    tag,
+                    include_archived,
+                },
+        } => {
+            let db = connect().await?;
+            let filter = MarketFilter {
+                category,
+                tag,
+                status: status.map(|s| s.to_uppercase()),
+                id: None,
+                include_archived,
+            };
+
+            for m in MarketRepo::list(&db, &filter, config.consensus.default_bps()).await? {
+                let closes_at = m
+                    .closes_at
+                    .map_or("on event".to_string(), |c| c.to_rfc3339());
+                println!("{}  {:<10}  {}  {}", m.id, m.status, closes_at, m.question);
+            }
+        }
+        Command::Proof { market_id } => {
+            let db = connect().await?;
+            print_json(&proof(&db, market_id).await?)?;
+        }
+        Command::Market {
+            command:
+                MarketCommand::Resolve {
+                    id,
+                    outcome,
+                    reason,
+                },
+        } => {
+            let api = api()?;
+            let body = json!({ "outcome": outcome, "reason": reason });
+            print_json(
+                &api.post(&format!("/markets/{}/force-resolve", id), Some(body))
+                    .await?,
+            )?;
+        }
+        Command::Outbox {
+            command: OutboxCommand::Retry { id },
+        } => {
+            let api = api()?;
+            print_json(&api.post(&format!("/outbox/{}/retry", id), None).await?)?;
+        }
+        Command::Batch {
+            command: BatchCommand::Flush,
+        } => {
+            let api = api()?;
+            print_json(&api.post("/batches/flush", None).await?)?;
+        }
+        Command::Batch {
+            command: BatchCommand::Void { id },
+        } => {
+            let api = api()?;
+            print_json(&api.post(&format!("/batches/{}/void", id), None).await?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn connect() -> Result<PgPool> {
+    let url = std::env::var("DATABASE_URL").context("DATABASE_URL must be set")?;
+    oraclesettle_backend::db::connect(&url).await
+}
+
+async fn proof(db: &PgPool, market_id: Uuid) -> Result<Value> {
+    let settlement = SettlementRepo::get(db, market_id)
+        .await?
+        .ok_or_else(|| anyhow!("market {} is not settled", market_id))?;
+
+    let leaf = hex::encode(settlement_leaf(
+        settlement.hash_algorithm(),
+        market_id,
+        settlement.outcome_e8,
+        settlement.decided_at,
+    ));
+
+    let Some(batch) = BatchRepo::for_market(db, market_id).await? else {
+        bail!(
+            "settlement of market {} is not batched yet (leaf {})",
+            market_id,
+            leaf
+        );
+    };
+
+    let inclusion = inclusion_proof(db, &batch, market_id)
+        .await?
+        .ok_or_else(|| anyhow!("batch {} has incomplete stored leaves", batch.id))?;
+
+    let batched_leaf = hex::encode(versioned_leaf(
+        inclusion.leaf_version,
+        settlement.hash_algorithm(),
+        market_id,
+        settlement.outcome,
+        settlement.outcome_e8,
+        settlement.decided_at,
+    ));
+    if inclusion.leaf != batched_leaf {
+        bail!(
+            "batch {} holds an earlier revision of market {}'s settlement",
+            batch.id,
+            market_id
+        );
+    }
+
+    Ok(json!({
+        "market_id": market_id,
+        "outcome": settlement.outcome,
+        "decided_at": settlement.decided_at,
+        "revision": settlement.revision,
+        "status": settlement.status,
+        "hash_algorithm": settlement.hash_algorithm,
+        "inclusion": inclusion,
+    }))
+}
+
+fn print_json(value: &Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+struct Api {
+    client: reqwest::Client,
+    base: String,
+    token: String,
+}
+
+impl Api {
+    fn new(url: Option<&str>, token: Option<&str>, config: &AppConfig) -> Result<Self> {
+        let base = url.map_or_else(
+            || format!("http://{}", config.listen_addr()),
+            str::to_string,
+        );
+        let token = token
+            .ok_or_else(|| anyhow!("admin commands need --token or ADMIN_TOKEN"))?
+            .to_string();
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            base: base.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    async fn post(&self, path: &str, body: Option<Value>) -> Result<Value> {
+        let mut request = self
+            .client
+            .post(format!("{}{}", self.base, path))
+            .bearer_auth(&self.token);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("POST {}{}", self.base, path))?;
+        let status = response.status();
+        let text = response.text().await?;
+        let body: Value = if text.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&text).unwrap_or(Value::String(text))
+        };
+
+        if !status.is_success() {
+            bail!("{} {}", status, body);
+        }
+        Ok(body)
+    }
+}
@@ -0,0 +1,176 @@
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use moka::future::Cache;
+use uuid::Uuid;
+
+use crate::config::CacheConfig;
+use crate::repo::MarketFilter;
+use crate::types::{CacheStats, Market, SettlementView};
+
+/// Hot reads kept in memory: market listings per filter, and FINAL
+/// settlement views per market. Writes in this process invalidate what they
+/// touch; the TTL bounds how long another replica's writes go unseen.
+pub struct ReadCache {
+    markets: TtlCache<MarketFilter, Vec<Market>>,
+    settlements: TtlCache<Uuid, Option<SettlementView>>,
+}
+
+impl ReadCache {
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            markets: TtlCache::new(config.markets_ttl(), config.max_entries),
+            settlements: TtlCache::new(config.settlements_ttl(), config.max_entries),
+        }
+    }
+
+    pub async fn markets<F, E>(&self, filter: &MarketFilter, load: F) -> Result<Arc<Vec<Market>>, E>
+    where
+        F: Future<Output = Result<Vec<Market>, E>>,
+    {
+        self.markets.get_or_load(filter, load, |_| true).await
+    }
+
+    /// The settlement view, kept only once it is settled for good: FINAL,
+    /// and batched unless it never goes on chain. Until then its status or
+    /// inclusion proof may change on the leader, where this process wouldn't
+    /// hear of it.
+    pub async fn settlement<F, E>(
+        &self,
+        market_id: Uuid,
+        load: F,
+    ) -> Result<Arc<Option<SettlementView>>, E>
+    where
+        F: Future<Output = Result<Option<SettlementView>, E>>,
+    {
+        self.settlements
+            .get_or_load(&market_id, load, |s| {
+                // anchored settlements are FINAL only with a chain submission
+                s.as_ref().is_some_and(|s| {
+                    s.status == "FINAL" && (s.inclusion.is_some() || s.chain.is_none())
+                })
+            })
+            .await
+    }
+
+    /// After any change to a market row: status, schedule, settings, or
+    /// markets created, deleted or archived.
+    pub fn invalidate_markets(&self) {
+        self.markets.invalidate_all();
+    }
+
+    /// After the market's settlement was replaced, or its batch changed.
+    pub async fn invalidate_settlement(&self, market_id: Uuid) {
+        self.settlements.invalidate(&market_id).await;
+    }
+
+    pub async fn invalidate_settlements(&self, market_ids: &[Uuid]) {
+        for id in market_ids {
+            self.settlements.invalidate(id).await;
+        }
+    }
+
+    pub async fn stats(&self) -> Vec<CacheStats> {
+        vec![
+            self.markets.stats("markets").await,
+            self.settlements.stats("settlements").await,
+        ]
+    }
+}
+
+struct TtlCache<K, V> {
+    // None when the TTL is 0: every read loads
+    cache: Option<Cache<K, Arc<V>>>,
+    ttl: Option<Duration>,
+    // bumped on every invalidation, so a load that raced one isn't stored
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    V: Send + Sync + 'static,
+{
+    fn new(ttl: Option<Duration>, max_entries: u64) -> Self {
+        Self {
+            cache: ttl.map(|ttl| {
+                Cache::builder()
+                    .time_to_live(ttl)
+                    .max_capacity(max_entries)
+                    .build()
+            }),
+            ttl,
+            generation: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    async fn get_or_load<F, E>(
+        &self,
+        key: &K,
+        load: F,
+        keep: impl Fn(&V) -> bool,
+    ) -> Result<Arc<V>, E>
+    where
+        F: Future<Output = Result<V, E>>,
+    {
+        let Some(cache) = &self.cache else {
+            return Ok(Arc::new(load.await?));
+        };
+
+        if let Some(value) = cache.get(key).await {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let generation = self.generation.load(Ordering::Acquire);
+        let value = Arc::new(load.await?);
+
+        if keep(&value) && self.generation.load(Ordering::Acquire) == generation {
+            cache.insert(key.clone(), value.clone()).await;
+        }
+
+        Ok(value)
+    }
+
+    fn invalidate_all(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        if let Some(cache) = &self.cache {
+            cache.invalidate_all();
+        }
+    }
+
+    async fn invalidate(&self, key: &K) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        if let Some(cache) = &self.cache {
+            cache.invalidate(key).await;
+        }
+    }
+
+    async fn stats(&self, name: &str) -> CacheStats {
+        // entry counts lag inserts and expiry until maintenance runs
+        if let Some(cache) = &self.cache {
+            cache.run_pending_tasks().await;
+        }
+
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+
+        CacheStats {
+            name: name.to_string(),
+            enabled: self.cache.is_some(),
+            ttl_secs: self.ttl.map_or(0, |ttl| ttl.as_secs()),
+            entries: self.cache.as_ref().map_or(0, |c| c.entry_count()),
+            hits,
+            misses,
+            hit_rate: (hits + misses > 0).then(|| hits as f64 / (hits + misses) as f64),
+        }
+    }
+}
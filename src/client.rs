@@ -0,0 +1,551 @@
+//! Typed async client for the HTTP API, built on the same request and
+//! response types the server uses. Enabled by the `client` feature.
+
+use std::fmt;
+
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::error::ErrorResponse;
+#[cfg(feature = "eth")]
+use crate::eth::chains::ChainSummary;
+use crate::loops::LoopStatus;
+use crate::types::*;
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request never got a response, or the body wasn't what the route
+    /// returns.
+    Http(reqwest::Error),
+    /// The API answered with an error; `error.code` is stable to branch on.
+    Api {
+        status: StatusCode,
+        error: ErrorResponse,
+    },
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Http(e) => write!(f, "request failed: {}", e),
+            ClientError::Api { status, error } => {
+                write!(f, "{} {}: {}", status, error.code, error.message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientError::Http(e)
+    }
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// One method per route. Admin and reporter routes need `with_token`.
+#[derive(Clone)]
+pub struct OracleSettleClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl OracleSettleClient {
+    /// `base_url` without a trailing path, e.g. `http://localhost:3000`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    /// For callers that need their own timeouts, proxies or TLS settings.
+    pub fn with_http_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+        }
+    }
+
+    /// Sent as `Authorization: Bearer <token>`: the admin token, a reporter
+    /// key, or a JWT from `issue_token`.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub async fn health(&self) -> ClientResult<String> {
+        text(self.request(Method::GET, "/health")).await
+    }
+
+    pub async fn issue_token(&self, request: &TokenRequest) -> ClientResult<TokenResponse> {
+        json(self.request(Method::POST, "/auth/token").json(request)).await
+    }
+
+    pub async fn create_user(&self, request: &CreateUserRequest) -> ClientResult<User> {
+        json(self.request(Method::POST, "/auth/users").json(request)).await
+    }
+
+    /// Replaces the sources `username` may report for.
+    pub async fn set_user_sources(
+        &self,
+        username: &str,
+        request: &SetUserSourcesRequest,
+    ) -> ClientResult<User> {
+        json(
+            self.request(Method::PUT, &format!("/auth/users/{}/sources", username))
+                .json(request),
+        )
+        .await
+    }
+
+    /// Retrying with the same `idempotency_key` returns the original market.
+    pub async fn create_market(
+        &self,
+        request: &CreateMarketRequest,
+        idempotency_key: Option<&str>,
+    ) -> ClientResult<Market> {
+        let mut req = self.request(Method::POST, "/markets").json(request);
+        if let Some(key) = idempotency_key {
+            req = req.header("Idempotency-Key", key);
+        }
+        json(req).await
+    }
+
+    pub async fn list_markets(&self, query: &MarketQuery) -> ClientResult<Vec<Market>> {
+        json(self.request(Method::GET, "/markets").query(query)).await
+    }
+
+    pub async fn get_market(
+        &self,
+        market_id: Uuid,
+        query: &ArchivedQuery,
+    ) -> ClientResult<MarketDetail> {
+        json(
+            self.request(Method::GET, &format!("/markets/{}", market_id))
+                .query(query),
+        )
+        .await
+    }
+
+    pub async fn delete_market(&self, market_id: Uuid) -> ClientResult<()> {
+        empty(self.request(Method::DELETE, &format!("/markets/{}", market_id))).await
+    }
+
+    pub async fn cancel_market(
+        &self,
+        market_id: Uuid,
+        request: &CancelMarketRequest,
+    ) -> ClientResult<Market> {
+        json(
+            self.request(Method::POST, &format!("/markets/{}/cancel", market_id))
+                .json(request),
+        )
+        .await
+    }
+
+    /// Closes an OPEN market now, e.g. one that closes on an event.
+    pub async fn close_market(
+        &self,
+        market_id: Uuid,
+        request: &CloseMarketRequest,
+    ) -> ClientResult<Market> {
+        json(
+            self.request(Method::POST, &format!("/markets/{}/close", market_id))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn extend_market(
+        &self,
+        market_id: Uuid,
+        request: &ExtendMarketRequest,
+    ) -> ClientResult<Market> {
+        json(
+            self.request(Method::POST, &format!("/markets/{}/extend", market_id))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn set_market_priority(
+        &self,
+        market_id: Uuid,
+        request: &SetPriorityRequest,
+    ) -> ClientResult<Market> {
+        json(
+            self.request(Method::POST, &format!("/markets/{}/priority", market_id))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn force_resolve_market(
+        &self,
+        market_id: Uuid,
+        request: &ForceResolveRequest,
+    ) -> ClientResult<Market> {
+        json(
+            self.request(
+                Method::POST,
+                &format!("/markets/{}/force-resolve", market_id),
+            )
+            .json(request),
+        )
+        .await
+    }
+
+    pub async fn get_outcome_format(&self, market_id: Uuid) -> ClientResult<MarketOutcomeFormat> {
+        json(self.request(
+            Method::GET,
+            &format!("/markets/{}/outcome-format", market_id),
+        ))
+        .await
+    }
+
+    pub async fn get_resolution_status(&self, market_id: Uuid) -> ClientResult<ResolutionStatus> {
+        json(self.request(
+            Method::GET,
+            &format!("/markets/{}/resolution-status", market_id),
+        ))
+        .await
+    }
+
+    pub async fn create_market_group(
+        &self,
+        request: &CreateMarketGroupRequest,
+    ) -> ClientResult<MarketGroup> {
+        json(self.request(Method::POST, "/market-groups").json(request)).await
+    }
+
+    pub async fn get_market_group(&self, group_id: Uuid) -> ClientResult<MarketGroup> {
+        json(self.request(Method::GET, &format!("/market-groups/{}", group_id))).await
+    }
+
+    /// A retry of the same value within the dedup window returns the
+    /// original report.
+    pub async fn submit_report(
+        &self,
+        market_id: Uuid,
+        request: &CreateReportRequest,
+    ) -> ClientResult<Report> {
+        json(
+            self.request(Method::POST, &format!("/markets/{}/reports", market_id))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn update_report(
+        &self,
+        market_id: Uuid,
+        report_id: Uuid,
+        request: &UpdateReportRequest,
+    ) -> ClientResult<Report> {
+        let path = format!("/markets/{}/reports/{}", market_id, report_id);
+        json(self.request(Method::PUT, &path).json(request)).await
+    }
+
+    pub async fn retract_report(&self, market_id: Uuid, report_id: Uuid) -> ClientResult<()> {
+        let path = format!("/markets/{}/reports/{}", market_id, report_id);
+        empty(self.request(Method::DELETE, &path)).await
+    }
+
+    pub async fn list_reports(
+        &self,
+        market_id: Uuid,
+        query: &ReportListQuery,
+    ) -> ClientResult<Vec<Report>> {
+        json(
+            self.request(Method::GET, &format!("/markets/{}/reports", market_id))
+                .query(query),
+        )
+        .await
+    }
+
+    pub async fn aggregate_reports(
+        &self,
+        market_id: Uuid,
+        query: &ReportAggregateQuery,
+    ) -> ClientResult<Vec<ReportBucket>> {
+        let path = format!("/markets/{}/reports/aggregate", market_id);
+        json(self.request(Method::GET, &path).query(query)).await
+    }
+
+    #[cfg(feature = "feeds")]
+    pub async fn create_feed(
+        &self,
+        market_id: Uuid,
+        request: &CreateFeedRequest,
+    ) -> ClientResult<MarketFeed> {
+        json(
+            self.request(Method::POST, &format!("/markets/{}/feeds", market_id))
+                .json(request),
+        )
+        .await
+    }
+
+    #[cfg(feature = "feeds")]
+    pub async fn list_feeds(&self, market_id: Uuid) -> ClientResult<Vec<MarketFeed>> {
+        json(self.request(Method::GET, &format!("/markets/{}/feeds", market_id))).await
+    }
+
+    /// 404 SETTLEMENT_NOT_FINAL while the settlement awaits confirmation.
+    pub async fn get_settlement(&self, market_id: Uuid) -> ClientResult<SettlementView> {
+        json(self.request(Method::GET, &format!("/markets/{}/settlement", market_id))).await
+    }
+
+    pub async fn get_settlement_history(
+        &self,
+        market_id: Uuid,
+    ) -> ClientResult<Vec<SettlementRevision>> {
+        json(self.request(
+            Method::GET,
+            &format!("/markets/{}/settlement/history", market_id),
+        ))
+        .await
+    }
+
+    /// The settlement's Merkle inclusion proof; `None` until it is batched.
+    pub async fn get_proof(&self, market_id: Uuid) -> ClientResult<Option<InclusionProof>> {
+        Ok(self.get_settlement(market_id).await?.inclusion)
+    }
+
+    /// One page of settlements across markets; pass `next_cursor` back as
+    /// `query.cursor` for the next.
+    pub async fn list_settlements(
+        &self,
+        query: &SettlementListQuery,
+    ) -> ClientResult<SettlementPage> {
+        json(self.request(Method::GET, "/settlements").query(query)).await
+    }
+
+    /// The raw CSV or JSONL body, per `query.format`.
+    pub async fn export_settlements(&self, query: &SettlementExportQuery) -> ClientResult<String> {
+        text(
+            self.request(Method::GET, "/settlements/export")
+                .query(query),
+        )
+        .await
+    }
+
+    pub async fn source_alerts(&self, source: &str) -> ClientResult<Vec<SourceAlert>> {
+        json(self.request(Method::GET, &format!("/sources/{}/alerts", source))).await
+    }
+
+    pub async fn create_template(
+        &self,
+        request: &CreateTemplateRequest,
+    ) -> ClientResult<MarketTemplate> {
+        json(self.request(Method::POST, "/templates").json(request)).await
+    }
+
+    pub async fn list_templates(&self) -> ClientResult<Vec<MarketTemplate>> {
+        json(self.request(Method::GET, "/templates")).await
+    }
+
+    pub async fn get_template(&self, template_id: Uuid) -> ClientResult<MarketTemplate> {
+        json(self.request(Method::GET, &format!("/templates/{}", template_id))).await
+    }
+
+    pub async fn deactivate_template(&self, template_id: Uuid) -> ClientResult<()> {
+        empty(self.request(Method::DELETE, &format!("/templates/{}", template_id))).await
+    }
+
+    pub async fn list_loops(&self) -> ClientResult<Vec<LoopStatus>> {
+        json(self.request(Method::GET, "/admin/loops")).await
+    }
+
+    pub async fn system_jobs(&self) -> ClientResult<SystemJobs> {
+        json(self.request(Method::GET, "/system/jobs")).await
+    }
+
+    pub async fn metrics_history(
+        &self,
+        query: &MetricsHistoryQuery,
+    ) -> ClientResult<Vec<MetricsSnapshot>> {
+        json(
+            self.request(Method::GET, "/admin/metrics/history")
+                .query(query),
+        )
+        .await
+    }
+
+    pub async fn cache_stats(&self) -> ClientResult<Vec<CacheStats>> {
+        json(self.request(Method::GET, "/admin/metrics/cache")).await
+    }
+
+    pub async fn list_audit(&self, query: &AuditQuery) -> ClientResult<Vec<AuditRecord>> {
+        json(self.request(Method::GET, "/audit").query(query)).await
+    }
+
+    pub async fn list_batches(&self, query: &BatchQuery) -> ClientResult<Vec<BatchSummary>> {
+        json(self.request(Method::GET, "/batches").query(query)).await
+    }
+
+    pub async fn flush_batches(&self) -> ClientResult<Vec<BatchSummary>> {
+        json(self.request(Method::POST, "/batches/flush")).await
+    }
+
+    pub async fn get_batch(&self, batch_id: Uuid) -> ClientResult<BatchDetail> {
+        json(self.request(Method::GET, &format!("/batches/{}", batch_id))).await
+    }
+
+    /// Releases a FAILED batch's settlements to be batched again.
+    pub async fn void_batch(&self, batch_id: Uuid) -> ClientResult<BatchDetail> {
+        json(self.request(Method::POST, &format!("/batches/{}/void", batch_id))).await
+    }
+
+    /// Long-polls for up to `query.wait`; the reqwest client's timeout must
+    /// allow for it.
+    pub async fn get_changes(&self, query: &ChangesQuery) -> ClientResult<ChangesPage> {
+        json(self.request(Method::GET, "/changes").query(query)).await
+    }
+
+    pub async fn list_outbox(&self, query: &OutboxQuery) -> ClientResult<Vec<OutboxJob>> {
+        json(self.request(Method::GET, "/outbox").query(query)).await
+    }
+
+    pub async fn get_outbox_job(&self, job_id: Uuid) -> ClientResult<OutboxJob> {
+        json(self.request(Method::GET, &format!("/outbox/{}", job_id))).await
+    }
+
+    #[cfg(feature = "eth")]
+    pub async fn estimate_outbox_job(&self, job_id: Uuid) -> ClientResult<OutboxEstimate> {
+        json(self.request(Method::GET, &format!("/outbox/{}/estimate", job_id))).await
+    }
+
+    pub async fn retry_outbox_job(&self, job_id: Uuid) -> ClientResult<()> {
+        empty(self.request(Method::POST, &format!("/outbox/{}/retry", job_id))).await
+    }
+
+    pub async fn set_outbox_priority(
+        &self,
+        job_id: Uuid,
+        request: &SetPriorityRequest,
+    ) -> ClientResult<OutboxJob> {
+        json(
+            self.request(Method::POST, &format!("/outbox/{}/priority", job_id))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn abandon_outbox_job(&self, job_id: Uuid) -> ClientResult<()> {
+        empty(self.request(Method::DELETE, &format!("/outbox/{}", job_id))).await
+    }
+
+    pub async fn verify_settlement_payload(
+        &self,
+        request: &VerifyPayloadRequest,
+    ) -> ClientResult<PayloadVerdict> {
+        json(self.request(Method::POST, "/verify").json(request)).await
+    }
+
+    pub async fn verify_settlements(
+        &self,
+        request: &VerifySettlementsRequest,
+    ) -> ClientResult<VerifySettlementsSummary> {
+        json(
+            self.request(Method::POST, "/verify/settlements")
+                .json(request),
+        )
+        .await
+    }
+
+    #[cfg(feature = "webhooks")]
+    pub async fn create_webhook(&self, request: &CreateWebhookRequest) -> ClientResult<Webhook> {
+        json(self.request(Method::POST, "/webhooks").json(request)).await
+    }
+
+    #[cfg(feature = "webhooks")]
+    pub async fn list_webhooks(&self) -> ClientResult<Vec<Webhook>> {
+        json(self.request(Method::GET, "/webhooks")).await
+    }
+
+    #[cfg(feature = "webhooks")]
+    pub async fn delete_webhook(&self, webhook_id: Uuid) -> ClientResult<()> {
+        empty(self.request(Method::DELETE, &format!("/webhooks/{}", webhook_id))).await
+    }
+
+    #[cfg(feature = "webhooks")]
+    pub async fn list_deliveries(
+        &self,
+        webhook_id: Uuid,
+        query: &WebhookDeliveryQuery,
+    ) -> ClientResult<Vec<WebhookDelivery>> {
+        let path = format!("/webhooks/{}/deliveries", webhook_id);
+        json(self.request(Method::GET, &path).query(query)).await
+    }
+
+    /// The raw GraphQL response: `data`, plus `errors` if any field failed.
+    #[cfg(feature = "graphql")]
+    pub async fn graphql(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        let body = serde_json::json!({ "query": query, "variables": variables });
+        json(self.request(Method::POST, "/graphql").json(&body)).await
+    }
+
+    #[cfg(feature = "eth")]
+    pub async fn get_market_snapshot(&self, market_id: Uuid) -> ClientResult<SignedMarketSnapshot> {
+        json(self.request(Method::GET, &format!("/markets/{}/snapshot", market_id))).await
+    }
+
+    #[cfg(feature = "eth")]
+    pub async fn list_chains(&self) -> ClientResult<Vec<ChainSummary>> {
+        json(self.request(Method::GET, "/chains")).await
+    }
+
+    #[cfg(feature = "eth")]
+    pub async fn list_wallets(&self) -> ClientResult<Vec<ChainWallets>> {
+        json(self.request(Method::GET, "/wallets")).await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let req = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path));
+        match &self.token {
+            Some(token) => req.bearer_auth(token),
+            None => req,
+        }
+    }
+}
+
+async fn send(req: RequestBuilder) -> ClientResult<reqwest::Response> {
+    let res = req.send().await?;
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+
+    // errors from outside the API (a proxy, say) have no ErrorResponse body
+    let body = res.text().await?;
+    let error = serde_json::from_str(&body).unwrap_or_else(|_| ErrorResponse {
+        code: "HTTP_ERROR".to_string(),
+        message: body,
+        details: None,
+        request_id: None,
+    });
+
+    Err(ClientError::Api { status, error })
+}
+
+async fn json<T: DeserializeOwned>(req: RequestBuilder) -> ClientResult<T> {
+    Ok(send(req).await?.json().await?)
+}
+
+async fn text(req: RequestBuilder) -> ClientResult<String> {
+    Ok(send(req).await?.text().await?)
+}
+
+async fn empty(req: RequestBuilder) -> ClientResult<()> {
+    send(req).await?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::resolution::SourceValue;
+
+/// Early-close rule checked on every resolver pass while a market is OPEN.
+/// Stored per market in `markets.close_conditions`; the first one that holds
+/// closes the market and is copied to `markets.close_trigger`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CloseCondition {
+    /// At least `min_reports` reports received.
+    ReportCount { min_reports: usize },
+    /// Latest report (from `source`, if given) is at or above `threshold`.
+    ValueAbove {
+        threshold: f64,
+        source: Option<String>,
+    },
+    /// Latest report (from `source`, if given) is at or below `threshold`.
+    ValueBelow {
+        threshold: f64,
+        source: Option<String>,
+    },
+}
+
+impl CloseCondition {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            CloseCondition::ReportCount { min_reports } if *min_reports == 0 => {
+                Err("REPORT_COUNT needs min_reports >= 1".to_string())
+            }
+            CloseCondition::ValueAbove { threshold, .. }
+            | CloseCondition::ValueBelow { threshold, .. }
+                if !threshold.is_finite() =>
+            {
+                Err("close condition threshold must be finite".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `reports` must be in submission order.
+    pub fn is_met(&self, reports: &[SourceValue]) -> bool {
+        match self {
+            CloseCondition::ReportCount { min_reports } => reports.len() >= *min_reports,
+            CloseCondition::ValueAbove { threshold, source } => {
+                latest(reports, source.as_deref()).is_some_and(|v| v >= *threshold)
+            }
+            CloseCondition::ValueBelow { threshold, source } => {
+                latest(reports, source.as_deref()).is_some_and(|v| v <= *threshold)
+            }
+        }
+    }
+}
+
+fn latest(reports: &[SourceValue], source: Option<&str>) -> Option<f64> {
+    reports
+        .iter()
+        .rev()
+        .find(|r| source.is_none_or(|s| r.source == s))
+        .map(|r| r.value)
+}
@@ -0,0 +1,738 @@
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::proof::HashAlgorithm;
+
+/// Tunables for the API and background loops. Read from the TOML file named
+/// by `APP_CONFIG` (every key optional), then overridden by env vars.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub host: IpAddr,
+    pub port: u16,
+    pub tls: TlsConfig,
+    pub resolver: ResolverConfig,
+    pub batcher: BatcherConfig,
+    pub worker: WorkerConfig,
+    pub reconciler: ReconcilerConfig,
+    pub listener: ListenerConfig,
+    pub archiver: ArchiverConfig,
+    pub webhooks: WebhookConfig,
+    pub feeds: LoopConfig,
+    pub metrics: LoopConfig,
+    pub templates: LoopConfig,
+    pub consensus: ConsensusConfig,
+    pub sanity: SanityConfig,
+    pub proof: ProofConfig,
+    pub database: DatabaseConfig,
+    pub leader: LeaderConfig,
+    pub cache: CacheConfig,
+    pub anomaly: AnomalyConfig,
+    pub rate_limit: RateLimitConfig,
+}
+
+/// HTTPS termination. Serves plain HTTP unless both paths are set.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    // PEM certificate chain, leaf first
+    pub cert_path: Option<PathBuf>,
+    // PEM private key (PKCS#8 or RSA)
+    pub key_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ResolverConfig {
+    pub interval_secs: u64,
+    // CLOSED markets each shard loads at a time; a pass pages through the
+    // whole backlog
+    pub batch_size: i64,
+    // markets each shard resolves at once
+    pub concurrency: usize,
+    // reports are still taken this long after closes_at, marked late, and
+    // markets don't resolve until it has passed
+    pub close_grace_secs: u64,
+    // resolution tasks, each owning the CLOSED markets whose id hashes to it
+    pub shards: i32,
+    // leads before closes_at a MarketClosingSoon goes out at, for markets
+    // that don't set their own
+    pub close_notice_secs: Vec<i32>,
+    // resolve_deadline of new markets that don't set one, after closes_at
+    pub resolve_window_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BatcherConfig {
+    pub interval_secs: u64,
+    // settlements per Merkle batch; a backlog is cut into several
+    pub max_batch_size: i64,
+    // a smaller batch waits until it has this many settlements...
+    pub min_batch_size: i64,
+    // ...or its oldest settlement has waited this long
+    pub max_wait_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WorkerConfig {
+    pub interval_secs: u64,
+    // outbox jobs claimed per pass
+    pub batch_size: i64,
+    // failed attempts before a job is dead-lettered as FAILED
+    pub max_retries: i32,
+    // settlement transactions awaiting a receipt at once on each chain, across
+    // all its wallets
+    pub max_in_flight: usize,
+    // blocks searched for the tx of a settlement found already on chain
+    // before submitting
+    pub replay_lookback_blocks: u64,
+    // this process in outbox.claimed_by; hostname and pid when unset
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ReconcilerConfig {
+    pub interval_secs: u64,
+    // blocks on top of (and including) the one holding the tx
+    pub confirmations: u64,
+    // SENT jobs checked per pass
+    pub batch_size: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ListenerConfig {
+    // wait before resubscribing after the websocket drops
+    pub reconnect_secs: u64,
+    // blocks replayed on the first subscription; later ones resume from the
+    // last block seen
+    pub lookback_blocks: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ArchiverConfig {
+    pub interval_secs: u64,
+    // RESOLVED markets decided longer ago than this move to the archive tables
+    pub archive_after_secs: u64,
+    // markets archived per pass
+    pub batch_size: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub interval_secs: u64,
+    // deliveries attempted per pass
+    pub batch_size: i64,
+    // failed attempts before a delivery is given up as FAILED
+    pub max_attempts: i32,
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoopConfig {
+    pub interval_secs: u64,
+}
+
+/// Thresholds for the default SPREAD strategy.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ConsensusConfig {
+    pub min_reports: usize,
+    // (max - min) relative to the largest |value|; markets may override it
+    // with consensus_bps
+    pub max_spread: f64,
+    // spreads up to this much always agree, for values at or around zero
+    pub abs_tolerance: f64,
+    // scaled MADs from the median past which a report is an outlier; 0 disables
+    pub outlier_k: f64,
+}
+
+/// Checks new reports against the market's recent reports.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SanityConfig {
+    // most recent live reports the median is taken over
+    pub window: i64,
+    // scaled MADs from that median past which a report is flagged; 0 disables
+    pub max_deviation: f64,
+    // answer 422 instead of accepting and flagging
+    pub reject: bool,
+    // a source repeating its value this soon after its report, under a fresh
+    // idempotency key, gets that report back instead of a 409; 0 disables
+    pub dedup_window_secs: u64,
+}
+
+/// How new settlements and batches are hashed. Existing ones keep the
+/// algorithm they recorded.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProofConfig {
+    pub hash_algorithm: HashAlgorithm,
+}
+
+/// How the server's pool reaches Postgres. The CLI connects once and fails
+/// fast instead.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    // retries before startup gives up on the database; the delay doubles
+    // from retry_delay_ms up to max_retry_delay_secs
+    pub connect_retries: u32,
+    pub retry_delay_ms: u64,
+    pub max_retry_delay_secs: u64,
+    // start without waiting; the first query connects
+    pub lazy: bool,
+    // wait for a free connection before a query fails
+    pub acquire_timeout_secs: u64,
+}
+
+/// Which replica runs the resolver, batcher, reconciler and the other loops
+/// that write shared state. Every replica serves HTTP.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LeaderConfig {
+    // off: this instance always runs them, for single-replica deployments
+    pub enabled: bool,
+    // how often the leader checks its lock connection and standbys try to
+    // take over
+    pub interval_secs: u64,
+}
+
+/// Thresholds for the pass that flags suspicious reporting patterns. A
+/// pattern needs `min_markets` markets and a `min_share` of them behind it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    pub interval_secs: u64,
+    // settlements decided this long ago or sooner are looked at
+    pub window_secs: u64,
+    pub min_markets: usize,
+    pub min_share: f64,
+    // mean deviation from the outcome, relative to it, past which a source
+    // counts as biased
+    pub bias_threshold: f64,
+}
+
+/// In-process caching of hot reads. A TTL of 0 turns that cache off.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    // market listings, per filter; other replicas' writes show up after this
+    pub markets_ttl_secs: u64,
+    // FINAL settlement views, per market
+    pub settlements_ttl_secs: u64,
+    // entries each cache holds before evicting
+    pub max_entries: u64,
+}
+
+/// Per-window request caps; a cap of 0 turns that limit off.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub window_secs: u64,
+    // report submissions per authenticated caller
+    pub reports: u32,
+    // market creations per client IP
+    pub markets: u32,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            host: IpAddr::from([0, 0, 0, 0]),
+            port: 3000,
+            tls: TlsConfig::default(),
+            resolver: ResolverConfig::default(),
+            batcher: BatcherConfig::default(),
+            worker: WorkerConfig::default(),
+            reconciler: ReconcilerConfig::default(),
+            listener: ListenerConfig::default(),
+            archiver: ArchiverConfig::default(),
+            webhooks: WebhookConfig::default(),
+            feeds: LoopConfig { interval_secs: 10 },
+            metrics: LoopConfig {
+                interval_secs: 3600,
+            },
+            templates: LoopConfig { interval_secs: 30 },
+            consensus: ConsensusConfig::default(),
+            sanity: SanityConfig::default(),
+            proof: ProofConfig::default(),
+            database: DatabaseConfig::default(),
+            leader: LeaderConfig::default(),
+            cache: CacheConfig::default(),
+            anomaly: AnomalyConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+        }
+    }
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 10,
+            batch_size: 100,
+            concurrency: 8,
+            close_grace_secs: 0,
+            shards: 1,
+            close_notice_secs: vec![900, 60],
+            resolve_window_secs: 24 * 3600,
+        }
+    }
+}
+
+impl Default for BatcherConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 30,
+            max_batch_size: 1000,
+            min_batch_size: 1,
+            max_wait_secs: 300,
+        }
+    }
+}
+
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 5,
+            batch_size: 10,
+            max_retries: 5,
+            max_in_flight: 4,
+            replay_lookback_blocks: 10_000,
+            id: None,
+        }
+    }
+}
+
+impl Default for ReconcilerConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 30,
+            confirmations: 12,
+            batch_size: 50,
+        }
+    }
+}
+
+impl Default for ListenerConfig {
+    fn default() -> Self {
+        Self {
+            reconnect_secs: 5,
+            lookback_blocks: 1000,
+        }
+    }
+}
+
+impl Default for ArchiverConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 3600,
+            archive_after_secs: 30 * 24 * 3600,
+            batch_size: 100,
+        }
+    }
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 5,
+            batch_size: 50,
+            max_attempts: 8,
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl Default for LeaderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 5,
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            markets_ttl_secs: 5,
+            settlements_ttl_secs: 300,
+            max_entries: 10_000,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 60,
+            reports: 60,
+            markets: 10,
+        }
+    }
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 3600,
+            window_secs: 30 * 24 * 3600,
+            min_markets: 10,
+            min_share: 0.8,
+            bias_threshold: 0.01,
+        }
+    }
+}
+
+impl Default for ConsensusConfig {
+    fn default() -> Self {
+        Self {
+            min_reports: 3,
+            max_spread: 0.01,
+            abs_tolerance: 1e-9,
+            outlier_k: 3.0,
+        }
+    }
+}
+
+impl Default for SanityConfig {
+    fn default() -> Self {
+        Self {
+            window: 20,
+            max_deviation: 10.0,
+            reject: false,
+            dedup_window_secs: 30,
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            connect_retries: 10,
+            retry_delay_ms: 500,
+            max_retry_delay_secs: 30,
+            lazy: false,
+            acquire_timeout_secs: 10,
+        }
+    }
+}
+
+impl ConsensusConfig {
+    /// `max_spread` in basis points, as markets store it.
+    pub fn default_bps(&self) -> u32 {
+        (self.max_spread * 10_000.0).round().max(0.0) as u32
+    }
+
+    /// This config with the market's own tolerance, if it set one.
+    pub fn for_market(&self, consensus_bps: Option<i32>) -> ConsensusConfig {
+        let mut consensus = self.clone();
+        if let Some(bps) = consensus_bps {
+            consensus.max_spread = bps.max(0) as f64 / 10_000.0;
+        }
+        consensus
+    }
+}
+
+impl TlsConfig {
+    /// Certificate and key paths, when TLS is on.
+    pub fn paths(&self) -> Option<(&PathBuf, &PathBuf)> {
+        self.cert_path.as_ref().zip(self.key_path.as_ref())
+    }
+}
+
+impl LoopConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+}
+
+impl ResolverConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+
+    pub fn close_grace(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.close_grace_secs.min(i64::MAX as u64) as i64)
+    }
+
+    pub fn shards(&self) -> i32 {
+        self.shards.max(1)
+    }
+
+    pub fn resolve_window(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.resolve_window_secs.clamp(1, i64::MAX as u64) as i64)
+    }
+}
+
+impl BatcherConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+}
+
+impl WorkerConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+
+    /// `id`, or hostname and pid.
+    pub fn id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| {
+            let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "worker".to_string());
+            format!("{}-{}", host, std::process::id())
+        })
+    }
+}
+
+impl ReconcilerConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+}
+
+impl ListenerConfig {
+    pub fn reconnect_delay(&self) -> Duration {
+        Duration::from_secs(self.reconnect_secs.max(1))
+    }
+}
+
+impl ArchiverConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+}
+
+impl WebhookConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.max(1))
+    }
+}
+
+impl DatabaseConfig {
+    pub fn acquire_timeout(&self) -> Duration {
+        Duration::from_secs(self.acquire_timeout_secs.max(1))
+    }
+
+    pub fn retry_delay(&self) -> Duration {
+        Duration::from_millis(self.retry_delay_ms.max(1))
+    }
+
+    pub fn max_retry_delay(&self) -> Duration {
+        Duration::from_secs(self.max_retry_delay_secs.max(1))
+    }
+}
+
+impl LeaderConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+}
+
+impl CacheConfig {
+    pub fn markets_ttl(&self) -> Option<Duration> {
+        (self.markets_ttl_secs > 0).then(|| Duration::from_secs(self.markets_ttl_secs))
+    }
+
+    pub fn settlements_ttl(&self) -> Option<Duration> {
+        (self.settlements_ttl_secs > 0).then(|| Duration::from_secs(self.settlements_ttl_secs))
+    }
+}
+
+impl AnomalyConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+}
+
+impl RateLimitConfig {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs.max(1))
+    }
+}
+
+impl AppConfig {
+    pub fn load() -> Result<Self> {
+        let mut config = match std::env::var("APP_CONFIG") {
+            Ok(path) => {
+                let raw = std::fs::read_to_string(&path)
+                    .with_context(|| format!("reading app config {}", path))?;
+                toml::from_str(&raw).with_context(|| format!("parsing app config {}", path))?
+            }
+            Err(_) => AppConfig::default(),
+        };
+
+        override_from_env(&mut config.host, "HOST")?;
+        override_from_env(&mut config.port, "PORT")?;
+        override_optional_from_env(&mut config.tls.cert_path, "TLS_CERT_PATH")?;
+        override_optional_from_env(&mut config.tls.key_path, "TLS_KEY_PATH")?;
+        override_from_env(&mut config.resolver.interval_secs, "RESOLVER_INTERVAL_SECS")?;
+        override_from_env(&mut config.resolver.batch_size, "RESOLVER_BATCH_SIZE")?;
+        override_from_env(&mut config.resolver.concurrency, "RESOLVER_CONCURRENCY")?;
+        override_from_env(&mut config.resolver.close_grace_secs, "CLOSE_GRACE_SECS")?;
+        override_from_env(&mut config.resolver.shards, "RESOLVER_SHARDS")?;
+        override_from_env(
+            &mut config.resolver.resolve_window_secs,
+            "RESOLVE_WINDOW_SECS",
+        )?;
+        override_from_env(&mut config.batcher.interval_secs, "BATCHER_INTERVAL_SECS")?;
+        override_from_env(&mut config.batcher.max_batch_size, "BATCH_MAX_SIZE")?;
+        override_from_env(&mut config.batcher.min_batch_size, "BATCH_MIN_SIZE")?;
+        override_from_env(&mut config.batcher.max_wait_secs, "BATCH_MAX_WAIT_SECS")?;
+        override_from_env(&mut config.worker.interval_secs, "WORKER_INTERVAL_SECS")?;
+        override_from_env(&mut config.worker.batch_size, "WORKER_BATCH_SIZE")?;
+        override_from_env(&mut config.worker.max_retries, "OUTBOX_MAX_RETRIES")?;
+        override_from_env(&mut config.worker.max_in_flight, "WORKER_MAX_IN_FLIGHT")?;
+        override_from_env(
+            &mut config.worker.replay_lookback_blocks,
+            "WORKER_REPLAY_LOOKBACK_BLOCKS",
+        )?;
+        override_optional_from_env(&mut config.worker.id, "WORKER_ID")?;
+        override_from_env(
+            &mut config.reconciler.interval_secs,
+            "RECONCILER_INTERVAL_SECS",
+        )?;
+        override_from_env(&mut config.reconciler.confirmations, "CONFIRMATIONS")?;
+        override_from_env(&mut config.reconciler.batch_size, "RECONCILER_BATCH_SIZE")?;
+        override_from_env(
+            &mut config.listener.reconnect_secs,
+            "LISTENER_RECONNECT_SECS",
+        )?;
+        override_from_env(
+            &mut config.listener.lookback_blocks,
+            "LISTENER_LOOKBACK_BLOCKS",
+        )?;
+        override_from_env(&mut config.archiver.interval_secs, "ARCHIVER_INTERVAL_SECS")?;
+        override_from_env(
+            &mut config.archiver.archive_after_secs,
+            "ARCHIVE_AFTER_SECS",
+        )?;
+        override_from_env(&mut config.archiver.batch_size, "ARCHIVER_BATCH_SIZE")?;
+        override_from_env(&mut config.webhooks.interval_secs, "WEBHOOK_INTERVAL_SECS")?;
+        override_from_env(&mut config.webhooks.batch_size, "WEBHOOK_BATCH_SIZE")?;
+        override_from_env(&mut config.webhooks.max_attempts, "WEBHOOK_MAX_ATTEMPTS")?;
+        override_from_env(&mut config.webhooks.timeout_secs, "WEBHOOK_TIMEOUT_SECS")?;
+        override_from_env(&mut config.feeds.interval_secs, "FEEDS_INTERVAL_SECS")?;
+        override_from_env(&mut config.metrics.interval_secs, "METRICS_INTERVAL_SECS")?;
+        override_from_env(
+            &mut config.templates.interval_secs,
+            "TEMPLATES_INTERVAL_SECS",
+        )?;
+        override_from_env(&mut config.consensus.min_reports, "CONSENSUS_MIN_REPORTS")?;
+        override_from_env(&mut config.consensus.max_spread, "CONSENSUS_MAX_SPREAD")?;
+        override_from_env(
+            &mut config.consensus.abs_tolerance,
+            "CONSENSUS_ABS_TOLERANCE",
+        )?;
+        override_from_env(&mut config.consensus.outlier_k, "CONSENSUS_OUTLIER_K")?;
+        override_from_env(&mut config.sanity.window, "SANITY_WINDOW")?;
+        override_from_env(&mut config.sanity.max_deviation, "SANITY_MAX_DEVIATION")?;
+        override_from_env(&mut config.sanity.reject, "SANITY_REJECT")?;
+        override_from_env(
+            &mut config.sanity.dedup_window_secs,
+            "REPORT_DEDUP_WINDOW_SECS",
+        )?;
+        override_from_env(&mut config.database.connect_retries, "DB_CONNECT_RETRIES")?;
+        override_from_env(&mut config.database.retry_delay_ms, "DB_RETRY_DELAY_MS")?;
+        override_from_env(
+            &mut config.database.max_retry_delay_secs,
+            "DB_MAX_RETRY_DELAY_SECS",
+        )?;
+        override_from_env(&mut config.database.lazy, "DB_LAZY_CONNECT")?;
+        override_from_env(
+            &mut config.database.acquire_timeout_secs,
+            "DB_ACQUIRE_TIMEOUT_SECS",
+        )?;
+        override_from_env(&mut config.leader.enabled, "LEADER_ELECTION")?;
+        override_from_env(&mut config.leader.interval_secs, "LEADER_INTERVAL_SECS")?;
+        override_from_env(&mut config.cache.markets_ttl_secs, "CACHE_MARKETS_TTL_SECS")?;
+        override_from_env(
+            &mut config.cache.settlements_ttl_secs,
+            "CACHE_SETTLEMENTS_TTL_SECS",
+        )?;
+        override_from_env(&mut config.cache.max_entries, "CACHE_MAX_ENTRIES")?;
+        override_from_env(&mut config.anomaly.interval_secs, "ANOMALY_INTERVAL_SECS")?;
+        override_from_env(&mut config.anomaly.window_secs, "ANOMALY_WINDOW_SECS")?;
+        override_from_env(&mut config.anomaly.min_markets, "ANOMALY_MIN_MARKETS")?;
+        override_from_env(&mut config.anomaly.min_share, "ANOMALY_MIN_SHARE")?;
+        override_from_env(&mut config.anomaly.bias_threshold, "ANOMALY_BIAS_THRESHOLD")?;
+        override_from_env(&mut config.rate_limit.window_secs, "RATE_LIMIT_WINDOW_SECS")?;
+        override_from_env(&mut config.rate_limit.reports, "RATE_LIMIT_REPORTS")?;
+        override_from_env(&mut config.rate_limit.markets, "RATE_LIMIT_MARKETS")?;
+
+        // comma-separated, e.g. 900,60; empty sends no notices
+        if let Ok(raw) = std::env::var("CLOSE_NOTICE_SECS") {
+            config.resolver.close_notice_secs = raw
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .with_context(|| format!("invalid CLOSE_NOTICE_SECS {:?}", raw))?;
+        }
+
+        if let Ok(raw) = std::env::var("PROOF_HASH_ALGORITHM") {
+            config.proof.hash_algorithm = HashAlgorithm::parse(&raw).with_context(|| {
+                format!(
+                    "invalid PROOF_HASH_ALGORITHM {:?}; expected sha256 or keccak256",
+                    raw
+                )
+            })?;
+        }
+
+        if config.resolver.close_notice_secs.iter().any(|s| *s <= 0) {
+            bail!("close notice leads must be positive");
+        }
+
+        if config.tls.cert_path.is_some() != config.tls.key_path.is_some() {
+            bail!("TLS needs both a certificate and a key path");
+        }
+
+        Ok(config)
+    }
+
+    pub fn listen_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.host, self.port)
+    }
+}
+
+fn override_from_env<T>(field: &mut T, var: &str) -> Result<()>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    if let Ok(raw) = std::env::var(var) {
+        *field = raw.parse().with_context(|| format!("invalid {}", var))?;
+    }
+    Ok(())
+}
+
+// an empty value clears the setting
+fn override_optional_from_env<T>(field: &mut Option<T>, var: &str) -> Result<()>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    if let Ok(raw) = std::env::var(var) {
+        *field = match raw.as_str() {
+            "" => None,
+            _ => Some(raw.parse().with_context(|| format!("invalid {}", var))?),
+        };
+    }
+    Ok(())
+}
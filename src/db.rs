@@ -0,0 +1,103 @@
+use anyhow::{Result, anyhow};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{Connection, PgConnection, PgPool};
+
+use crate::config::DatabaseConfig;
+
+const MAX_CONNECTIONS: u32 = 10;
+
+/// Opens a Postgres pool for `url`.
+pub async fn connect(url: &str) -> Result<PgPool> {
+    let options = connect_options(url)?;
+
+    Ok(PgPoolOptions::new()
+        .max_connections(MAX_CONNECTIONS)
+        .connect_with(options)
+        .await?)
+}
+
+/// The server's pool. Waits for the database with exponential backoff, up
+/// to `connect_retries` retries, so a database that comes up alongside the
+/// service (or blips during a deploy) doesn't abort startup. In lazy mode the
+/// pool is returned at once and connects on first use.
+pub async fn connect_with_retry(url: &str, config: &DatabaseConfig) -> Result<PgPool> {
+    let options = connect_options(url)?;
+    tracing::info!("Connecting to {}", describe(&options));
+
+    let pool = PgPoolOptions::new()
+        .max_connections(MAX_CONNECTIONS)
+        .acquire_timeout(config.acquire_timeout());
+
+    if config.lazy {
+        tracing::info!("Lazy database connect: the first query opens the pool");
+        return Ok(pool.connect_lazy_with(options));
+    }
+
+    wait_until_ready(url, config).await?;
+    Ok(pool.connect_with(options).await?)
+}
+
+/// Opens throwaway connections until one succeeds or the retries run out.
+/// Each failure is logged with the driver's own error (refused, bad
+/// password, unknown database...).
+pub async fn wait_until_ready(url: &str, config: &DatabaseConfig) -> Result<()> {
+    let options = connect_options(url)?;
+    let mut delay = config.retry_delay();
+    let mut attempt = 0;
+
+    loop {
+        let result = tokio::time::timeout(
+            config.acquire_timeout(),
+            PgConnection::connect_with(&options),
+        )
+        .await;
+
+        let error = match result {
+            Ok(Ok(conn)) => {
+                let _ = conn.close().await;
+                if attempt > 0 {
+                    tracing::info!("Database reachable after {} retries", attempt);
+                }
+                return Ok(());
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => format!("no answer within {:?}", config.acquire_timeout()),
+        };
+
+        if attempt >= config.connect_retries {
+            return Err(anyhow!(
+                "database {} not reachable after {} retries: {}",
+                describe(&options),
+                config.connect_retries,
+                error
+            ));
+        }
+
+        attempt += 1;
+        tracing::warn!(
+            "Database not reachable ({}), retry {}/{} in {:?}",
+            error,
+            attempt,
+            config.connect_retries,
+            delay
+        );
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(config.max_retry_delay());
+    }
+}
+
+fn connect_options(url: &str) -> Result<PgConnectOptions> {
+    url.parse::<PgConnectOptions>()
+        .map_err(|e| anyhow!("invalid DATABASE_URL: {}", e))
+}
+
+// where the pool points, without the password, for the logs
+fn describe(options: &PgConnectOptions) -> String {
+    format!(
+        "{}@{}:{}/{}",
+        options.get_username(),
+        options.get_host(),
+        options.get_port(),
+        options.get_database().unwrap_or_default()
+    )
+}
@@ -0,0 +1,66 @@
+// backend/src/eth/attest.rs
+
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use serde::Serialize;
+
+use crate::types::SnapshotSignature;
+
+/// How snapshot signatures are made, as named in `SnapshotSignature`.
+pub const SIGNATURE_SCHEME: &str = "eip191-keccak256-json";
+
+/// The server's attestation key, distinct from the submitter wallets: it
+/// signs documents, never transactions.
+pub struct Attestor {
+    wallet: LocalWallet,
+}
+
+impl Attestor {
+    /// `SNAPSHOT_SIGNING_KEY` (hex private key); `None` when unset, which
+    /// turns snapshots off.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(key) = std::env::var("SNAPSHOT_SIGNING_KEY") else {
+            return Ok(None);
+        };
+        if key.trim().is_empty() {
+            return Ok(None);
+        }
+
+        // the parse error never echoes the key
+        let wallet: LocalWallet = key
+            .trim()
+            .parse()
+            .context("SNAPSHOT_SIGNING_KEY is not a valid private key")?;
+
+        Ok(Some(Self { wallet }))
+    }
+
+    pub fn address(&self) -> Address {
+        self.wallet.address()
+    }
+
+    /// Signs `document` serialized as compact JSON with object keys sorted.
+    /// The digest is the keccak256 of those bytes, and the signature an
+    /// EIP-191 personal_sign over the digest, so `ecrecover` on
+    /// `hashMessage(digest)` yields `signer`.
+    pub async fn sign<T: Serialize>(&self, document: &T) -> Result<SnapshotSignature> {
+        let digest = keccak256(canonical_json(document)?);
+        let signature = self.wallet.sign_message(digest).await?;
+
+        Ok(SnapshotSignature {
+            scheme: SIGNATURE_SCHEME.to_string(),
+            signer: format!("{:?}", self.address()),
+            digest: format!("0x{}", hex::encode(digest)),
+            signature: format!("0x{}", signature),
+        })
+    }
+}
+
+/// Compact JSON with every object's keys in sorted order, the form snapshot
+/// digests are taken over.
+pub fn canonical_json<T: Serialize>(document: &T) -> Result<Vec<u8>> {
+    // Value's maps are ordered by key
+    let value = serde_json::to_value(document)?;
+    Ok(serde_json::to_vec(&value)?)
+}
@@ -0,0 +1,190 @@
+// backend/src/eth/chains.rs
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, anyhow};
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::signer::{self, SignerType};
+use super::wallets::WalletPool;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChainConfig {
+    pub chain_id: u64,
+    pub name: String,
+    pub rpc_url: String,
+    // websocket endpoint for eth::listener; without one the chain isn't watched
+    pub ws_url: Option<String>,
+    pub contract_address: Address,
+    // where submitter keys come from; defaults to SIGNER_TYPE
+    pub signer_type: Option<SignerType>,
+    // local: keys inline, or the name of an env var holding comma separated keys
+    #[serde(default)]
+    pub private_keys: Vec<String>,
+    pub private_keys_env: Option<String>,
+    // keystore: encrypted JSON keystores sharing one password, read from
+    // keystore_password_file or else the keystore_password_env var
+    #[serde(default)]
+    pub keystores: Vec<PathBuf>,
+    pub keystore_password_file: Option<PathBuf>,
+    pub keystore_password_env: Option<String>,
+    // kms: AWS KMS key ids or ARNs (secp256k1)
+    #[serde(default)]
+    pub kms_key_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChainsFile {
+    default_chain_id: Option<u64>,
+    chains: Vec<ChainConfig>,
+}
+
+pub struct ChainTarget {
+    pub config: ChainConfig,
+    pub wallets: WalletPool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ChainSummary {
+    pub chain_id: u64,
+    pub name: String,
+    pub contract_address: String,
+    pub wallets: usize,
+    pub default: bool,
+}
+
+pub struct ChainRegistry {
+    chains: BTreeMap<u64, ChainTarget>,
+    default_chain_id: Option<u64>,
+}
+
+impl ChainRegistry {
+    /// Reads the TOML file named by `CHAINS_CONFIG` when set. Otherwise falls
+    /// back to the single-chain `RPC_URL`/`WS_URL`/`CONTRACT_ADDRESS`/`CHAIN_ID`
+    /// env vars plus the signer ones (see `env_chain`); with none of those the
+    /// registry is empty and chain submission is disabled.
+    pub async fn load() -> Result<Self> {
+        let default_signer = signer::default_signer_type()?;
+
+        let (configs, default_chain_id) = match std::env::var("CHAINS_CONFIG") {
+            Ok(path) => {
+                let raw = std::fs::read_to_string(&path)
+                    .with_context(|| format!("reading chains config {}", path))?;
+                let file: ChainsFile = toml::from_str(&raw)
+                    .with_context(|| format!("parsing chains config {}", path))?;
+                (file.chains, file.default_chain_id)
+            }
+            Err(_) => (env_chain()?.into_iter().collect(), None),
+        };
+
+        let mut chains = BTreeMap::new();
+        for config in configs {
+            let signers = signer::load(&config, default_signer)
+                .await
+                .with_context(|| format!("loading signers for chain {}", config.chain_id))?;
+            let wallets = WalletPool::new(signers);
+            let chain_id = config.chain_id;
+            if chains
+                .insert(chain_id, ChainTarget { config, wallets })
+                .is_some()
+            {
+                return Err(anyhow!("chain {} configured twice", chain_id));
+            }
+        }
+
+        let default_chain_id = default_chain_id.or_else(|| chains.keys().next().copied());
+        if let Some(id) = default_chain_id
+            && !chains.contains_key(&id)
+        {
+            return Err(anyhow!("default_chain_id {} is not configured", id));
+        }
+
+        Ok(Self {
+            chains,
+            default_chain_id,
+        })
+    }
+
+    pub fn get(&self, chain_id: u64) -> Option<&ChainTarget> {
+        self.chains.get(&chain_id)
+    }
+
+    pub fn default_chain_id(&self) -> Option<u64> {
+        self.default_chain_id
+    }
+
+    /// Chain for a payload/market that may not name one explicitly.
+    pub fn resolve(&self, chain_id: Option<u64>) -> Result<&ChainTarget> {
+        let id = chain_id
+            .or(self.default_chain_id)
+            .ok_or_else(|| anyhow!("no chains configured"))?;
+
+        self.get(id)
+            .ok_or_else(|| anyhow!("chain {} is not configured", id))
+    }
+
+    pub fn targets(&self) -> impl Iterator<Item = &ChainTarget> {
+        self.chains.values()
+    }
+
+    pub fn summaries(&self) -> Vec<ChainSummary> {
+        self.chains
+            .values()
+            .map(|t| ChainSummary {
+                chain_id: t.config.chain_id,
+                name: t.config.name.clone(),
+                contract_address: format!("{:?}", t.config.contract_address),
+                wallets: t.wallets.len(),
+                default: Some(t.config.chain_id) == self.default_chain_id,
+            })
+            .collect()
+    }
+}
+
+fn env_chain() -> Result<Option<ChainConfig>> {
+    let (Ok(rpc_url), Ok(addr)) = (std::env::var("RPC_URL"), std::env::var("CONTRACT_ADDRESS"))
+    else {
+        return Ok(None);
+    };
+
+    let chain_id: u64 = match std::env::var("CHAIN_ID") {
+        Ok(v) => v.parse()?,
+        Err(_) => 1,
+    };
+
+    let keys = std::env::var("PRIVATE_KEYS")
+        .or_else(|_| std::env::var("PRIVATE_KEY"))
+        .unwrap_or_default();
+
+    Ok(Some(ChainConfig {
+        chain_id,
+        name: format!("chain-{}", chain_id),
+        rpc_url,
+        ws_url: std::env::var("WS_URL").ok().filter(|u| !u.is_empty()),
+        contract_address: addr.parse()?,
+        signer_type: None,
+        private_keys: split_list(&keys),
+        private_keys_env: None,
+        keystores: split_list(&std::env::var("KEYSTORE_PATHS").unwrap_or_default())
+            .into_iter()
+            .map(PathBuf::from)
+            .collect(),
+        keystore_password_file: std::env::var("KEYSTORE_PASSWORD_FILE")
+            .ok()
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from),
+        keystore_password_env: None,
+        kms_key_ids: split_list(&std::env::var("KMS_KEY_IDS").unwrap_or_default()),
+    }))
+}
+
+fn split_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -1,26 +1,22 @@
 // backend/src/eth/client.rs
 
+use super::OracleSettle;
+use super::chains::ChainConfig;
+use super::signer::TxSigner;
+use anyhow::Result;
 use ethers::prelude::*;
 use std::sync::Arc;
-use anyhow::Result;
-use super::OracleSettle;
-
-pub async fn eth_client() -> Result<OracleSettle<SignerMiddleware<Provider<Http>, Wallet<k256::ecdsa::SigningKey>>>> {
 
-    let rpc = std::env::var("RPC_URL")?;
-    let key = std::env::var("PRIVATE_KEY")?;
-    let addr = std::env::var("CONTRACT_ADDRESS")?;
+pub type EthClient = SignerMiddleware<Provider<Http>, TxSigner>;
 
-    let provider = Provider::<Http>::try_from(rpc)?;
-    let wallet: LocalWallet = key.parse()?;
-
-    let chain_id: u64 = std::env::var("CHAIN_ID")?.parse()?;
-    let wallet = wallet.with_chain_id(chain_id);
-
-    let client = SignerMiddleware::new(provider, wallet);
-    let client = Arc::new(client);
-
-    let address: Address = addr.parse()?;
+pub fn provider(chain: &ChainConfig) -> Result<Provider<Http>> {
+    Ok(Provider::<Http>::try_from(chain.rpc_url.as_str())?)
+}
 
-    Ok(OracleSettle::new(address, client))
+/// Contract handle for view calls only; needs no signer.
+pub fn read_client(chain: &ChainConfig) -> Result<OracleSettle<Provider<Http>>> {
+    Ok(OracleSettle::new(
+        chain.contract_address,
+        Arc::new(provider(chain)?),
+    ))
 }
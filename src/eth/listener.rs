@@ -0,0 +1,214 @@
+// backend/src/eth/listener.rs
+
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use ethers::contract::LogMeta;
+use ethers::prelude::*;
+use futures_util::future::join_all;
+
+use super::chains::ChainTarget;
+use super::{MarketSettledFilter, OracleSettle};
+use crate::audit::{self, AuditEntry};
+use crate::models::outbox::SettlementPayload;
+use crate::repo::{NewSubmission, OutboxRepo};
+use crate::state::AppState;
+
+/// Watches `MarketSettled` on every chain with a `ws_url` and brings the
+/// outbox in line with what actually landed. A worker that crashed between
+/// broadcast and `mark_sent`, or a job dead-lettered while its tx was still
+/// pending, otherwise leaves the row claiming the settlement never happened.
+/// Confirmation depth is still the reconciler's call; this only moves jobs to
+/// SENT and records the tx it saw.
+pub async fn listener_loop(state: AppState) {
+    let targets: Vec<&ChainTarget> = state
+        .chains
+        .targets()
+        .filter(|t| t.config.ws_url.is_some())
+        .collect();
+
+    if targets.is_empty() {
+        tracing::info!("no chain has a ws_url, event listener idle");
+        std::future::pending::<()>().await;
+    }
+
+    join_all(
+        targets
+            .into_iter()
+            .map(|target| listen_chain(&state, target)),
+    )
+    .await;
+}
+
+async fn listen_chain(state: &AppState, target: &ChainTarget) {
+    let delay = state.config.listener.reconnect_delay();
+    // resume point across reconnects so nothing emitted while down is missed
+    let mut next_block: Option<u64> = None;
+
+    loop {
+        if let Err(e) = watch(state, target, &mut next_block).await {
+            tracing::warn!(
+                "chain {} event subscription dropped: {}",
+                target.config.chain_id,
+                e
+            );
+            state
+                .loops
+                .start("listener", delay)
+                .fail(format!("chain {}: {}", target.config.chain_id, e));
+        }
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Replays logs from `next_block` (or the configured lookback) to the head,
+/// then follows the live subscription until it ends.
+async fn watch(state: &AppState, target: &ChainTarget, next_block: &mut Option<u64>) -> Result<()> {
+    let ws_url = target
+        .config
+        .ws_url
+        .as_deref()
+        .ok_or_else(|| anyhow!("no ws_url"))?;
+    let provider = Arc::new(Provider::<Ws>::connect(ws_url).await?);
+    let contract = OracleSettle::new(target.config.contract_address, provider.clone());
+
+    let head = provider.get_block_number().await?.as_u64();
+    let from =
+        next_block.unwrap_or_else(|| head.saturating_sub(state.config.listener.lookback_blocks));
+
+    if from <= head {
+        let missed = contract
+            .market_settled_filter()
+            .from_block(from)
+            .to_block(head)
+            .query_with_meta()
+            .await?;
+
+        for (event, meta) in missed {
+            handle(state, target, &provider, &event, &meta).await?;
+        }
+    }
+    *next_block = Some(head + 1);
+
+    tracing::info!(
+        "listening for settlements on chain {}",
+        target.config.chain_id
+    );
+
+    let events = contract.market_settled_filter().from_block(head + 1);
+    let mut stream = events.subscribe_with_meta().await?;
+
+    while let Some(item) = stream.next().await {
+        let (event, meta) = item?;
+        handle(state, target, &provider, &event, &meta).await?;
+        *next_block = Some(next_block.unwrap_or(0).max(meta.block_number.as_u64()));
+    }
+
+    Err(anyhow!("subscription closed"))
+}
+
+/// Each observed event is one run of the `listener` loop; failures are
+/// recorded by `listen_chain` when the subscription is torn down.
+async fn handle(
+    state: &AppState,
+    target: &ChainTarget,
+    provider: &Provider<Ws>,
+    event: &MarketSettledFilter,
+    meta: &LogMeta,
+) -> Result<()> {
+    let run = state
+        .loops
+        .start("listener", state.config.listener.reconnect_delay());
+    run.finish(observe(state, target, provider, event, meta).await?);
+
+    Ok(())
+}
+
+/// Marks the outbox jobs the event settles as SENT and records its tx.
+/// Returns the number of jobs touched.
+async fn observe(
+    state: &AppState,
+    target: &ChainTarget,
+    provider: &Provider<Ws>,
+    event: &MarketSettledFilter,
+    meta: &LogMeta,
+) -> Result<usize> {
+    let chain_id = target.config.chain_id;
+    let market_hash = hex::encode(event.market_id);
+    let root = hex::encode(event.merkle_root);
+    let tx_hash = format!("{:?}", meta.transaction_hash);
+
+    let mut tx = state.db.begin().await?;
+
+    let candidates = OutboxRepo::lock_for_settlement(&mut *tx, &market_hash, &root).await?;
+
+    let jobs: Vec<_> = candidates
+        .into_iter()
+        .filter(|job| {
+            serde_json::from_value::<SettlementPayload>(job.payload.clone())
+                .ok()
+                .and_then(|p| state.chains.resolve(p.chain_id).ok())
+                .is_some_and(|t| t.config.chain_id == chain_id)
+        })
+        .collect();
+
+    if jobs.is_empty() {
+        return Ok(0);
+    }
+
+    let submitter = provider
+        .get_transaction(meta.transaction_hash)
+        .await?
+        .map(|t| format!("{:?}", t.from))
+        .unwrap_or_default();
+
+    for job in &jobs {
+        if job.status != "SENT" {
+            OutboxRepo::mark_sent(&mut *tx, job.id).await?;
+        }
+
+        // the reconciler follows the newest submission, so the observed tx
+        // supersedes a stale intent hash
+        let inserted = OutboxRepo::record_submission(
+            &mut *tx,
+            &NewSubmission {
+                outbox_id: job.id,
+                market_id: job.market_id,
+                tx_hash: &tx_hash,
+                block_number: Some(meta.block_number.as_u64() as i64),
+                gas_used: None,
+                submitter: &submitter,
+            },
+        )
+        .await?;
+
+        let mut entry = AuditEntry::new("outbox", job.id, "observed_on_chain", "listener");
+        if job.status != "SENT" {
+            entry = entry.transition(Some(&job.status), Some("SENT"));
+        } else if !inserted {
+            continue;
+        }
+
+        audit::record(
+            &mut *tx,
+            entry.details(serde_json::json!({
+                "chain_id": chain_id,
+                "tx_hash": tx_hash,
+                "block_number": meta.block_number.as_u64(),
+            })),
+        )
+        .await?;
+
+        tracing::info!(
+            "outbox {} settled on chain {} by {}",
+            job.id,
+            chain_id,
+            tx_hash
+        );
+    }
+
+    tx.commit().await?;
+
+    Ok(jobs.len())
+}
@@ -2,10 +2,16 @@
 
 use ethers::prelude::*;
 
-pub mod submit;
+pub mod attest;
+pub mod chains;
 pub mod client;
+pub mod listener;
+pub mod read;
+pub mod revert;
+pub mod sender;
+pub mod signer;
+pub mod submit;
+pub mod verify;
+pub mod wallets;
 
-abigen!(
-    OracleSettle,
-    "./abi/OracleSettle.json"
-);
+abigen!(OracleSettle, "./abi/OracleSettle.json");
@@ -0,0 +1,111 @@
+// backend/src/eth/read.rs
+
+use super::chains::ChainConfig;
+use super::client::{provider, read_client};
+use super::submit::SubmissionReceipt;
+use anyhow::Result;
+use ethers::prelude::*;
+
+/// What the contract holds for one market.
+#[derive(Debug, Clone)]
+pub struct OnChainSettlement {
+    pub root: [u8; 32],
+    pub outcome: u64,
+    pub decided_at: u64,
+    pub exists: bool,
+}
+
+pub async fn settlement(chain: &ChainConfig, market_hash: [u8; 32]) -> Result<OnChainSettlement> {
+    let contract = read_client(chain)?;
+    let (root, outcome, decided_at, exists) = contract.settlements(market_hash).call().await?;
+
+    Ok(OnChainSettlement {
+        root,
+        outcome: outcome.as_u64(),
+        decided_at: decided_at.as_u64(),
+        exists,
+    })
+}
+
+/// The transaction whose `MarketSettled` log put `root` on chain for
+/// `market_hash`, searched for in the last `lookback_blocks`; the newest if
+/// there were several.
+pub async fn settlement_tx(
+    chain: &ChainConfig,
+    market_hash: [u8; 32],
+    root: [u8; 32],
+    lookback_blocks: u64,
+) -> Result<Option<SubmissionReceipt>> {
+    let contract = read_client(chain)?;
+    let provider = contract.client();
+    let head = provider.get_block_number().await?.as_u64();
+
+    let logs = contract
+        .market_settled_filter()
+        .topic1(H256::from(market_hash))
+        .from_block(head.saturating_sub(lookback_blocks))
+        .to_block(head)
+        .query_with_meta()
+        .await?;
+
+    let Some((_, meta)) = logs
+        .into_iter()
+        .filter(|(event, _)| event.merkle_root == root)
+        .max_by_key(|(_, meta)| (meta.block_number, meta.log_index))
+    else {
+        return Ok(None);
+    };
+
+    let submitter = provider
+        .get_transaction(meta.transaction_hash)
+        .await?
+        .map(|t| t.from);
+    let gas_used = provider
+        .get_transaction_receipt(meta.transaction_hash)
+        .await?
+        .and_then(|r| r.gas_used)
+        .map(|g| g.as_u64());
+
+    Ok(Some(SubmissionReceipt {
+        tx_hash: meta.transaction_hash,
+        block_number: Some(meta.block_number.as_u64()),
+        gas_used,
+        submitter: submitter.unwrap_or_default(),
+    }))
+}
+
+pub enum TxState {
+    /// In the canonical chain; `confirmations` counts the inclusion block.
+    Mined {
+        block_number: u64,
+        confirmations: u64,
+        success: bool,
+    },
+    /// Known to the node but not in a block (possibly sent back by a reorg).
+    Pending,
+    /// Neither mined nor in the mempool: dropped, or reorged out and evicted.
+    Unknown,
+}
+
+pub async fn tx_state(chain: &ChainConfig, tx_hash: TxHash) -> Result<TxState> {
+    let provider = provider(chain)?;
+
+    if let Some(receipt) = provider.get_transaction_receipt(tx_hash).await?
+        && let Some(block) = receipt.block_number
+    {
+        let head = provider.get_block_number().await?.as_u64();
+        let block_number = block.as_u64();
+
+        return Ok(TxState::Mined {
+            block_number,
+            confirmations: (head + 1).saturating_sub(block_number),
+            success: receipt.status.is_none_or(|s| s.as_u64() == 1),
+        });
+    }
+
+    if provider.get_transaction(tx_hash).await?.is_some() {
+        return Ok(TxState::Pending);
+    }
+
+    Ok(TxState::Unknown)
+}
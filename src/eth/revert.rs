@@ -0,0 +1,85 @@
+// backend/src/eth/revert.rs
+
+use ethers::abi::Token;
+use ethers::contract::EthError;
+use ethers::prelude::*;
+
+use super::ORACLESETTLE_ABI;
+use super::client::EthClient;
+
+// require() messages the deployed contract reverts with, and the names
+// operators look for in `last_error`
+const KNOWN_REASONS: &[(&str, &str)] = &[
+    ("Already settled", "SettlementAlreadyExists"),
+    ("Not authorized", "NotAuthorized"),
+    ("Not found", "SettlementNotFound"),
+    ("Market not settled", "MarketNotSettled"),
+];
+
+/// Describes a failed contract interaction. Reverts are decoded into the
+/// contract's error name and reason; anything else keeps its own message.
+pub fn describe(err: &anyhow::Error) -> String {
+    match revert_data(err) {
+        Some(data) => describe_revert(&data),
+        None => err.to_string(),
+    }
+}
+
+/// Decodes revert data returned by the node: a custom error declared in the
+/// contract's ABI, or a `require` message.
+pub fn describe_revert(data: &[u8]) -> String {
+    if let Some(reason) = String::decode_with_selector(data) {
+        return match KNOWN_REASONS.iter().find(|(known, _)| *known == reason) {
+            Some((_, name)) => format!("{}: contract reverted with \"{}\"", name, reason),
+            None => format!("contract reverted with \"{}\"", reason),
+        };
+    }
+
+    custom_error(data).unwrap_or_else(|| {
+        format!(
+            "contract reverted without a reason (data 0x{})",
+            hex::encode(data)
+        )
+    })
+}
+
+fn custom_error(data: &[u8]) -> Option<String> {
+    let (selector, args) = (data.get(..4)?, &data[4..]);
+    let error = ORACLESETTLE_ABI
+        .errors()
+        .find(|e| e.signature().as_bytes()[..4] == *selector)?;
+    let tokens = error.decode(args).ok()?;
+
+    let params = error
+        .inputs
+        .iter()
+        .zip(tokens)
+        .map(|(param, token)| format!("{}={}", param.name, display_token(token)))
+        .collect::<Vec<_>>();
+
+    Some(format!("{}({})", error.name, params.join(", ")))
+}
+
+fn display_token(token: Token) -> String {
+    match token {
+        Token::Address(a) => format!("{:?}", a),
+        Token::FixedBytes(b) | Token::Bytes(b) => format!("0x{}", hex::encode(b)),
+        other => other.to_string(),
+    }
+}
+
+// the revert payload, wherever in the chain it surfaced: estimating gas while
+// filling the tx, a contract call, or the raw provider
+fn revert_data(err: &anyhow::Error) -> Option<Bytes> {
+    err.chain().find_map(|e| {
+        if let Some(e) = e.downcast_ref::<ContractError<EthClient>>() {
+            return e.as_revert().cloned();
+        }
+
+        if let Some(e) = e.downcast_ref::<<EthClient as Middleware>::Error>() {
+            return e.as_error_response()?.as_revert_data();
+        }
+
+        RpcError::as_error_response(e.downcast_ref::<ProviderError>()?)?.as_revert_data()
+    })
+}
@@ -0,0 +1,137 @@
+// backend/src/eth/sender.rs
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use ethers::prelude::*;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+
+use super::OracleSettle;
+use super::chains::ChainRegistry;
+use super::client::{EthClient, provider};
+
+/// Signer clients built once per submitter wallet, shared by every
+/// submission. Nonces are handed out locally so concurrent transactions from
+/// the same wallet never collide, and a semaphore per chain caps how many are
+/// in flight, so a congested chain can't hold up the others.
+pub struct EthSender {
+    providers: HashMap<u64, Provider<Http>>,
+    wallets: HashMap<(u64, Address), SenderWallet>,
+    in_flight: HashMap<u64, Arc<Semaphore>>,
+}
+
+pub struct SenderWallet {
+    pub contract: OracleSettle<EthClient>,
+    // next nonce to hand out; None until read from the chain (or after a resync)
+    nonce: Mutex<Option<U256>>,
+}
+
+impl EthSender {
+    pub fn new(chains: &ChainRegistry, max_in_flight: usize) -> Result<Self> {
+        let mut providers = HashMap::new();
+        let mut wallets = HashMap::new();
+        let mut in_flight = HashMap::new();
+
+        for target in chains.targets() {
+            let chain = &target.config;
+            let provider = provider(chain)?;
+
+            for wallet in target.wallets.signers() {
+                let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet.clone()));
+                wallets.insert(
+                    (chain.chain_id, wallet.address()),
+                    SenderWallet {
+                        contract: OracleSettle::new(chain.contract_address, client),
+                        nonce: Mutex::new(None),
+                    },
+                );
+            }
+
+            providers.insert(chain.chain_id, provider);
+            in_flight.insert(
+                chain.chain_id,
+                Arc::new(Semaphore::new(max_in_flight.max(1))),
+            );
+        }
+
+        Ok(Self {
+            providers,
+            wallets,
+            in_flight,
+        })
+    }
+
+    pub fn provider(&self, chain_id: u64) -> Result<&Provider<Http>> {
+        self.providers
+            .get(&chain_id)
+            .ok_or_else(|| anyhow!("chain {} is not configured", chain_id))
+    }
+
+    pub fn wallet(&self, chain_id: u64, address: Address) -> Result<&SenderWallet> {
+        self.wallets.get(&(chain_id, address)).ok_or_else(|| {
+            anyhow!(
+                "wallet {:?} is not configured on chain {}",
+                address,
+                chain_id
+            )
+        })
+    }
+
+    /// Held for the whole sign/broadcast/receipt round trip of one submission.
+    pub async fn permit(&self, chain_id: u64) -> Result<OwnedSemaphorePermit> {
+        let in_flight = self
+            .in_flight
+            .get(&chain_id)
+            .ok_or_else(|| anyhow!("chain {} is not configured", chain_id))?;
+
+        Ok(in_flight
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("in-flight semaphore closed"))
+    }
+}
+
+impl SenderWallet {
+    /// Reserves the next nonce and hands it to `build`. The nonce is only
+    /// consumed when `build` succeeds, so a failed gas estimate leaves no gap.
+    pub async fn with_nonce<F, Fut, T>(&self, build: F) -> Result<T>
+    where
+        F: FnOnce(U256) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let mut next = self.nonce.lock().await;
+
+        let nonce = match *next {
+            Some(n) => n,
+            None => {
+                let client = self.contract.client();
+                client
+                    .get_transaction_count(client.address(), Some(BlockNumber::Pending.into()))
+                    .await?
+            }
+        };
+
+        let built = build(nonce).await?;
+        *next = Some(nonce + 1);
+
+        Ok(built)
+    }
+
+    /// Forgets the local nonce so the next reservation re-reads the pending
+    /// count. Called whenever a broadcast is rejected, since the nonce it held
+    /// either was taken elsewhere or now leaves a gap.
+    pub async fn resync(&self) {
+        *self.nonce.lock().await = None;
+    }
+}
+
+/// Broadcast errors fixed by re-reading the nonce and signing again with the
+/// same wallet: the nonce was used by another tx, or a different tx already
+/// sits in the mempool at that nonce.
+pub fn is_nonce_error(err: &str) -> bool {
+    let err = err.to_lowercase();
+
+    err.contains("nonce too low") || err.contains("replacement transaction underpriced")
+}
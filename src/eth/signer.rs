@@ -0,0 +1,248 @@
+// backend/src/eth/signer.rs
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow};
+use axum::async_trait;
+use ethers::prelude::*;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use serde::Deserialize;
+
+use super::chains::ChainConfig;
+
+/// Where a chain's submitter keys come from. `local` reads raw private keys
+/// and is meant for development; deployments should use an encrypted
+/// `keystore` or `kms`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignerType {
+    #[default]
+    Local,
+    Keystore,
+    Kms,
+}
+
+impl FromStr for SignerType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "local" => Ok(Self::Local),
+            "keystore" => Ok(Self::Keystore),
+            "kms" => Ok(Self::Kms),
+            other => Err(anyhow!(
+                "unknown SIGNER_TYPE {:?}; expected local, keystore or kms",
+                other
+            )),
+        }
+    }
+}
+
+/// `SIGNER_TYPE`, for chains that don't pick their own.
+pub fn default_signer_type() -> Result<SignerType> {
+    std::env::var("SIGNER_TYPE").unwrap_or_default().parse()
+}
+
+/// Signs submissions for one wallet, whichever backend holds its key.
+#[derive(Debug, Clone)]
+pub enum TxSigner {
+    Local(LocalWallet),
+    #[cfg(feature = "kms")]
+    Kms(AwsSigner),
+}
+
+#[derive(Debug)]
+pub enum TxSignerError {
+    Local(WalletError),
+    #[cfg(feature = "kms")]
+    Kms(AwsSignerError),
+}
+
+impl fmt::Display for TxSignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(e) => write!(f, "{}", e),
+            #[cfg(feature = "kms")]
+            Self::Kms(e) => write!(f, "kms: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TxSignerError {}
+
+#[async_trait]
+impl Signer for TxSigner {
+    type Error = TxSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Local(w) => w.sign_message(message).await.map_err(TxSignerError::Local),
+            #[cfg(feature = "kms")]
+            Self::Kms(k) => k.sign_message(message).await.map_err(TxSignerError::Kms),
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Local(w) => w.sign_transaction(tx).await.map_err(TxSignerError::Local),
+            #[cfg(feature = "kms")]
+            Self::Kms(k) => k.sign_transaction(tx).await.map_err(TxSignerError::Kms),
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Local(w) => w
+                .sign_typed_data(payload)
+                .await
+                .map_err(TxSignerError::Local),
+            #[cfg(feature = "kms")]
+            Self::Kms(k) => k.sign_typed_data(payload).await.map_err(TxSignerError::Kms),
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            Self::Local(w) => w.address(),
+            #[cfg(feature = "kms")]
+            Self::Kms(k) => k.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            Self::Local(w) => w.chain_id(),
+            #[cfg(feature = "kms")]
+            Self::Kms(k) => k.chain_id(),
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            Self::Local(w) => Self::Local(w.with_chain_id(chain_id)),
+            #[cfg(feature = "kms")]
+            Self::Kms(k) => Self::Kms(k.with_chain_id(chain_id)),
+        }
+    }
+}
+
+/// Builds the submitter signers for `config` with its `signer_type`, or
+/// `default` when it names none. No key material means no signers, which
+/// leaves the chain read-only.
+pub async fn load(config: &ChainConfig, default: SignerType) -> Result<Vec<TxSigner>> {
+    let chain_id = config.chain_id;
+
+    let signers = match config.signer_type.unwrap_or(default) {
+        SignerType::Local => {
+            let keys = match &config.private_keys_env {
+                Some(var) => std::env::var(var)
+                    .with_context(|| format!("{} not set for chain {}", var, chain_id))?
+                    .split(',')
+                    .map(str::to_string)
+                    .collect(),
+                None => config.private_keys.clone(),
+            };
+
+            let mut signers = Vec::new();
+            for key in keys.iter().map(|k| k.trim()).filter(|k| !k.is_empty()) {
+                let wallet: LocalWallet = key.parse()?;
+                signers.push(TxSigner::Local(wallet));
+            }
+
+            if !signers.is_empty() {
+                tracing::warn!(
+                    "Chain {} signs with plaintext private keys; use SIGNER_TYPE=keystore or kms outside development",
+                    chain_id
+                );
+            }
+            signers
+        }
+        SignerType::Keystore => {
+            if config.keystores.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let password = keystore_password(config)?;
+            let mut signers = Vec::new();
+            for path in &config.keystores {
+                signers.push(TxSigner::Local(
+                    decrypt_keystore(path.clone(), password.clone()).await?,
+                ));
+            }
+            signers
+        }
+        SignerType::Kms => kms_signers(config).await?,
+    };
+
+    Ok(signers
+        .into_iter()
+        .map(|s| s.with_chain_id(chain_id))
+        .collect())
+}
+
+// from the file named by keystore_password_file, else the env var named by
+// keystore_password_env (KEYSTORE_PASSWORD by default)
+fn keystore_password(config: &ChainConfig) -> Result<String> {
+    if let Some(path) = &config.keystore_password_file {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("reading keystore password file {}", path.display()))?;
+        return Ok(raw.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    let var = config
+        .keystore_password_env
+        .as_deref()
+        .unwrap_or("KEYSTORE_PASSWORD");
+    std::env::var(var).with_context(|| format!("{} not set for chain {}", var, config.chain_id))
+}
+
+// scrypt is deliberately slow, so keep it off the runtime threads
+async fn decrypt_keystore(path: PathBuf, password: String) -> Result<LocalWallet> {
+    tokio::task::spawn_blocking(move || {
+        LocalWallet::decrypt_keystore(&path, password)
+            .with_context(|| format!("decrypting keystore {}", path.display()))
+    })
+    .await?
+}
+
+#[cfg(feature = "kms")]
+async fn kms_signers(config: &ChainConfig) -> Result<Vec<TxSigner>> {
+    use rusoto_core::Region;
+    use rusoto_kms::KmsClient;
+
+    if config.kms_key_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // region and credentials come from the usual AWS_* env vars / profile
+    let kms = KmsClient::new(Region::default());
+
+    let mut signers = Vec::new();
+    for key_id in &config.kms_key_ids {
+        let signer = AwsSigner::new(kms.clone(), key_id, config.chain_id)
+            .await
+            .with_context(|| format!("loading KMS key {}", key_id))?;
+        signers.push(TxSigner::Kms(signer));
+    }
+    Ok(signers)
+}
+
+#[cfg(not(feature = "kms"))]
+async fn kms_signers(config: &ChainConfig) -> Result<Vec<TxSigner>> {
+    if config.kms_key_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    Err(anyhow!(
+        "chain {} uses KMS signers, which need the `kms` feature",
+        config.chain_id
+    ))
+}
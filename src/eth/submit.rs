@@ -1,30 +1,299 @@
 // backend/src/eth/submit.rs
 
-use super::client::eth_client;
-use anyhow::Result;
+use super::chains::ChainTarget;
+use super::revert::{describe, describe_revert};
+use super::sender::{EthSender, SenderWallet, is_nonce_error};
+use super::wallets::{SubmitterWallet, is_wallet_error};
+use anyhow::{Result, anyhow};
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use std::future::Future;
 
-pub async fn submit_settlement(
+#[derive(Debug, Clone)]
+pub struct SubmissionReceipt {
+    pub tx_hash: TxHash,
+    pub block_number: Option<u64>,
+    pub gas_used: Option<u64>,
+    pub submitter: Address,
+}
+
+impl SubmissionReceipt {
+    fn from_receipt(r: TransactionReceipt, submitter: Address) -> Self {
+        Self {
+            tx_hash: r.transaction_hash,
+            block_number: r.block_number.map(|b| b.as_u64()),
+            gas_used: r.gas_used.map(|g| g.as_u64()),
+            submitter,
+        }
+    }
+}
+
+/// What submitting a settlement would take, from a dry run that sends nothing.
+pub enum SettlementEstimate {
+    Estimated {
+        from: Address,
+        gas: U256,
+        gas_price: U256,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+    },
+    // the node says the call would revert; retrying won't help
+    Reverted {
+        from: Address,
+        reason: String,
+    },
+}
+
+/// A fully signed transaction whose hash is known before it is broadcast.
+#[derive(Debug, Clone)]
+pub struct SignedSettlement {
+    pub tx_hash: TxHash,
+    pub raw: Bytes,
+    pub submitter: Address,
+}
+
+pub enum IntentStatus {
+    Mined(SubmissionReceipt),
+    Pending,
+    Dropped,
+}
+
+// times one wallet re-signs after its nonce went stale before rotating away
+const NONCE_RETRIES: u32 = 2;
+
+/// Signs, records the intent via `record_intent`, then broadcasts. The intent
+/// must be durable before anything hits the network so a crash mid-send can be
+/// recovered with `resume_intent` instead of signing a second transaction.
+pub async fn submit_settlement<F, Fut>(
+    sender: &EthSender,
+    target: &ChainTarget,
+    market_id: [u8; 32],
+    root: [u8; 32],
+    outcome: u64,
+    decided_at: u64,
+    record_intent: F,
+) -> Result<Option<SubmissionReceipt>>
+where
+    F: Fn(SignedSettlement) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let chain_id = target.config.chain_id;
+    let _permit = sender.permit(chain_id).await?;
+    let provider = sender.provider(chain_id)?;
+    let mut last_err = None;
+
+    for wallet in target.wallets.rotation()? {
+        let signer = sender.wallet(chain_id, wallet.signer.address())?;
+        let mut attempts = 0;
+
+        let result = loop {
+            let result = async {
+                let signed =
+                    sign_with(signer, wallet, market_id, root, outcome, decided_at).await?;
+                record_intent(signed.clone()).await?;
+                broadcast(provider, signer, &signed).await
+            }
+            .await;
+
+            match result {
+                // the intent just recorded is overwritten by the re-signed tx;
+                // its nonce is spent elsewhere so it can never be mined
+                Err(e) if attempts < NONCE_RETRIES && is_nonce_error(&e.to_string()) => {
+                    attempts += 1;
+                    tracing::warn!(
+                        "wallet {:?} nonce out of sync, re-signing: {}",
+                        wallet.signer.address(),
+                        e
+                    );
+                }
+                other => break other,
+            }
+        };
+
+        match result {
+            Ok(receipt) => {
+                wallet.record_success();
+                return Ok(receipt);
+            }
+            Err(e) => {
+                let msg = describe(&e);
+                let rotate = is_wallet_error(&msg);
+                wallet.record_failure(&msg, rotate);
+
+                if !rotate {
+                    // reverts etc. would fail the same way on any wallet
+                    return Err(e);
+                }
+
+                tracing::warn!(
+                    "wallet {:?} failed, rotating: {}",
+                    wallet.signer.address(),
+                    msg
+                );
+                last_err = Some(e);
+            }
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| anyhow!("all submitter wallets failed")))
+}
+
+/// Estimates gas for `submitSettlement` from the chain's first submitter
+/// wallet, along with the node's current fee data. Nothing is signed and no
+/// nonce is taken.
+pub async fn estimate_settlement(
+    sender: &EthSender,
+    target: &ChainTarget,
+    market_id: [u8; 32],
+    root: [u8; 32],
+    outcome: u64,
+    decided_at: u64,
+) -> Result<SettlementEstimate> {
+    let chain_id = target.config.chain_id;
+    let from = target
+        .wallets
+        .signers()
+        .next()
+        .ok_or_else(|| anyhow!("no submitter wallets configured for this chain"))?
+        .address();
+    let signer = sender.wallet(chain_id, from)?;
+    let provider = sender.provider(chain_id)?;
+
+    let call =
+        signer
+            .contract
+            .submit_settlement(market_id, root, outcome.into(), decided_at.into());
+
+    let gas = match call.estimate_gas().await {
+        Ok(gas) => gas,
+        Err(e) if e.is_revert() => {
+            let reason = match e.as_revert() {
+                Some(data) => describe_revert(data),
+                None => e.to_string(),
+            };
+            return Ok(SettlementEstimate::Reverted { from, reason });
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let gas_price = provider.get_gas_price().await?;
+    // chains without EIP-1559 pay gas_price and no tip
+    let (max_fee_per_gas, max_priority_fee_per_gas) = provider
+        .estimate_eip1559_fees(None)
+        .await
+        .unwrap_or((gas_price, U256::zero()));
+
+    Ok(SettlementEstimate::Estimated {
+        from,
+        gas,
+        gas_price,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+    })
+}
+
+async fn sign_with(
+    signer: &SenderWallet,
+    wallet: &SubmitterWallet,
     market_id: [u8; 32],
     root: [u8; 32],
     outcome: u64,
     decided_at: u64,
-) -> Result<()> {
-    let contract = eth_client().await?;
-
-    let receipt = contract
-        .submit_settlement(
-            market_id.into(),
-            root.into(),
-            outcome.into(),
-            decided_at.into(),
-        )
-        .send()
-        .await?
-        .await?;
-
-    if let Some(receipt) = receipt {
-        println!("TX confirmed: {:?}", receipt.transaction_hash);
+) -> Result<SignedSettlement> {
+    let client = signer.contract.client();
+    let address = wallet.signer.address();
+    let balance = client.get_balance(address, None).await.ok();
+
+    let call =
+        signer
+            .contract
+            .submit_settlement(market_id, root, outcome.into(), decided_at.into());
+
+    signer
+        .with_nonce(|nonce| async move {
+            let mut tx = call.tx;
+            tx.set_nonce(nonce);
+            client.fill_transaction(&mut tx, None).await?;
+
+            let signature = client.signer().sign_transaction(&tx).await?;
+            let raw = tx.rlp_signed(&signature);
+            wallet.record_chain_state(Some(nonce), balance);
+
+            Ok(SignedSettlement {
+                tx_hash: H256::from(keccak256(&raw)),
+                raw,
+                submitter: address,
+            })
+        })
+        .await
+}
+
+async fn broadcast(
+    provider: &Provider<Http>,
+    signer: &SenderWallet,
+    signed: &SignedSettlement,
+) -> Result<Option<SubmissionReceipt>> {
+    let pending = match provider.send_raw_transaction(signed.raw.clone()).await {
+        Ok(pending) => pending,
+        // these exact bytes are already in the mempool
+        Err(e) if e.to_string().to_lowercase().contains("already known") => {
+            PendingTransaction::new(signed.tx_hash, provider)
+        }
+        Err(e) => {
+            signer.resync().await;
+            return Err(e.into());
+        }
+    };
+
+    let receipt = pending.await?;
+
+    if let Some(receipt) = &receipt {
+        tracing::info!(tx = ?receipt.transaction_hash, "tx confirmed");
+    }
+
+    Ok(receipt.map(|r| SubmissionReceipt::from_receipt(r, signed.submitter)))
+}
+
+/// Checks what became of a previously recorded intent. Unknown transactions
+/// are re-broadcast verbatim (same nonce, same hash), so this can never
+/// produce a second settlement.
+pub async fn resume_intent(
+    provider: &Provider<Http>,
+    signed: &SignedSettlement,
+) -> Result<IntentStatus> {
+    if let Some(r) = provider.get_transaction_receipt(signed.tx_hash).await? {
+        return Ok(IntentStatus::Mined(SubmissionReceipt::from_receipt(
+            r,
+            signed.submitter,
+        )));
     }
 
-    Ok(())
+    if provider.get_transaction(signed.tx_hash).await?.is_some() {
+        return Ok(IntentStatus::Pending);
+    }
+
+    match provider.send_raw_transaction(signed.raw.clone()).await {
+        Ok(_) => Ok(IntentStatus::Pending),
+        Err(e) => {
+            let msg = e.to_string().to_lowercase();
+
+            if msg.contains("already known") {
+                return Ok(IntentStatus::Pending);
+            }
+
+            // the nonce may have been consumed by this very tx in the meantime
+            if let Some(r) = provider.get_transaction_receipt(signed.tx_hash).await? {
+                return Ok(IntentStatus::Mined(SubmissionReceipt::from_receipt(
+                    r,
+                    signed.submitter,
+                )));
+            }
+
+            if is_wallet_error(&msg) {
+                Ok(IntentStatus::Dropped)
+            } else {
+                Err(e.into())
+            }
+        }
+    }
 }
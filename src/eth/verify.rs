@@ -0,0 +1,42 @@
+// backend/src/eth/verify.rs
+
+use anyhow::{Result, anyhow};
+use ethers::prelude::*;
+use uuid::Uuid;
+
+use crate::proof::to_fixed;
+
+/// Text a reporter signs with `personal_sign` (EIP-191). The value goes in as
+/// its 8-decimal fixed-point integer so every client renders it identically.
+pub fn report_message(market_id: Uuid, value: f64, timestamp: i64) -> String {
+    format!(
+        "oraclesettle report\nmarket: {}\nvalue_e8: {}\ntimestamp: {}",
+        market_id,
+        to_fixed(value),
+        timestamp
+    )
+}
+
+/// Checks `signature` (65-byte hex) over `report_message` recovers to
+/// `reporter`, returning the parsed address.
+pub fn verify_report(
+    market_id: Uuid,
+    value: f64,
+    timestamp: i64,
+    reporter: &str,
+    signature: &str,
+) -> Result<Address> {
+    let reporter: Address = reporter
+        .parse()
+        .map_err(|_| anyhow!("reporter_address is not a valid address"))?;
+    let signature: Signature = signature
+        .trim_start_matches("0x")
+        .parse()
+        .map_err(|_| anyhow!("signature is not a valid 65-byte hex signature"))?;
+
+    signature
+        .verify(report_message(market_id, value, timestamp), reporter)
+        .map_err(|_| anyhow!("signature does not match reporter_address"))?;
+
+    Ok(reporter)
+}
@@ -0,0 +1,160 @@
+// backend/src/eth/wallets.rs
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::signer::TxSigner;
+
+// how long a wallet sits out after a nonce/funds failure before it is tried again
+const COOLDOWN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WalletHealth {
+    pub address: String,
+    pub nonce: Option<u64>,
+    pub balance_wei: Option<String>,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+pub struct SubmitterWallet {
+    pub signer: TxSigner,
+    health: Mutex<WalletHealth>,
+    cooldown_until: Mutex<Option<Instant>>,
+}
+
+impl SubmitterWallet {
+    fn new(signer: TxSigner) -> Self {
+        let health = WalletHealth {
+            address: format!("{:?}", signer.address()),
+            nonce: None,
+            balance_wei: None,
+            healthy: true,
+            consecutive_failures: 0,
+            last_error: None,
+            last_used_at: None,
+        };
+
+        Self {
+            signer,
+            health: Mutex::new(health),
+            cooldown_until: Mutex::new(None),
+        }
+    }
+
+    fn available(&self) -> bool {
+        match *self.cooldown_until.lock().unwrap() {
+            Some(until) => Instant::now() >= until,
+            None => true,
+        }
+    }
+
+    pub fn record_chain_state(&self, nonce: Option<U256>, balance: Option<U256>) {
+        let mut h = self.health.lock().unwrap();
+        if let Some(n) = nonce {
+            h.nonce = Some(n.as_u64());
+        }
+        if let Some(b) = balance {
+            h.balance_wei = Some(b.to_string());
+        }
+    }
+
+    pub fn record_success(&self) {
+        *self.cooldown_until.lock().unwrap() = None;
+
+        let mut h = self.health.lock().unwrap();
+        h.healthy = true;
+        h.consecutive_failures = 0;
+        h.last_error = None;
+        h.last_used_at = Some(Utc::now());
+    }
+
+    pub fn record_failure(&self, err: &str, rotate: bool) {
+        if rotate {
+            *self.cooldown_until.lock().unwrap() = Some(Instant::now() + COOLDOWN);
+        }
+
+        let mut h = self.health.lock().unwrap();
+        h.healthy = !rotate;
+        h.consecutive_failures += 1;
+        h.last_error = Some(err.to_string());
+        h.last_used_at = Some(Utc::now());
+    }
+
+    pub fn health(&self) -> WalletHealth {
+        self.health.lock().unwrap().clone()
+    }
+}
+
+pub struct WalletPool {
+    wallets: Vec<SubmitterWallet>,
+    next: AtomicUsize,
+}
+
+impl WalletPool {
+    /// An empty pool is allowed so the API can still boot without chain
+    /// credentials; submissions will fail until keys are set.
+    pub fn new(signers: Vec<TxSigner>) -> Self {
+        Self {
+            wallets: signers.into_iter().map(SubmitterWallet::new).collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.wallets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wallets.is_empty()
+    }
+
+    /// Round-robin order of wallets to try for one submission. Wallets in
+    /// cooldown go to the back so they're only used when nothing else is left.
+    pub fn rotation(&self) -> Result<Vec<&SubmitterWallet>> {
+        if self.wallets.is_empty() {
+            return Err(anyhow!("no submitter wallets configured for this chain"));
+        }
+
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % self.wallets.len();
+
+        let ordered: Vec<&SubmitterWallet> = (0..self.wallets.len())
+            .map(|i| &self.wallets[(start + i) % self.wallets.len()])
+            .collect();
+
+        let (mut ready, cooling): (Vec<_>, Vec<_>) =
+            ordered.into_iter().partition(|w| w.available());
+        ready.extend(cooling);
+
+        Ok(ready)
+    }
+
+    pub fn signers(&self) -> impl Iterator<Item = &TxSigner> {
+        self.wallets.iter().map(|w| &w.signer)
+    }
+
+    pub fn health(&self) -> Vec<WalletHealth> {
+        self.wallets.iter().map(|w| w.health()).collect()
+    }
+}
+
+/// Errors tied to a specific signer (stuck nonce, drained balance) where
+/// another wallet has a fair chance of succeeding.
+pub fn is_wallet_error(err: &str) -> bool {
+    let err = err.to_lowercase();
+
+    err.contains("nonce too low")
+        || err.contains("nonce too high")
+        || err.contains("replacement transaction underpriced")
+        || err.contains("already known")
+        || err.contains("insufficient funds")
+}
@@ -0,0 +1,322 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgExecutor;
+use sqlx::postgres::PgListener;
+use tokio::sync::{broadcast, watch};
+use uuid::Uuid;
+
+use crate::state::AppState;
+#[cfg(feature = "webhooks")]
+use crate::webhooks;
+
+// see the events_notify trigger
+const NOTIFY_CHANNEL: &str = "oraclesettle_events";
+const DISPATCH_LOCK_KEY: i64 = 0x6f72_6163_6c65_0001;
+const DISPATCH_BATCH: i64 = 500;
+const DISPATCH_POLL: Duration = Duration::from_secs(1);
+// while polling only, how often to try LISTEN again
+const LISTEN_RETRY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    MarketCreated {
+        market_id: Uuid,
+        question: String,
+        // None when it closes on an event
+        closes_at: Option<DateTime<Utc>>,
+    },
+    MarketOpened {
+        market_id: Uuid,
+    },
+    MarketClosingSoon {
+        market_id: Uuid,
+        closes_at: DateTime<Utc>,
+        lead_secs: i32,
+    },
+    MarketClosed {
+        market_id: Uuid,
+        // set when an operator closed it through /markets/{id}/close
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    MarketExtended {
+        market_id: Uuid,
+        closes_at: DateTime<Utc>,
+    },
+    MarketUnresolved {
+        market_id: Uuid,
+        resolve_deadline: DateTime<Utc>,
+    },
+    MarketVoided {
+        market_id: Uuid,
+        reason: Option<String>,
+    },
+    // awaiting chain confirmation; settlement_decided follows once it is final
+    SettlementProposed {
+        market_id: Uuid,
+        outcome: f64,
+        decided_at: DateTime<Utc>,
+    },
+    SettlementDecided {
+        market_id: Uuid,
+        outcome: f64,
+        decided_at: DateTime<Utc>,
+    },
+    ReportAccepted {
+        market_id: Uuid,
+        report_id: Uuid,
+        source: String,
+        value: f64,
+        // an existing report replaced in place
+        updated: bool,
+    },
+    ReportRetracted {
+        market_id: Uuid,
+        report_id: Uuid,
+        source: String,
+    },
+    MarketGroupBlocked {
+        group_id: Uuid,
+        reason: String,
+    },
+    BatchCreated {
+        batch_id: Uuid,
+        merkle_root: String,
+        size: usize,
+    },
+    // an item's chain submission failed; see POST /batches/{id}/void
+    BatchFailed {
+        batch_id: Uuid,
+    },
+    TxConfirmed {
+        outbox_id: Uuid,
+        market_id: String,
+        tx_hash: String,
+    },
+    TxDropped {
+        outbox_id: Uuid,
+        market_id: String,
+        tx_hash: String,
+    },
+    SourceAlertRaised {
+        source: String,
+        alert: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        related_source: Option<String>,
+    },
+}
+
+/// In-process fan-out of lifecycle events, fed by the dispatcher once they
+/// are committed. Publishing never blocks; slow subscribers lag and skip
+/// ahead rather than holding up the loops.
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<Event>,
+    recorded: Arc<watch::Sender<i64>>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        let (recorded, _) = watch::channel(0);
+        Self {
+            tx,
+            recorded: Arc::new(recorded),
+        }
+    }
+
+    fn publish(&self, event: Event) {
+        // no subscribers is fine
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.tx.subscribe()
+    }
+
+    /// Id of the latest dispatched row in the `events` table; changes
+    /// whenever the dispatcher fans out something new.
+    pub fn watch_recorded(&self) -> watch::Receiver<i64> {
+        self.recorded.subscribe()
+    }
+}
+
+impl Event {
+    /// Every value `kind` can return.
+    pub const KINDS: &'static [&'static str] = &[
+        "market_created",
+        "market_opened",
+        "market_closing_soon",
+        "market_closed",
+        "market_extended",
+        "market_unresolved",
+        "market_voided",
+        "settlement_proposed",
+        "settlement_decided",
+        "report_accepted",
+        "report_retracted",
+        "market_group_blocked",
+        "batch_created",
+        "batch_failed",
+        "tx_confirmed",
+        "tx_dropped",
+        "source_alert_raised",
+    ];
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::MarketCreated { .. } => "market_created",
+            Event::MarketOpened { .. } => "market_opened",
+            Event::MarketClosingSoon { .. } => "market_closing_soon",
+            Event::MarketClosed { .. } => "market_closed",
+            Event::MarketExtended { .. } => "market_extended",
+            Event::MarketUnresolved { .. } => "market_unresolved",
+            Event::MarketVoided { .. } => "market_voided",
+            Event::SettlementProposed { .. } => "settlement_proposed",
+            Event::SettlementDecided { .. } => "settlement_decided",
+            Event::ReportAccepted { .. } => "report_accepted",
+            Event::ReportRetracted { .. } => "report_retracted",
+            Event::MarketGroupBlocked { .. } => "market_group_blocked",
+            Event::BatchCreated { .. } => "batch_created",
+            Event::BatchFailed { .. } => "batch_failed",
+            Event::TxConfirmed { .. } => "tx_confirmed",
+            Event::TxDropped { .. } => "tx_dropped",
+            Event::SourceAlertRaised { .. } => "source_alert_raised",
+        }
+    }
+}
+
+/// Writes `event` to the `events` table. Called inside the transaction that
+/// makes the change it describes, so the event is stored if and only if the
+/// change commits. The insert trigger's NOTIFY goes out on commit and wakes
+/// the dispatcher.
+pub async fn append<'e, E: PgExecutor<'e>>(db: E, event: &Event) -> Result<i64, sqlx::Error> {
+    let payload = serde_json::to_value(event).unwrap();
+
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO events (kind, payload)
+        VALUES ($1, $2)
+        RETURNING id
+        "#,
+        event.kind(),
+        payload
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Fans out committed events in id order: queues a delivery for each webhook
+/// subscribed to the event, marks it dispatched in the same transaction, and
+/// then publishes it on the in-process bus. `GET /changes` only pages through
+/// dispatched events.
+pub async fn dispatch_loop(state: AppState) {
+    let mut listener = listen(&state).await;
+    let mut listen_tried = Instant::now();
+
+    loop {
+        match dispatch(&state).await {
+            // more may be waiting
+            Ok(n) if n as i64 == DISPATCH_BATCH => continue,
+            Ok(_) => {}
+            Err(e) => tracing::error!("event dispatch failed: {}", e),
+        }
+
+        match listener.as_mut() {
+            Some(listener) => {
+                if let Ok(Err(e)) = tokio::time::timeout(DISPATCH_POLL, listener.recv()).await {
+                    // PgListener reconnects on the next recv
+                    tracing::warn!("event dispatcher listener error: {}", e);
+                    tokio::time::sleep(DISPATCH_POLL).await;
+                }
+            }
+            None => {
+                tokio::time::sleep(DISPATCH_POLL).await;
+
+                // the database may just have been down when we started
+                if listen_tried.elapsed() >= LISTEN_RETRY {
+                    listener = listen(&state).await;
+                    listen_tried = Instant::now();
+                }
+            }
+        }
+    }
+}
+
+async fn listen(state: &AppState) -> Option<PgListener> {
+    let result = async {
+        let mut listener = PgListener::connect_with(&state.db).await?;
+        listener.listen(NOTIFY_CHANNEL).await?;
+        Ok::<_, sqlx::Error>(listener)
+    }
+    .await;
+
+    match result {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            tracing::warn!("event dispatcher could not listen, polling only: {}", e);
+            None
+        }
+    }
+}
+
+async fn dispatch(state: &AppState) -> Result<usize, sqlx::Error> {
+    let mut tx = state.db.begin().await?;
+
+    // one dispatcher at a time keeps the fan-out in id order across replicas
+    let leader = sqlx::query_scalar!(
+        r#"SELECT pg_try_advisory_xact_lock($1) AS "leader!""#,
+        DISPATCH_LOCK_KEY
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if !leader {
+        return Ok(0);
+    }
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, kind, payload
+        FROM events
+        WHERE dispatched_at IS NULL
+        ORDER BY id ASC
+        LIMIT $1
+        "#,
+        DISPATCH_BATCH
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let Some(last) = rows.last().map(|r| r.id) else {
+        return Ok(0);
+    };
+
+    #[cfg(feature = "webhooks")]
+    for row in &rows {
+        webhooks::enqueue(&mut *tx, row.id, &row.kind, &row.payload).await?;
+    }
+
+    let ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
+    sqlx::query!(
+        "UPDATE events SET dispatched_at = now() WHERE id = ANY($1)",
+        &ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    for row in &rows {
+        match serde_json::from_value::<Event>(row.payload.clone()) {
+            Ok(event) => state.events.publish(event),
+            Err(e) => tracing::warn!("event {} ({}) not published: {}", row.id, row.kind, e),
+        }
+    }
+    state.events.recorded.send_replace(last);
+
+    Ok(rows.len())
+}
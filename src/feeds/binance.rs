@@ -0,0 +1,23 @@
+use anyhow::{Result, anyhow};
+
+use super::{FeedReading, get};
+
+const API_URL: &str = "https://api.binance.com/api/v3/ticker/price";
+
+/// `GET /ticker/price?symbol=<symbol>`, which answers
+/// `{"symbol": "...", "price": "<decimal string>"}`.
+pub async fn fetch(client: &reqwest::Client, symbol: &str) -> Result<FeedReading> {
+    let url = format!("{}?symbol={}", API_URL, symbol);
+    let (body, provenance) = get(client, &url).await?;
+
+    let value = body
+        .get("price")
+        .and_then(|price| price.as_str())
+        .and_then(|price| price.parse::<f64>().ok())
+        .ok_or_else(|| anyhow!("no price for {} in response", symbol))?;
+
+    Ok(FeedReading {
+        value,
+        provenance: Some(provenance),
+    })
+}
@@ -0,0 +1,27 @@
+use anyhow::{Result, anyhow};
+
+use super::{FeedReading, get};
+
+const API_URL: &str = "https://api.coingecko.com/api/v3/simple/price";
+
+/// `GET /simple/price?ids=<coin>&vs_currencies=<currency>`, which answers
+/// `{"<coin>": {"<currency>": <price>}}`.
+pub async fn fetch(
+    client: &reqwest::Client,
+    coin_id: &str,
+    vs_currency: &str,
+) -> Result<FeedReading> {
+    let url = format!("{}?ids={}&vs_currencies={}", API_URL, coin_id, vs_currency);
+    let (body, provenance) = get(client, &url).await?;
+
+    let value = body
+        .get(coin_id)
+        .and_then(|prices| prices.get(vs_currency))
+        .and_then(|price| price.as_f64())
+        .ok_or_else(|| anyhow!("no {} price for {} in response", vs_currency, coin_id))?;
+
+    Ok(FeedReading {
+        value,
+        provenance: Some(provenance),
+    })
+}
@@ -0,0 +1,337 @@
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use chrono::{SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::repo::{NewReport, ReportRepo};
+use crate::state::AppState;
+use crate::types::Provenance;
+
+pub mod binance;
+#[cfg(feature = "eth")]
+pub mod chainlink;
+pub mod coingecko;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where a market feed pulls its value from. Stored in `market_feeds.source`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FeedSource {
+    /// CoinGecko simple price, e.g. `bitcoin` in `usd`.
+    #[serde(rename = "COINGECKO")]
+    CoinGecko {
+        coin_id: String,
+        vs_currency: String,
+    },
+    /// Binance spot ticker, e.g. `BTCUSDT`.
+    Binance { symbol: String },
+    /// Chainlink aggregator read over a configured chain's RPC. Answers older
+    /// than `max_age_secs` are rejected.
+    Chainlink {
+        chain_id: Option<u64>,
+        aggregator: String,
+        max_age_secs: Option<u64>,
+    },
+}
+
+impl FeedSource {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            FeedSource::CoinGecko {
+                coin_id,
+                vs_currency,
+            } => {
+                if !is_query_safe(coin_id) || !is_query_safe(vs_currency) {
+                    return Err("coin_id and vs_currency must be non-empty identifiers".to_string());
+                }
+            }
+            FeedSource::Binance { symbol } => {
+                if !is_query_safe(symbol) {
+                    return Err("symbol must be a non-empty identifier".to_string());
+                }
+            }
+            FeedSource::Chainlink { aggregator, .. } => {
+                if !cfg!(feature = "eth") {
+                    return Err("chainlink feeds need the eth feature".to_string());
+                }
+
+                let hex = aggregator.strip_prefix("0x").unwrap_or(aggregator);
+                if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err("aggregator must be a 0x-prefixed address".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Report `source` for values from this feed.
+    pub fn name(&self) -> String {
+        match self {
+            FeedSource::CoinGecko {
+                coin_id,
+                vs_currency,
+            } => {
+                format!("coingecko:{}/{}", coin_id, vs_currency)
+            }
+            FeedSource::Binance { symbol } => format!("binance:{}", symbol),
+            FeedSource::Chainlink { aggregator, .. } => {
+                format!("chainlink:{}", aggregator.to_lowercase())
+            }
+        }
+    }
+}
+
+// ids are interpolated into query strings
+fn is_query_safe(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub struct FeedReading {
+    pub value: f64,
+    pub provenance: Option<Provenance>,
+}
+
+pub async fn fetch(
+    state: &AppState,
+    client: &reqwest::Client,
+    source: &FeedSource,
+) -> Result<FeedReading> {
+    let reading = match source {
+        FeedSource::CoinGecko {
+            coin_id,
+            vs_currency,
+        } => coingecko::fetch(client, coin_id, vs_currency).await?,
+        FeedSource::Binance { symbol } => binance::fetch(client, symbol).await?,
+        #[cfg(feature = "eth")]
+        FeedSource::Chainlink {
+            chain_id,
+            aggregator,
+            max_age_secs,
+        } => chainlink::fetch(state, *chain_id, aggregator.parse()?, *max_age_secs).await?,
+        #[cfg(not(feature = "eth"))]
+        FeedSource::Chainlink { .. } => {
+            let _ = state;
+            return Err(anyhow!("chainlink feeds need the eth feature"));
+        }
+    };
+
+    if !reading.value.is_finite() {
+        return Err(anyhow!("feed returned a non-finite value"));
+    }
+
+    Ok(reading)
+}
+
+/// GETs a JSON document and describes the exchange as report provenance.
+async fn get(client: &reqwest::Client, url: &str) -> Result<(serde_json::Value, Provenance)> {
+    let fetched_at = Utc::now().trunc_subsecs(6);
+
+    let response = client.get(url).send().await?;
+    let status = response.status();
+    let body = response.bytes().await?;
+
+    if !status.is_success() {
+        return Err(anyhow!("{} returned {}", url, status));
+    }
+
+    let json = serde_json::from_slice(&body)?;
+
+    Ok((
+        json,
+        Provenance {
+            source_url: url.to_string(),
+            http_status: Some(status.as_u16()),
+            response_sha256: hex::encode(Sha256::digest(&body)),
+            fetched_at,
+        },
+    ))
+}
+
+pub async fn feeds_loop(state: AppState) {
+    let client = reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .expect("Failed to build feed HTTP client");
+
+    let interval = state.config.feeds.interval();
+
+    loop {
+        let run = state.loops.start("feeds", interval);
+
+        match poll_feeds(&state, &client).await {
+            Ok(stored) => run.finish(stored),
+            Err(e) => {
+                tracing::error!("feed poll failed: {}", e);
+                run.fail(e);
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Fetches every feed whose interval has elapsed on an OPEN market. Returns
+/// the number of readings stored as reports.
+async fn poll_feeds(state: &AppState, client: &reqwest::Client) -> Result<usize, sqlx::Error> {
+    let feeds = sqlx::query!(
+        r#"
+        SELECT f.id, f.market_id, f.source, m.min_value, m.max_value
+        FROM market_feeds f
+        JOIN markets m ON m.id = f.market_id
+        WHERE m.status = 'OPEN'
+          AND (f.last_fetched_at IS NULL
+               OR f.last_fetched_at + make_interval(secs => f.interval_secs) <= now())
+        ORDER BY f.last_fetched_at ASC NULLS FIRST
+        LIMIT 50
+        "#
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let mut stored = 0;
+
+    for feed in feeds {
+        let source: FeedSource = match serde_json::from_value(feed.source) {
+            Ok(s) => s,
+            Err(e) => {
+                record_error(state, feed.id, &format!("invalid feed source: {}", e)).await?;
+                continue;
+            }
+        };
+
+        let reading = match fetch(state, client, &source).await {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::warn!("feed {} ({}) failed: {}", feed.id, source.name(), e);
+                record_error(state, feed.id, &e.to_string()).await?;
+                continue;
+            }
+        };
+
+        if feed.min_value.is_some_and(|min| reading.value < min)
+            || feed.max_value.is_some_and(|max| reading.value > max)
+        {
+            let msg = format!("value {} outside market range", reading.value);
+            record_error(state, feed.id, &msg).await?;
+            continue;
+        }
+
+        store_reading(state, feed.id, feed.market_id, &source, reading).await?;
+        stored += 1;
+    }
+
+    Ok(stored)
+}
+
+async fn store_reading(
+    state: &AppState,
+    feed_id: Uuid,
+    market_id: Uuid,
+    source: &FeedSource,
+    reading: FeedReading,
+) -> Result<(), sqlx::Error> {
+    let id = Uuid::new_v4();
+    let now = Utc::now().trunc_subsecs(6);
+    let source_name = source.name();
+
+    let mut tx = state.db.begin().await?;
+
+    // one report per source: each reading replaces the feed's last one
+    let stored = ReportRepo::upsert_reading(
+        &mut *tx,
+        &NewReport {
+            id,
+            market_id,
+            source: &source_name,
+            value: reading.value,
+            idempotency_key: &format!("feed:{}:{}", feed_id, now.timestamp_micros()),
+            self_reported: true,
+            provenance: reading.provenance.map(|p| serde_json::to_value(p).unwrap()),
+            confidence: None,
+            stake: None,
+            reporter_address: None,
+            signature: None,
+            signed_at: None,
+            verified: false,
+            late: false,
+            submitted_by: None,
+            created_at: now,
+        },
+    )
+    .await?;
+
+    let Some(report_id) = stored else {
+        tracing::warn!(
+            "Feed {} reading skipped: {} already reported on market {} as an external source",
+            feed_id,
+            source_name,
+            market_id
+        );
+        tx.rollback().await?;
+        return record_error(
+            state,
+            feed_id,
+            "source already reported by an external reporter",
+        )
+        .await;
+    };
+    let action = if report_id == id {
+        "accepted"
+    } else {
+        "updated"
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE market_feeds
+        SET last_fetched_at = $1,
+            last_value = $2,
+            last_error = NULL
+        WHERE id = $3
+        "#,
+    )
+    .bind(now)
+    .bind(reading.value)
+    .bind(feed_id)
+    .execute(&mut *tx)
+    .await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("report", report_id, action, "feeds").details(serde_json::json!({
+            "market_id": market_id,
+            "source": source_name,
+            "value": reading.value,
+            "feed_id": feed_id,
+        })),
+    )
+    .await?;
+
+    tx.commit().await
+}
+
+// still counts as a fetch so a broken feed waits out its interval
+async fn record_error(state: &AppState, feed_id: Uuid, error: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE market_feeds
+        SET last_fetched_at = now(),
+            last_error = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(error)
+    .bind(feed_id)
+    .execute(&state.db)
+    .await?;
+
+    Ok(())
+}
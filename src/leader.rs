@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use sqlx::{Connection, PgConnection};
+use tokio::sync::watch;
+
+use crate::state::AppState;
+
+// held for the life of the leader's session; Postgres drops it with the
+// connection, so a crashed or partitioned leader frees it on its own
+const LEADER_LOCK_KEY: i64 = 0x6f72_6163_6c65_0002;
+
+/// Whether this replica runs the singleton loops (resolver and batcher).
+#[derive(Clone)]
+pub struct Leadership {
+    elected: Arc<watch::Sender<bool>>,
+}
+
+impl Leadership {
+    /// Starts as a standby, or as the leader when election is off.
+    pub fn new(election: bool) -> Self {
+        Self {
+            elected: Arc::new(watch::Sender::new(!election)),
+        }
+    }
+
+    pub fn is_leader(&self) -> bool {
+        *self.elected.borrow()
+    }
+
+    /// Returns once this replica is the leader.
+    pub async fn wait(&self) {
+        let mut elected = self.elected.subscribe();
+        // the sender lives as long as self
+        elected.wait_for(|leader| *leader).await.ok();
+    }
+
+    fn set(&self, leader: bool) {
+        if self.elected.send_replace(leader) != leader {
+            if leader {
+                tracing::info!("This instance is now the leader; running the resolver and batcher");
+            } else {
+                tracing::warn!("This instance lost leadership; the resolver and batcher stand by");
+            }
+        }
+    }
+}
+
+/// Holds the leader lock on a connection of its own, pinging it every
+/// interval. A standby tries to take the lock each interval. A pass already
+/// running when leadership is lost still finishes.
+pub async fn election_loop(state: AppState) {
+    let interval = state.config.leader.interval();
+    let mut held: Option<PgConnection> = None;
+
+    loop {
+        let run = state.loops.start("leader", interval);
+
+        match campaign(&state, &mut held).await {
+            Ok(leader) => {
+                state.leader.set(leader);
+                run.finish(leader as usize);
+            }
+            Err(e) => {
+                // dropping the connection gives the lock back
+                held = None;
+                state.leader.set(false);
+                tracing::error!("leader election failed: {:#}", e);
+                run.fail(format!("{:#}", e));
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn campaign(state: &AppState, held: &mut Option<PgConnection>) -> Result<bool> {
+    let timeout = state.config.leader.interval();
+
+    if let Some(conn) = held.as_mut() {
+        tokio::time::timeout(timeout, conn.ping())
+            .await
+            .context("lock connection timed out")?
+            .context("lock connection lost")?;
+        return Ok(true);
+    }
+
+    // detached so the lock never goes back to the pool with the connection
+    let mut conn = state.db.acquire().await?.detach();
+
+    let won = sqlx::query_scalar!(
+        r#"SELECT pg_try_advisory_lock($1) AS "won!""#,
+        LEADER_LOCK_KEY
+    )
+    .fetch_one(&mut conn)
+    .await?;
+
+    if won {
+        *held = Some(conn);
+    } else {
+        conn.close().await.ok();
+    }
+
+    Ok(won)
+}
@@ -1,10 +1,46 @@
+pub mod archive;
+pub mod audit;
+pub mod auth;
+pub mod cache;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod config;
+pub mod db;
+pub mod error;
+pub mod routes;
 pub mod state;
 pub mod types;
-pub mod routes;
+pub mod validation;
+pub mod value_type;
+pub mod verify;
 
+pub mod anomaly;
+pub mod batcher;
+pub mod close_condition;
+#[cfg(feature = "eth")]
 pub mod eth;
+pub mod events;
+#[cfg(feature = "feeds")]
+pub mod feeds;
+pub mod leader;
+pub mod linked;
+pub mod loops;
+pub mod metrics;
+pub mod migrations;
 pub mod models;
+pub mod outcome_type;
+pub mod payout;
 pub mod proof;
+pub mod rate_limit;
+pub mod repo;
+pub mod request_id;
+pub mod resolution;
+pub mod resolver;
+pub mod server;
+pub mod templates;
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
+#[cfg(feature = "eth")]
 pub mod worker;
 
 // Optional: expose a router builder so main.rs can be tiny
@@ -13,4 +49,4 @@ use state::AppState;
 
 pub fn app(state: AppState) -> Router {
     routes::router(state)
-}
\ No newline at end of file
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// What must hold across the outcomes of a market group before any of them
+/// settles. Stored in `market_groups.invariant`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GroupInvariant {
+    /// No constraint on values; members only settle in the same transaction.
+    #[default]
+    SettleTogether,
+    /// Outcomes sum to `total` within `tolerance` (absolute), e.g. a YES/NO
+    /// pair priced in percent sums to 100.
+    SumTo { total: f64, tolerance: f64 },
+}
+
+impl GroupInvariant {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            GroupInvariant::SumTo { total, tolerance }
+                if !total.is_finite() || !tolerance.is_finite() || *tolerance < 0.0 =>
+            {
+                Err("SUM_TO needs a finite total and a non-negative tolerance".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check(&self, outcomes: &[f64]) -> Result<(), String> {
+        match self {
+            GroupInvariant::SettleTogether => Ok(()),
+            GroupInvariant::SumTo { total, tolerance } => {
+                let sum: f64 = outcomes.iter().sum();
+                if (sum - total).abs() <= *tolerance {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "outcomes sum to {} but must sum to {} (±{})",
+                        sum, total, tolerance
+                    ))
+                }
+            }
+        }
+    }
+}
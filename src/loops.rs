@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LoopStatus {
+    pub name: String,
+    pub interval_secs: u64,
+    pub running: bool,
+    pub runs: u64,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_finished_at: Option<DateTime<Utc>>,
+    pub last_duration_ms: Option<u64>,
+    pub last_items: Option<u64>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+/// Shared bookkeeping for the background loops, read by `GET /admin/loops`.
+#[derive(Clone, Default)]
+pub struct LoopRegistry {
+    inner: Arc<Mutex<BTreeMap<String, LoopStatus>>>,
+}
+
+/// One pass of a loop. Dropping it without `finish`/`fail` (e.g. on panic)
+/// leaves the loop marked as running from `last_started_at`.
+pub struct LoopRun {
+    registry: LoopRegistry,
+    name: String,
+    interval: Duration,
+    started: Instant,
+}
+
+impl LoopRegistry {
+    pub fn start(&self, name: &str, interval: Duration) -> LoopRun {
+        let mut loops = self.inner.lock().unwrap();
+        let status = entry(&mut loops, name);
+
+        status.interval_secs = interval.as_secs();
+        status.running = true;
+        status.last_started_at = Some(Utc::now());
+        status.next_run_at = None;
+
+        LoopRun {
+            registry: self.clone(),
+            name: name.to_string(),
+            interval,
+            started: Instant::now(),
+        }
+    }
+
+    /// Spawns a loop task and records it as dead if it ever returns or panics.
+    pub fn spawn<F>(&self, name: &str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        // listed even if it never calls `start` (e.g. event-driven loops)
+        entry(&mut self.inner.lock().unwrap(), name);
+
+        let registry = self.clone();
+        let name = name.to_string();
+        let handle = tokio::spawn(task);
+
+        tokio::spawn(async move {
+            let reason = match handle.await {
+                Ok(()) => "task exited".to_string(),
+                Err(e) => format!("task died: {}", e),
+            };
+
+            tracing::error!("background loop {} stopped: {}", name, reason);
+
+            let mut loops = registry.inner.lock().unwrap();
+            if let Some(status) = loops.get_mut(&name) {
+                status.running = false;
+                status.last_error = Some(reason);
+                status.last_error_at = Some(Utc::now());
+                status.next_run_at = None;
+            }
+        });
+    }
+
+    pub fn snapshot(&self) -> Vec<LoopStatus> {
+        self.inner.lock().unwrap().values().cloned().collect()
+    }
+}
+
+impl LoopRun {
+    pub fn finish(self, items: usize) {
+        self.complete(Some(items), None);
+    }
+
+    pub fn fail(self, error: impl ToString) {
+        self.complete(None, Some(error.to_string()));
+    }
+
+    fn complete(self, items: Option<usize>, error: Option<String>) {
+        let now = Utc::now();
+        let mut loops = self.registry.inner.lock().unwrap();
+        let Some(status) = loops.get_mut(&self.name) else {
+            return;
+        };
+
+        status.running = false;
+        status.runs += 1;
+        status.last_finished_at = Some(now);
+        status.last_duration_ms = Some(self.started.elapsed().as_millis() as u64);
+        status.last_items = items.map(|n| n as u64);
+        status.next_run_at = chrono::Duration::from_std(self.interval)
+            .ok()
+            .map(|d| now + d);
+
+        if let Some(error) = error {
+            status.last_error = Some(error);
+            status.last_error_at = Some(now);
+        }
+    }
+}
+
+fn entry<'a>(loops: &'a mut BTreeMap<String, LoopStatus>, name: &str) -> &'a mut LoopStatus {
+    loops.entry(name.to_string()).or_insert_with(|| LoopStatus {
+        name: name.to_string(),
+        interval_secs: 0,
+        running: false,
+        runs: 0,
+        last_started_at: None,
+        last_finished_at: None,
+        last_duration_ms: None,
+        last_items: None,
+        last_error: None,
+        last_error_at: None,
+        next_run_at: None,
+    })
+}
@@ -1,31 +1,182 @@
-use sqlx::postgres::PgPoolOptions;
-use std::net::SocketAddr;
+use std::sync::Arc;
 
-use oraclesettle_backend::{app, state::AppState};
+#[cfg(feature = "eth")]
+use oraclesettle_backend::eth::attest::Attestor;
+#[cfg(feature = "eth")]
+use oraclesettle_backend::eth::chains::ChainRegistry;
+#[cfg(feature = "eth")]
+use oraclesettle_backend::eth::sender::EthSender;
+use oraclesettle_backend::{
+    app, auth::AuthConfig, cache::ReadCache, config::AppConfig, events::EventBus,
+    leader::Leadership, loops::LoopRegistry, rate_limit::RateLimiter, state::AppState,
+};
 
 #[tokio::main]
 async fn main() {
     tracing_subscriber::fmt::init();
     dotenvy::dotenv().ok();
 
-    let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let config = AppConfig::load().unwrap_or_else(|e| fatal("Invalid app config", e));
 
-    let pool = PgPoolOptions::new()
-        .max_connections(10)
-        .connect(&db_url)
+    let db_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| {
+        fatal(
+            "DATABASE_URL must be set",
+            "point it at the Postgres database",
+        )
+    });
+
+    let pool = oraclesettle_backend::db::connect_with_retry(&db_url, &config.database)
+        .await
+        .unwrap_or_else(|e| fatal("Failed to connect to the database", e));
+
+    // opt-in so deployments that manage schema out of band aren't surprised
+    let run_migrations =
+        std::env::var("RUN_MIGRATIONS").is_ok_and(|v| matches!(v.as_str(), "1" | "true" | "yes"));
+    if run_migrations {
+        // a lazy pool hasn't seen the database yet
+        if config.database.lazy {
+            oraclesettle_backend::db::wait_until_ready(&db_url, &config.database)
+                .await
+                .unwrap_or_else(|e| fatal("Failed to connect to the database", e));
+        }
+        oraclesettle_backend::migrations::run(&pool)
+            .await
+            .unwrap_or_else(|e| fatal("Failed to run migrations", e));
+        tracing::info!("Database migrations applied");
+    }
+
+    #[cfg(feature = "eth")]
+    let chains = ChainRegistry::load()
         .await
-        .expect("Failed to connect DB");
+        .unwrap_or_else(|e| fatal("Failed to load chain config", e));
+    #[cfg(feature = "eth")]
+    for chain in chains.summaries() {
+        tracing::info!(
+            "Chain {} ({}) with {} submitter wallet(s)",
+            chain.chain_id,
+            chain.name,
+            chain.wallets
+        );
+    }
+
+    #[cfg(feature = "eth")]
+    let sender = EthSender::new(&chains, config.worker.max_in_flight)
+        .unwrap_or_else(|e| fatal("Failed to build chain clients", e));
+
+    #[cfg(feature = "eth")]
+    let attestor = Attestor::from_env()
+        .unwrap_or_else(|e| fatal("Failed to load the snapshot signing key", e));
+    #[cfg(feature = "eth")]
+    match &attestor {
+        Some(attestor) => tracing::info!("Signing market snapshots as {:?}", attestor.address()),
+        None => tracing::info!("SNAPSHOT_SIGNING_KEY not set; market snapshots are off"),
+    }
+
+    let leader = Leadership::new(config.leader.enabled);
+    let cache = ReadCache::new(&config.cache);
+    let rate_limiter = RateLimiter::new(&config.rate_limit);
+
+    let state = AppState {
+        db: pool,
+        config: Arc::new(config),
+        #[cfg(feature = "eth")]
+        chains: Arc::new(chains),
+        #[cfg(feature = "eth")]
+        sender: Arc::new(sender),
+        #[cfg(feature = "eth")]
+        attestor: attestor.map(Arc::new),
+        events: EventBus::new(1024),
+        loops: LoopRegistry::default(),
+        leader,
+        auth: AuthConfig::from_env(),
+        rate_limiter,
+        cache: Arc::new(cache),
+    };
+
+    // every replica serves HTTP; one at a time resolves and batches
+    if state.config.leader.enabled {
+        let leader_state = state.clone();
+        state.loops.spawn("leader", async move {
+            oraclesettle_backend::leader::election_loop(leader_state).await
+        });
+    }
+
+    let dispatch_state = state.clone();
+    state.loops.spawn("dispatcher", async move {
+        oraclesettle_backend::events::dispatch_loop(dispatch_state).await
+    });
+
+    let resolver_state = state.clone();
+    state.loops.spawn("resolver", async move {
+        oraclesettle_backend::resolver::resolver_loop(resolver_state).await
+    });
 
-    let state = AppState { db: pool };
+    let batch_state = state.clone();
+    state.loops.spawn("batcher", async move {
+        oraclesettle_backend::batcher::batcher_loop(batch_state).await
+    });
 
-    // spawn loops/workers here (or move them into lib as well)
-    let worker_state = state.clone();
-    tokio::spawn(async move { oraclesettle_backend::worker::run_worker(worker_state).await });
+    #[cfg(feature = "feeds")]
+    {
+        let feeds_state = state.clone();
+        state.loops.spawn("feeds", async move {
+            oraclesettle_backend::feeds::feeds_loop(feeds_state).await
+        });
+    }
 
+    let metrics_state = state.clone();
+    state.loops.spawn("metrics", async move {
+        oraclesettle_backend::metrics::snapshot_loop(metrics_state).await
+    });
+
+    #[cfg(feature = "webhooks")]
+    {
+        let webhooks_state = state.clone();
+        state.loops.spawn("webhooks", async move {
+            oraclesettle_backend::webhooks::delivery_loop(webhooks_state).await
+        });
+    }
+
+    let templates_state = state.clone();
+    state.loops.spawn("templates", async move {
+        oraclesettle_backend::templates::templates_loop(templates_state).await
+    });
+
+    let archiver_state = state.clone();
+    state.loops.spawn("archiver", async move {
+        oraclesettle_backend::archive::archive_loop(archiver_state).await
+    });
+
+    let anomaly_state = state.clone();
+    state.loops.spawn("anomaly", async move {
+        oraclesettle_backend::anomaly::anomaly_loop(anomaly_state).await
+    });
+
+    #[cfg(feature = "eth")]
+    {
+        oraclesettle_backend::worker::spawn_workers(&state);
+
+        let reconciler_state = state.clone();
+        state.loops.spawn("reconciler", async move {
+            oraclesettle_backend::worker::reconcile_loop(reconciler_state).await
+        });
+
+        let listener_state = state.clone();
+        state.loops.spawn("listener", async move {
+            oraclesettle_backend::eth::listener::listener_loop(listener_state).await
+        });
+    }
+
+    let config = state.config.clone();
     let app = app(state);
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
-    // let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
-}
\ No newline at end of file
+    oraclesettle_backend::server::serve(app, &config)
+        .await
+        .unwrap_or_else(|e| fatal("Server failed", e));
+}
+
+/// Startup can't continue: says why in the log, not a panic backtrace.
+fn fatal(context: &str, error: impl std::fmt::Display) -> ! {
+    tracing::error!("{}: {:#}", context, error);
+    std::process::exit(1);
+}
@@ -0,0 +1,50 @@
+use crate::state::AppState;
+
+/// Runs on the leader replica only, so there is one snapshot per interval.
+pub async fn snapshot_loop(state: AppState) {
+    let interval = state.config.metrics.interval();
+
+    loop {
+        state.leader.wait().await;
+
+        let run = state.loops.start("metrics", interval);
+
+        match take_snapshot(&state).await {
+            Ok(()) => run.finish(1),
+            Err(e) => {
+                tracing::error!("metrics snapshot failed: {}", e);
+                run.fail(e);
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Records trailing-24h throughput and current DB size into `metrics_history`.
+async fn take_snapshot(state: &AppState) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO metrics_history
+        (reports_24h, settlements_24h, batches_24h, avg_batch_size, db_size_bytes)
+        SELECT
+          (SELECT COUNT(*) FROM reports WHERE created_at > now() - INTERVAL '1 day'),
+          (SELECT COUNT(*) FROM settlements WHERE decided_at > now() - INTERVAL '1 day'),
+          (SELECT COUNT(*) FROM batches WHERE created_at > now() - INTERVAL '1 day'),
+          (
+            SELECT AVG(n)::FLOAT8 FROM (
+              SELECT COUNT(bi.market_id) AS n
+              FROM batches b
+              JOIN batch_items bi ON bi.batch_id = b.id
+              WHERE b.created_at > now() - INTERVAL '1 day'
+              GROUP BY b.id
+            ) sizes
+          ),
+          pg_database_size(current_database())
+        "#
+    )
+    .execute(&state.db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use sqlx::PgPool;
+use sqlx::migrate::{MigrateError, Migrator};
+
+/// The repo's `migrations/` directory, embedded at compile time so a deployed
+/// binary always carries the schema it was built against.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Applies any pending migrations. Already-applied ones are skipped, so this
+/// is safe to run on every start.
+pub async fn run(pool: &PgPool) -> Result<(), MigrateError> {
+    MIGRATOR.run(pool).await
+}
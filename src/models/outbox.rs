@@ -5,6 +5,27 @@ pub struct SettlementPayload {
     pub market_id: String,
     pub market_hash_hex: String,
     pub leaf_hex: String,
+    // option index on BINARY/CATEGORICAL markets
     pub outcome_u64: u64,
+    // exact outcome at proof::FIXED_POINT_DECIMALS; 0 on payloads queued before it existed
+    #[serde(default)]
+    pub outcome_e8: i64,
     pub ts: u64,
+    // None on payloads queued before multi-chain support: use the default chain
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+}
+
+impl SettlementPayload {
+    /// The market hash and leaf as the 32-byte words the contract takes.
+    pub fn words(&self) -> Result<([u8; 32], [u8; 32]), String> {
+        let market_hash = hex::decode(&self.market_hash_hex)
+            .map_err(|e| format!("bad market_hash hex: {}", e))?;
+        let leaf = hex::decode(&self.leaf_hex).map_err(|e| format!("bad leaf hex: {}", e))?;
+
+        match (market_hash.try_into(), leaf.try_into()) {
+            (Ok(market_hash), Ok(leaf)) => Ok((market_hash, leaf)),
+            _ => Err("hash/leaf wrong length (expected 32 bytes)".to_string()),
+        }
+    }
 }
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+const BINARY_OPTIONS: [&str; 2] = ["NO", "YES"];
+
+// keeps option indices comfortably inside a single byte on-chain
+const MAX_OPTIONS: usize = 256;
+
+/// Shape of a market's answer. Stored in `markets.outcome_type`. Reports on
+/// BINARY and CATEGORICAL markets carry an option index as their `value`, and
+/// the settled outcome is the index of the winning option.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OutcomeType {
+    #[default]
+    Numeric,
+    /// 0 = NO, 1 = YES.
+    Binary,
+    Categorical {
+        options: Vec<String>,
+    },
+}
+
+impl OutcomeType {
+    pub fn is_discrete(&self) -> bool {
+        !matches!(self, OutcomeType::Numeric)
+    }
+
+    /// Option labels in index order; empty for NUMERIC markets.
+    pub fn options(&self) -> Vec<&str> {
+        match self {
+            OutcomeType::Numeric => Vec::new(),
+            OutcomeType::Binary => BINARY_OPTIONS.to_vec(),
+            OutcomeType::Categorical { options } => options.iter().map(String::as_str).collect(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let OutcomeType::Categorical { options } = self {
+            if options.len() < 2 || options.len() > MAX_OPTIONS {
+                return Err(format!(
+                    "CATEGORICAL needs between 2 and {} options",
+                    MAX_OPTIONS
+                ));
+            }
+
+            if options.iter().any(|o| o.trim().is_empty()) {
+                return Err("options must not be empty".to_string());
+            }
+
+            let mut sorted: Vec<&String> = options.iter().collect();
+            sorted.sort();
+            sorted.dedup();
+            if sorted.len() != options.len() {
+                return Err("options must be distinct".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// The option a report value (or outcome) names, if it names one.
+    pub fn option_index(&self, value: f64) -> Option<usize> {
+        let count = self.options().len();
+        if value.fract() != 0.0 || value < 0.0 || value >= count as f64 {
+            return None;
+        }
+        Some(value as usize)
+    }
+
+    pub fn option_label(&self, value: f64) -> Option<String> {
+        let index = self.option_index(value)?;
+        self.options().get(index).map(|o| o.to_string())
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// How a market's outcome is split into payouts, set at creation and stored
+/// in `markets.payout`. Served with the settlement so downstream apps don't
+/// each reimplement it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PayoutFormula {
+    /// YES takes everything when the outcome is at or above `threshold`,
+    /// NO otherwise.
+    BinaryThreshold { threshold: f64 },
+    /// LONG's share grows linearly from 0 at `low` to 1 at `high`, clamped
+    /// outside the range; SHORT takes the rest.
+    LinearRange { low: f64, high: f64 },
+}
+
+/// One side's share of the payout, between 0 and 1.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PayoutSide {
+    pub side: String,
+    pub fraction: f64,
+}
+
+/// The market's formula and what it pays each side for the settled outcome.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Payout {
+    pub formula: PayoutFormula,
+    // fractions add up to 1
+    pub sides: Vec<PayoutSide>,
+}
+
+impl PayoutFormula {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            PayoutFormula::BinaryThreshold { threshold } if !threshold.is_finite() => {
+                Err("payout threshold must be finite".to_string())
+            }
+            PayoutFormula::LinearRange { low, high } if !low.is_finite() || !high.is_finite() => {
+                Err("payout range must be finite".to_string())
+            }
+            PayoutFormula::LinearRange { low, high } if low >= high => {
+                Err("LINEAR_RANGE needs low < high".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn compute(&self, outcome: f64) -> Vec<PayoutSide> {
+        let (first, second, fraction) = match self {
+            PayoutFormula::BinaryThreshold { threshold } => {
+                ("YES", "NO", if outcome >= *threshold { 1.0 } else { 0.0 })
+            }
+            PayoutFormula::LinearRange { low, high } => (
+                "LONG",
+                "SHORT",
+                ((outcome - low) / (high - low)).clamp(0.0, 1.0),
+            ),
+        };
+
+        vec![
+            PayoutSide {
+                side: first.to_string(),
+                fraction,
+            },
+            PayoutSide {
+                side: second.to_string(),
+                fraction: 1.0 - fraction,
+            },
+        ]
+    }
+
+    pub fn payout(&self, outcome: f64) -> Payout {
+        Payout {
+            formula: self.clone(),
+            sides: self.compute(outcome),
+        }
+    }
+}
@@ -1,50 +1,483 @@
-use sha2::{Sha256, Digest};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use utoipa::ToSchema;
+use uuid::Uuid;
 
-pub fn hash_leaf(data: &str) -> [u8; 32] {
-    let mut hasher = Sha256::new();
-    hasher.update(data.as_bytes());
+/// Hash behind settlement hashes, leaves and Merkle nodes. `keccak256` is
+/// Solidity's `keccak256`, so a contract can check roots and proofs itself.
+/// Each settlement and batch records the one it was built with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Keccak256,
+}
+
+impl HashAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Keccak256 => "keccak256",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "sha256" => Some(HashAlgorithm::Sha256),
+            "keccak256" => Some(HashAlgorithm::Keccak256),
+            _ => None,
+        }
+    }
+
+    /// Digest of `parts` concatenated.
+    pub fn digest(&self, parts: &[&[u8]]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Sha256 => digest_with::<Sha256>(parts),
+            HashAlgorithm::Keccak256 => digest_with::<Keccak256>(parts),
+        }
+    }
+}
 
-    let result = hasher.finalize();
+fn digest_with<D: Digest>(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = D::new();
+    for part in parts {
+        hasher.update(part);
+    }
 
     let mut out = [0u8; 32];
-    out.copy_from_slice(&result);
+    out.copy_from_slice(&hasher.finalize());
     out
 }
 
+pub fn hash_leaf(alg: HashAlgorithm, data: &str) -> [u8; 32] {
+    alg.digest(&[data.as_bytes()])
+}
 
-pub fn build_merkle_root(
-    mut leaves: Vec<[u8; 32]>,
-) -> [u8; 32] {
+pub fn build_merkle_root(alg: HashAlgorithm, mut leaves: Vec<[u8; 32]>) -> [u8; 32] {
     if leaves.is_empty() {
         return [0u8; 32];
     }
 
     while leaves.len() > 1 {
-        let mut next = Vec::new();
+        leaves = leaves
+            .chunks(2)
+            // an odd node out is paired with itself
+            .map(|pair| hash_pair(alg, &pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+    }
+
+    leaves[0]
+}
+
+/// Sibling hashes from `leaves[index]` up to the root, bottom first, for the
+/// tree `build_merkle_root` builds.
+pub fn merkle_proof(alg: HashAlgorithm, leaves: &[[u8; 32]], mut index: usize) -> Vec<[u8; 32]> {
+    let mut level = leaves.to_vec();
+    let mut proof = Vec::new();
+
+    while level.len() > 1 {
+        let sibling = if index.is_multiple_of(2) {
+            index + 1
+        } else {
+            index - 1
+        };
+        proof.push(*level.get(sibling).unwrap_or(&level[index]));
+
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(alg, &pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+        index /= 2;
+    }
+
+    proof
+}
+
+/// Whether `proof` (from `merkle_proof`) takes `leaf` at `index` to `root`.
+pub fn verify_merkle_proof(
+    alg: HashAlgorithm,
+    leaf: [u8; 32],
+    mut index: usize,
+    proof: &[[u8; 32]],
+    root: [u8; 32],
+) -> bool {
+    let mut node = leaf;
+
+    for sibling in proof {
+        node = if index.is_multiple_of(2) {
+            hash_pair(alg, &node, sibling)
+        } else {
+            hash_pair(alg, sibling, &node)
+        };
+        index /= 2;
+    }
+
+    node == root
+}
+
+fn hash_pair(alg: HashAlgorithm, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    alg.digest(&[left, right])
+}
+
+/// bytes32 market id used as the contract key. Always sha256: it names
+/// the market on chain whichever algorithm its settlement was hashed with.
+pub fn market_hash(market_id: Uuid) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(market_id.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Leaf committed on-chain and into batch roots for one settlement: `alg`
+/// over `encode_leaf`. `decided_at` must be the stored (microsecond) value.
+pub fn settlement_leaf(
+    alg: HashAlgorithm,
+    market_id: Uuid,
+    outcome_e8: i64,
+    decided_at: DateTime<Utc>,
+) -> [u8; 32] {
+    alg.digest(&[&encode_leaf(market_id, outcome_e8, decided_at)])
+}
+
+/// Hex of the bytes `settlement_leaf` hashes. Batches store it next to the
+/// leaf.
+pub fn settlement_leaf_data(market_id: Uuid, outcome_e8: i64, decided_at: DateTime<Utc>) -> String {
+    hex::encode(encode_leaf(market_id, outcome_e8, decided_at))
+}
+
+/// Leaf encoding of batches cut before `encode_leaf`: `alg` over
+/// `"{market_id}:{outcome}:{decided_at as RFC 3339}"`. Their roots were
+/// built from it, so their leaves keep it.
+pub const LEGACY_LEAF_VERSION: u8 = 1;
+
+/// The string a legacy leaf hashes. Batches store it next to the leaf.
+pub fn legacy_leaf_data(market_id: Uuid, outcome: f64, decided_at: DateTime<Utc>) -> String {
+    format!("{}:{}:{}", market_id, outcome, decided_at.to_rfc3339())
+}
+
+/// A settlement's leaf in leaf encoding `version`: `LEGACY_LEAF_VERSION`
+/// hashes `outcome`, anything else is `settlement_leaf` over `outcome_e8`.
+pub fn versioned_leaf(
+    version: u8,
+    alg: HashAlgorithm,
+    market_id: Uuid,
+    outcome: f64,
+    outcome_e8: i64,
+    decided_at: DateTime<Utc>,
+) -> [u8; 32] {
+    if version == LEGACY_LEAF_VERSION {
+        hash_leaf(alg, &legacy_leaf_data(market_id, outcome, decided_at))
+    } else {
+        settlement_leaf(alg, market_id, outcome_e8, decided_at)
+    }
+}
 
-        for pair in leaves.chunks(2) {
-            let left = pair[0];
-            let right = if pair.len() == 2 {
-                pair[1]
-            } else {
-                pair[0]
-            };
+/// Version byte leading every `encode_leaf` and `encode_settlement` output.
+/// Bump it whenever either layout changes so old and new hashes can't be
+/// confused.
+pub const SETTLEMENT_ENCODING_VERSION: u8 = 2;
 
-            let mut hasher = Sha256::new();
+/// Canonical bytes behind a settlement leaf, and the head of
+/// `encode_settlement`. Big-endian, as there:
+///
+/// ```text
+/// u8        version            SETTLEMENT_ENCODING_VERSION
+/// [u8; 16]  market_id          UUID bytes
+/// i64       outcome            fixed point (outcome_e8)
+/// i64       decided_at         micros
+/// ```
+///
+/// In Solidity this is `abi.encodePacked(uint8, bytes16, int64, int64)`, so
+/// a contract can rebuild the leaf from the settlement it was sent.
+pub fn encode_leaf(market_id: Uuid, outcome_e8: i64, decided_at: DateTime<Utc>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(33);
 
-            hasher.update(left);
-            hasher.update(right);
+    out.push(SETTLEMENT_ENCODING_VERSION);
+    out.extend_from_slice(market_id.as_bytes());
+    out.extend_from_slice(&outcome_e8.to_be_bytes());
+    out.extend_from_slice(&decided_at.timestamp_micros().to_be_bytes());
 
-            let hash = hasher.finalize();
+    out
+}
+
+/// Decimal places kept when a value is encoded as fixed point.
+pub const FIXED_POINT_DECIMALS: u32 = 8;
+
+/// `value * 10^8`, rounded half away from zero.
+pub fn to_fixed(value: f64) -> i64 {
+    (value * 10f64.powi(FIXED_POINT_DECIMALS as i32)).round() as i64
+}
+
+/// Closest f64 to a fixed-point value; `to_fixed(from_fixed(x)) == x` for any
+/// value a market can realistically settle at.
+pub fn from_fixed(value: i64) -> f64 {
+    value as f64 / 10f64.powi(FIXED_POINT_DECIMALS as i32)
+}
+
+/// A fixed-point value at `decimals` places instead of
+/// `FIXED_POINT_DECIMALS`, e.g. cents for 2. Rounds half away from zero,
+/// without going through a float.
+pub fn rescale_fixed(value: i64, decimals: u32) -> i64 {
+    let divisor = 10i64.pow(FIXED_POINT_DECIMALS - decimals.min(FIXED_POINT_DECIMALS));
+    let half = divisor / 2;
+    if value >= 0 {
+        (value + half) / divisor
+    } else {
+        (value - half) / divisor
+    }
+}
+
+/// A fixed-point value rounded to `decimals` places, still at
+/// `FIXED_POINT_DECIMALS`.
+pub fn round_fixed(value: i64, decimals: u32) -> i64 {
+    rescale_fixed(value, decimals)
+        * 10i64.pow(FIXED_POINT_DECIMALS - decimals.min(FIXED_POINT_DECIMALS))
+}
+
+/// One report as it enters the settlement hash.
+pub struct EncodedReport<'a> {
+    pub id: Uuid,
+    pub source: &'a str,
+    pub value: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Canonical bytes behind a settlement hash: `encode_leaf`, then the
+/// reports. All integers are big-endian; values are fixed point with
+/// `FIXED_POINT_DECIMALS`; timestamps are microseconds since the Unix epoch
+/// (what Postgres stores).
+///
+/// ```text
+/// u8        version            SETTLEMENT_ENCODING_VERSION
+/// [u8; 16]  market_id          UUID bytes
+/// i64       outcome            fixed point
+/// i64       decided_at         micros
+/// u32       report count
+/// per report, in (created_at, id) order:
+///   [u8; 16]  id
+///   u32       source length    bytes of UTF-8
+///   [u8]      source
+///   i64       value            fixed point
+///   i64       created_at       micros
+/// ```
+///
+/// In Solidity this is `abi.encodePacked(uint8, bytes16, int64, int64,
+/// uint32, ...)`, hashed with `sha256` or `keccak256` as the settlement
+/// records.
+pub fn encode_settlement(
+    market_id: Uuid,
+    outcome_e8: i64,
+    decided_at: DateTime<Utc>,
+    reports: &[EncodedReport],
+) -> Vec<u8> {
+    let mut out = encode_leaf(market_id, outcome_e8, decided_at);
+    out.reserve(4 + reports.len() * 48);
+
+    out.extend_from_slice(&(reports.len() as u32).to_be_bytes());
+
+    for r in reports {
+        out.extend_from_slice(r.id.as_bytes());
+        out.extend_from_slice(&(r.source.len() as u32).to_be_bytes());
+        out.extend_from_slice(r.source.as_bytes());
+        out.extend_from_slice(&to_fixed(r.value).to_be_bytes());
+        out.extend_from_slice(&r.created_at.timestamp_micros().to_be_bytes());
+    }
+
+    out
+}
+
+/// `alg` over `encode_settlement`.
+pub fn settlement_hash(
+    alg: HashAlgorithm,
+    market_id: Uuid,
+    outcome_e8: i64,
+    decided_at: DateTime<Utc>,
+    reports: &[EncodedReport],
+) -> [u8; 32] {
+    alg.digest(&[&encode_settlement(
+        market_id, outcome_e8, decided_at, reports,
+    )])
+}
 
-            let mut out = [0u8; 32];
-            out.copy_from_slice(&hash);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            next.push(out);
+    const MARKET: &str = "6f1d2c3b-4a5e-4f60-8b7c-9d0e1f2a3b4c";
+    // 65432.10987654
+    const OUTCOME_E8: i64 = 6_543_210_987_654;
+    const DECIDED_AT_MICROS: i64 = 1_760_000_000_123_456;
+
+    fn market() -> Uuid {
+        MARKET.parse().unwrap()
+    }
+
+    fn decided_at() -> DateTime<Utc> {
+        DateTime::from_timestamp_micros(DECIDED_AT_MICROS).unwrap()
+    }
+
+    #[test]
+    fn leaf_golden_vector() {
+        let bytes = encode_leaf(market(), OUTCOME_E8, decided_at());
+        assert_eq!(
+            hex::encode(&bytes),
+            "026f1d2c3b4a5e4f608b7c9d0e1f2a3b4c000005f375c44086000640b5eecfe240"
+        );
+
+        assert_eq!(
+            hex::encode(settlement_leaf(
+                HashAlgorithm::Sha256,
+                market(),
+                OUTCOME_E8,
+                decided_at()
+            )),
+            "daedf0662d94221d486ff4a5736a111209fc19200d5b097e9a323125156864d3"
+        );
+        assert_eq!(
+            hex::encode(settlement_leaf(
+                HashAlgorithm::Keccak256,
+                market(),
+                OUTCOME_E8,
+                decided_at()
+            )),
+            "626c28b1cb551424f1efe1f5580f2af8f3084858a0d73c4d5017e76a5d4f07c4"
+        );
+        assert_eq!(
+            settlement_leaf_data(market(), OUTCOME_E8, decided_at()),
+            hex::encode(bytes)
+        );
+    }
+
+    #[test]
+    fn negative_outcomes_encode_as_twos_complement() {
+        assert_eq!(
+            hex::encode(encode_leaf(market(), -150_000_000, decided_at())),
+            "026f1d2c3b4a5e4f608b7c9d0e1f2a3b4cfffffffff70f2e80000640b5eecfe240"
+        );
+    }
+
+    #[test]
+    fn legacy_leaf_golden_vector() {
+        let outcome = from_fixed(OUTCOME_E8);
+        assert_eq!(
+            legacy_leaf_data(market(), outcome, decided_at()),
+            "6f1d2c3b-4a5e-4f60-8b7c-9d0e1f2a3b4c:65432.10987654:2025-10-09T08:53:20.123456+00:00"
+        );
+        assert_eq!(
+            hex::encode(versioned_leaf(
+                LEGACY_LEAF_VERSION,
+                HashAlgorithm::Sha256,
+                market(),
+                outcome,
+                OUTCOME_E8,
+                decided_at()
+            )),
+            "cd2dc9bc7a420022e99380dc649b8c4cbbc45b07e5f334f43d97796e90b8c279"
+        );
+        assert_eq!(
+            versioned_leaf(
+                SETTLEMENT_ENCODING_VERSION,
+                HashAlgorithm::Sha256,
+                market(),
+                outcome,
+                OUTCOME_E8,
+                decided_at()
+            ),
+            settlement_leaf(HashAlgorithm::Sha256, market(), OUTCOME_E8, decided_at())
+        );
+    }
+
+    #[test]
+    fn settlement_golden_vector() {
+        let reports = [EncodedReport {
+            id: "0a0b0c0d-0e0f-4011-8213-141516171819".parse().unwrap(),
+            source: "binance",
+            value: 65432.1,
+            created_at: DateTime::from_timestamp_micros(1_759_999_999_000_001).unwrap(),
+        }];
+
+        let bytes = encode_settlement(market(), OUTCOME_E8, decided_at(), &reports);
+        assert_eq!(
+            hex::encode(&bytes),
+            "026f1d2c3b4a5e4f608b7c9d0e1f2a3b4c000005f375c44086000640b5eecfe240\
+             00000001\
+             0a0b0c0d0e0f40118213141516171819\
+             00000007\
+             62696e616e6365\
+             000005f375b52e80\
+             000640b5eebebdc1"
+        );
+
+        assert_eq!(
+            hex::encode(settlement_hash(
+                HashAlgorithm::Sha256,
+                market(),
+                OUTCOME_E8,
+                decided_at(),
+                &reports
+            )),
+            "8d8c864690342eb02f0125ebe112a20d6dfbf67c5d91f20f623c6908671b9ea4"
+        );
+        assert_eq!(
+            hex::encode(settlement_hash(
+                HashAlgorithm::Keccak256,
+                market(),
+                OUTCOME_E8,
+                decided_at(),
+                &reports
+            )),
+            "9798e251760f67a8d69649e3256e0ae6f6c84450d76d4d969016920174b4f629"
+        );
+    }
+
+    fn leaves(n: u8) -> Vec<[u8; 32]> {
+        (0..n)
+            .map(|i| HashAlgorithm::Sha256.digest(&[&[i]]))
+            .collect()
+    }
+
+    #[test]
+    fn merkle_proofs_round_trip() {
+        for alg in [HashAlgorithm::Sha256, HashAlgorithm::Keccak256] {
+            // odd counts pair their last node with itself on some level
+            for n in [1, 2, 3, 5, 7, 8] {
+                let leaves = leaves(n);
+                let root = build_merkle_root(alg, leaves.clone());
+
+                for (index, leaf) in leaves.iter().enumerate() {
+                    let proof = merkle_proof(alg, &leaves, index);
+                    assert!(
+                        verify_merkle_proof(alg, *leaf, index, &proof, root),
+                        "n={} index={}",
+                        n,
+                        index
+                    );
+                }
+            }
         }
+    }
+
+    #[test]
+    fn merkle_proofs_reject_the_wrong_leaf_or_index() {
+        let alg = HashAlgorithm::Sha256;
+        let leaves = leaves(5);
+        let root = build_merkle_root(alg, leaves.clone());
+        let proof = merkle_proof(alg, &leaves, 2);
 
-        leaves = next;
+        assert!(verify_merkle_proof(alg, leaves[2], 2, &proof, root));
+        assert!(!verify_merkle_proof(alg, leaves[3], 2, &proof, root));
+        assert!(!verify_merkle_proof(alg, leaves[2], 3, &proof, root));
     }
 
-    leaves[0]
+    #[test]
+    fn single_leaf_is_its_own_root() {
+        let leaves = leaves(1);
+        assert_eq!(
+            build_merkle_root(HashAlgorithm::Sha256, leaves.clone()),
+            leaves[0]
+        );
+        assert!(merkle_proof(HashAlgorithm::Sha256, &leaves, 0).is_empty());
+    }
 }
@@ -0,0 +1,179 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{HeaderValue, StatusCode, header::RETRY_AFTER},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::auth::RequireReporter;
+use crate::config::RateLimitConfig;
+use crate::error::AppError;
+use crate::state::AppState;
+
+// sweep idle keys once the map grows past this
+const SWEEP_THRESHOLD: usize = 10_000;
+
+pub enum Decision {
+    Allowed,
+    Limited { retry_after: Duration },
+}
+
+/// Where hit counters live. The in-memory store is per process; a shared
+/// backend (Redis, say) would implement this to limit across replicas.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    async fn hit(&self, key: &str, limit: u32, window: Duration) -> Decision;
+}
+
+/// Sliding window: keeps the timestamps of hits within the last `window`.
+#[derive(Default)]
+pub struct InMemoryStore {
+    hits: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryStore {
+    async fn hit(&self, key: &str, limit: u32, window: Duration) -> Decision {
+        let now = Instant::now();
+        let mut hits = self.hits.lock().unwrap();
+
+        if hits.len() > SWEEP_THRESHOLD {
+            hits.retain(|_, q| q.back().is_some_and(|t| now.duration_since(*t) < window));
+        }
+
+        let queue = hits.entry(key.to_string()).or_default();
+        while queue
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= window)
+        {
+            queue.pop_front();
+        }
+
+        if queue.len() >= limit as usize {
+            let oldest = queue.front().copied().unwrap_or(now);
+            return Decision::Limited {
+                retry_after: window.saturating_sub(now.duration_since(oldest)),
+            };
+        }
+
+        queue.push_back(now);
+        Decision::Allowed
+    }
+}
+
+/// Limits per `rate_limit.window_secs` (default 60). `rate_limit.reports`
+/// caps report submissions per authenticated caller and
+/// `rate_limit.markets` caps market creation per client IP; 0 disables.
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    window: Duration,
+    reports: u32,
+    markets: u32,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            store: Arc::new(InMemoryStore::default()),
+            window: config.window(),
+            reports: config.reports,
+            markets: config.markets,
+        }
+    }
+
+    pub fn with_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    async fn check(&self, key: &str, limit: u32) -> Result<(), Response> {
+        if limit == 0 {
+            return Ok(());
+        }
+
+        match self.store.hit(key, limit, self.window).await {
+            Decision::Allowed => Ok(()),
+            Decision::Limited { retry_after } => Err(too_many_requests(retry_after)),
+        }
+    }
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    // round up so clients never retry a moment too early
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+    let mut response = AppError::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        "RATE_LIMITED",
+        "Too many requests, slow down",
+    )
+    .details(serde_json::json!({ "retry_after_secs": secs }))
+    .into_response();
+
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(secs));
+    response
+}
+
+/// Market creation, keyed by client IP.
+pub async fn limit_markets(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let limiter = &state.rate_limiter;
+    if let Err(response) = limiter
+        .check(&format!("markets:ip:{}", ip), limiter.markets)
+        .await
+    {
+        return response;
+    }
+
+    next.run(request).await
+}
+
+/// Report submission, keyed by the authenticated caller. Runs the same
+/// check as `RequireReporter`, so unauthenticated requests are refused here
+/// and can't spend anyone's quota.
+pub async fn limit_reports(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limiter = &state.rate_limiter;
+    if limiter.reports == 0 {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let reporter = match RequireReporter::from_request_parts(&mut parts, &state).await {
+        Ok(r) => r,
+        Err(e) => return e.into_response(),
+    };
+
+    if let Err(response) = limiter
+        .check(
+            &format!("reports:actor:{}", reporter.actor),
+            limiter.reports,
+        )
+        .await
+    {
+        return response;
+    }
+
+    next.run(Request::from_parts(parts, body)).await
+}
@@ -0,0 +1,411 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgExecutor};
+use uuid::Uuid;
+
+use crate::proof::{HashAlgorithm, SETTLEMENT_ENCODING_VERSION};
+use crate::types::BatchSummary;
+
+pub struct BatchRepo;
+
+/// One settlement as it went into a batch.
+pub struct NewLeaf {
+    pub market_id: Uuid,
+    pub leaf_hash: String,
+    pub leaf_data: String,
+}
+
+pub struct StoredLeaf {
+    pub market_id: Uuid,
+    pub leaf_index: i32,
+    pub leaf_hash: String,
+    pub leaf_data: String,
+    pub leaf_version: Option<i16>,
+}
+
+impl StoredLeaf {
+    /// The encoding the leaf was built with.
+    pub fn leaf_version(&self) -> u8 {
+        self.leaf_version
+            .map_or(SETTLEMENT_ENCODING_VERSION, |v| v as u8)
+    }
+}
+
+pub struct BatchRef {
+    pub id: Uuid,
+    pub merkle_root: String,
+    pub hash_algorithm: String,
+}
+
+impl BatchRef {
+    /// The algorithm its tree's nodes were hashed with.
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::parse(&self.hash_algorithm).unwrap_or_default()
+    }
+}
+
+/// A batch whose status the batcher just moved.
+pub struct BatchTransition {
+    pub id: Uuid,
+    pub from: String,
+    pub to: String,
+}
+
+pub struct LockedBatch {
+    pub status: String,
+    pub voided_at: Option<DateTime<Utc>>,
+}
+
+/// An unbatched settlement claimed for a new batch.
+pub struct ClaimedSettlement {
+    pub market_id: Uuid,
+    pub outcome_e8: i64,
+    pub decided_at: DateTime<Utc>,
+    // the settlement's, which its leaf is hashed with
+    pub hash_algorithm: String,
+}
+
+/// Settlement fields a legacy batch item's leaf is rebuilt from.
+pub struct UnrecordedLeaf {
+    pub market_id: Uuid,
+    pub outcome: f64,
+    pub decided_at: DateTime<Utc>,
+}
+
+impl BatchRepo {
+    /// Assigns up to `limit` unbatched settlements of on-chain markets,
+    /// oldest first, to `batch_id`. Claimed in one statement, so a flush
+    /// racing the loop skips what the other took and a crash before commit
+    /// leaves every settlement unclaimed. Returned in leaf order:
+    /// (decided_at, market_id), so the root can be recomputed.
+    pub async fn claim_unbatched<'e, E: PgExecutor<'e>>(
+        db: E,
+        batch_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<ClaimedSettlement>, sqlx::Error> {
+        let mut rows = sqlx::query_as!(
+            ClaimedSettlement,
+            r#"
+            UPDATE settlements s
+            SET batch_id = $1
+            WHERE s.market_id IN (
+                SELECT p.market_id
+                FROM settlements p
+                JOIN markets m ON m.id = p.market_id
+                WHERE m.anchor_on_chain AND p.batch_id IS NULL
+                ORDER BY p.decided_at ASC, p.market_id ASC
+                LIMIT $2
+                FOR UPDATE OF p SKIP LOCKED
+            )
+            AND s.batch_id IS NULL
+            RETURNING s.market_id, s.outcome_e8, s.decided_at, s.hash_algorithm
+            "#,
+            batch_id,
+            limit
+        )
+        .fetch_all(db)
+        .await?;
+
+        rows.sort_by_key(|r| (r.decided_at, r.market_id));
+        Ok(rows)
+    }
+
+    /// Stores the batch and its leaves, indexed in the order given. Storing
+    /// the same batch again is a no-op.
+    pub async fn insert(
+        db: &mut PgConnection,
+        id: Uuid,
+        merkle_root: &str,
+        hash_algorithm: HashAlgorithm,
+        leaves: &[NewLeaf],
+        created_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO batches (id, merkle_root, hash_algorithm, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+            id,
+            merkle_root,
+            hash_algorithm.as_str(),
+            created_at
+        )
+        .execute(&mut *db)
+        .await?;
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            sqlx::query!(
+                r#"
+                INSERT INTO batch_items (batch_id, market_id, leaf_index, leaf_hash, leaf_data, leaf_version)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (batch_id, market_id) DO NOTHING
+                "#,
+                id,
+                leaf.market_id,
+                index as i32,
+                leaf.leaf_hash,
+                leaf.leaf_data,
+                SETTLEMENT_ENCODING_VERSION as i16
+            )
+            .execute(&mut *db)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Batches newest first, all of them or those in `status`.
+    pub async fn list<'e, E: PgExecutor<'e>>(
+        db: E,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<BatchSummary>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT b.id, b.merkle_root, b.hash_algorithm, b.status, b.voided_at, b.created_at,
+                   (SELECT COUNT(*) FROM batch_items bi WHERE bi.batch_id = b.id) AS "size!"
+            FROM batches b
+            WHERE ($1::TEXT IS NULL OR b.status = $1)
+            ORDER BY b.created_at DESC, b.id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            status,
+            limit,
+            offset
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| BatchSummary {
+                id: row.id,
+                merkle_root: row.merkle_root,
+                hash_algorithm: HashAlgorithm::parse(&row.hash_algorithm).unwrap_or_default(),
+                size: row.size,
+                status: row.status,
+                voided_at: row.voided_at,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+
+    pub async fn summary<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+    ) -> Result<Option<BatchSummary>, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT b.id, b.merkle_root, b.hash_algorithm, b.status, b.voided_at, b.created_at,
+                   (SELECT COUNT(*) FROM batch_items bi WHERE bi.batch_id = b.id) AS "size!"
+            FROM batches b
+            WHERE b.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(row.map(|row| BatchSummary {
+            id: row.id,
+            merkle_root: row.merkle_root,
+            hash_algorithm: HashAlgorithm::parse(&row.hash_algorithm).unwrap_or_default(),
+            size: row.size,
+            status: row.status,
+            voided_at: row.voided_at,
+            created_at: row.created_at,
+        }))
+    }
+
+    /// The markets a batch holds, in leaf order.
+    pub async fn market_ids<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            "SELECT market_id FROM batch_items WHERE batch_id = $1 ORDER BY leaf_index ASC",
+            id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// The batch holding a market's settlement. Voided batches hold nothing.
+    pub async fn for_market<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Option<BatchRef>, sqlx::Error> {
+        sqlx::query_as!(
+            BatchRef,
+            r#"
+            SELECT b.id, b.merkle_root, b.hash_algorithm
+            FROM batch_items bi
+            JOIN batches b ON b.id = bi.batch_id
+            WHERE bi.market_id = $1 AND b.voided_at IS NULL
+            "#,
+            market_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// A batch's stored leaves in leaf order. Items whose leaf was never
+    /// recorded are left out.
+    pub async fn leaves<'e, E: PgExecutor<'e>>(
+        db: E,
+        batch_id: Uuid,
+    ) -> Result<Vec<StoredLeaf>, sqlx::Error> {
+        sqlx::query_as!(
+            StoredLeaf,
+            r#"
+            SELECT market_id, leaf_index AS "leaf_index!", leaf_hash AS "leaf_hash!", leaf_data AS "leaf_data!",
+                   leaf_version
+            FROM batch_items
+            WHERE batch_id = $1 AND leaf_hash IS NOT NULL
+            ORDER BY leaf_index ASC
+            "#,
+            batch_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Batches with items from before leaves were stored.
+    pub async fn unrecorded<'e, E: PgExecutor<'e>>(db: E) -> Result<Vec<BatchRef>, sqlx::Error> {
+        sqlx::query_as!(
+            BatchRef,
+            r#"
+            SELECT b.id, b.merkle_root, b.hash_algorithm
+            FROM batches b
+            WHERE EXISTS (SELECT 1 FROM batch_items bi WHERE bi.batch_id = b.id AND bi.leaf_hash IS NULL)
+            ORDER BY b.created_at ASC
+            "#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// A legacy batch's items with their settlements, in the order they
+    /// were inserted. The legacy batcher inserted them in leaf order.
+    pub async fn legacy_items<'e, E: PgExecutor<'e>>(
+        db: E,
+        batch_id: Uuid,
+    ) -> Result<Vec<UnrecordedLeaf>, sqlx::Error> {
+        sqlx::query_as!(
+            UnrecordedLeaf,
+            r#"
+            SELECT bi.market_id, s.outcome, s.decided_at
+            FROM batch_items bi
+            JOIN settlements s ON s.market_id = bi.market_id
+            WHERE bi.batch_id = $1
+            ORDER BY bi.ctid ASC
+            "#,
+            batch_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Moves PENDING and SUBMITTED batches on by their items' latest outbox
+    /// jobs: FAILED once one of them failed or was abandoned, CONFIRMED once
+    /// all are confirmed, SUBMITTED once one has been sent.
+    pub async fn sync_statuses<'e, E: PgExecutor<'e>>(
+        db: E,
+    ) -> Result<Vec<BatchTransition>, sqlx::Error> {
+        sqlx::query_as!(
+            BatchTransition,
+            r#"
+            WITH derived AS (
+                SELECT b.id, b.status AS previous,
+                       CASE
+                           WHEN bool_or(o.status IN ('FAILED', 'ABANDONED')) THEN 'FAILED'
+                           WHEN bool_and(o.status = 'CONFIRMED') THEN 'CONFIRMED'
+                           WHEN bool_or(o.status IN ('SENT', 'CONFIRMED')) THEN 'SUBMITTED'
+                           ELSE 'PENDING'
+                       END AS status
+                FROM batches b
+                JOIN batch_items bi ON bi.batch_id = b.id
+                LEFT JOIN LATERAL (
+                    SELECT status FROM outbox
+                    WHERE market_id = bi.market_id
+                    ORDER BY created_at DESC
+                    LIMIT 1
+                ) o ON true
+                WHERE b.status IN ('PENDING', 'SUBMITTED') AND b.voided_at IS NULL
+                GROUP BY b.id, b.status
+            )
+            UPDATE batches b
+            SET status = d.status, status_changed_at = now()
+            FROM derived d
+            WHERE b.id = d.id AND b.status <> d.status
+            RETURNING b.id, d.previous AS "from!", d.status AS "to!"
+            "#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn lock<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+    ) -> Result<Option<LockedBatch>, sqlx::Error> {
+        sqlx::query_as!(
+            LockedBatch,
+            "SELECT status, voided_at FROM batches WHERE id = $1 FOR UPDATE",
+            id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Releases the batch's settlements to be batched again. Its items stay
+    /// for the record. Returns their markets.
+    pub async fn void(
+        db: &mut PgConnection,
+        id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query!("UPDATE batches SET voided_at = $2 WHERE id = $1", id, now)
+            .execute(&mut *db)
+            .await?;
+
+        sqlx::query!(
+            "UPDATE settlements SET batch_id = NULL WHERE batch_id = $1",
+            id
+        )
+        .execute(&mut *db)
+        .await?;
+
+        Self::market_ids(&mut *db, id).await
+    }
+
+    /// Fills in a leaf left unrecorded. No-op if it already has one.
+    pub async fn record_leaf<'e, E: PgExecutor<'e>>(
+        db: E,
+        batch_id: Uuid,
+        market_id: Uuid,
+        leaf_index: i32,
+        leaf_hash: &str,
+        leaf_data: &str,
+        leaf_version: u8,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE batch_items
+            SET leaf_index = $3, leaf_hash = $4, leaf_data = $5, leaf_version = $6
+            WHERE batch_id = $1 AND market_id = $2 AND leaf_hash IS NULL
+            "#,
+            batch_id,
+            market_id,
+            leaf_index,
+            leaf_hash,
+            leaf_data,
+            leaf_version as i16
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,718 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{PgConnection, PgExecutor};
+use uuid::Uuid;
+
+use crate::resolution::MarketRequirements;
+use crate::types::Market;
+use crate::value_type::ValueType;
+
+pub struct MarketRepo;
+
+/// Filters for `MarketRepo::list`; `None` matches everything.
+#[derive(Default, Clone, PartialEq, Eq, Hash)]
+pub struct MarketFilter {
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    pub status: Option<String>,
+    pub id: Option<Uuid>,
+    pub include_archived: bool,
+}
+
+pub struct NewMarket<'a> {
+    pub id: Uuid,
+    pub question: &'a str,
+    pub opens_at: Option<DateTime<Utc>>,
+    pub reporting_opens_at: Option<DateTime<Utc>>,
+    pub closes_at: Option<DateTime<Utc>>,
+    pub resolve_deadline: Option<DateTime<Utc>>,
+    pub status: &'a str,
+    pub outcome_type: Value,
+    pub value_type: &'a str,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub resolution: Value,
+    pub self_report_policy: Value,
+    pub late_report_policy: Value,
+    pub category: Option<&'a str>,
+    pub tags: &'a [String],
+    pub chain_id: Option<i64>,
+    pub anchor_on_chain: bool,
+    pub close_notice_secs: Option<&'a [i32]>,
+    pub close_conditions: Value,
+    pub requirements: Option<&'a MarketRequirements>,
+    pub consensus_bps: Option<i32>,
+    pub decimals: Option<i16>,
+    pub payout: Option<Value>,
+    pub idempotency_key: Option<&'a str>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What the resolver needs to decide a CLOSED market.
+pub(crate) struct ClosedMarket {
+    pub id: Uuid,
+    pub resolution: Value,
+    pub self_report_policy: Value,
+    pub late_report_policy: Value,
+    pub outcome_type: Value,
+    pub value_type: String,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub chain_id: Option<i64>,
+    pub anchor_on_chain: bool,
+    pub consensus_bps: Option<i32>,
+    pub priority: i32,
+    pub decimals: Option<i16>,
+    // reporting_opens_at, else opens_at, else created_at
+    pub reporting_opens_at: DateTime<Utc>,
+    pub closes_at: DateTime<Utc>,
+}
+
+/// A market row held `FOR UPDATE` while an admin action checks it.
+pub struct LockedMarket {
+    pub status: String,
+    pub opens_at: Option<DateTime<Utc>>,
+    pub closes_at: Option<DateTime<Utc>>,
+    pub resolve_deadline: Option<DateTime<Utc>>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub outcome_type: Value,
+    pub group_id: Option<Uuid>,
+    pub group_status: Option<String>,
+    pub priority: i32,
+}
+
+pub struct ConditionalMarket {
+    pub id: Uuid,
+    pub close_conditions: Value,
+}
+
+pub struct CloseNotice {
+    pub market_id: Uuid,
+    pub lead_secs: i32,
+    pub closes_at: DateTime<Utc>,
+}
+
+pub struct ExpiredMarket {
+    pub id: Uuid,
+    pub resolve_deadline: DateTime<Utc>,
+    pub group_id: Option<Uuid>,
+}
+
+impl MarketRepo {
+    /// Markets matching `filter`, newest first. Archived rows come from
+    /// `markets_archive` when asked for. `default_bps` fills in
+    /// `consensus_bps` for markets that didn't set their own.
+    pub async fn list<'e, E: PgExecutor<'e>>(
+        db: E,
+        filter: &MarketFilter,
+        default_bps: u32,
+    ) -> Result<Vec<Market>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT m.id AS "id!", m.question AS "question!", m.opens_at, m.reporting_opens_at,
+                   m.closes_at, m.resolve_deadline, m.status AS "status!",
+                   m.outcome_type AS "outcome_type!", m.value_type AS "value_type!", m.min_value, m.max_value,
+                   m.resolution AS "resolution!", m.self_report_policy AS "self_report_policy!",
+                   m.late_report_policy AS "late_report_policy!", m.category,
+                   m.chain_id, m.anchor_on_chain AS "anchor_on_chain!", m.close_notice_secs,
+                   m.close_conditions AS "close_conditions!", m.close_trigger, m.closed_reason,
+                   m.consensus_bps, m.priority AS "priority!", m.decimals, m.payout, m.group_id, m.created_at AS "created_at!",
+                   m.tags AS "tags!",
+                   m.required_sources AS "required_sources?", m.min_reports AS "min_reports?",
+                   m.min_sources AS "min_sources?",
+                   m.archived_at AS "archived_at?"
+            FROM (
+                SELECT m.id, m.question, m.opens_at, m.reporting_opens_at, m.closes_at, m.resolve_deadline,
+                       m.status, m.outcome_type,
+                       m.value_type, m.min_value, m.max_value, m.resolution, m.self_report_policy,
+                       m.late_report_policy, m.category,
+                       m.chain_id, m.anchor_on_chain, m.close_notice_secs, m.close_conditions, m.close_trigger, m.closed_reason,
+                       m.consensus_bps, m.priority, m.decimals, m.payout,
+                       m.group_id, m.created_at,
+                       ARRAY(SELECT t.tag FROM market_tags t WHERE t.market_id = m.id ORDER BY t.tag) AS tags,
+                       r.required_sources, r.min_reports, r.min_sources,
+                       NULL::TIMESTAMPTZ AS archived_at
+                FROM markets m
+                LEFT JOIN market_requirements r ON r.market_id = m.id
+                UNION ALL
+                SELECT a.id, a.question, a.opens_at, a.reporting_opens_at, a.closes_at, a.resolve_deadline,
+                       a.status, a.outcome_type,
+                       a.value_type, a.min_value, a.max_value, a.resolution, a.self_report_policy,
+                       a.late_report_policy, a.category,
+                       a.chain_id, a.anchor_on_chain, a.close_notice_secs, a.close_conditions, a.close_trigger, a.closed_reason,
+                       a.consensus_bps, a.priority, a.decimals, a.payout,
+                       a.group_id, a.created_at, a.tags, a.required_sources, a.min_reports,
+                       a.min_sources, a.archived_at
+                FROM markets_archive a
+                WHERE $5
+            ) m
+            WHERE ($1::TEXT IS NULL OR m.category = $1)
+              AND ($2::TEXT IS NULL OR $2 = ANY(m.tags))
+              AND ($3::TEXT IS NULL OR m.status = $3)
+              AND ($4::UUID IS NULL OR m.id = $4)
+            ORDER BY m.created_at DESC
+            "#,
+            filter.category,
+            filter.tag,
+            filter.status,
+            filter.id,
+            filter.include_archived
+        )
+        .fetch_all(db)
+        .await?;
+
+        let markets = rows
+            .into_iter()
+            .map(|row| Market {
+                id: row.id,
+                question: row.question,
+                opens_at: row.opens_at,
+                reporting_opens_at: row.reporting_opens_at,
+                closes_at: row.closes_at,
+                resolve_deadline: row.resolve_deadline,
+                status: row.status,
+                outcome_type: serde_json::from_value(row.outcome_type).unwrap_or_default(),
+                value_type: ValueType::parse(&row.value_type).unwrap_or_default(),
+                min_value: row.min_value,
+                max_value: row.max_value,
+                resolution: serde_json::from_value(row.resolution).unwrap_or_default(),
+                self_report_policy: serde_json::from_value(row.self_report_policy)
+                    .unwrap_or_default(),
+                late_report_policy: serde_json::from_value(row.late_report_policy)
+                    .unwrap_or_default(),
+                category: row.category,
+                tags: row.tags,
+                chain_id: row.chain_id.map(|c| c as u64),
+                anchor_on_chain: row.anchor_on_chain,
+                close_notice_secs: row.close_notice_secs,
+                close_conditions: serde_json::from_value(row.close_conditions).unwrap_or_default(),
+                close_trigger: row
+                    .close_trigger
+                    .and_then(|t| serde_json::from_value(t).ok()),
+                closed_reason: row.closed_reason,
+                requirements: row.required_sources.map(|sources| MarketRequirements {
+                    required_sources: sources,
+                    min_reports: row.min_reports.map(|n| n.max(0) as usize),
+                    min_sources: row.min_sources.map(|n| n.max(0) as usize),
+                }),
+                consensus_bps: row
+                    .consensus_bps
+                    .map(|bps| bps.max(0) as u32)
+                    .unwrap_or(default_bps),
+                priority: row.priority,
+                decimals: row.decimals.map(|d| d.max(0) as u32),
+                payout: row.payout.and_then(|p| serde_json::from_value(p).ok()),
+                group_id: row.group_id,
+                created_at: row.created_at,
+                archived_at: row.archived_at,
+            })
+            .collect();
+
+        Ok(markets)
+    }
+
+    pub async fn get<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        include_archived: bool,
+        default_bps: u32,
+    ) -> Result<Option<Market>, sqlx::Error> {
+        let filter = MarketFilter {
+            id: Some(market_id),
+            include_archived,
+            ..Default::default()
+        };
+
+        Ok(Self::list(db, &filter, default_bps)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    pub async fn id_for_idempotency_key<'e, E: PgExecutor<'e>>(
+        db: E,
+        key: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!("SELECT id FROM markets WHERE idempotency_key = $1", key)
+            .fetch_optional(db)
+            .await
+    }
+
+    /// Inserts the market with its tags and requirements. A duplicate
+    /// `idempotency_key` surfaces as the unique violation from the first
+    /// insert.
+    pub async fn insert(
+        conn: &mut PgConnection,
+        market: &NewMarket<'_>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO markets
+            (id, question, opens_at, closes_at, status, value_type, min_value, max_value,
+             resolution, self_report_policy, category, chain_id, close_notice_secs,
+             close_conditions, idempotency_key, resolve_deadline, outcome_type, consensus_bps, created_at,
+             reporting_opens_at, late_report_policy, anchor_on_chain, decimals, payout)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21,
+                    $22, $23, $24)
+            "#,
+            market.id,
+            market.question,
+            market.opens_at,
+            market.closes_at,
+            market.status,
+            market.value_type,
+            market.min_value,
+            market.max_value,
+            market.resolution,
+            market.self_report_policy,
+            market.category,
+            market.chain_id,
+            market.close_notice_secs,
+            market.close_conditions,
+            market.idempotency_key,
+            market.resolve_deadline,
+            market.outcome_type,
+            market.consensus_bps,
+            market.created_at,
+            market.reporting_opens_at,
+            market.late_report_policy,
+            market.anchor_on_chain,
+            market.decimals,
+            market.payout
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        for tag in market.tags {
+            sqlx::query!(
+                "INSERT INTO market_tags (market_id, tag) VALUES ($1, $2)",
+                market.id,
+                tag
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        if let Some(r) = market.requirements {
+            sqlx::query!(
+                r#"
+                INSERT INTO market_requirements (market_id, required_sources, min_reports, min_sources)
+                VALUES ($1, $2, $3, $4)
+                "#,
+                market.id,
+                &r.required_sources,
+                r.min_reports.map(|n| n as i32),
+                r.min_sources.map(|n| n as i32)
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn requirements<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Option<MarketRequirements>, sqlx::Error> {
+        let row = sqlx::query!(
+            "SELECT required_sources, min_reports, min_sources FROM market_requirements WHERE market_id = $1",
+            market_id
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(row.map(|r| MarketRequirements {
+            required_sources: r.required_sources,
+            min_reports: r.min_reports.map(|n| n.max(0) as usize),
+            min_sources: r.min_sources.map(|n| n.max(0) as usize),
+        }))
+    }
+
+    /// The market's value type and its own decimals, if it set them.
+    pub async fn value_format<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Option<(String, Option<i16>)>, sqlx::Error> {
+        let row = sqlx::query!(
+            "SELECT value_type, decimals FROM markets WHERE id = $1",
+            market_id
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(row.map(|r| (r.value_type, r.decimals)))
+    }
+
+    /// Locks the market row (not its group) for the rest of the transaction.
+    pub async fn lock<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Option<LockedMarket>, sqlx::Error> {
+        sqlx::query_as!(
+            LockedMarket,
+            r#"
+            SELECT m.status, m.opens_at, m.closes_at, m.resolve_deadline, m.min_value, m.max_value,
+                   m.outcome_type, m.group_id, g.status AS "group_status?", m.priority
+            FROM markets m
+            LEFT JOIN market_groups g ON g.id = m.group_id
+            WHERE m.id = $1
+            FOR UPDATE OF m
+            "#,
+            market_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn set_status<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        status: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE markets SET status = $1 WHERE id = $2",
+            status,
+            market_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_priority<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        priority: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE markets SET priority = $1 WHERE id = $2",
+            priority,
+            market_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Moves closes_at and resolve_deadline, clears any close trigger and
+    /// forgets the closing-soon notices already sent so they fire again.
+    pub async fn reschedule(
+        conn: &mut PgConnection,
+        market_id: Uuid,
+        closes_at: DateTime<Utc>,
+        resolve_deadline: DateTime<Utc>,
+        status: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE markets
+            SET closes_at = $1,
+                resolve_deadline = $2,
+                status = $3,
+                close_trigger = NULL
+            WHERE id = $4
+            "#,
+            closes_at,
+            resolve_deadline,
+            status,
+            market_id
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM market_close_notices WHERE market_id = $1",
+            market_id
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Closes the market now (if it hadn't already) for an operator settlement.
+    pub(crate) async fn force_close<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<ClosedMarket, sqlx::Error> {
+        sqlx::query_as!(
+            ClosedMarket,
+            r#"
+            UPDATE markets
+            SET status = 'CLOSED',
+                closes_at = LEAST(closes_at, now())
+            WHERE id = $1
+            RETURNING id, resolution, self_report_policy, late_report_policy, outcome_type, value_type, min_value, max_value,
+                      chain_id, anchor_on_chain, consensus_bps, priority, decimals,
+                      COALESCE(reporting_opens_at, opens_at, created_at) AS "reporting_opens_at!", closes_at AS "closes_at!"
+            "#,
+            market_id
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    /// SCHEDULED markets whose opens_at has passed become OPEN.
+    pub async fn open_scheduled<'e, E: PgExecutor<'e>>(
+        db: E,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            UPDATE markets
+            SET status = 'OPEN'
+            WHERE status = 'SCHEDULED'
+              AND opens_at <= $1
+            RETURNING id
+            "#,
+            now
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Records one notice per (market, lead time) that has come due and
+    /// returns the new ones; already-sent notices conflict and are skipped.
+    pub async fn claim_close_notices<'e, E: PgExecutor<'e>>(
+        db: E,
+        now: DateTime<Utc>,
+        default_secs: &[i32],
+    ) -> Result<Vec<CloseNotice>, sqlx::Error> {
+        sqlx::query_as!(
+            CloseNotice,
+            r#"
+            WITH sent AS (
+                INSERT INTO market_close_notices (market_id, lead_secs, sent_at)
+                SELECT m.id, l.lead_secs, $1
+                FROM markets m
+                CROSS JOIN LATERAL unnest(COALESCE(m.close_notice_secs, $2::INT[])) AS l(lead_secs)
+                WHERE m.status = 'OPEN'
+                  AND m.closes_at > $1
+                  AND m.closes_at - make_interval(secs => l.lead_secs) <= $1
+                ON CONFLICT DO NOTHING
+                RETURNING market_id, lead_secs
+            )
+            SELECT sent.market_id, sent.lead_secs, m.closes_at AS "closes_at!"
+            FROM sent
+            JOIN markets m ON m.id = sent.market_id
+            ORDER BY sent.lead_secs DESC
+            "#,
+            now,
+            default_secs
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn open_with_close_conditions<'e, E: PgExecutor<'e>>(
+        db: E,
+    ) -> Result<Vec<ConditionalMarket>, sqlx::Error> {
+        sqlx::query_as!(
+            ConditionalMarket,
+            r#"
+            SELECT id, close_conditions
+            FROM markets
+            WHERE status = 'OPEN'
+              AND close_conditions <> '[]'::JSONB
+            "#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Closes an OPEN market early, pulling closes_at in to `now`. False if
+    /// it was no longer OPEN.
+    pub async fn close_on_trigger<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        now: DateTime<Utc>,
+        trigger: &Value,
+    ) -> Result<bool, sqlx::Error> {
+        let closed = sqlx::query!(
+            r#"
+            UPDATE markets
+            SET status = 'CLOSED',
+                closes_at = LEAST(closes_at, $1),
+                close_trigger = $2
+            WHERE id = $3 AND status = 'OPEN'
+            "#,
+            now,
+            trigger,
+            market_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(closed.rows_affected() == 1)
+    }
+
+    /// Closes an OPEN market now on an operator's word, for markets that
+    /// close on an event. closes_at is pulled in to `now` and a missing
+    /// resolve_deadline set to `default_deadline`. False if it was no longer
+    /// OPEN.
+    pub async fn close_now<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        now: DateTime<Utc>,
+        default_deadline: DateTime<Utc>,
+        reason: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let closed = sqlx::query!(
+            r#"
+            UPDATE markets
+            SET status = 'CLOSED',
+                closes_at = LEAST(closes_at, $1),
+                resolve_deadline = COALESCE(resolve_deadline, $2),
+                closed_reason = $3
+            WHERE id = $4 AND status = 'OPEN'
+            "#,
+            now,
+            default_deadline,
+            reason,
+            market_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(closed.rows_affected() == 1)
+    }
+
+    /// OPEN markets whose closes_at has passed become CLOSED; event-closed
+    /// markets, with no closes_at, are left to `close_now`.
+    pub async fn close_due<'e, E: PgExecutor<'e>>(
+        db: E,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            UPDATE markets
+            SET status = 'CLOSED'
+            WHERE status = 'OPEN'
+              AND closes_at <= $1
+            RETURNING id
+            "#,
+            now
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Ungrouped CLOSED markets whose id hashes to `shard` and that closed
+    /// by `closed_before`, highest priority first and then in (closes_at, id)
+    /// order, starting after the `after` key. The order matches
+    /// `markets_resolution_queue_idx`.
+    pub(crate) async fn closed_in_shard<'e, E: PgExecutor<'e>>(
+        db: E,
+        shard: i32,
+        shards: i32,
+        closed_before: DateTime<Utc>,
+        after: Option<(i32, DateTime<Utc>, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<ClosedMarket>, sqlx::Error> {
+        let (after_priority, after_closes_at, after_id) = match after {
+            Some((priority, closes_at, id)) => (Some(priority), Some(closes_at), Some(id)),
+            None => (None, None, None),
+        };
+
+        sqlx::query_as!(
+            ClosedMarket,
+            r#"
+            SELECT id, resolution, self_report_policy, late_report_policy, outcome_type, value_type, min_value, max_value,
+                   chain_id, anchor_on_chain, consensus_bps, priority, decimals,
+                   COALESCE(reporting_opens_at, opens_at, created_at) AS "reporting_opens_at!", closes_at AS "closes_at!"
+            FROM markets
+            WHERE status = 'CLOSED'
+              AND group_id IS NULL
+              AND abs(hashtext(id::TEXT) % $2) = $1
+              AND closes_at <= $3
+              AND ($4::INTEGER IS NULL
+                   OR priority < $4
+                   OR (priority = $4 AND (closes_at, id) > ($5::TIMESTAMPTZ, $6::UUID)))
+            ORDER BY priority DESC, closes_at ASC, id ASC
+            LIMIT $7
+            "#,
+            shard,
+            shards,
+            closed_before,
+            after_priority,
+            after_closes_at,
+            after_id,
+            limit
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub(crate) async fn group_members<'e, E: PgExecutor<'e>>(
+        db: E,
+        group_id: Uuid,
+    ) -> Result<Vec<ClosedMarket>, sqlx::Error> {
+        sqlx::query_as!(
+            ClosedMarket,
+            r#"
+            SELECT id, resolution, self_report_policy, late_report_policy, outcome_type, value_type, min_value, max_value,
+                   chain_id, anchor_on_chain, consensus_bps, priority, decimals,
+                   COALESCE(reporting_opens_at, opens_at, created_at) AS "reporting_opens_at!", closes_at AS "closes_at!"
+            FROM markets
+            WHERE group_id = $1
+            ORDER BY id
+            "#,
+            group_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// CLOSED markets past their resolve deadline become UNRESOLVED.
+    pub async fn expire_unresolved<'e, E: PgExecutor<'e>>(
+        db: E,
+    ) -> Result<Vec<ExpiredMarket>, sqlx::Error> {
+        sqlx::query_as!(
+            ExpiredMarket,
+            r#"
+            UPDATE markets
+            SET status = 'UNRESOLVED'
+            WHERE status = 'CLOSED'
+              AND resolve_deadline <= now()
+            RETURNING id, resolve_deadline AS "resolve_deadline!", group_id
+            "#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// CLOSED -> PROPOSED, for a settlement awaiting its chain confirmation.
+    /// Returns whether the market moved.
+    pub async fn mark_proposed<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE markets SET status = 'PROPOSED' WHERE id = $1 AND status = 'CLOSED'",
+            market_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// CLOSED or PROPOSED -> RESOLVED. Returns whether the market moved.
+    pub async fn mark_resolved<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE markets SET status = 'RESOLVED' WHERE id = $1 AND status IN ('CLOSED', 'PROPOSED')",
+            market_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,26 @@
+//! Typed access to the market, report, settlement, batch, outbox and
+//! template tables. Every method takes an executor so callers decide whether it runs
+//! on the pool or inside their transaction.
+
+mod batch;
+mod market;
+mod outbox;
+mod report;
+mod settlement;
+mod template;
+
+pub use batch::{
+    BatchRef, BatchRepo, BatchTransition, ClaimedSettlement, LockedBatch, NewLeaf, StoredLeaf,
+    UnrecordedLeaf,
+};
+pub(crate) use market::ClosedMarket;
+pub use market::{
+    CloseNotice, ConditionalMarket, ExpiredMarket, LockedMarket, MarketFilter, MarketRepo,
+    NewMarket,
+};
+pub use outbox::{ClaimedJob, MatchedJob, NewSubmission, OutboxRepo, SentJob};
+pub use report::{
+    ExistingReport, LockedReport, NewReport, ReportRepo, ReportUpdate, SOURCE_UNIQUE_CONSTRAINT,
+};
+pub use settlement::{NewSettlement, SettlementRecord, SettlementRepo};
+pub use template::{NewTemplate, TemplateRepo};
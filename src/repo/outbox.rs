@@ -0,0 +1,583 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::types::OutboxJob;
+
+pub struct OutboxRepo;
+
+/// A job taken by a worker pass, with any intent an earlier pass recorded.
+pub struct ClaimedJob {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub payload: Value,
+    pub status: String,
+    pub retries: i32,
+    pub intent_tx_hash: Option<String>,
+    pub intent_raw_tx: Option<String>,
+    pub intent_submitter: Option<String>,
+}
+
+/// A SENT job and the transaction the reconciler should follow.
+pub struct SentJob {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub payload: Value,
+    pub tx_hash: Option<String>,
+    pub block_number: Option<i64>,
+}
+
+/// A job whose payload anchors a given settlement, locked for update.
+pub struct MatchedJob {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub status: String,
+    pub payload: Value,
+}
+
+pub struct NewSubmission<'a> {
+    pub outbox_id: Uuid,
+    pub market_id: Uuid,
+    pub tx_hash: &'a str,
+    pub block_number: Option<i64>,
+    pub gas_used: Option<i64>,
+    pub submitter: &'a str,
+}
+
+impl OutboxRepo {
+    /// Newest first.
+    pub async fn list<'e, E: PgExecutor<'e>>(
+        db: E,
+        status: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<OutboxJob>, sqlx::Error> {
+        sqlx::query_as!(
+            OutboxJob,
+            r#"
+            SELECT id, market_id, payload, status, retries, last_error, intent_tx_hash, claimed_by, claimed_at,
+                   next_attempt_at, confirmed_at, priority, chain_id, created_at, updated_at
+            FROM outbox
+            WHERE ($1::TEXT IS NULL OR status = $1)
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            status,
+            limit
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn get<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+    ) -> Result<Option<OutboxJob>, sqlx::Error> {
+        sqlx::query_as!(
+            OutboxJob,
+            r#"
+            SELECT id, market_id, payload, status, retries, last_error, intent_tx_hash, claimed_by, claimed_at,
+                   next_attempt_at, confirmed_at, priority, chain_id, created_at, updated_at
+            FROM outbox
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn status<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!("SELECT status FROM outbox WHERE id = $1", id)
+            .fetch_optional(db)
+            .await
+    }
+
+    /// Payload of the market's newest job.
+    pub async fn latest_payload<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Option<Value>, sqlx::Error> {
+        sqlx::query_scalar!(
+            "SELECT payload FROM outbox WHERE market_id = $1 ORDER BY created_at DESC LIMIT 1",
+            market_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// `chain_id` is the chain the payload is pinned to, if any.
+    pub async fn enqueue<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+        market_id: Uuid,
+        payload: &Value,
+        chain_id: Option<i64>,
+        priority: i32,
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO outbox
+            (id, market_id, payload, status, retries, last_error, chain_id, priority, created_at, updated_at)
+            VALUES ($1, $2, $3, 'PENDING', 0, NULL, $4, $5, $6, $6)
+            "#,
+            id,
+            market_id,
+            payload,
+            chain_id,
+            priority,
+            now
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Claims up to `limit` due PENDING/INTENT jobs bound for `chain_id` for
+    /// `worker_id`, highest priority first, then oldest. The default chain's
+    /// queue passes the other configured chains as `others` and also takes
+    /// jobs pinned to no chain or to one that isn't configured. Claims older
+    /// than `claim_ttl_secs` are taken over. SKIP LOCKED lets concurrent
+    /// workers each take a disjoint set of rows.
+    pub async fn claim<'e, E: PgExecutor<'e>>(
+        db: E,
+        worker_id: &str,
+        chain_id: i64,
+        others: Option<&[i64]>,
+        claim_ttl_secs: f64,
+        limit: i64,
+    ) -> Result<Vec<ClaimedJob>, sqlx::Error> {
+        sqlx::query_as!(
+            ClaimedJob,
+            r#"
+            UPDATE outbox
+            SET claimed_by = $1,
+                claimed_at = now()
+            WHERE id IN (
+                SELECT id
+                FROM outbox
+                WHERE status IN ('PENDING', 'INTENT')
+                  AND (chain_id = $2
+                       OR ($3::BIGINT[] IS NOT NULL
+                           AND (chain_id IS NULL OR chain_id <> ALL($3))))
+                  AND next_attempt_at <= now()
+                  AND (claimed_at IS NULL
+                       OR claimed_at < now() - make_interval(secs => $4))
+                ORDER BY priority DESC, created_at ASC
+                LIMIT $5
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, market_id, payload, status, retries,
+                      intent_tx_hash, intent_raw_tx, intent_submitter
+            "#,
+            worker_id,
+            chain_id,
+            others,
+            claim_ttl_secs,
+            limit
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn set_priority<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+        priority: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE outbox SET priority = $1, updated_at = now() WHERE id = $2",
+            priority,
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn release_claim<'e, E: PgExecutor<'e>>(db: E, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE outbox SET claimed_by = NULL, claimed_at = NULL WHERE id = $1",
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stores a signed transaction before it is broadcast.
+    pub async fn record_intent<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+        tx_hash: &str,
+        raw_tx: &str,
+        submitter: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE outbox
+            SET status = 'INTENT',
+                intent_tx_hash = $1,
+                intent_raw_tx = $2,
+                intent_submitter = $3,
+                intent_at = now(),
+                updated_at = now()
+            WHERE id = $4
+            "#,
+            tx_hash,
+            raw_tx,
+            submitter,
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Forgets the recorded intent and sends a job currently in `from` back to
+    /// PENDING for an immediate re-sign. False if the job had moved on.
+    pub async fn requeue<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+        from: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let requeued = sqlx::query!(
+            r#"
+            UPDATE outbox
+            SET status = 'PENDING',
+                intent_tx_hash = NULL,
+                intent_raw_tx = NULL,
+                intent_submitter = NULL,
+                intent_at = NULL,
+                claimed_by = NULL,
+                claimed_at = NULL,
+                next_attempt_at = now(),
+                updated_at = now()
+            WHERE id = $1 AND status = $2
+            "#,
+            id,
+            from
+        )
+        .execute(db)
+        .await?;
+
+        Ok(requeued.rows_affected() > 0)
+    }
+
+    /// Operator retry of a dead-lettered job with a fresh retry budget. A
+    /// recorded intent is kept so the worker checks that tx first.
+    pub async fn retry<'e, E: PgExecutor<'e>>(db: E, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE outbox
+            SET status = 'PENDING',
+                retries = 0,
+                last_error = NULL,
+                claimed_by = NULL,
+                claimed_at = NULL,
+                next_attempt_at = now(),
+                updated_at = now()
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The markets' latest jobs, where those are FAILED.
+    pub async fn latest_failed<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT o.id AS "id!"
+            FROM unnest($1::UUID[]) AS m(market_id)
+            JOIN LATERAL (
+                SELECT id, status FROM outbox
+                WHERE market_id = m.market_id
+                ORDER BY created_at DESC
+                LIMIT 1
+            ) o ON true
+            WHERE o.status = 'FAILED'
+            "#,
+            market_ids
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Abandons the market's jobs no worker has picked up, so a superseded
+    /// settlement isn't anchored after its correction. Jobs already in
+    /// flight are left to finish.
+    pub async fn abandon_unclaimed<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        reason: &str,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            UPDATE outbox
+            SET status = 'ABANDONED',
+                last_error = $2,
+                updated_at = now()
+            WHERE market_id = $1
+              AND status = 'PENDING'
+              AND claimed_by IS NULL
+            RETURNING id
+            "#,
+            market_id,
+            reason
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn abandon<'e, E: PgExecutor<'e>>(db: E, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE outbox SET status = 'ABANDONED', updated_at = now() WHERE id = $1",
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_sent<'e, E: PgExecutor<'e>>(db: E, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE outbox
+            SET status = 'SENT',
+                updated_at = now(),
+                last_error = NULL,
+                claimed_by = NULL,
+                claimed_at = NULL
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Counts a failed attempt and schedules the next one `delay_secs` out.
+    /// Past `max_retries` the job is FAILED; a job with a recorded intent
+    /// stays INTENT so the next pass asks the chain first. Returns the new
+    /// status.
+    pub async fn record_failure<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+        retries: i32,
+        error: &str,
+        delay_secs: f64,
+        max_retries: i32,
+    ) -> Result<String, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            UPDATE outbox
+            SET retries = $1::INT,
+                last_error = $2,
+                status = CASE
+                    WHEN $1::INT > $5::INT THEN 'FAILED'
+                    WHEN intent_tx_hash IS NOT NULL THEN 'INTENT'
+                    ELSE 'PENDING'
+                END,
+                next_attempt_at = now() + make_interval(secs => $4::FLOAT8),
+                claimed_by = NULL,
+                claimed_at = NULL,
+                updated_at = now()
+            WHERE id = $3
+            RETURNING status
+            "#,
+            retries,
+            error,
+            id,
+            delay_secs,
+            max_retries
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    pub async fn mark_failed<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE outbox
+            SET status = 'FAILED',
+                last_error = $1,
+                claimed_by = NULL,
+                claimed_at = NULL,
+                updated_at = now()
+            WHERE id = $2
+            "#,
+            error,
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Whether the job was still SENT and is now CONFIRMED.
+    pub async fn mark_confirmed<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+        block_number: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let confirmed = sqlx::query!(
+            r#"
+            UPDATE outbox
+            SET status = 'CONFIRMED',
+                confirmed_at = now(),
+                confirmed_block = $1,
+                updated_at = now()
+            WHERE id = $2 AND status = 'SENT'
+            "#,
+            block_number,
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(confirmed.rows_affected() == 1)
+    }
+
+    /// SENT jobs, least recently touched first, with the tx to follow: the
+    /// newest recorded submission, else the intent.
+    pub async fn sent<'e, E: PgExecutor<'e>>(
+        db: E,
+        limit: i64,
+    ) -> Result<Vec<SentJob>, sqlx::Error> {
+        sqlx::query_as!(
+            SentJob,
+            r#"
+            SELECT o.id, o.market_id, o.payload,
+                   COALESCE(c.tx_hash, o.intent_tx_hash) AS tx_hash,
+                   c.block_number AS "block_number?"
+            FROM outbox o
+            LEFT JOIN LATERAL (
+                SELECT tx_hash, block_number FROM chain_submissions
+                WHERE outbox_id = o.id
+                ORDER BY created_at DESC
+                LIMIT 1
+            ) c ON true
+            WHERE o.status = 'SENT'
+            ORDER BY o.updated_at ASC
+            LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Unconfirmed jobs anchoring `leaf_hex` for `market_hash_hex`, locked.
+    pub async fn lock_for_settlement<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_hash_hex: &str,
+        leaf_hex: &str,
+    ) -> Result<Vec<MatchedJob>, sqlx::Error> {
+        sqlx::query_as!(
+            MatchedJob,
+            r#"
+            SELECT id, market_id, status, payload
+            FROM outbox
+            WHERE payload->>'market_hash_hex' = $1
+              AND payload->>'leaf_hex' = $2
+              AND status <> 'CONFIRMED'
+            FOR UPDATE
+            "#,
+            market_hash_hex,
+            leaf_hex
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Records a mined transaction for a job. False if the tx was already
+    /// recorded.
+    pub async fn record_submission<'e, E: PgExecutor<'e>>(
+        db: E,
+        submission: &NewSubmission<'_>,
+    ) -> Result<bool, sqlx::Error> {
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO chain_submissions
+            (id, outbox_id, market_id, tx_hash, block_number, gas_used, submitter)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (tx_hash) DO NOTHING
+            "#,
+            Uuid::new_v4(),
+            submission.outbox_id,
+            submission.market_id,
+            submission.tx_hash,
+            submission.block_number,
+            submission.gas_used,
+            submission.submitter
+        )
+        .execute(db)
+        .await?;
+
+        Ok(inserted.rows_affected() > 0)
+    }
+
+    /// Whether any transaction has been recorded for the market.
+    pub async fn anchored<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM chain_submissions WHERE market_id = $1) AS "exists!""#,
+            market_id
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    /// A reorg can re-include the tx in a different block.
+    pub async fn move_submission<'e, E: PgExecutor<'e>>(
+        db: E,
+        tx_hash: &str,
+        block_number: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE chain_submissions SET block_number = $1 WHERE tx_hash = $2",
+            block_number,
+            tx_hash
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_submission<'e, E: PgExecutor<'e>>(
+        db: E,
+        tx_hash: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM chain_submissions WHERE tx_hash = $1", tx_hash)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,452 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::resolution::SourceValue;
+use crate::types::{Report, ReportBucket};
+
+pub struct ReportRepo;
+
+pub struct NewReport<'a> {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub source: &'a str,
+    pub value: f64,
+    pub idempotency_key: &'a str,
+    pub self_reported: bool,
+    pub provenance: Option<Value>,
+    pub confidence: Option<f64>,
+    pub stake: Option<f64>,
+    pub reporter_address: Option<&'a str>,
+    pub signature: Option<&'a str>,
+    pub signed_at: Option<DateTime<Utc>>,
+    pub verified: bool,
+    pub late: bool,
+    // audit actor of the caller, who alone may retract it
+    pub submitted_by: Option<&'a str>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A source's report on a market, as create_report checks for it.
+pub struct ExistingReport {
+    pub id: Uuid,
+    pub idempotency_key: String,
+    pub value: f64,
+    // updated_at, else created_at
+    pub written_at: DateTime<Utc>,
+}
+
+/// What an update needs of the report it replaces.
+pub struct LockedReport {
+    pub source: String,
+    pub self_reported: bool,
+    pub submitted_by: Option<String>,
+    pub value: f64,
+    pub reporter_address: Option<String>,
+    pub verified: bool,
+    pub late: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct ReportUpdate<'a> {
+    pub value: f64,
+    pub provenance: Option<Value>,
+    pub confidence: Option<f64>,
+    pub stake: Option<f64>,
+    pub reporter_address: Option<&'a str>,
+    pub signature: Option<&'a str>,
+    pub signed_at: Option<DateTime<Utc>>,
+    pub verified: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Constraint behind the one-report-per-source rule; retracted reports
+/// don't take part.
+pub const SOURCE_UNIQUE_CONSTRAINT: &str = "reports_market_source_key";
+
+impl ReportRepo {
+    /// A reused idempotency key, or a second report from the same source,
+    /// comes back as the unique violation (23505); the constraint name says
+    /// which.
+    pub async fn insert<'e, E: PgExecutor<'e>>(
+        db: E,
+        report: &NewReport<'_>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO reports
+            (id, market_id, source, value, idempotency_key, self_reported, provenance, confidence, stake,
+             reporter_address, signature, signed_at, verified, created_at, late, submitted_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            "#,
+            report.id,
+            report.market_id,
+            report.source,
+            report.value,
+            report.idempotency_key,
+            report.self_reported,
+            report.provenance,
+            report.confidence,
+            report.stake,
+            report.reporter_address,
+            report.signature,
+            report.signed_at,
+            report.verified,
+            report.created_at,
+            report.late,
+            report.submitted_by
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stores a feed reading as its source's report, replacing the previous
+    /// reading. `None` if the source's report on the market came from an
+    /// external reporter, which a feed never overwrites.
+    pub async fn upsert_reading<'e, E: PgExecutor<'e>>(
+        db: E,
+        report: &NewReport<'_>,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO reports
+            (id, market_id, source, value, idempotency_key, self_reported, provenance, created_at)
+            VALUES ($1, $2, $3, $4, $5, true, $6, $7)
+            ON CONFLICT (market_id, source) WHERE retracted_at IS NULL DO UPDATE
+            SET value = EXCLUDED.value,
+                provenance = EXCLUDED.provenance,
+                updated_at = EXCLUDED.created_at
+            WHERE reports.self_reported
+            RETURNING id
+            "#,
+            report.id,
+            report.market_id,
+            report.source,
+            report.value,
+            report.idempotency_key,
+            report.provenance,
+            report.created_at
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn by_source<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        source: &str,
+    ) -> Result<Option<ExistingReport>, sqlx::Error> {
+        sqlx::query_as!(
+            ExistingReport,
+            r#"
+            SELECT id, idempotency_key, value, COALESCE(updated_at, created_at) AS "written_at!"
+            FROM reports
+            WHERE market_id = $1 AND source = $2 AND retracted_at IS NULL
+            "#,
+            market_id,
+            source
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// A live, unretracted report on `market_id`, locked for update.
+    pub async fn lock<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<LockedReport>, sqlx::Error> {
+        sqlx::query_as!(
+            LockedReport,
+            r#"
+            SELECT source, self_reported, submitted_by, value, reporter_address, verified, late, created_at
+            FROM reports
+            WHERE id = $1 AND market_id = $2 AND retracted_at IS NULL
+            FOR UPDATE
+            "#,
+            id,
+            market_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Replaces a report's value and the fields that vouch for it.
+    pub async fn update<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+        update: &ReportUpdate<'_>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE reports
+            SET value = $2,
+                provenance = $3,
+                confidence = $4,
+                stake = $5,
+                reporter_address = $6,
+                signature = $7,
+                signed_at = $8,
+                verified = $9,
+                updated_at = $10
+            WHERE id = $1
+            "#,
+            id,
+            update.value,
+            update.provenance,
+            update.confidence,
+            update.stake,
+            update.reporter_address,
+            update.signature,
+            update.signed_at,
+            update.verified,
+            update.updated_at
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Tombstones a report: it stays listed for audit but no longer counts.
+    pub async fn retract<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE reports SET retracted_at = $2 WHERE id = $1",
+            id,
+            now
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// A live report on `market_id`.
+    pub async fn get<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<Report>, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, market_id, source, value, self_reported, provenance, confidence, stake,
+                   reporter_address, verified, late, created_at, updated_at, retracted_at
+            FROM reports
+            WHERE market_id = $1 AND id = $2
+            "#,
+            market_id,
+            id
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(row.map(|r| Report {
+            id: r.id,
+            market_id: r.market_id,
+            source: r.source,
+            value: r.value,
+            self_reported: r.self_reported,
+            provenance: r.provenance.and_then(|p| serde_json::from_value(p).ok()),
+            confidence: r.confidence,
+            stake: r.stake,
+            reporter_address: r.reporter_address,
+            verified: r.verified,
+            late: r.late,
+            weight: None,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+            retracted_at: r.retracted_at,
+        }))
+    }
+
+    /// Reports on a market, oldest first. Archived reports are included when
+    /// `include_archived` is set, retracted ones when `include_retracted`
+    /// is; `flagged_only` keeps those with a report_flags row.
+    pub async fn list<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        include_archived: bool,
+        include_retracted: bool,
+        flagged_only: bool,
+    ) -> Result<Vec<Report>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id AS "id!", market_id AS "market_id!", source AS "source!", value AS "value!",
+                   self_reported AS "self_reported!", provenance, confidence, stake,
+                   reporter_address, verified AS "verified!", late AS "late!", created_at AS "created_at!",
+                   updated_at, retracted_at
+            FROM (
+                SELECT id, market_id, source, value, self_reported, provenance, confidence, stake,
+                       reporter_address, verified, late, created_at, updated_at, retracted_at
+                FROM reports
+                WHERE market_id = $1
+                UNION ALL
+                SELECT id, market_id, source, value, self_reported, provenance, confidence, stake,
+                       reporter_address, verified, late, created_at, updated_at, retracted_at
+                FROM reports_archive
+                WHERE market_id = $1 AND $2
+            ) r
+            WHERE (NOT $3 OR EXISTS (SELECT 1 FROM report_flags f WHERE f.report_id = r.id))
+              AND ($4 OR retracted_at IS NULL)
+            ORDER BY created_at ASC, id ASC
+            "#,
+            market_id,
+            include_archived,
+            flagged_only,
+            include_retracted
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Report {
+                id: r.id,
+                market_id: r.market_id,
+                source: r.source,
+                value: r.value,
+                self_reported: r.self_reported,
+                provenance: r.provenance.and_then(|p| serde_json::from_value(p).ok()),
+                confidence: r.confidence,
+                stake: r.stake,
+                reporter_address: r.reporter_address,
+                verified: r.verified,
+                late: r.late,
+                weight: None,
+                created_at: r.created_at,
+                updated_at: r.updated_at,
+                retracted_at: r.retracted_at,
+            })
+            .collect())
+    }
+
+    /// The fields resolution looks at, oldest first. Live reports only.
+    pub async fn source_values<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Vec<SourceValue>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, source, value, self_reported, confidence, stake, late, submitted_by
+            FROM reports
+            WHERE market_id = $1 AND retracted_at IS NULL
+            ORDER BY created_at ASC
+            "#,
+            market_id
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| SourceValue {
+                id: r.id,
+                source: r.source,
+                value: r.value,
+                self_reported: r.self_reported,
+                confidence: r.confidence,
+                stake: r.stake,
+                late: r.late,
+                submitted_by: r.submitted_by,
+                held: Vec::new(),
+            })
+            .collect())
+    }
+
+    /// Every value each report on a market has held, as
+    /// `(report_id, value, recorded_at)` ordered by report then time.
+    pub async fn value_history<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Vec<(Uuid, f64, DateTime<Utc>)>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT report_id, value, recorded_at
+            FROM report_values
+            WHERE market_id = $1
+            ORDER BY report_id, recorded_at ASC, id ASC
+            "#,
+            market_id
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.report_id, r.value, r.recorded_at))
+            .collect())
+    }
+
+    /// Values of the `limit` most recent live reports on a market.
+    pub async fn recent_values<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<f64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT value
+            FROM reports
+            WHERE market_id = $1 AND retracted_at IS NULL
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            market_id,
+            limit
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Value statistics per `bucket_secs` of created_at, oldest first.
+    /// Buckets are aligned to the unix epoch and empty ones are left out.
+    pub async fn aggregate<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        bucket_secs: i64,
+    ) -> Result<Vec<ReportBucket>, sqlx::Error> {
+        sqlx::query_as!(
+            ReportBucket,
+            r#"
+            SELECT date_bin(make_interval(secs => $2), created_at, TIMESTAMPTZ 'epoch') AS "bucket_start!",
+                   COUNT(*) AS "count!",
+                   MIN(value) AS "min!",
+                   MAX(value) AS "max!",
+                   AVG(value) AS "mean!",
+                   percentile_cont(0.5) WITHIN GROUP (ORDER BY value) AS "median!"
+            FROM (
+                SELECT value, created_at FROM reports WHERE market_id = $1 AND retracted_at IS NULL
+                UNION ALL
+                SELECT value, created_at FROM reports_archive WHERE market_id = $1 AND retracted_at IS NULL
+            ) r
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+            market_id,
+            bucket_secs as f64
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Live and archived reports on a market, retracted ones left out.
+    pub async fn count<'e, E: PgExecutor<'e>>(db: E, market_id: Uuid) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT (SELECT COUNT(*) FROM reports WHERE market_id = $1 AND retracted_at IS NULL)
+                 + (SELECT COUNT(*) FROM reports_archive WHERE market_id = $1 AND retracted_at IS NULL)
+                 AS "count!"
+            "#,
+            market_id
+        )
+        .fetch_one(db)
+        .await
+    }
+}
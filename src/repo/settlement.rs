@@ -0,0 +1,355 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{PgConnection, PgExecutor};
+use uuid::Uuid;
+
+use crate::proof::HashAlgorithm;
+use crate::resolution::SettlementInputs;
+use crate::types::{ChainSubmission, ReportFlag, SettlementListItem, SettlementRevision};
+
+pub struct SettlementRepo;
+
+pub struct SettlementRecord {
+    pub outcome: f64,
+    pub outcome_e8: i64,
+    pub decided_at: DateTime<Utc>,
+    pub revision: i32,
+    // PROPOSED until its chain transaction is confirmed, then FINAL
+    pub status: String,
+    pub finalized_at: Option<DateTime<Utc>>,
+    pub hash_algorithm: String,
+    // places the outcome was anchored at; None before it was recorded, and
+    // on discrete markets
+    pub decimals: Option<i16>,
+    pub inputs: Option<Value>,
+}
+
+impl SettlementRecord {
+    /// The algorithm its hash and leaf were computed with.
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::parse(&self.hash_algorithm).unwrap_or_default()
+    }
+
+    /// What the resolver decided from; `None` for operator outcomes.
+    pub fn inputs(&self) -> Option<SettlementInputs> {
+        self.inputs
+            .clone()
+            .and_then(|i| serde_json::from_value(i).ok())
+    }
+}
+
+pub struct NewSettlement {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub outcome: f64,
+    pub outcome_e8: i64,
+    pub decided_at: DateTime<Utc>,
+    // waits for `finalize` instead of being final as of decided_at
+    pub proposed: bool,
+    pub hash_algorithm: HashAlgorithm,
+    pub decimals: Option<i16>,
+    // serialized SettlementInputs
+    pub inputs: Option<Value>,
+}
+
+impl SettlementRepo {
+    pub async fn get<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Option<SettlementRecord>, sqlx::Error> {
+        sqlx::query_as!(
+            SettlementRecord,
+            r#"
+            SELECT outcome, outcome_e8, decided_at, revision, status, finalized_at, hash_algorithm, decimals,
+                   inputs
+            FROM settlements
+            WHERE market_id = $1
+            "#,
+            market_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Numbered after any revisions the market's earlier settlements left,
+    /// and covered by the batch holding the one it replaces, if any.
+    pub async fn insert<'e, E: PgExecutor<'e>>(
+        db: E,
+        settlement: &NewSettlement,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO settlements
+            (id, market_id, outcome, outcome_e8, decided_at, revision, status, finalized_at, hash_algorithm,
+             inputs, decimals, batch_id)
+            VALUES ($1, $2, $3, $4, $5,
+                    COALESCE((SELECT MAX(revision) FROM settlement_revisions WHERE market_id = $2), 0) + 1,
+                    CASE WHEN $6 THEN 'PROPOSED' ELSE 'FINAL' END,
+                    CASE WHEN $6 THEN NULL ELSE $5::TIMESTAMPTZ END,
+                    $7, $8, $9,
+                    (SELECT bi.batch_id FROM batch_items bi
+                     JOIN batches b ON b.id = bi.batch_id
+                     WHERE bi.market_id = $2 AND b.voided_at IS NULL))
+            "#,
+            settlement.id,
+            settlement.market_id,
+            settlement.outcome,
+            settlement.outcome_e8,
+            settlement.decided_at,
+            settlement.proposed,
+            settlement.hash_algorithm.as_str(),
+            settlement.inputs,
+            settlement.decimals
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Moves the market's settlement into settlement_revisions so a new one
+    /// can take its place. Returns the superseded revision, if there was one.
+    pub async fn supersede(
+        db: &mut PgConnection,
+        market_id: Uuid,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let superseded = sqlx::query_scalar!(
+            r#"
+            INSERT INTO settlement_revisions
+            (id, market_id, revision, outcome, outcome_e8, decided_at, finalized_at, hash_algorithm, inputs,
+             decimals, superseded_by, reason)
+            SELECT id, market_id, revision, outcome, outcome_e8, decided_at, finalized_at, hash_algorithm, inputs,
+                   decimals, $2, $3
+            FROM settlements
+            WHERE market_id = $1
+            RETURNING revision
+            "#,
+            market_id,
+            actor,
+            reason
+        )
+        .fetch_optional(&mut *db)
+        .await?;
+
+        sqlx::query!("DELETE FROM settlements WHERE market_id = $1", market_id)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(superseded)
+    }
+
+    /// Makes the market's PROPOSED settlement FINAL if it is still the one
+    /// with `decided_at`; an override may have replaced it since its
+    /// transaction went out. Returns whether it was finalized.
+    pub async fn finalize<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+        decided_at: DateTime<Utc>,
+        finalized_at: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE settlements
+            SET status = 'FINAL', finalized_at = $3
+            WHERE market_id = $1 AND decided_at = $2 AND status = 'PROPOSED'
+            "#,
+            market_id,
+            decided_at,
+            finalized_at
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Every settlement the market has had, oldest first; the live one last
+    /// with no `superseded_at`.
+    pub async fn history<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Vec<SettlementRevision>, sqlx::Error> {
+        sqlx::query_as!(
+            SettlementRevision,
+            r#"
+            SELECT revision AS "revision!", outcome AS "outcome!", outcome_e8 AS "outcome_e8!",
+                   decided_at AS "decided_at!", finalized_at, superseded_at, superseded_by, reason
+            FROM (
+                SELECT revision, outcome, outcome_e8, decided_at, finalized_at, superseded_at, superseded_by, reason
+                FROM settlement_revisions
+                WHERE market_id = $1
+                UNION ALL
+                SELECT revision, outcome, outcome_e8, decided_at, finalized_at, NULL, NULL, NULL
+                FROM settlements
+                WHERE market_id = $1
+            ) h
+            ORDER BY revision ASC
+            "#,
+            market_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Flags a report, e.g. one the resolver left out. Re-flagging is a no-op.
+    pub async fn flag_report<'e, E: PgExecutor<'e>>(
+        db: E,
+        report_id: Uuid,
+        market_id: Uuid,
+        flag: &str,
+        details: &Value,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO report_flags (report_id, market_id, flag, details)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (report_id, flag) DO NOTHING
+            "#,
+            report_id,
+            market_id,
+            flag,
+            details
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn unflag_report<'e, E: PgExecutor<'e>>(
+        db: E,
+        report_id: Uuid,
+        flag: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM report_flags WHERE report_id = $1 AND flag = $2",
+            report_id,
+            flag
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Reports the resolver left out of the settlement.
+    pub async fn flags<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Vec<ReportFlag>, sqlx::Error> {
+        sqlx::query_as!(
+            ReportFlag,
+            r#"
+            SELECT report_id, flag, details, created_at
+            FROM report_flags
+            WHERE market_id = $1 AND flag = 'OUTLIER'
+            ORDER BY created_at ASC, report_id ASC
+            "#,
+            market_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// The most recent transaction anchoring the market's settlement.
+    pub async fn chain_submission<'e, E: PgExecutor<'e>>(
+        db: E,
+        market_id: Uuid,
+    ) -> Result<Option<ChainSubmission>, sqlx::Error> {
+        sqlx::query_as!(
+            ChainSubmission,
+            r#"
+            SELECT tx_hash, block_number, gas_used, submitter, created_at AS submitted_at
+            FROM chain_submissions
+            WHERE market_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            market_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Settlements decided in `[from, to]` across markets, archived ones
+    /// included, ordered by `(decided_at, market_id)` and starting after
+    /// `after`.
+    pub async fn list<'e, E: PgExecutor<'e>>(
+        db: E,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<SettlementListItem>, sqlx::Error> {
+        let (after_decided_at, after_id) = after.unzip();
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT s.market_id, s.outcome, s.outcome_e8, s.decided_at, s.revision, s.status, s.batch_id,
+                   COALESCE(m.question, a.question) AS "question!",
+                   COALESCE(m.anchor_on_chain, a.anchor_on_chain) AS "anchor_on_chain!",
+                   a.id IS NOT NULL AS "archived!",
+                   o.status AS "outbox_status?",
+                   c.tx_hash AS "tx_hash?",
+                   c.block_number AS "block_number?"
+            FROM settlements s
+            LEFT JOIN markets m ON m.id = s.market_id
+            LEFT JOIN markets_archive a ON a.id = s.market_id
+            LEFT JOIN LATERAL (
+                SELECT status FROM outbox
+                WHERE market_id = s.market_id
+                ORDER BY created_at DESC
+                LIMIT 1
+            ) o ON true
+            LEFT JOIN LATERAL (
+                SELECT tx_hash, block_number FROM chain_submissions
+                WHERE market_id = s.market_id
+                ORDER BY created_at DESC
+                LIMIT 1
+            ) c ON true
+            WHERE (m.id IS NOT NULL OR a.id IS NOT NULL)
+              AND ($1::TIMESTAMPTZ IS NULL OR s.decided_at >= $1)
+              AND ($2::TIMESTAMPTZ IS NULL OR s.decided_at <= $2)
+              AND ($3::TIMESTAMPTZ IS NULL OR (s.decided_at, s.market_id) > ($3, $4::UUID))
+            ORDER BY s.decided_at ASC, s.market_id ASC
+            LIMIT $5
+            "#,
+            from,
+            to,
+            after_decided_at,
+            after_id,
+            limit
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let chain_status = match (row.anchor_on_chain, row.batch_id, row.outbox_status) {
+                    (false, _, _) => "OFF_CHAIN".to_string(),
+                    (true, None, _) => "UNBATCHED".to_string(),
+                    (true, Some(_), None) => "BATCHED".to_string(),
+                    (true, Some(_), Some(status)) => status,
+                };
+
+                SettlementListItem {
+                    market_id: row.market_id,
+                    question: row.question,
+                    outcome: row.outcome,
+                    outcome_e8: row.outcome_e8,
+                    decided_at: row.decided_at,
+                    revision: row.revision,
+                    status: row.status,
+                    batch_id: row.batch_id,
+                    chain_status,
+                    tx_hash: row.tx_hash,
+                    block_number: row.block_number,
+                    archived: row.archived,
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::types::MarketTemplate;
+
+pub struct TemplateRepo;
+
+pub struct NewTemplate<'a> {
+    pub id: Uuid,
+    pub name: &'a str,
+    pub question_pattern: &'a str,
+    pub starts_at: DateTime<Utc>,
+    pub cadence_secs: i32,
+    pub duration_secs: i32,
+    pub resolve_window_secs: Option<i32>,
+    pub lead_secs: i32,
+    pub settings: Value,
+    pub next_run_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+struct TemplateRow {
+    id: Uuid,
+    name: String,
+    question_pattern: String,
+    starts_at: DateTime<Utc>,
+    cadence_secs: i32,
+    duration_secs: i32,
+    resolve_window_secs: Option<i32>,
+    lead_secs: i32,
+    settings: Value,
+    next_run_at: DateTime<Utc>,
+    active: bool,
+    last_market_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<TemplateRow> for MarketTemplate {
+    fn from(row: TemplateRow) -> Self {
+        MarketTemplate {
+            id: row.id,
+            name: row.name,
+            question_pattern: row.question_pattern,
+            starts_at: row.starts_at,
+            cadence_secs: row.cadence_secs,
+            duration_secs: row.duration_secs,
+            resolve_window_secs: row.resolve_window_secs,
+            lead_secs: row.lead_secs,
+            settings: serde_json::from_value(row.settings).unwrap_or_default(),
+            next_run_at: row.next_run_at,
+            active: row.active,
+            last_market_id: row.last_market_id,
+            created_at: row.created_at,
+        }
+    }
+}
+
+impl TemplateRepo {
+    pub async fn insert<'e, E: PgExecutor<'e>>(
+        db: E,
+        template: &NewTemplate<'_>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO market_templates
+            (id, name, question_pattern, starts_at, cadence_secs, duration_secs, resolve_window_secs,
+             lead_secs, settings, next_run_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+            template.id,
+            template.name,
+            template.question_pattern,
+            template.starts_at,
+            template.cadence_secs,
+            template.duration_secs,
+            template.resolve_window_secs,
+            template.lead_secs,
+            template.settings,
+            template.next_run_at,
+            template.created_at
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Newest first, inactive ones included.
+    pub async fn list<'e, E: PgExecutor<'e>>(db: E) -> Result<Vec<MarketTemplate>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            TemplateRow,
+            r#"
+            SELECT id, name, question_pattern, starts_at, cadence_secs, duration_secs, resolve_window_secs,
+                   lead_secs, settings, next_run_at, active, last_market_id, created_at
+            FROM market_templates
+            ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(rows.into_iter().map(MarketTemplate::from).collect())
+    }
+
+    pub async fn get<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+    ) -> Result<Option<MarketTemplate>, sqlx::Error> {
+        let row = sqlx::query_as!(
+            TemplateRow,
+            r#"
+            SELECT id, name, question_pattern, starts_at, cadence_secs, duration_secs, resolve_window_secs,
+                   lead_secs, settings, next_run_at, active, last_market_id, created_at
+            FROM market_templates
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(row.map(MarketTemplate::from))
+    }
+
+    /// Active templates whose next slot is within its lead of `now`.
+    pub async fn due<'e, E: PgExecutor<'e>>(
+        db: E,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<MarketTemplate>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            TemplateRow,
+            r#"
+            SELECT id, name, question_pattern, starts_at, cadence_secs, duration_secs, resolve_window_secs,
+                   lead_secs, settings, next_run_at, active, last_market_id, created_at
+            FROM market_templates
+            WHERE active
+              AND next_run_at - make_interval(secs => lead_secs) <= $1
+            ORDER BY next_run_at ASC
+            LIMIT $2
+            "#,
+            now,
+            limit
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(rows.into_iter().map(MarketTemplate::from).collect())
+    }
+
+    /// Moves the template from slot `from` to `to`. False if another pass
+    /// already moved it.
+    pub async fn advance<'e, E: PgExecutor<'e>>(
+        db: E,
+        id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        market_id: Option<Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let advanced = sqlx::query!(
+            r#"
+            UPDATE market_templates
+            SET next_run_at = $3,
+                last_market_id = COALESCE($4, last_market_id)
+            WHERE id = $1 AND next_run_at = $2
+            "#,
+            id,
+            from,
+            to,
+            market_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(advanced.rows_affected() > 0)
+    }
+
+    /// Stops new instances; markets already created are untouched.
+    pub async fn deactivate<'e, E: PgExecutor<'e>>(db: E, id: Uuid) -> Result<bool, sqlx::Error> {
+        let updated = sqlx::query!(
+            "UPDATE market_templates SET active = false WHERE id = $1 AND active",
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(updated.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,62 @@
+use axum::{extract::Request, http::HeaderName, middleware::Next, response::Response};
+use tracing::Span;
+
+/// Sent back on every response; a caller's own id is kept if it sends one.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+// longer ids from callers are replaced rather than logged
+const MAX_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// The id of the request being handled, for error bodies; `None` outside
+/// one (background loops).
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Drops a caller's id that is too long or not printable ASCII, so
+/// `SetRequestIdLayer` mints a fresh one.
+pub async fn sanitize(mut req: Request, next: Next) -> Response {
+    let usable = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .map(|v| v.len() <= MAX_LEN && v.as_bytes().iter().all(|b| b.is_ascii_graphic()));
+
+    if usable == Some(false) {
+        req.headers_mut().remove(&REQUEST_ID_HEADER);
+    }
+
+    next.run(req).await
+}
+
+/// Makes the request's id available to `current` for the rest of the
+/// request.
+pub async fn scope(req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    REQUEST_ID.scope(id, next.run(req)).await
+}
+
+/// The span every request's logs are recorded in.
+pub fn make_span<B>(req: &axum::http::Request<B>) -> Span {
+    let id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    )
+}
@@ -0,0 +1,973 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::ConsensusConfig;
+use crate::outcome_type::OutcomeType;
+
+/// How a closed market turns its reports into an outcome. Stored per market
+/// in `markets.resolution`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Strategy {
+    /// At least `consensus.min_reports` reports whose global min/max spread is
+    /// within the market's `consensus_bps` (3 reports and 1% unless
+    /// configured); see `spread_within`.
+    #[default]
+    Spread,
+    /// At least `min_pairs` pairs of distinct sources agreeing within
+    /// `tolerance` (relative, e.g. 0.01 = 1%), all inside the largest group
+    /// of sources that agree with each other.
+    AgreementMatrix { min_pairs: usize, tolerance: f64 },
+    /// Weighted mean over at least `min_reports` reports, each weighted by
+    /// its reporter's `confidence` times `stake` (either defaults to 1).
+    ConfidenceWeighted { min_reports: usize },
+    /// Time-weighted average over at least `min_reports` reports: each value
+    /// a report held counts for as long as it was its source's latest
+    /// during the reporting window.
+    Twap { min_reports: usize },
+}
+
+/// What to do with reports our own feed adapters submitted, for markets that
+/// need independent attestation. Stored in `markets.self_report_policy`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "mode", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SelfReportPolicy {
+    #[default]
+    Include,
+    Exclude,
+    /// Self reports count with `weight` (0..1) instead of 1.
+    DownWeight {
+        weight: f64,
+    },
+}
+
+impl SelfReportPolicy {
+    pub fn weight(&self, self_reported: bool) -> f64 {
+        match (self, self_reported) {
+            (_, false) | (SelfReportPolicy::Include, true) => 1.0,
+            (SelfReportPolicy::Exclude, true) => 0.0,
+            (SelfReportPolicy::DownWeight { weight }, true) => weight.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// What to do with reports accepted during the close grace period, after
+/// closes_at. Stored in `markets.late_report_policy`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "mode", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LateReportPolicy {
+    #[default]
+    Include,
+    Exclude,
+}
+
+impl LateReportPolicy {
+    pub fn counts(&self, late: bool) -> bool {
+        !late || matches!(self, LateReportPolicy::Include)
+    }
+}
+
+#[derive(Clone)]
+pub struct SourceValue {
+    pub id: Uuid,
+    pub source: String,
+    pub value: f64,
+    pub self_reported: bool,
+    pub confidence: Option<f64>,
+    pub stake: Option<f64>,
+    // submitted during the close grace period
+    pub late: bool,
+    // the account that submitted it; None for feed reports
+    pub submitted_by: Option<String>,
+    // the report's values over the reporting window; only loaded for TWAP
+    pub held: Vec<HeldValue>,
+}
+
+/// A value a report held for part of the reporting window, the unit TWAP
+/// weighs by.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HeldValue {
+    pub report_id: Uuid,
+    pub value: f64,
+    pub from: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+}
+
+impl HeldValue {
+    fn secs(&self) -> f64 {
+        (self.until - self.from).num_milliseconds() as f64 / 1000.0
+    }
+}
+
+/// Turns one report's value history, oldest first, into what it held
+/// between `opens_at` and `closes_at`. A value set before the window holds
+/// from its start; values set after it hold for nothing and are dropped.
+pub fn held_values(
+    report_id: Uuid,
+    history: &[(f64, DateTime<Utc>)],
+    opens_at: DateTime<Utc>,
+    closes_at: DateTime<Utc>,
+) -> Vec<HeldValue> {
+    history
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (value, set_at))| {
+            let from = (*set_at).max(opens_at);
+            let until = history
+                .get(i + 1)
+                .map_or(closes_at, |(_, next)| (*next).min(closes_at));
+            (until > from).then_some(HeldValue {
+                report_id,
+                value: *value,
+                from,
+                until,
+            })
+        })
+        .collect()
+}
+
+/// Preconditions a market creator puts on resolution, checked before the
+/// strategy runs. Stored in `market_requirements`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct MarketRequirements {
+    // each must have at least one counted report; "binance" also matches
+    // feed sources like "binance:BTCUSDT"
+    #[serde(default)]
+    pub required_sources: Vec<String>,
+    pub min_reports: Option<usize>,
+    // distinct submitters with a counted report: an account counts once
+    // however many sources it reports for, and feed sources of one provider
+    // ("binance:BTCUSDT", "binance:ETHUSDT") count once
+    pub min_sources: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum UnmetRequirement {
+    MissingSource { source: String },
+    TooFewReports { required: usize, counted: usize },
+    TooFewSources { required: usize, counted: usize },
+}
+
+impl MarketRequirements {
+    /// Trims, lowercases and dedups the source list.
+    pub fn normalize(mut self) -> Result<Self, String> {
+        let mut sources: Vec<String> = self
+            .required_sources
+            .iter()
+            .map(|s| s.trim().to_lowercase())
+            .collect();
+
+        if sources.iter().any(|s| s.is_empty()) {
+            return Err("required_sources must not contain empty names".to_string());
+        }
+        sources.sort();
+        sources.dedup();
+
+        if sources.len() > 32 {
+            return Err("at most 32 required_sources".to_string());
+        }
+        if self.min_reports == Some(0) {
+            return Err("min_reports must be at least 1".to_string());
+        }
+        if self.min_sources == Some(0) {
+            return Err("min_sources must be at least 1".to_string());
+        }
+
+        self.required_sources = sources;
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.required_sources.is_empty() && self.min_reports.is_none() && self.min_sources.is_none()
+    }
+
+    /// The source quorum `resolve` and `majority` hold the outcome to.
+    pub fn quorum(&self) -> usize {
+        self.min_sources.unwrap_or(0)
+    }
+
+    /// Requirements `reports` don't meet yet. Reports the self-report policy
+    /// weighs at zero don't count.
+    pub fn unmet(
+        &self,
+        strategy: &Strategy,
+        policy: &SelfReportPolicy,
+        reports: &[SourceValue],
+    ) -> Vec<UnmetRequirement> {
+        let counted: Vec<&SourceValue> = reports
+            .iter()
+            .filter(|r| report_weight(strategy, policy, r) > 0.0)
+            .collect();
+
+        let mut unmet: Vec<UnmetRequirement> = self
+            .required_sources
+            .iter()
+            .filter(|required| !counted.iter().any(|r| source_matches(&r.source, required)))
+            .map(|source| UnmetRequirement::MissingSource {
+                source: source.clone(),
+            })
+            .collect();
+
+        if let Some(required) = self.min_reports
+            && counted.len() < required
+        {
+            unmet.push(UnmetRequirement::TooFewReports {
+                required,
+                counted: counted.len(),
+            });
+        }
+
+        let sources = distinct_submitters(counted.iter().copied());
+        if let Some(required) = self.min_sources
+            && sources < required
+        {
+            unmet.push(UnmetRequirement::TooFewSources {
+                required,
+                counted: sources,
+            });
+        }
+
+        unmet
+    }
+}
+
+/// Number of distinct submitters behind `reports`: the account that
+/// submitted each, or for reports without one (feeds, and reports from
+/// before submitters were recorded) the source, taking a feed source like
+/// "binance:BTCUSDT" as its provider "binance".
+pub fn distinct_submitters<'a>(reports: impl Iterator<Item = &'a SourceValue>) -> usize {
+    let mut seen: Vec<(bool, String)> = reports
+        .map(|r| match &r.submitted_by {
+            Some(account) if !r.self_reported => (true, account.clone()),
+            _ => (
+                false,
+                r.source
+                    .split(':')
+                    .next()
+                    .unwrap_or(&r.source)
+                    .to_lowercase(),
+            ),
+        })
+        .collect();
+    seen.sort();
+    seen.dedup();
+    seen.len()
+}
+
+fn source_matches(source: &str, required: &str) -> bool {
+    let source = source.to_lowercase();
+    source == required
+        || source
+            .strip_prefix(required)
+            .is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// Weight a report carries into the outcome under `strategy` and `policy`.
+pub fn report_weight(strategy: &Strategy, policy: &SelfReportPolicy, report: &SourceValue) -> f64 {
+    let base = policy.weight(report.self_reported);
+
+    match strategy {
+        Strategy::ConfidenceWeighted { .. } => {
+            let confidence = report.confidence.unwrap_or(1.0).clamp(0.0, 1.0);
+            let stake = report.stake.unwrap_or(1.0).max(0.0);
+            base * confidence * stake
+        }
+        _ => base,
+    }
+}
+
+struct Weighted<'a> {
+    source: &'a str,
+    value: f64,
+    weight: f64,
+}
+
+/// Outcome of `reports` under `strategy`, or `None` until it is decided.
+/// Reports weighed at zero don't count towards the `quorum` of distinct
+/// submitters.
+pub fn resolve(
+    strategy: &Strategy,
+    policy: &SelfReportPolicy,
+    consensus: &ConsensusConfig,
+    quorum: usize,
+    reports: &[SourceValue],
+) -> Option<f64> {
+    let weighted: Vec<Weighted> = reports
+        .iter()
+        .map(|r| Weighted {
+            source: &r.source,
+            value: r.value,
+            weight: report_weight(strategy, policy, r),
+        })
+        .filter(|w| w.weight > 0.0)
+        .collect();
+
+    let counted = reports
+        .iter()
+        .filter(|r| report_weight(strategy, policy, r) > 0.0);
+    if distinct_submitters(counted) < quorum {
+        return None;
+    }
+
+    match strategy {
+        Strategy::Spread => {
+            let values: Vec<f64> = weighted.iter().map(|r| r.value).collect();
+            try_resolve(&values, consensus)?;
+
+            // spread passed; down-weighted reports pull less on the outcome
+            let total: f64 = weighted.iter().map(|r| r.weight).sum();
+            Some(weighted.iter().map(|r| r.value * r.weight).sum::<f64>() / total)
+        }
+        Strategy::AgreementMatrix {
+            min_pairs,
+            tolerance,
+        } => agreement_matrix(&weighted, *min_pairs, *tolerance),
+        Strategy::ConfidenceWeighted { min_reports } => {
+            if *min_reports == 0 || weighted.len() < *min_reports {
+                return None;
+            }
+
+            // nothing to divide by when every weight is zero
+            let total: f64 = weighted.iter().map(|r| r.weight).sum();
+            (total > 0.0 && total.is_finite())
+                .then(|| weighted.iter().map(|r| r.value * r.weight).sum::<f64>() / total)
+        }
+        Strategy::Twap { min_reports } => {
+            if *min_reports == 0 || weighted.len() < *min_reports {
+                return None;
+            }
+
+            // the self-report policy still scales each source's time
+            let held: Vec<(f64, f64)> = reports
+                .iter()
+                .flat_map(|r| {
+                    let weight = report_weight(strategy, policy, r);
+                    r.held.iter().map(move |h| (h.value, h.secs() * weight))
+                })
+                .filter(|(_, w)| *w > 0.0)
+                .collect();
+
+            let total: f64 = held.iter().map(|(_, w)| w).sum();
+            (total > 0.0).then(|| held.iter().map(|(v, w)| v * w).sum::<f64>() / total)
+        }
+    }
+}
+
+/// Weighted majority vote for BINARY/CATEGORICAL markets. Each report names
+/// an option index; the option with the most weight wins once at least
+/// `consensus.min_reports` reports from `quorum` distinct submitters count. A
+/// tie for first waits for more.
+pub fn majority(
+    strategy: &Strategy,
+    policy: &SelfReportPolicy,
+    consensus: &ConsensusConfig,
+    quorum: usize,
+    outcome_type: &OutcomeType,
+    reports: &[SourceValue],
+) -> Option<f64> {
+    let mut tally = vec![0.0; outcome_type.options().len()];
+    let mut counted = 0;
+    let mut submitters = Vec::new();
+
+    for r in reports {
+        let weight = report_weight(strategy, policy, r);
+        if weight <= 0.0 {
+            continue;
+        }
+        if let Some(index) = outcome_type.option_index(r.value) {
+            tally[index] += weight;
+            counted += 1;
+            submitters.push(r);
+        }
+    }
+
+    if counted < consensus.min_reports.max(1)
+        || distinct_submitters(submitters.into_iter()) < quorum
+    {
+        return None;
+    }
+
+    let best = tally.iter().cloned().fold(0.0, f64::max);
+    let mut leaders = tally.iter().enumerate().filter(|(_, w)| **w == best);
+    let (winner, _) = leaders.next()?;
+
+    if leaders.next().is_some() {
+        return None;
+    }
+
+    Some(winner as f64)
+}
+
+pub fn try_resolve(values: &[f64], consensus: &ConsensusConfig) -> Option<f64> {
+    if values.len() < consensus.min_reports.max(1) {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let min = sorted[0];
+    let max = sorted[sorted.len() - 1];
+
+    if spread_within(min, max, consensus) {
+        let avg = sorted.iter().sum::<f64>() / sorted.len() as f64;
+        Some(avg)
+    } else {
+        None
+    }
+}
+
+/// The basis of a resolver decision, stored with the settlement so reports
+/// arriving later can't change what it appears to have been decided from.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SettlementInputs {
+    pub strategy: Strategy,
+    pub report_count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub median: f64,
+    // the reports the strategy ran over, outliers already left out
+    pub report_ids: Vec<Uuid>,
+    // TWAP only: every value those reports held and for how long
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub held: Vec<HeldValue>,
+}
+
+impl SettlementInputs {
+    /// `None` without reports to describe.
+    pub fn new(strategy: &Strategy, counted: &[SourceValue]) -> Option<Self> {
+        let mut values: Vec<f64> = counted.iter().map(|r| r.value).collect();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        Some(Self {
+            strategy: strategy.clone(),
+            report_count: counted.len(),
+            min: *values.first()?,
+            max: *values.last()?,
+            median: median(&values),
+            report_ids: counted.iter().map(|r| r.id).collect(),
+            held: counted
+                .iter()
+                .flat_map(|r| r.held.iter().cloned())
+                .collect(),
+        })
+    }
+}
+
+/// A report left out of consensus for sitting too far from the rest.
+#[derive(Debug, Clone, Serialize)]
+pub struct Outlier {
+    // the report_flags row carries it already
+    #[serde(skip_serializing)]
+    pub report_id: Uuid,
+    pub value: f64,
+    pub median: f64,
+    // |value - median| beyond which reports were excluded
+    pub threshold: f64,
+}
+
+/// Reports more than `consensus.outlier_k` scaled median absolute deviations
+/// from the median. The cutoff never drops below the market's consensus band
+/// around the median, so a tight cluster (MAD of zero) only sheds values that
+/// could never have agreed with it. Needs three reports to have a majority;
+/// `outlier_k` of zero turns filtering off.
+pub fn outliers(reports: &[SourceValue], consensus: &ConsensusConfig) -> Vec<Outlier> {
+    if consensus.outlier_k <= 0.0 || reports.len() < 3 {
+        return Vec::new();
+    }
+
+    let mut values: Vec<f64> = reports
+        .iter()
+        .map(|r| r.value)
+        .filter(|v| v.is_finite())
+        .collect();
+    if values.len() < 3 {
+        return Vec::new();
+    }
+
+    let (mid, threshold) = cutoff(&mut values, consensus.outlier_k, consensus);
+
+    reports
+        .iter()
+        .filter(|r| !r.value.is_finite() || (r.value - mid).abs() > threshold)
+        .map(|r| Outlier {
+            report_id: r.id,
+            value: r.value,
+            median: mid,
+            threshold,
+        })
+        .collect()
+}
+
+/// `value` measured against the median of `history`, the values reported
+/// before it, with the same cutoff as `outliers` at `k` scaled MADs.
+/// `Some` when it falls outside. Needs three finite values of history.
+pub fn deviation(
+    report_id: Uuid,
+    value: f64,
+    history: &[f64],
+    k: f64,
+    consensus: &ConsensusConfig,
+) -> Option<Outlier> {
+    let mut values: Vec<f64> = history.iter().copied().filter(|v| v.is_finite()).collect();
+    if k <= 0.0 || values.len() < 3 {
+        return None;
+    }
+
+    let (mid, threshold) = cutoff(&mut values, k, consensus);
+
+    (!value.is_finite() || (value - mid).abs() > threshold).then_some(Outlier {
+        report_id,
+        value,
+        median: mid,
+        threshold,
+    })
+}
+
+// median of `values` and the distance from it past which a value is out;
+// sorts `values`, which must be finite and non-empty
+fn cutoff(values: &mut [f64], k: f64, consensus: &ConsensusConfig) -> (f64, f64) {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = median(values);
+
+    let mut deviations: Vec<f64> = values.iter().map(|v| (v - mid).abs()).collect();
+    deviations.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    // 1.4826 scales the MAD to a standard deviation for normal data
+    let mad = 1.4826 * median(&deviations);
+
+    let threshold = (k * mad)
+        .max(consensus.max_spread * mid.abs())
+        .max(consensus.abs_tolerance);
+
+    (mid, threshold)
+}
+
+/// `max - min` measured against the larger magnitude of the two, so zero and
+/// negative values behave (dividing by `min` blew up at zero and flipped the
+/// sign below it). Spreads within `abs_tolerance` always agree, which keeps
+/// values clustered around zero from needing an exact match.
+pub fn spread_within(min: f64, max: f64, consensus: &ConsensusConfig) -> bool {
+    let spread = max - min;
+    if !spread.is_finite() {
+        return false;
+    }
+
+    let scale = min.abs().max(max.abs());
+    spread <= consensus.abs_tolerance.max(0.0) || spread <= consensus.max_spread * scale
+}
+
+/// `max - min` over the larger magnitude, the figure `spread_within` compares
+/// against `max_spread`. `None` with no finite values; zero when they are all
+/// zero.
+pub fn relative_spread(values: &[f64]) -> Option<f64> {
+    let finite = values.iter().copied().filter(|v| v.is_finite());
+    let min = finite.clone().reduce(f64::min)?;
+    let max = finite.reduce(f64::max)?;
+
+    let scale = min.abs().max(max.abs());
+    Some(if scale == 0.0 {
+        0.0
+    } else {
+        (max - min) / scale
+    })
+}
+
+/// Builds the pairwise agreement matrix over one value per source (the mean
+/// of that source's reports, so repeat submissions can't add pairs) and
+/// resolves to the median of the largest group of sources that all agree
+/// with each other, which leaves a consistently offset source out entirely.
+/// The group needs `min_pairs` agreeing pairs of its own; a pair involving a
+/// down-weighted source only counts for the smaller of the two weights. Two
+/// groups of the same size are a split vote, and nothing resolves.
+fn agreement_matrix(reports: &[Weighted], min_pairs: usize, tolerance: f64) -> Option<f64> {
+    let mut by_source: BTreeMap<&str, (f64, usize, f64)> = BTreeMap::new();
+    for r in reports {
+        if !r.value.is_finite() {
+            continue;
+        }
+        let entry = by_source.entry(r.source).or_insert((0.0, 0, 0.0));
+        entry.0 += r.value;
+        entry.1 += 1;
+        entry.2 = entry.2.max(r.weight);
+    }
+
+    let mut sources: Vec<(f64, f64)> = by_source
+        .values()
+        .map(|(sum, n, weight)| (sum / *n as f64, *weight))
+        .collect();
+    sources.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    // agreement narrows as values move apart, so a mutually agreeing group
+    // is a run of neighbours in value order: the longest run from each start
+    let mut groups: Vec<&[(f64, f64)]> = Vec::new();
+    let mut last_end = 0;
+    for start in 0..sources.len() {
+        let mut end = start + 1;
+        while end < sources.len()
+            && sources[start..end]
+                .iter()
+                .all(|(v, _)| within_tolerance(*v, sources[end].0, tolerance))
+        {
+            end += 1;
+        }
+
+        // a run ending where the previous one did is part of it
+        if end > last_end {
+            groups.push(&sources[start..end]);
+            last_end = end;
+        }
+    }
+
+    let largest = groups.iter().map(|g| g.len()).max()?;
+    let mut candidates = groups.iter().filter(|g| g.len() == largest);
+    let group = candidates.next()?;
+    if candidates.next().is_some() {
+        return None;
+    }
+
+    let mut agreeing_pairs = 0.0;
+    for i in 0..group.len() {
+        for j in (i + 1)..group.len() {
+            agreeing_pairs += group[i].1.min(group[j].1);
+        }
+    }
+
+    if min_pairs == 0 || agreeing_pairs < min_pairs as f64 {
+        return None;
+    }
+
+    let values: Vec<f64> = group.iter().map(|(v, _)| *v).collect();
+    Some(median(&values))
+}
+
+fn within_tolerance(a: f64, b: f64, tolerance: f64) -> bool {
+    let scale = a.abs().max(b.abs());
+    if scale == 0.0 {
+        return true;
+    }
+    (a - b).abs() / scale <= tolerance
+}
+
+// expects sorted, non-empty input
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(source: &str, value: f64) -> SourceValue {
+        SourceValue {
+            id: Uuid::new_v4(),
+            source: source.to_string(),
+            value,
+            self_reported: false,
+            confidence: None,
+            stake: None,
+            late: false,
+            submitted_by: None,
+            held: Vec::new(),
+        }
+    }
+
+    fn submitted(account: &str, source: &str, value: f64) -> SourceValue {
+        SourceValue {
+            submitted_by: Some(account.to_string()),
+            ..report(source, value)
+        }
+    }
+
+    #[test]
+    fn quorum_counts_submitters_not_sources() {
+        let spread = |reports: &[SourceValue]| {
+            resolve(
+                &Strategy::Spread,
+                &SelfReportPolicy::Include,
+                &ConsensusConfig::default(),
+                2,
+                reports,
+            )
+        };
+
+        // one account reporting for three sources is one voice
+        let one_account = [
+            submitted("alice", "binance", 100.0),
+            submitted("alice", "coinbase", 100.2),
+            submitted("alice", "kraken", 100.1),
+        ];
+        assert_eq!(spread(&one_account), None);
+
+        let requirements = MarketRequirements {
+            min_sources: Some(2),
+            ..Default::default()
+        };
+        assert!(matches!(
+            requirements.unmet(&Strategy::Spread, &SelfReportPolicy::Include, &one_account)[..],
+            [UnmetRequirement::TooFewSources {
+                required: 2,
+                counted: 1
+            }]
+        ));
+
+        let two_accounts = [
+            submitted("alice", "binance", 100.0),
+            submitted("alice", "coinbase", 100.2),
+            submitted("bob", "kraken", 100.1),
+        ];
+        assert!(spread(&two_accounts).is_some());
+    }
+
+    fn matrix(min_pairs: usize, tolerance: f64, reports: &[SourceValue]) -> Option<f64> {
+        resolve(
+            &Strategy::AgreementMatrix {
+                min_pairs,
+                tolerance,
+            },
+            &SelfReportPolicy::Include,
+            &ConsensusConfig::default(),
+            0,
+            reports,
+        )
+    }
+
+    #[test]
+    fn consistently_offset_source_is_left_out() {
+        let reports = [
+            report("binance", 100.0),
+            report("coinbase", 100.2),
+            report("kraken", 99.9),
+            report("rogue", 110.0),
+            report("rogue", 110.4),
+        ];
+
+        assert_eq!(matrix(2, 0.01, &reports), Some(100.0));
+    }
+
+    #[test]
+    fn duplicate_reports_from_one_source_add_no_pairs() {
+        let mut reports = vec![report("binance", 100.0)];
+        reports.extend((0..5).map(|_| report("colluder", 150.0)));
+
+        // five copies of one source are still one source: no pair agrees
+        assert_eq!(matrix(1, 0.01, &reports), None);
+
+        reports.push(report("kraken", 100.5));
+        assert_eq!(matrix(1, 0.01, &reports), Some(100.25));
+        // only binance/kraken agree, however many times colluder repeats itself
+        assert_eq!(matrix(2, 0.01, &reports), None);
+    }
+
+    #[test]
+    fn separate_agreeing_groups_do_not_blend() {
+        let two_by_two = [
+            report("binance", 100.0),
+            report("coinbase", 100.0),
+            report("r1", 200.0),
+            report("r2", 200.0),
+        ];
+        // 150 was never reported; a split vote settles nothing
+        assert_eq!(matrix(2, 0.01, &two_by_two), None);
+        assert_eq!(matrix(1, 0.01, &two_by_two), None);
+
+        let three_by_three = [
+            report("binance", 100.0),
+            report("coinbase", 100.0),
+            report("kraken", 100.0),
+            report("r1", 200.0),
+            report("r2", 200.0),
+            report("r3", 200.0),
+        ];
+        assert_eq!(matrix(3, 0.01, &three_by_three), None);
+
+        let three_by_two = [
+            report("binance", 100.0),
+            report("coinbase", 100.2),
+            report("kraken", 100.4),
+            report("r1", 200.0),
+            report("r2", 200.0),
+        ];
+        assert_eq!(matrix(3, 0.01, &three_by_two), Some(100.2));
+        // the larger group must make min_pairs on its own
+        assert_eq!(matrix(4, 0.01, &three_by_two), None);
+    }
+
+    #[test]
+    fn min_pairs_above_possible_pairs_never_resolves() {
+        let reports = [
+            report("binance", 100.0),
+            report("coinbase", 100.0),
+            report("kraken", 100.0),
+        ];
+
+        // three sources make at most three pairs
+        assert_eq!(matrix(3, 0.01, &reports), Some(100.0));
+        assert_eq!(matrix(4, 0.01, &reports), None);
+    }
+
+    #[test]
+    fn identical_sources_all_agree() {
+        let reports = [
+            report("binance", 42.0),
+            report("coinbase", 42.0),
+            report("kraken", 42.0),
+            report("bitstamp", 42.0),
+        ];
+        assert_eq!(matrix(6, 0.0, &reports), Some(42.0));
+
+        let zeros = [report("binance", 0.0), report("coinbase", 0.0)];
+        assert_eq!(matrix(1, 0.0, &zeros), Some(0.0));
+    }
+
+    #[test]
+    fn tolerance_boundary_is_inclusive() {
+        // |99 - 100| / 100 is exactly the tolerance
+        let at = [report("binance", 99.0), report("coinbase", 100.0)];
+        assert_eq!(matrix(1, 0.01, &at), Some(99.5));
+
+        let past = [report("binance", 98.9), report("coinbase", 100.0)];
+        assert_eq!(matrix(1, 0.01, &past), None);
+    }
+
+    fn weighted(
+        source: &str,
+        value: f64,
+        confidence: Option<f64>,
+        stake: Option<f64>,
+    ) -> SourceValue {
+        SourceValue {
+            confidence,
+            stake,
+            ..report(source, value)
+        }
+    }
+
+    fn confidence_weighted(min_reports: usize, reports: &[SourceValue]) -> Option<f64> {
+        resolve(
+            &Strategy::ConfidenceWeighted { min_reports },
+            &SelfReportPolicy::Include,
+            &ConsensusConfig::default(),
+            0,
+            reports,
+        )
+    }
+
+    #[test]
+    fn confidence_weighted_ignores_zero_confidence_and_zero_stake() {
+        let reports = [
+            weighted("binance", 100.0, Some(1.0), Some(2.0)),
+            weighted("coinbase", 103.0, Some(0.5), None),
+            weighted("kraken", 500.0, Some(0.0), Some(10.0)),
+            weighted("bitstamp", 700.0, Some(1.0), Some(0.0)),
+        ];
+
+        // (100 * 2 + 103 * 0.5) / 2.5
+        assert_eq!(confidence_weighted(2, &reports), Some(100.6));
+        // the zero-weight reports don't make up the count either
+        assert_eq!(confidence_weighted(3, &reports), None);
+    }
+
+    #[test]
+    fn confidence_weighted_with_every_weight_zero_does_not_resolve() {
+        let reports = [
+            weighted("binance", 100.0, Some(0.0), None),
+            weighted("coinbase", 101.0, None, Some(0.0)),
+        ];
+
+        assert_eq!(confidence_weighted(1, &reports), None);
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    fn twap(min_reports: usize, reports: &[SourceValue]) -> Option<f64> {
+        resolve(
+            &Strategy::Twap { min_reports },
+            &SelfReportPolicy::Include,
+            &ConsensusConfig::default(),
+            0,
+            reports,
+        )
+    }
+
+    fn held(
+        source: &str,
+        history: &[(f64, DateTime<Utc>)],
+        opens_at: DateTime<Utc>,
+        closes_at: DateTime<Utc>,
+    ) -> SourceValue {
+        let mut r = report(source, history.last().unwrap().0);
+        r.held = held_values(r.id, history, opens_at, closes_at);
+        r
+    }
+
+    fn held_source(id: Uuid, held: Vec<HeldValue>) -> SourceValue {
+        SourceValue {
+            id,
+            held,
+            ..report("binance", 0.0)
+        }
+    }
+
+    #[test]
+    fn held_values_clip_to_the_window() {
+        let id = Uuid::new_v4();
+        // set before the window opens, replaced a quarter of the way in
+        let history = [(100.0, at(-50)), (110.0, at(25))];
+
+        let held = held_values(id, &history, at(0), at(100));
+
+        assert_eq!(held.len(), 2);
+        assert_eq!(
+            (held[0].value, held[0].from, held[0].until),
+            (100.0, at(0), at(25))
+        );
+        assert_eq!(
+            (held[1].value, held[1].from, held[1].until),
+            (110.0, at(25), at(100))
+        );
+
+        let r = held_source(id, held);
+        // 100 for 25s, 110 for 75s
+        assert_eq!(twap(1, &[r]), Some(107.5));
+    }
+
+    #[test]
+    fn values_set_after_close_hold_for_nothing() {
+        let history = [(100.0, at(10)), (500.0, at(150))];
+
+        let r = held("binance", &history, at(0), at(100));
+
+        assert_eq!(r.held.len(), 1);
+        assert_eq!((r.held[0].from, r.held[0].until), (at(10), at(100)));
+        assert_eq!(twap(1, &[r]), Some(100.0));
+    }
+
+    #[test]
+    fn twap_over_a_single_report() {
+        let r = held("binance", &[(42.0, at(30))], at(0), at(100));
+
+        assert_eq!(twap(1, std::slice::from_ref(&r)), Some(42.0));
+        assert_eq!(twap(2, &[r]), None);
+    }
+
+    #[test]
+    fn twap_weighs_sources_by_time_held() {
+        let a = held("binance", &[(100.0, at(0))], at(0), at(100));
+        let b = held("coinbase", &[(200.0, at(75))], at(0), at(100));
+
+        // 100 for 100s, 200 for 25s
+        assert_eq!(twap(2, &[a, b]), Some(120.0));
+    }
+}
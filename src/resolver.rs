@@ -0,0 +1,1024 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, SubsecRound, Utc};
+use futures_util::{StreamExt, stream};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::close_condition::CloseCondition;
+use crate::config::ConsensusConfig;
+use crate::events::{self, Event};
+use crate::linked::GroupInvariant;
+use crate::models::outbox::SettlementPayload;
+use crate::outcome_type::OutcomeType;
+use crate::proof::{from_fixed, market_hash, round_fixed, settlement_leaf, to_fixed};
+use crate::repo::{
+    ClosedMarket, MarketRepo, NewSettlement, OutboxRepo, ReportRepo, SettlementRepo,
+};
+use crate::resolution::{
+    self, LateReportPolicy, MarketRequirements, Outlier, SelfReportPolicy, SettlementInputs,
+    SourceValue, Strategy,
+};
+use crate::state::AppState;
+use crate::value_type::{ValueType, encode_fixed_at};
+
+/// Lifecycle transitions run here; resolution of CLOSED markets is split
+/// across `resolver.shards` tasks (default 1), each owning the markets whose
+/// id hashes to its shard so no two tasks ever finalize the same market.
+/// Only the leader replica runs any of them.
+pub async fn resolver_loop(state: AppState) {
+    let notice_secs = state.config.resolver.close_notice_secs.clone();
+    let shards = state.config.resolver.shards();
+
+    for shard in 0..shards {
+        let shard_state = state.clone();
+        state
+            .loops
+            .spawn(&format!("resolver-shard-{}", shard), async move {
+                resolve_shard_loop(shard_state, shard, shards).await
+            });
+    }
+
+    let interval = state.config.resolver.interval();
+
+    loop {
+        state.leader.wait().await;
+
+        let run = state.loops.start("resolver", interval);
+
+        // a database outage fails the pass, not the task
+        match lifecycle_pass(&state, &notice_secs).await {
+            Ok(items) => run.finish(items),
+            Err(e) => {
+                tracing::error!("resolver pass failed: {}", e);
+                run.fail(e);
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn lifecycle_pass(state: &AppState, notice_secs: &[i32]) -> Result<usize, sqlx::Error> {
+    Ok(open_scheduled_markets(state).await?
+        + announce_closing_soon(state, notice_secs).await?
+        + close_on_conditions(state).await?
+        + auto_close_markets(state).await?
+        + resolve_groups(state).await?
+        + expire_unresolved(state).await?)
+}
+
+async fn resolve_shard_loop(state: AppState, shard: i32, shards: i32) {
+    if shards > 1 {
+        tracing::info!("Resolver shard {}/{} started", shard + 1, shards);
+    }
+
+    let name = format!("resolver-shard-{}", shard);
+    let interval = state.config.resolver.interval();
+
+    loop {
+        state.leader.wait().await;
+
+        let run = state.loops.start(&name, interval);
+
+        match resolve_markets(&state, shard, shards).await {
+            Ok(resolved) => run.finish(resolved),
+            Err(e) => {
+                tracing::error!("resolver shard {} pass failed: {}", shard, e);
+                run.fail(e);
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn open_scheduled_markets(state: &AppState) -> Result<usize, sqlx::Error> {
+    let now = Utc::now();
+    let mut tx = state.db.begin().await?;
+
+    let opened = MarketRepo::open_scheduled(&mut *tx, now).await?;
+
+    for id in &opened {
+        audit::record(
+            &mut *tx,
+            AuditEntry::new("market", *id, "opened", "resolver")
+                .transition(Some("SCHEDULED"), Some("OPEN")),
+        )
+        .await?;
+
+        events::append(&mut *tx, &Event::MarketOpened { market_id: *id }).await?;
+    }
+
+    tx.commit().await?;
+
+    if !opened.is_empty() {
+        state.cache.invalidate_markets();
+        tracing::info!("Opened {} scheduled markets", opened.len());
+    }
+
+    Ok(opened.len())
+}
+
+/// Emits one `MarketClosingSoon` per (market, lead time) once closes_at is
+/// within that lead. The notice table makes this at-most-once across restarts.
+async fn announce_closing_soon(
+    state: &AppState,
+    default_secs: &[i32],
+) -> Result<usize, sqlx::Error> {
+    let now = Utc::now();
+
+    let mut tx = state.db.begin().await?;
+
+    let due = MarketRepo::claim_close_notices(&mut *tx, now, default_secs).await?;
+
+    for row in &due {
+        let event = Event::MarketClosingSoon {
+            market_id: row.market_id,
+            closes_at: row.closes_at,
+            lead_secs: row.lead_secs,
+        };
+        events::append(&mut *tx, &event).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(due.len())
+}
+
+/// Closes OPEN markets whose close conditions are met. closes_at is pulled in
+/// to now so the market resolves on the same schedule as a timed close.
+async fn close_on_conditions(state: &AppState) -> Result<usize, sqlx::Error> {
+    let markets = MarketRepo::open_with_close_conditions(&state.db).await?;
+
+    let mut count = 0;
+
+    for market in markets {
+        let conditions: Vec<CloseCondition> = match serde_json::from_value(market.close_conditions)
+        {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::error!("market {} has invalid close conditions: {}", market.id, e);
+                continue;
+            }
+        };
+
+        let reports = ReportRepo::source_values(&state.db, market.id).await?;
+
+        let Some(trigger) = conditions.iter().find(|c| c.is_met(&reports)) else {
+            continue;
+        };
+
+        let trigger_json = serde_json::to_value(trigger).unwrap();
+        let mut tx = state.db.begin().await?;
+
+        let closed =
+            MarketRepo::close_on_trigger(&mut *tx, market.id, Utc::now(), &trigger_json).await?;
+
+        if closed {
+            audit::record(
+                &mut *tx,
+                AuditEntry::new("market", market.id, "closed", "resolver")
+                    .transition(Some("OPEN"), Some("CLOSED"))
+                    .details(serde_json::json!({ "trigger": trigger_json })),
+            )
+            .await?;
+
+            let event = Event::MarketClosed {
+                market_id: market.id,
+                reason: None,
+            };
+            events::append(&mut *tx, &event).await?;
+        }
+
+        tx.commit().await?;
+
+        if closed {
+            state.cache.invalidate_markets();
+            tracing::info!("Closed market {} early on {:?}", market.id, trigger);
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+async fn auto_close_markets(state: &AppState) -> Result<usize, sqlx::Error> {
+    let now = Utc::now();
+    let mut tx = state.db.begin().await?;
+
+    let closed = MarketRepo::close_due(&mut *tx, now).await?;
+
+    for id in &closed {
+        audit::record(
+            &mut *tx,
+            AuditEntry::new("market", *id, "closed", "resolver")
+                .transition(Some("OPEN"), Some("CLOSED")),
+        )
+        .await?;
+
+        let event = Event::MarketClosed {
+            market_id: *id,
+            reason: None,
+        };
+        events::append(&mut *tx, &event).await?;
+    }
+
+    tx.commit().await?;
+
+    if !closed.is_empty() {
+        state.cache.invalidate_markets();
+        tracing::info!("Auto-closed {} markets", closed.len());
+    }
+
+    Ok(closed.len())
+}
+
+/// Pages through the shard's whole backlog, admin-boosted markets first and
+/// then the longest closed, resolving up to `resolver.concurrency` markets at
+/// a time. Markets without an outcome yet are passed over until the next
+/// pass.
+async fn resolve_markets(state: &AppState, shard: i32, shards: i32) -> Result<usize, sqlx::Error> {
+    // grouped markets settle together in resolve_groups
+    // markets in their close grace period may still take late reports
+    let closed_before = Utc::now() - state.config.resolver.close_grace();
+    let page_size = state.config.resolver.batch_size.max(1);
+    let concurrency = state.config.resolver.concurrency.max(1);
+
+    let mut after = None;
+    let mut resolved = 0;
+
+    loop {
+        let markets =
+            MarketRepo::closed_in_shard(&state.db, shard, shards, closed_before, after, page_size)
+                .await?;
+
+        let Some(last) = markets.last() else {
+            break;
+        };
+        after = Some((last.priority, last.closes_at, last.id));
+        let full = markets.len() as i64 == page_size;
+
+        resolved += stream::iter(markets)
+            .map(|market| async move {
+                match compute_outcome(state, &market).await? {
+                    Some(computed) => Ok(finalize_market(state, &market, computed).await? as usize),
+                    None => Ok::<_, sqlx::Error>(0),
+                }
+            })
+            .buffer_unordered(concurrency)
+            .fold(Ok(0), |n: Result<usize, sqlx::Error>, r| async move {
+                Ok(n? + r?)
+            })
+            .await?;
+
+        if !full {
+            break;
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Settles market groups whose members have all closed: every member gets
+/// an outcome and the group invariant holds, or nothing settles and the
+/// group is BLOCKED for review.
+async fn resolve_groups(state: &AppState) -> Result<usize, sqlx::Error> {
+    let groups = sqlx::query!(
+        r#"
+        SELECT g.id, g.invariant
+        FROM market_groups g
+        WHERE g.status = 'ACTIVE'
+          AND NOT EXISTS (
+            SELECT 1 FROM markets m
+            WHERE m.group_id = g.id
+              AND (m.status <> 'CLOSED' OR m.closes_at > $1)
+          )
+        "#,
+        Utc::now() - state.config.resolver.close_grace()
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let mut settled = 0;
+
+    for group in groups {
+        let members = MarketRepo::group_members(&state.db, group.id).await?;
+
+        let mut computed = Vec::with_capacity(members.len());
+        for market in &members {
+            match compute_outcome(state, market).await? {
+                Some(c) => computed.push(c),
+                // not enough agreement yet; try again next pass
+                None => break,
+            }
+        }
+
+        if members.is_empty() || computed.len() != members.len() {
+            continue;
+        }
+
+        let outcomes: Vec<f64> = computed.iter().map(|c| c.outcome).collect();
+        let invariant: GroupInvariant = serde_json::from_value(group.invariant).unwrap_or_default();
+
+        if let Err(reason) = invariant.check(&outcomes) {
+            block_group(state, group.id, &reason).await?;
+            continue;
+        }
+
+        let mut tx = state.db.begin().await?;
+
+        let mut all_moved = true;
+        for (market, c) in members.iter().zip(&computed) {
+            record_outliers(&mut tx, market.id, &c.outliers).await?;
+            if !finalize_in_tx(
+                state,
+                &mut tx,
+                market,
+                c.outcome,
+                c.inputs.as_ref(),
+                "resolver",
+            )
+            .await?
+            {
+                all_moved = false;
+                break;
+            }
+        }
+
+        // a group settles whole or not at all; dropping the transaction
+        // rolls back the members already written
+        if !all_moved {
+            continue;
+        }
+
+        sqlx::query("UPDATE market_groups SET status = 'SETTLED' WHERE id = $1")
+            .bind(group.id)
+            .execute(&mut *tx)
+            .await?;
+
+        audit::record(
+            &mut *tx,
+            AuditEntry::new("market_group", group.id, "settled", "resolver")
+                .transition(Some("ACTIVE"), Some("SETTLED")),
+        )
+        .await?;
+
+        tx.commit().await?;
+        state.cache.invalidate_markets();
+
+        tracing::info!(
+            "Settled market group {} ({} markets)",
+            group.id,
+            members.len()
+        );
+
+        settled += members.len();
+    }
+
+    Ok(settled)
+}
+
+/// CLOSED markets that missed their resolve deadline become UNRESOLVED and
+/// wait for an admin (extend or force-resolve). A group can't settle without
+/// every member, so active groups holding one are blocked.
+async fn expire_unresolved(state: &AppState) -> Result<usize, sqlx::Error> {
+    let mut tx = state.db.begin().await?;
+
+    let expired = MarketRepo::expire_unresolved(&mut *tx).await?;
+
+    for row in &expired {
+        audit::record(
+            &mut *tx,
+            AuditEntry::new("market", row.id, "unresolved", "resolver")
+                .transition(Some("CLOSED"), Some("UNRESOLVED"))
+                .details(serde_json::json!({ "resolve_deadline": row.resolve_deadline })),
+        )
+        .await?;
+
+        let event = Event::MarketUnresolved {
+            market_id: row.id,
+            resolve_deadline: row.resolve_deadline,
+        };
+        events::append(&mut *tx, &event).await?;
+    }
+
+    tx.commit().await?;
+
+    if !expired.is_empty() {
+        state.cache.invalidate_markets();
+        tracing::warn!("{} markets passed their resolve deadline", expired.len());
+    }
+
+    let count = expired.len();
+    for row in expired {
+        if let Some(group_id) = row.group_id {
+            let active = sqlx::query_scalar!(
+                r#"SELECT status = 'ACTIVE' AS "active!" FROM market_groups WHERE id = $1"#,
+                group_id
+            )
+            .fetch_optional(&state.db)
+            .await?
+            .unwrap_or(false);
+
+            if active {
+                block_group(state, group_id, &format!("market {} is unresolved", row.id)).await?;
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+async fn block_group(state: &AppState, group_id: Uuid, reason: &str) -> Result<(), sqlx::Error> {
+    tracing::warn!("Market group {} blocked: {}", group_id, reason);
+
+    let mut tx = state.db.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE market_groups
+        SET status = 'BLOCKED',
+            blocked_reason = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(reason)
+    .bind(group_id)
+    .execute(&mut *tx)
+    .await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market_group", group_id, "blocked", "resolver")
+            .transition(Some("ACTIVE"), Some("BLOCKED"))
+            .details(serde_json::json!({ "reason": reason })),
+    )
+    .await?;
+
+    let event = Event::MarketGroupBlocked {
+        group_id,
+        reason: reason.to_string(),
+    };
+    events::append(&mut *tx, &event).await?;
+
+    tx.commit().await
+}
+
+/// An outcome on the fixed-point grid, rounded to the market's own
+/// `decimals` when it set them. Discrete outcomes are option indexes and
+/// stay as they are.
+pub(crate) fn outcome_fixed(
+    outcome: f64,
+    outcome_type: &OutcomeType,
+    decimals: Option<u32>,
+) -> i64 {
+    let fixed = to_fixed(outcome);
+    match decimals {
+        Some(places) if !outcome_type.is_discrete() => round_fixed(fixed, places),
+        _ => fixed,
+    }
+}
+
+struct Computed {
+    outcome: f64,
+    // reports left out before the strategy ran
+    outliers: Vec<Outlier>,
+    inputs: Option<SettlementInputs>,
+}
+
+/// A closed market's resolution settings, parsed from its row.
+struct Rules {
+    strategy: Strategy,
+    policy: SelfReportPolicy,
+    outcome_type: OutcomeType,
+    late_policy: LateReportPolicy,
+}
+
+impl Rules {
+    /// Fails only on an unreadable strategy; the policies fall back to their
+    /// defaults.
+    fn parse(market: &ClosedMarket) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            strategy: serde_json::from_value(market.resolution.clone())?,
+            policy: serde_json::from_value(market.self_report_policy.clone()).unwrap_or_default(),
+            outcome_type: serde_json::from_value(market.outcome_type.clone()).unwrap_or_default(),
+            late_policy: serde_json::from_value(market.late_report_policy.clone())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+async fn compute_outcome(
+    state: &AppState,
+    market: &ClosedMarket,
+) -> Result<Option<Computed>, sqlx::Error> {
+    let rules = match Rules::parse(market) {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::error!("market {} has invalid resolution config: {}", market.id, e);
+            return Ok(None);
+        }
+    };
+
+    let consensus = state.config.consensus.for_market(market.consensus_bps);
+
+    let mut reports = load_source_values(
+        state,
+        market.id,
+        market.min_value,
+        market.max_value,
+        &rules.late_policy,
+    )
+    .await?;
+    if matches!(rules.strategy, Strategy::Twap { .. }) {
+        load_held_values(
+            state,
+            market.id,
+            market.reporting_opens_at,
+            market.closes_at,
+            &mut reports,
+        )
+        .await?;
+    }
+    let requirements = MarketRepo::requirements(&state.db, market.id).await?;
+
+    Ok(decide(&rules, &consensus, requirements.as_ref(), reports))
+}
+
+/// Everything `compute_outcome` does once the reports are loaded: `None`
+/// while the market can't be decided yet.
+fn decide(
+    rules: &Rules,
+    consensus: &ConsensusConfig,
+    requirements: Option<&MarketRequirements>,
+    reports: Vec<SourceValue>,
+) -> Option<Computed> {
+    let evaluation = evaluate(
+        &rules.strategy,
+        &rules.policy,
+        consensus,
+        &rules.outcome_type,
+        requirements,
+        reports,
+    );
+
+    Some(Computed {
+        outcome: evaluation.outcome?,
+        inputs: SettlementInputs::new(&rules.strategy, &evaluation.counted),
+        outliers: evaluation.outliers,
+    })
+}
+
+/// What the resolver makes of a market's reports as they stand.
+pub(crate) struct Evaluation {
+    pub outcome: Option<f64>,
+    pub requirements_met: bool,
+    pub outliers: Vec<Outlier>,
+    // reports the strategy saw, outliers removed
+    pub counted: Vec<SourceValue>,
+}
+
+/// The resolver's decision without side effects, shared with the market
+/// preview so the two can't drift apart.
+pub(crate) fn evaluate(
+    strategy: &Strategy,
+    policy: &SelfReportPolicy,
+    consensus: &ConsensusConfig,
+    outcome_type: &OutcomeType,
+    requirements: Option<&MarketRequirements>,
+    reports: Vec<SourceValue>,
+) -> Evaluation {
+    // creator-declared sources and counts gate the strategy entirely
+    let requirements_met =
+        requirements.is_none_or(|r| r.unmet(strategy, policy, &reports).is_empty());
+    // checked again on what the strategy sees, after outliers
+    let quorum = requirements.map_or(0, MarketRequirements::quorum);
+
+    if outcome_type.is_discrete() {
+        let outcome = requirements_met
+            .then(|| {
+                resolution::majority(strategy, policy, consensus, quorum, outcome_type, &reports)
+            })
+            .flatten();
+
+        return Evaluation {
+            outcome,
+            requirements_met,
+            outliers: Vec::new(),
+            counted: reports,
+        };
+    }
+
+    // a lone reporter far from the rest would otherwise hold the spread
+    // open forever
+    let outliers = resolution::outliers(&reports, consensus);
+    let counted: Vec<SourceValue> = reports
+        .into_iter()
+        .filter(|r| !outliers.iter().any(|o| o.report_id == r.id))
+        .collect();
+
+    let outcome = requirements_met
+        .then(|| resolution::resolve(strategy, policy, consensus, quorum, &counted))
+        .flatten();
+
+    Evaluation {
+        outcome,
+        requirements_met,
+        outliers,
+        counted,
+    }
+}
+
+/// Flags the reports consensus excluded, in the transaction that settles
+/// the market.
+async fn record_outliers(
+    tx: &mut Transaction<'_, Postgres>,
+    market_id: Uuid,
+    outliers: &[Outlier],
+) -> Result<(), sqlx::Error> {
+    for outlier in outliers {
+        SettlementRepo::flag_report(
+            &mut **tx,
+            outlier.report_id,
+            market_id,
+            "OUTLIER",
+            &serde_json::to_value(outlier).unwrap(),
+        )
+        .await?;
+    }
+
+    if !outliers.is_empty() {
+        tracing::info!(
+            "Market {} settled without {} outlier report(s)",
+            market_id,
+            outliers.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Reports that may sway a market's outcome, oldest first and one per
+/// source. Late reports are dropped when the market's policy excludes them.
+pub(crate) async fn load_source_values(
+    state: &AppState,
+    market_id: Uuid,
+    min_value: Option<f64>,
+    max_value: Option<f64>,
+    late_policy: &LateReportPolicy,
+) -> Result<Vec<SourceValue>, sqlx::Error> {
+    let reports = ReportRepo::source_values(&state.db, market_id).await?;
+
+    Ok(source_values(reports, min_value, max_value, late_policy))
+}
+
+/// The filtering behind `load_source_values`, over a market's live reports
+/// oldest first.
+fn source_values(
+    mut reports: Vec<SourceValue>,
+    min_value: Option<f64>,
+    max_value: Option<f64>,
+    late_policy: &LateReportPolicy,
+) -> Vec<SourceValue> {
+    // the schema allows one report per source; should duplicates slip in
+    // anyway, only the newest counts
+    let mut seen = HashSet::new();
+    reports.reverse();
+    reports.retain(|r| seen.insert(r.source.clone()));
+    reports.reverse();
+
+    // bounds are enforced on submission, but rows predating them (or
+    // inserted out of band) must not sway the outcome
+    reports
+        .into_iter()
+        .filter(|r| {
+            min_value.is_none_or(|min| r.value >= min) && max_value.is_none_or(|max| r.value <= max)
+        })
+        .filter(|r| late_policy.counts(r.late))
+        .collect()
+}
+
+/// Fills in what each report held between `opens_at` and `closes_at`, for
+/// TWAP.
+pub(crate) async fn load_held_values(
+    state: &AppState,
+    market_id: Uuid,
+    opens_at: DateTime<Utc>,
+    closes_at: DateTime<Utc>,
+    reports: &mut [SourceValue],
+) -> Result<(), sqlx::Error> {
+    let history = ReportRepo::value_history(&state.db, market_id).await?;
+
+    for report in reports {
+        let values: Vec<(f64, DateTime<Utc>)> = history
+            .iter()
+            .filter(|(id, _, _)| *id == report.id)
+            .map(|(_, value, at)| (*value, *at))
+            .collect();
+        report.held = resolution::held_values(report.id, &values, opens_at, closes_at);
+    }
+
+    Ok(())
+}
+
+async fn finalize_market(
+    state: &AppState,
+    market: &ClosedMarket,
+    computed: Computed,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = state.db.begin().await?;
+    record_outliers(&mut tx, market.id, &computed.outliers).await?;
+    if !finalize_in_tx(
+        state,
+        &mut tx,
+        market,
+        computed.outcome,
+        computed.inputs.as_ref(),
+        "resolver",
+    )
+    .await?
+    {
+        // dropping the transaction rolls back the outlier flags too
+        return Ok(false);
+    }
+    tx.commit().await?;
+    state.cache.invalidate_markets();
+    Ok(true)
+}
+
+/// Writes the settlement and queues the outbox job. A settlement bound for a
+/// chain is only proposed: the market waits in PROPOSED until the reconciler
+/// sees the transaction confirmed. One with no chain to go to is final and
+/// the market RESOLVED at once. `inputs` is stored as the basis of the
+/// decision; operator outcomes have none.
+///
+/// Returns `false`, having written nothing, when the market is no longer
+/// CLOSED (an admin cancelled it, or it expired, since it was loaded); the
+/// caller should then roll back.
+pub(crate) async fn finalize_in_tx(
+    state: &AppState,
+    tx: &mut Transaction<'_, Postgres>,
+    market: &ClosedMarket,
+    outcome: f64,
+    inputs: Option<&SettlementInputs>,
+    actor: &str,
+) -> Result<bool, sqlx::Error> {
+    let market_id = market.id;
+    let value_type = ValueType::parse(&market.value_type).unwrap_or_default();
+    let outcome_type: OutcomeType =
+        serde_json::from_value(market.outcome_type.clone()).unwrap_or_default();
+    let chain_id = market.chain_id.map(|c| c as u64);
+    let decimals = market.decimals.map(|d| d.max(0) as u32);
+
+    let settlement_id = Uuid::new_v4();
+    // Postgres keeps microseconds; the leaf must match what the batcher reads back
+    let now = Utc::now().trunc_subsecs(6);
+
+    // snap to the fixed-point grid (and the market's decimals) so the API,
+    // the leaf and the chain all carry the same number
+    let outcome_e8 = outcome_fixed(outcome, &outcome_type, decimals);
+    let outcome = from_fixed(outcome_e8);
+    // discrete markets anchor the winning option's index itself
+    let anchored_decimals =
+        (!outcome_type.is_discrete()).then(|| decimals.unwrap_or(value_type.default_decimals()));
+    let outcome_u64 = match anchored_decimals {
+        Some(places) => encode_fixed_at(outcome_e8, places),
+        None => outcome as u64,
+    };
+
+    let market_hash = market_hash(market_id);
+    let hash_algorithm = state.config.proof.hash_algorithm;
+    let leaf = settlement_leaf(hash_algorithm, market_id, outcome_e8, now);
+    let ts = now.timestamp() as u64;
+
+    let payload = SettlementPayload {
+        market_id: market_id.to_string(),
+        market_hash_hex: hex::encode(market_hash),
+        leaf_hex: hex::encode(leaf),
+        outcome_u64,
+        outcome_e8,
+        ts,
+        // pin the chain now so a later change of default doesn't move queued jobs
+        chain_id: chain_id.or(state.default_chain_id()),
+    };
+
+    let payload_json = serde_json::to_value(&payload).unwrap();
+    // an off-chain market is final as soon as it's decided
+    let proposed = market.anchor_on_chain && payload.chain_id.is_some();
+
+    // the conditional update takes the row lock and re-checks the status, so
+    // a market cancelled or expired since it was loaded settles nothing
+    let moved = if proposed {
+        MarketRepo::mark_proposed(&mut **tx, market_id).await?
+    } else {
+        MarketRepo::mark_resolved(&mut **tx, market_id).await?
+    };
+
+    if !moved {
+        tracing::warn!(
+            "Market {} left CLOSED before it settled; not settling",
+            market_id
+        );
+        return Ok(false);
+    }
+
+    let status = if proposed { "PROPOSED" } else { "RESOLVED" };
+
+    SettlementRepo::insert(
+        &mut **tx,
+        &NewSettlement {
+            id: settlement_id,
+            market_id,
+            outcome,
+            outcome_e8,
+            decided_at: now,
+            proposed,
+            hash_algorithm,
+            decimals: anchored_decimals.map(|d| d as i16),
+            inputs: inputs.map(|i| serde_json::to_value(i).unwrap()),
+        },
+    )
+    .await?;
+
+    audit::record(
+        &mut **tx,
+        AuditEntry::new(
+            "market",
+            market_id,
+            if proposed { "proposed" } else { "resolved" },
+            actor,
+        )
+        .transition(Some("CLOSED"), Some(status)),
+    )
+    .await?;
+
+    audit::record(
+        &mut **tx,
+        AuditEntry::new("settlement", settlement_id, "created", actor).details(serde_json::json!({
+            "market_id": market_id,
+            "outcome": outcome,
+            "outcome_e8": outcome_e8,
+            "status": if proposed { "PROPOSED" } else { "FINAL" },
+            "hash_algorithm": hash_algorithm,
+            "decimals": anchored_decimals,
+        })),
+    )
+    .await?;
+
+    let event = if proposed {
+        Event::SettlementProposed {
+            market_id,
+            outcome,
+            decided_at: now,
+        }
+    } else {
+        Event::SettlementDecided {
+            market_id,
+            outcome,
+            decided_at: now,
+        }
+    };
+    events::append(&mut **tx, &event).await?;
+
+    if !market.anchor_on_chain {
+        tracing::info!("Settled market {} off chain", market_id);
+        return Ok(true);
+    }
+
+    let outbox_id = Uuid::new_v4();
+
+    OutboxRepo::enqueue(
+        &mut **tx,
+        outbox_id,
+        market_id,
+        &payload_json,
+        payload.chain_id.map(|c| c as i64),
+        market.priority,
+        now,
+    )
+    .await?;
+
+    audit::record(
+        &mut **tx,
+        AuditEntry::new("outbox", outbox_id, "queued", actor)
+            .transition(None, Some("PENDING"))
+            .details(serde_json::json!({ "market_id": market_id })),
+    )
+    .await?;
+
+    tracing::info!("Queued settlement in outbox id={}", outbox_id);
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(source: &str, value: f64) -> SourceValue {
+        SourceValue {
+            id: Uuid::new_v4(),
+            source: source.to_string(),
+            value,
+            self_reported: false,
+            confidence: None,
+            stake: None,
+            late: false,
+            submitted_by: None,
+            held: Vec::new(),
+        }
+    }
+
+    fn spread_rules() -> Rules {
+        Rules {
+            strategy: Strategy::Spread,
+            policy: SelfReportPolicy::Include,
+            outcome_type: OutcomeType::default(),
+            late_policy: LateReportPolicy::Include,
+        }
+    }
+
+    #[test]
+    fn source_values_keep_the_newest_in_bounds_report_per_source() {
+        let late = SourceValue {
+            late: true,
+            ..report("kraken", 101.0)
+        };
+        let reports = vec![
+            report("binance", 99.0),
+            report("coinbase", 500.0),
+            report("binance", 100.0),
+            late,
+        ];
+
+        let included = source_values(
+            reports.clone(),
+            Some(0.0),
+            Some(200.0),
+            &LateReportPolicy::Include,
+        );
+        let values: Vec<f64> = included.iter().map(|r| r.value).collect();
+        assert_eq!(values, [100.0, 101.0]);
+
+        let excluded = source_values(reports, Some(0.0), Some(200.0), &LateReportPolicy::Exclude);
+        let values: Vec<f64> = excluded.iter().map(|r| r.value).collect();
+        assert_eq!(values, [100.0]);
+    }
+
+    #[test]
+    fn decide_settles_without_the_outlier() {
+        let reports = vec![
+            report("binance", 100.0),
+            report("coinbase", 100.0),
+            report("kraken", 100.0),
+            report("rogue", 250.0),
+        ];
+        let rogue = reports[3].id;
+
+        let computed = decide(&spread_rules(), &ConsensusConfig::default(), None, reports).unwrap();
+
+        assert_eq!(computed.outcome, 100.0);
+        assert_eq!(computed.outliers.len(), 1);
+        assert_eq!(computed.outliers[0].report_id, rogue);
+        let inputs = computed.inputs.unwrap();
+        assert_eq!(inputs.report_count, 3);
+        assert!(!inputs.report_ids.contains(&rogue));
+    }
+
+    #[test]
+    fn decide_waits_for_required_sources() {
+        let reports = vec![
+            report("binance", 100.0),
+            report("coinbase", 100.0),
+            report("kraken", 100.0),
+        ];
+        let requirements = MarketRequirements {
+            required_sources: vec!["chainlink".to_string()],
+            ..Default::default()
+        };
+
+        assert!(
+            decide(
+                &spread_rules(),
+                &ConsensusConfig::default(),
+                Some(&requirements),
+                reports.clone()
+            )
+            .is_none()
+        );
+        assert!(decide(&spread_rules(), &ConsensusConfig::default(), None, reports).is_some());
+    }
+
+    #[test]
+    fn decide_waits_while_the_spread_is_too_wide() {
+        let reports = vec![
+            report("binance", 100.0),
+            report("coinbase", 103.0),
+            report("kraken", 106.0),
+        ];
+
+        assert!(decide(&spread_rules(), &ConsensusConfig::default(), None, reports).is_none());
+    }
+}
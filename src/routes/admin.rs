@@ -0,0 +1,620 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{SubsecRound, Utc};
+use uuid::Uuid;
+
+use crate::archive::archive_markets;
+use crate::audit::{self, AuditEntry};
+use crate::auth::RequireAdmin;
+use crate::error::{AppError, AppJson};
+use crate::events::{self, Event};
+use crate::outcome_type::OutcomeType;
+use crate::repo::{MarketRepo, OutboxRepo, ReportRepo, SettlementRepo};
+use crate::resolver::finalize_in_tx;
+use crate::state::AppState;
+use crate::types::{
+    CancelMarketRequest, CloseMarketRequest, ExtendMarketRequest, ForceResolveRequest, Market,
+    SetPriorityRequest,
+};
+use crate::validation::{MAX_REASON_LEN, Validation};
+
+/// Marks a market VOID. Void markets take no reports and are never resolved;
+/// an active group containing one is blocked since it can no longer settle.
+#[utoipa::path(
+    post,
+    path = "/markets/{id}/cancel",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Market id")),
+    request_body(content = Option<CancelMarketRequest>),
+    responses(
+        (status = 200, body = Market),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 409, description = "Market is already resolved or void", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn cancel_market(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(market_id): Path<Uuid>,
+    payload: Option<Json<CancelMarketRequest>>,
+) -> Result<Json<Market>, AppError> {
+    let Json(payload) = payload.unwrap_or_default();
+
+    let mut tx = state.db.begin().await?;
+
+    let market = MarketRepo::lock(&mut *tx, market_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    if matches!(market.status.as_str(), "PROPOSED" | "RESOLVED" | "VOID") {
+        return Err(AppError::conflict(
+            "MARKET_FINALIZED",
+            format!("market is already {}", market.status.to_lowercase()),
+        ));
+    }
+
+    MarketRepo::set_status(&mut *tx, market_id, "VOID").await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market", market_id, "cancelled", &admin.actor)
+            .transition(Some(&market.status), Some("VOID"))
+            .details(serde_json::json!({ "reason": payload.reason })),
+    )
+    .await?;
+
+    let mut blocked_group = None;
+
+    if let Some(group_id) = market.group_id {
+        let reason = format!("market {} was cancelled", market_id);
+
+        let blocked = sqlx::query(
+            r#"
+            UPDATE market_groups
+            SET status = 'BLOCKED',
+                blocked_reason = $1
+            WHERE id = $2 AND status = 'ACTIVE'
+            "#,
+        )
+        .bind(&reason)
+        .bind(group_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if blocked {
+            audit::record(
+                &mut *tx,
+                AuditEntry::new("market_group", group_id, "blocked", &admin.actor)
+                    .transition(Some("ACTIVE"), Some("BLOCKED"))
+                    .details(serde_json::json!({ "reason": reason })),
+            )
+            .await?;
+
+            blocked_group = Some((group_id, reason));
+        }
+    }
+
+    let voided = Event::MarketVoided {
+        market_id,
+        reason: payload.reason,
+    };
+    events::append(&mut *tx, &voided).await?;
+
+    if let Some((group_id, reason)) = blocked_group {
+        events::append(&mut *tx, &Event::MarketGroupBlocked { group_id, reason }).await?;
+    }
+
+    tx.commit().await?;
+    state.cache.invalidate_markets();
+
+    tracing::info!("Market {} cancelled by {}", market_id, admin.actor);
+
+    reload(&state, market_id).await
+}
+
+/// Pushes `closes_at` later, moving `resolve_deadline` by the same amount. A
+/// market that already closed but hasn't been resolved goes back to OPEN, and
+/// its closing-soon notices fire again.
+#[utoipa::path(
+    post,
+    path = "/markets/{id}/extend",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Market id")),
+    request_body = ExtendMarketRequest,
+    responses(
+        (status = 200, body = Market),
+        (status = 400, description = "closes_at is not later than the current one", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 409, description = "Market is already resolved or void, or closes on an event", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn extend_market(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(market_id): Path<Uuid>,
+    AppJson(payload): AppJson<ExtendMarketRequest>,
+) -> Result<Json<Market>, AppError> {
+    let closes_at = chrono::DateTime::parse_from_rfc3339(&payload.closes_at)
+        .map_err(|e| AppError::bad_request("INVALID_TIMESTAMP", format!("closes_at: {}", e)))?
+        .with_timezone(&Utc)
+        .trunc_subsecs(6);
+
+    if closes_at <= Utc::now() {
+        return Err(AppError::bad_request(
+            "INVALID_CLOSES_AT",
+            "closes_at must be in the future",
+        ));
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    let market = MarketRepo::lock(&mut *tx, market_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    if matches!(market.status.as_str(), "PROPOSED" | "RESOLVED" | "VOID") {
+        return Err(AppError::conflict(
+            "MARKET_FINALIZED",
+            format!("market is already {}", market.status.to_lowercase()),
+        ));
+    }
+
+    // an event-closed market gets a closes_at only by being closed
+    let Some(current_closes_at) = market.closes_at else {
+        return Err(AppError::conflict(
+            "MARKET_CLOSES_ON_EVENT",
+            "market has no closes_at to extend; close it with POST /markets/{id}/close",
+        ));
+    };
+
+    if closes_at <= current_closes_at {
+        return Err(AppError::bad_request(
+            "INVALID_CLOSES_AT",
+            "closes_at must be later than the current closes_at",
+        ));
+    }
+
+    if market.opens_at.is_some_and(|o| o >= closes_at) {
+        return Err(AppError::bad_request(
+            "INVALID_OPENS_AT",
+            "opens_at must be before closes_at",
+        ));
+    }
+
+    let status = if market.status == "CLOSED" || market.status == "UNRESOLVED" {
+        "OPEN"
+    } else {
+        market.status.as_str()
+    };
+
+    // set whenever closes_at is
+    let resolve_deadline = market
+        .resolve_deadline
+        .unwrap_or(current_closes_at + state.config.resolver.resolve_window())
+        + (closes_at - current_closes_at);
+
+    MarketRepo::reschedule(&mut tx, market_id, closes_at, resolve_deadline, status).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market", market_id, "extended", &admin.actor)
+            .transition(Some(&market.status), Some(status))
+            .details(serde_json::json!({
+                "previous_closes_at": market.closes_at,
+                "closes_at": closes_at,
+                "resolve_deadline": resolve_deadline,
+            })),
+    )
+    .await?;
+
+    events::append(
+        &mut *tx,
+        &Event::MarketExtended {
+            market_id,
+            closes_at,
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+    state.cache.invalidate_markets();
+
+    reload(&state, market_id).await
+}
+
+/// Closes an OPEN market now, with the reason given. Meant for markets that
+/// close on an event rather than at `closes_at`, which the auto-closer never
+/// closes; timed markets can be closed early this way too. A missing
+/// `resolve_deadline` is set `resolver.resolve_window_secs` out.
+#[utoipa::path(
+    post,
+    path = "/markets/{id}/close",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Market id")),
+    request_body = CloseMarketRequest,
+    responses(
+        (status = 200, body = Market),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 409, description = "Market is not OPEN", body = ErrorResponse),
+        (status = 422, description = "closed_reason is empty or too long", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn close_market(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(market_id): Path<Uuid>,
+    AppJson(payload): AppJson<CloseMarketRequest>,
+) -> Result<Json<Market>, AppError> {
+    let reason = payload.closed_reason.trim();
+    let mut validation = Validation::new();
+    validation.text("closed_reason", reason, MAX_REASON_LEN);
+    validation.finish()?;
+
+    let now = Utc::now().trunc_subsecs(6);
+    let mut tx = state.db.begin().await?;
+
+    let market = MarketRepo::lock(&mut *tx, market_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    if market.status != "OPEN" {
+        return Err(AppError::conflict(
+            "MARKET_NOT_OPEN",
+            format!("market is {}", market.status.to_lowercase()),
+        ));
+    }
+
+    MarketRepo::close_now(
+        &mut *tx,
+        market_id,
+        now,
+        now + state.config.resolver.resolve_window(),
+        reason,
+    )
+    .await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market", market_id, "closed", &admin.actor)
+            .transition(Some("OPEN"), Some("CLOSED"))
+            .details(serde_json::json!({
+                "closed_reason": reason,
+                "closes_at": market.closes_at,
+            })),
+    )
+    .await?;
+
+    let closed = Event::MarketClosed {
+        market_id,
+        reason: Some(reason.to_string()),
+    };
+    events::append(&mut *tx, &closed).await?;
+
+    tx.commit().await?;
+    state.cache.invalidate_markets();
+
+    tracing::info!("Market {} closed by {}", market_id, admin.actor);
+
+    reload(&state, market_id).await
+}
+
+/// Sets the market's resolution priority. Once it has closed, the resolver
+/// takes higher priorities first and, within one, the longest closed; an
+/// overdue market can be boosted past a backlog this way.
+#[utoipa::path(
+    post,
+    path = "/markets/{id}/priority",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Market id")),
+    request_body = SetPriorityRequest,
+    responses(
+        (status = 200, body = Market),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 409, description = "Market is already resolved or void", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn set_market_priority(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(market_id): Path<Uuid>,
+    AppJson(payload): AppJson<SetPriorityRequest>,
+) -> Result<Json<Market>, AppError> {
+    let mut tx = state.db.begin().await?;
+
+    let market = MarketRepo::lock(&mut *tx, market_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    if matches!(market.status.as_str(), "PROPOSED" | "RESOLVED" | "VOID") {
+        return Err(AppError::conflict(
+            "MARKET_FINALIZED",
+            format!("market is already {}", market.status.to_lowercase()),
+        ));
+    }
+
+    MarketRepo::set_priority(&mut *tx, market_id, payload.priority).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market", market_id, "reprioritized", &admin.actor).details(
+            serde_json::json!({
+                "previous_priority": market.priority,
+                "priority": payload.priority,
+            }),
+        ),
+    )
+    .await?;
+
+    tx.commit().await?;
+    state.cache.invalidate_markets();
+
+    reload(&state, market_id).await
+}
+
+/// Settles a market with an operator-supplied outcome, skipping the
+/// resolution strategy. The settlement goes through the same outbox path as
+/// any other. Markets in an active group must settle with their group.
+///
+/// On a PROPOSED or RESOLVED market this overrides the outcome: the old
+/// settlement is kept as a superseded revision, its unclaimed outbox jobs are
+/// abandoned and a correction is queued for the chain.
+#[utoipa::path(
+    post,
+    path = "/markets/{id}/force-resolve",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Market id")),
+    request_body = ForceResolveRequest,
+    responses(
+        (status = 200, body = Market),
+        (status = 400, description = "Outcome out of bounds", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 409, description = "Market is void or in an active group", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn force_resolve_market(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(market_id): Path<Uuid>,
+    AppJson(payload): AppJson<ForceResolveRequest>,
+) -> Result<Json<Market>, AppError> {
+    if !payload.outcome.is_finite() {
+        return Err(AppError::bad_request(
+            "INVALID_OUTCOME",
+            "outcome must be a finite number",
+        ));
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    let current = MarketRepo::lock(&mut *tx, market_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    if current.status == "VOID" {
+        return Err(AppError::conflict(
+            "MARKET_FINALIZED",
+            "market is already void",
+        ));
+    }
+
+    if current.group_status.as_deref() == Some("ACTIVE") {
+        return Err(AppError::conflict(
+            "MARKET_IN_GROUP",
+            "market belongs to an active group and settles with it",
+        ));
+    }
+
+    let outcome_type: OutcomeType =
+        serde_json::from_value(current.outcome_type).unwrap_or_default();
+    if outcome_type.is_discrete() && outcome_type.option_index(payload.outcome).is_none() {
+        return Err(AppError::bad_request(
+            "INVALID_OPTION",
+            format!(
+                "outcome must be an option index between 0 and {}",
+                outcome_type.options().len() - 1
+            ),
+        ));
+    }
+
+    if let Some(min) = current.min_value
+        && payload.outcome < min
+    {
+        return Err(AppError::bad_request(
+            "OUTCOME_OUT_OF_RANGE",
+            format!("outcome is below the market minimum of {}", min),
+        ));
+    }
+
+    if let Some(max) = current.max_value
+        && payload.outcome > max
+    {
+        return Err(AppError::bad_request(
+            "OUTCOME_OUT_OF_RANGE",
+            format!("outcome is above the market maximum of {}", max),
+        ));
+    }
+
+    // an override keeps what it replaces
+    let superseded = if current.status == "RESOLVED" || current.status == "PROPOSED" {
+        let revision =
+            SettlementRepo::supersede(&mut tx, market_id, &admin.actor, payload.reason.as_deref())
+                .await?;
+
+        let abandoned = OutboxRepo::abandon_unclaimed(
+            &mut *tx,
+            market_id,
+            &format!(
+                "settlement revision {} superseded",
+                revision.unwrap_or_default()
+            ),
+        )
+        .await?;
+
+        for id in &abandoned {
+            audit::record(
+                &mut *tx,
+                AuditEntry::new("outbox", *id, "abandoned", &admin.actor)
+                    .transition(Some("PENDING"), Some("ABANDONED"))
+                    .details(serde_json::json!({ "market_id": market_id, "superseded": true })),
+            )
+            .await?;
+        }
+
+        revision
+    } else {
+        None
+    };
+
+    let market = MarketRepo::force_close(&mut *tx, market_id).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market", market_id, "force_resolved", &admin.actor)
+            .transition(Some(&current.status), Some("CLOSED"))
+            .details(serde_json::json!({
+                "outcome": payload.outcome,
+                "reason": payload.reason,
+                "superseded_revision": superseded,
+            })),
+    )
+    .await?;
+
+    if !matches!(current.status.as_str(), "CLOSED" | "PROPOSED" | "RESOLVED") {
+        let closed = Event::MarketClosed {
+            market_id,
+            reason: None,
+        };
+        events::append(&mut *tx, &closed).await?;
+    }
+
+    // the market is locked and was just force-closed, so this only fails if
+    // that invariant breaks
+    if !finalize_in_tx(
+        &state,
+        &mut tx,
+        &market,
+        payload.outcome,
+        None,
+        &admin.actor,
+    )
+    .await?
+    {
+        return Err(AppError::conflict(
+            "MARKET_NOT_CLOSED",
+            "Market left CLOSED before it could settle",
+        ));
+    }
+
+    tx.commit().await?;
+    state.cache.invalidate_markets();
+    state.cache.invalidate_settlement(market_id).await;
+
+    tracing::info!(
+        "Market {} force-resolved to {} by {}",
+        market_id,
+        payload.outcome,
+        admin.actor
+    );
+
+    reload(&state, market_id).await
+}
+
+/// Soft-deletes a market that never received a report: it moves to
+/// `markets_archive` with `deleted_at` set and disappears from listings
+/// unless `include_archived` is passed.
+#[utoipa::path(
+    delete,
+    path = "/markets/{id}",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Market id")),
+    responses(
+        (status = 204, description = "Market archived"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 409, description = "Market has reports, a settlement or a group", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn delete_market(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(market_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let mut tx = state.db.begin().await?;
+
+    let market = MarketRepo::lock(&mut *tx, market_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    let has_reports = ReportRepo::count(&mut *tx, market_id).await? > 0;
+    let has_settlement = SettlementRepo::get(&mut *tx, market_id).await?.is_some();
+
+    if has_reports {
+        return Err(AppError::conflict(
+            "MARKET_HAS_REPORTS",
+            "market has reports and cannot be deleted",
+        ));
+    }
+
+    if has_settlement {
+        return Err(AppError::conflict(
+            "MARKET_FINALIZED",
+            "market has a settlement and cannot be deleted",
+        ));
+    }
+
+    if market.group_id.is_some() {
+        return Err(AppError::conflict(
+            "MARKET_IN_GROUP",
+            "market belongs to a group and cannot be deleted",
+        ));
+    }
+
+    archive_markets(
+        &mut tx,
+        &[market_id],
+        Some(Utc::now().trunc_subsecs(6)),
+        &admin.actor,
+    )
+    .await?;
+
+    tx.commit().await?;
+    state.cache.invalidate_markets();
+
+    tracing::info!("Market {} deleted by {}", market_id, admin.actor);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn reload(state: &AppState, market_id: Uuid) -> Result<Json<Market>, AppError> {
+    MarketRepo::get(
+        &state.db,
+        market_id,
+        false,
+        state.config.consensus.default_bps(),
+    )
+    .await?
+    .map(Json)
+    .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))
+}
@@ -0,0 +1,65 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+
+use crate::auth::RequireAdmin;
+use crate::error::AppError;
+use crate::state::AppState;
+use crate::types::{AuditQuery, AuditRecord};
+
+const DEFAULT_LIMIT: i64 = 500;
+
+/// State-change history, oldest first, for compliance review.
+#[utoipa::path(
+    get,
+    path = "/audit",
+    tag = "system",
+    params(AuditQuery),
+    responses(
+        (status = 200, body = Vec<AuditRecord>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn list_audit(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditRecord>>, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, DEFAULT_LIMIT);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, entity_type, entity_id, action, actor, before_state, after_state, details, created_at
+        FROM audit_log
+        WHERE ($1::UUID IS NULL OR entity_id = $1)
+          AND ($2::TEXT IS NULL OR entity_type = $2)
+        ORDER BY id ASC
+        LIMIT $3
+        "#,
+        query.entity_id,
+        query.entity_type,
+        limit
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let records = rows
+        .into_iter()
+        .map(|row| AuditRecord {
+            id: row.id,
+            entity_type: row.entity_type,
+            entity_id: row.entity_id,
+            action: row.action,
+            actor: row.actor,
+            before_state: row.before_state,
+            after_state: row.after_state,
+            details: row.details,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(Json(records))
+}
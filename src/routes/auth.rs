@@ -0,0 +1,281 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{SubsecRound, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::auth::{RequireAdmin, Role, dummy_password_hash, hash_password, verify_password};
+use crate::error::{AppError, AppJson};
+use crate::state::AppState;
+use crate::types::{CreateUserRequest, SetUserSourcesRequest, TokenRequest, TokenResponse, User};
+use crate::validation::{MAX_SOURCE_LEN, Validation};
+
+const MIN_PASSWORD_LEN: usize = 12;
+
+/// Exchanges a username and password for a JWT carrying the user's role.
+#[utoipa::path(
+    post,
+    path = "/auth/token",
+    tag = "auth",
+    request_body = TokenRequest,
+    responses(
+        (status = 200, body = TokenResponse),
+        (status = 401, description = "Unknown user, wrong password or disabled account", body = ErrorResponse),
+        (status = 503, description = "JWT_SECRET not set", body = ErrorResponse),
+    )
+)]
+pub async fn issue_token(
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<TokenRequest>,
+) -> Result<Json<TokenResponse>, AppError> {
+    let user = sqlx::query!(
+        "SELECT password_hash, role, disabled FROM users WHERE username = $1",
+        payload.username
+    )
+    .fetch_optional(&state.db)
+    .await?;
+
+    let invalid = || {
+        AppError::new(
+            StatusCode::UNAUTHORIZED,
+            "INVALID_CREDENTIALS",
+            "invalid username or password",
+        )
+    };
+
+    let user = user.filter(|u| !u.disabled);
+
+    // argon2 is deliberately slow; keep it off the async workers. Unknown
+    // and disabled users are checked against a dummy hash so they take as
+    // long to refuse as a wrong password
+    let password = payload.password;
+    let hash = user.as_ref().map(|u| u.password_hash.clone());
+    let matches = tokio::task::spawn_blocking(move || {
+        verify_password(
+            &password,
+            hash.as_deref().unwrap_or_else(|| dummy_password_hash()),
+        )
+    })
+    .await
+    .map_err(|e| AppError::internal(e.to_string()))?;
+
+    let Some(user) = user.filter(|_| matches) else {
+        return Err(invalid());
+    };
+
+    let role =
+        Role::parse(&user.role).ok_or_else(|| AppError::internal("user has an unknown role"))?;
+    let (access_token, expires_at) = state.auth.issue(&payload.username, role)?;
+
+    Ok(Json(TokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_at,
+        role,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/users",
+    tag = "auth",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 201, body = User),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 409, description = "Username or one of the sources taken", body = ErrorResponse),
+        (status = 422, description = "Invalid username or sources, or password too short", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn create_user(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    AppJson(payload): AppJson<CreateUserRequest>,
+) -> Result<(StatusCode, Json<User>), AppError> {
+    let username = payload.username.trim().to_string();
+
+    if username.is_empty() || username.len() > 64 {
+        return Err(AppError::unprocessable(
+            "INVALID_USERNAME",
+            "username must be 1-64 characters",
+        ));
+    }
+
+    if payload.password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(AppError::unprocessable(
+            "WEAK_PASSWORD",
+            format!("password must be at least {} characters", MIN_PASSWORD_LEN),
+        ));
+    }
+
+    let sources = normalize_sources(&payload.sources)?;
+
+    let password = payload.password;
+    let password_hash = tokio::task::spawn_blocking(move || hash_password(&password))
+        .await
+        .map_err(|e| AppError::internal(e.to_string()))??;
+
+    let id = Uuid::new_v4();
+    let now = Utc::now().trunc_subsecs(6);
+
+    let mut tx = state.db.begin().await?;
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO users (id, username, password_hash, role, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (username) DO NOTHING
+        "#,
+        id,
+        username,
+        password_hash,
+        payload.role.as_str(),
+        now
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if !inserted {
+        return Err(AppError::conflict(
+            "USERNAME_TAKEN",
+            "username is already taken",
+        ));
+    }
+
+    assign_sources(&mut tx, &username, &sources).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("user", id, "created", &admin.actor).details(serde_json::json!({
+            "username": username,
+            "role": payload.role,
+            "sources": sources,
+        })),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(User {
+            id,
+            username,
+            role: payload.role,
+            sources,
+            created_at: now,
+        }),
+    ))
+}
+
+/// Replaces the sources a user may report for.
+#[utoipa::path(
+    put,
+    path = "/auth/users/{username}/sources",
+    tag = "auth",
+    params(
+        ("username" = String, Path, description = "Username"),
+    ),
+    request_body = SetUserSourcesRequest,
+    responses(
+        (status = 200, body = User),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 409, description = "A source belongs to another user", body = ErrorResponse),
+        (status = 422, description = "Invalid sources", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn set_user_sources(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(username): Path<String>,
+    AppJson(payload): AppJson<SetUserSourcesRequest>,
+) -> Result<Json<User>, AppError> {
+    let sources = normalize_sources(&payload.sources)?;
+
+    let mut tx = state.db.begin().await?;
+
+    let user = sqlx::query!(
+        "SELECT id, role, created_at FROM users WHERE username = $1 FOR UPDATE",
+        username
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::not_found("USER_NOT_FOUND", "User not found"))?;
+
+    sqlx::query!("DELETE FROM user_sources WHERE username = $1", username)
+        .execute(&mut *tx)
+        .await?;
+    assign_sources(&mut tx, &username, &sources).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("user", user.id, "sources_set", &admin.actor)
+            .details(serde_json::json!({ "username": username, "sources": sources })),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let role =
+        Role::parse(&user.role).ok_or_else(|| AppError::internal("user has an unknown role"))?;
+
+    Ok(Json(User {
+        id: user.id,
+        username,
+        role,
+        sources,
+        created_at: user.created_at,
+    }))
+}
+
+// the requested sources validated, sorted and deduplicated
+fn normalize_sources(sources: &[String]) -> Result<Vec<String>, AppError> {
+    let mut validation = Validation::new();
+    for source in sources {
+        validation.text("sources", source, MAX_SOURCE_LEN);
+    }
+    validation.finish()?;
+
+    let mut sources = sources.to_vec();
+    sources.sort();
+    sources.dedup();
+    Ok(sources)
+}
+
+async fn assign_sources(
+    db: &mut PgConnection,
+    username: &str,
+    sources: &[String],
+) -> Result<(), AppError> {
+    for source in sources {
+        let assigned = sqlx::query!(
+            "INSERT INTO user_sources (username, source) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            username,
+            source
+        )
+        .execute(&mut *db)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if !assigned {
+            return Err(AppError::conflict(
+                "SOURCE_TAKEN",
+                format!("source {} belongs to another user", source),
+            ));
+        }
+    }
+
+    Ok(())
+}
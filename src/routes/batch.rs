@@ -0,0 +1,223 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::auth::RequireAdmin;
+use crate::batcher;
+use crate::error::AppError;
+use crate::proof::HashAlgorithm;
+use crate::repo::{BatchRepo, OutboxRepo};
+use crate::state::AppState;
+use crate::types::{BatchDetail, BatchItem, BatchQuery, BatchSummary};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+#[utoipa::path(
+    get,
+    path = "/batches",
+    tag = "batches",
+    params(BatchQuery),
+    responses((status = 200, body = Vec<BatchSummary>))
+)]
+pub async fn list_batches(
+    State(state): State<AppState>,
+    Query(query): Query<BatchQuery>,
+) -> Result<Json<Vec<BatchSummary>>, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    Ok(Json(BatchRepo::list(&state.db, None, limit, offset).await?))
+}
+
+/// Batches every unbatched settlement now, ignoring the min size and wait
+/// policy. Returns the batches created, oldest settlements first.
+#[utoipa::path(
+    post,
+    path = "/batches/flush",
+    tag = "batches",
+    responses(
+        (status = 200, body = Vec<BatchSummary>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn flush_batches(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+) -> Result<Json<Vec<BatchSummary>>, AppError> {
+    Ok(Json(batcher::flush(&state, true, &admin.actor).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/batches/{id}",
+    tag = "batches",
+    params(("id" = Uuid, Path, description = "Batch id")),
+    responses(
+        (status = 200, body = BatchDetail),
+        (status = 404, description = "Batch not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_batch(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BatchDetail>, AppError> {
+    let batch = sqlx::query!(
+        "SELECT id, merkle_root, hash_algorithm, status, voided_at, created_at FROM batches WHERE id = $1",
+        id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::not_found("BATCH_NOT_FOUND", "Batch not found"))?;
+
+    // leaf order; (decided_at, market_id) is how the batcher assigned it
+    let rows = sqlx::query!(
+        r#"
+        SELECT s.market_id, s.outcome, s.outcome_e8, s.decided_at,
+               bi.leaf_index, bi.leaf_hash, bi.leaf_data,
+               o.status AS "outbox_status?",
+               c.tx_hash AS "tx_hash?"
+        FROM batch_items bi
+        JOIN settlements s ON s.market_id = bi.market_id
+        LEFT JOIN LATERAL (
+            SELECT status FROM outbox
+            WHERE market_id = bi.market_id
+            ORDER BY created_at DESC
+            LIMIT 1
+        ) o ON true
+        LEFT JOIN LATERAL (
+            SELECT tx_hash FROM chain_submissions
+            WHERE market_id = bi.market_id
+            ORDER BY created_at DESC
+            LIMIT 1
+        ) c ON true
+        WHERE bi.batch_id = $1
+        ORDER BY bi.leaf_index ASC, s.decided_at ASC, s.market_id ASC
+        "#,
+        id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let items: Vec<BatchItem> = rows
+        .into_iter()
+        .map(|row| BatchItem {
+            market_id: row.market_id,
+            outcome: row.outcome,
+            outcome_e8: row.outcome_e8,
+            decided_at: row.decided_at,
+            leaf_index: row.leaf_index,
+            leaf_hash: row.leaf_hash,
+            leaf_data: row.leaf_data,
+            outbox_status: row.outbox_status,
+            tx_hash: row.tx_hash,
+        })
+        .collect();
+
+    let anchored = items.iter().filter(|i| i.tx_hash.is_some()).count();
+    let chain_status = match anchored {
+        0 => "PENDING",
+        n if n == items.len() => "ANCHORED",
+        _ => "PARTIAL",
+    }
+    .to_string();
+
+    Ok(Json(BatchDetail {
+        id: batch.id,
+        merkle_root: batch.merkle_root,
+        hash_algorithm: HashAlgorithm::parse(&batch.hash_algorithm).unwrap_or_default(),
+        created_at: batch.created_at,
+        status: batch.status,
+        voided_at: batch.voided_at,
+        chain_status,
+        items,
+    }))
+}
+
+/// Recovery for a FAILED batch: releases its settlements so the batcher
+/// puts them in a new batch, and gives their failed outbox jobs a fresh
+/// retry budget. The voided batch stays listed.
+#[utoipa::path(
+    post,
+    path = "/batches/{id}/void",
+    tag = "batches",
+    params(("id" = Uuid, Path, description = "Batch id")),
+    responses(
+        (status = 200, body = BatchDetail),
+        (status = 404, description = "Batch not found", body = ErrorResponse),
+        (status = 409, description = "Batch is not FAILED, or already voided", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn void_batch(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BatchDetail>, AppError> {
+    let mut tx = state.db.begin().await?;
+
+    let batch = BatchRepo::lock(&mut *tx, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("BATCH_NOT_FOUND", "Batch not found"))?;
+
+    if batch.voided_at.is_some() {
+        return Err(AppError::conflict(
+            "BATCH_VOIDED",
+            "Batch is already voided",
+        ));
+    }
+    if batch.status != "FAILED" {
+        return Err(AppError::conflict(
+            "BATCH_NOT_FAILED",
+            format!(
+                "Only FAILED batches can be voided (status is {})",
+                batch.status
+            ),
+        ));
+    }
+
+    let market_ids = BatchRepo::void(&mut tx, id, Utc::now()).await?;
+    let retried = OutboxRepo::latest_failed(&mut *tx, &market_ids).await?;
+
+    for job_id in &retried {
+        OutboxRepo::retry(&mut *tx, *job_id).await?;
+
+        audit::record(
+            &mut *tx,
+            AuditEntry::new("outbox", *job_id, "retried", &admin.actor)
+                .transition(Some("FAILED"), Some("PENDING"))
+                .details(serde_json::json!({ "voided_batch": id })),
+        )
+        .await?;
+    }
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("batch", id, "voided", &admin.actor).details(serde_json::json!({
+            "market_ids": market_ids,
+            "retried_jobs": retried,
+        })),
+    )
+    .await?;
+
+    tx.commit().await?;
+    state.cache.invalidate_settlements(&market_ids).await;
+
+    tracing::info!(
+        "Batch {} voided by {}; {} settlement(s) released, {} job(s) retried",
+        id,
+        admin.actor,
+        market_ids.len(),
+        retried.len()
+    );
+
+    get_batch(State(state), Path(id)).await
+}
@@ -0,0 +1,14 @@
+use axum::{Json, extract::State};
+
+use crate::eth::chains::ChainSummary;
+use crate::state::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/chains",
+    tag = "chains",
+    responses((status = 200, body = Vec<ChainSummary>))
+)]
+pub async fn list_chains(State(state): State<AppState>) -> Json<Vec<ChainSummary>> {
+    Json(state.chains.summaries())
+}
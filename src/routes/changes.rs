@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+
+use crate::error::AppError;
+use crate::state::AppState;
+use crate::types::{ChangeEvent, ChangesPage, ChangesQuery};
+
+const MAX_WAIT: Duration = Duration::from_secs(60);
+const PAGE_SIZE: i64 = 100;
+
+/// Long-poll over dispatched events: returns immediately if anything newer
+/// than `cursor` exists, otherwise holds the request open for up to `wait`.
+#[utoipa::path(
+    get,
+    path = "/changes",
+    tag = "events",
+    params(ChangesQuery),
+    responses(
+        (status = 200, body = ChangesPage),
+        (status = 400, description = "Invalid wait", body = ErrorResponse),
+    )
+)]
+pub async fn get_changes(
+    State(state): State<AppState>,
+    Query(query): Query<ChangesQuery>,
+) -> Result<Json<ChangesPage>, AppError> {
+    let cursor = query.cursor.unwrap_or(0);
+    let wait = match query.wait.as_deref() {
+        Some(raw) => parse_wait(raw)
+            .ok_or_else(|| AppError::bad_request("INVALID_WAIT", format!("invalid wait: {}", raw)))?
+            .min(MAX_WAIT),
+        None => Duration::ZERO,
+    };
+
+    // subscribe before the first read so an insert in between still wakes us
+    let mut recorded = state.events.watch_recorded();
+
+    let mut events = fetch_changes(&state, cursor).await?;
+
+    if events.is_empty() && !wait.is_zero() {
+        let woke = tokio::time::timeout(wait, recorded.wait_for(|id| *id > cursor))
+            .await
+            .is_ok_and(|r| r.is_ok());
+        if woke {
+            events = fetch_changes(&state, cursor).await?;
+        }
+    }
+
+    let next_cursor = events.last().map(|e| e.id).unwrap_or(cursor);
+
+    Ok(Json(ChangesPage {
+        events,
+        next_cursor,
+    }))
+}
+
+async fn fetch_changes(state: &AppState, cursor: i64) -> Result<Vec<ChangeEvent>, AppError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, kind, payload, created_at
+        FROM events
+        WHERE id > $1 AND dispatched_at IS NOT NULL
+        ORDER BY id ASC
+        LIMIT $2
+        "#,
+        cursor,
+        PAGE_SIZE
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ChangeEvent {
+            id: row.id,
+            kind: row.kind,
+            payload: row.payload,
+            created_at: row.created_at,
+        })
+        .collect())
+}
+
+fn parse_wait(raw: &str) -> Option<Duration> {
+    if let Some(ms) = raw.strip_suffix("ms") {
+        return ms.parse().ok().map(Duration::from_millis);
+    }
+
+    let secs = raw.strip_suffix('s').unwrap_or(raw);
+    secs.parse().ok().map(Duration::from_secs)
+}
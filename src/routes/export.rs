@@ -0,0 +1,174 @@
+use std::io;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use futures_util::{StreamExt, stream};
+use tokio::sync::mpsc;
+
+use crate::error::AppError;
+use crate::proof::{HashAlgorithm, settlement_leaf};
+use crate::state::AppState;
+use crate::types::{SettlementExportQuery, SettlementExportRow};
+
+// rows buffered between the query and a slow client
+const CHANNEL_ROWS: usize = 256;
+
+const CSV_HEADER: &str = "market_id,outcome,outcome_e8,decided_at,leaf,batch_id,merkle_root,tx_hash,block_number,hash_algorithm\n";
+
+#[derive(Clone, Copy)]
+enum Format {
+    Csv,
+    Jsonl,
+}
+
+/// Every settlement decided in `[from, to]`, oldest first, with its batch and
+/// latest chain submission. Rows are streamed straight from the cursor so an
+/// export never sits in memory whole.
+#[utoipa::path(
+    get,
+    path = "/settlements/export",
+    tag = "settlements",
+    params(SettlementExportQuery),
+    responses(
+        (status = 200, description = "CSV with a header row, or one JSON object per line",
+         body = SettlementExportRow, content_type = ["text/csv", "application/x-ndjson"]),
+        (status = 400, description = "Unknown format or from after to", body = ErrorResponse),
+    )
+)]
+pub async fn export_settlements(
+    State(state): State<AppState>,
+    Query(query): Query<SettlementExportQuery>,
+) -> Result<Response, AppError> {
+    let (format, content_type, extension) = match query.format.as_deref().unwrap_or("csv") {
+        "csv" => (Format::Csv, "text/csv", "csv"),
+        "jsonl" => (Format::Jsonl, "application/x-ndjson", "jsonl"),
+        other => {
+            return Err(AppError::bad_request(
+                "INVALID_FORMAT",
+                format!("unknown format {} (expected csv or jsonl)", other),
+            ));
+        }
+    };
+
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return Err(AppError::bad_request(
+            "INVALID_RANGE",
+            "from must not be after to",
+        ));
+    }
+
+    let (tx, rx) = mpsc::channel::<Result<Bytes, io::Error>>(CHANNEL_ROWS);
+
+    tokio::spawn(async move {
+        if let Format::Csv = format
+            && tx
+                .send(Ok(Bytes::from_static(CSV_HEADER.as_bytes())))
+                .await
+                .is_err()
+        {
+            return;
+        }
+
+        let mut rows = sqlx::query!(
+            r#"
+            SELECT s.market_id, s.outcome, s.outcome_e8, s.decided_at, s.hash_algorithm,
+                   bi.batch_id AS "batch_id?",
+                   b.merkle_root AS "merkle_root?",
+                   c.tx_hash AS "tx_hash?",
+                   c.block_number AS "block_number?"
+            FROM settlements s
+            LEFT JOIN (
+                batch_items bi JOIN batches b ON b.id = bi.batch_id AND b.voided_at IS NULL
+            ) ON bi.market_id = s.market_id
+            LEFT JOIN LATERAL (
+                SELECT tx_hash, block_number FROM chain_submissions
+                WHERE market_id = s.market_id
+                ORDER BY created_at DESC
+                LIMIT 1
+            ) c ON true
+            WHERE ($1::TIMESTAMPTZ IS NULL OR s.decided_at >= $1)
+              AND ($2::TIMESTAMPTZ IS NULL OR s.decided_at <= $2)
+            ORDER BY s.decided_at ASC, s.market_id ASC
+            "#,
+            query.from,
+            query.to
+        )
+        .fetch(&state.db);
+
+        while let Some(row) = rows.next().await {
+            let chunk = row.map_err(io::Error::other).map(|r| {
+                let hash_algorithm = HashAlgorithm::parse(&r.hash_algorithm).unwrap_or_default();
+                let row = SettlementExportRow {
+                    market_id: r.market_id,
+                    outcome: r.outcome,
+                    outcome_e8: r.outcome_e8,
+                    decided_at: r.decided_at,
+                    leaf: hex::encode(settlement_leaf(
+                        hash_algorithm,
+                        r.market_id,
+                        r.outcome_e8,
+                        r.decided_at,
+                    )),
+                    batch_id: r.batch_id,
+                    merkle_root: r.merkle_root,
+                    tx_hash: r.tx_hash,
+                    block_number: r.block_number,
+                    hash_algorithm,
+                };
+                Bytes::from(render(&row, format))
+            });
+
+            let failed = chunk.is_err();
+            // the client went away
+            if tx.send(chunk).await.is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    // a mid-stream error aborts the body so the client sees a truncated export
+    let body = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|c| (c, rx)) });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"settlements.{}\"", extension),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+// every field is a uuid, number, hex, timestamp or algorithm name, so CSV
+// needs no quoting
+fn render(row: &SettlementExportRow, format: Format) -> String {
+    match format {
+        Format::Jsonl => {
+            let mut line = serde_json::to_string(row).unwrap();
+            line.push('\n');
+            line
+        }
+        Format::Csv => format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            row.market_id,
+            row.outcome,
+            row.outcome_e8,
+            row.decided_at.to_rfc3339(),
+            row.leaf,
+            row.batch_id.map(|b| b.to_string()).unwrap_or_default(),
+            row.merkle_root.as_deref().unwrap_or_default(),
+            row.tx_hash.as_deref().unwrap_or_default(),
+            row.block_number.map(|b| b.to_string()).unwrap_or_default(),
+            row.hash_algorithm.as_str(),
+        ),
+    }
+}
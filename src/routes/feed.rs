@@ -0,0 +1,162 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{SubsecRound, Utc};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::auth::RequireAdmin;
+use crate::error::{AppError, AppJson};
+use crate::outcome_type::OutcomeType;
+use crate::state::AppState;
+use crate::types::{CreateFeedRequest, MarketFeed};
+
+const DEFAULT_INTERVAL_SECS: i32 = 60;
+const MIN_INTERVAL_SECS: i32 = 10;
+
+/// Attaches an external price feed; the feeds loop polls it while the
+/// market is OPEN and stores each value as a self-reported report.
+#[utoipa::path(
+    post,
+    path = "/markets/{id}/feeds",
+    tag = "feeds",
+    params(("id" = Uuid, Path, description = "Market id")),
+    request_body = CreateFeedRequest,
+    responses(
+        (status = 201, body = MarketFeed),
+        (status = 400, description = "Invalid source or interval, or market closed", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn create_feed(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(market_id): Path<Uuid>,
+    AppJson(payload): AppJson<CreateFeedRequest>,
+) -> Result<(StatusCode, Json<MarketFeed>), AppError> {
+    payload
+        .source
+        .validate()
+        .map_err(|e| AppError::bad_request("INVALID_FEED_SOURCE", e))?;
+
+    let interval_secs = payload.interval_secs.unwrap_or(DEFAULT_INTERVAL_SECS);
+    if interval_secs < MIN_INTERVAL_SECS {
+        return Err(AppError::bad_request(
+            "INVALID_INTERVAL",
+            format!("interval_secs must be at least {}", MIN_INTERVAL_SECS),
+        ));
+    }
+
+    let market = sqlx::query!(
+        "SELECT status, outcome_type FROM markets WHERE id = $1",
+        market_id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    if market.status != "OPEN" && market.status != "SCHEDULED" {
+        return Err(AppError::bad_request("MARKET_CLOSED", "Market is closed"));
+    }
+
+    let outcome_type: OutcomeType = serde_json::from_value(market.outcome_type).unwrap_or_default();
+    if outcome_type.is_discrete() {
+        return Err(AppError::bad_request(
+            "INVALID_OUTCOME_TYPE",
+            "feeds only apply to NUMERIC markets",
+        ));
+    }
+
+    let id = Uuid::new_v4();
+    let now = Utc::now().trunc_subsecs(6);
+
+    let mut tx = state.db.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO market_feeds (id, market_id, source, interval_secs, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(id)
+    .bind(market_id)
+    .bind(serde_json::to_value(&payload.source).unwrap())
+    .bind(interval_secs)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market_feed", id, "created", &admin.actor).details(serde_json::json!({
+            "market_id": market_id,
+            "source": payload.source.name(),
+            "interval_secs": interval_secs,
+        })),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(MarketFeed {
+            id,
+            market_id,
+            source: payload.source,
+            interval_secs,
+            last_fetched_at: None,
+            last_value: None,
+            last_error: None,
+            created_at: now,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/markets/{id}/feeds",
+    tag = "feeds",
+    params(("id" = Uuid, Path, description = "Market id")),
+    responses((status = 200, body = Vec<MarketFeed>))
+)]
+pub async fn list_feeds(
+    State(state): State<AppState>,
+    Path(market_id): Path<Uuid>,
+) -> Result<Json<Vec<MarketFeed>>, AppError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, market_id, source, interval_secs, last_fetched_at, last_value, last_error,
+               created_at
+        FROM market_feeds
+        WHERE market_id = $1
+        ORDER BY created_at ASC
+        "#,
+        market_id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let feeds = rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(MarketFeed {
+                id: row.id,
+                market_id: row.market_id,
+                source: serde_json::from_value(row.source).ok()?,
+                interval_secs: row.interval_secs,
+                last_fetched_at: row.last_fetched_at,
+                last_value: row.last_value,
+                last_error: row.last_error,
+                created_at: row.created_at,
+            })
+        })
+        .collect();
+
+    Ok(Json(feeds))
+}
@@ -0,0 +1,562 @@
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    BatchRequest, BatchResponse, Context, EmptyMutation, EmptySubscription, ErrorExtensions,
+    InputObject, Json as JsonValue, Object, Schema,
+};
+use axum::{Extension, Json, extract::State, response::Html};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::close_condition::CloseCondition;
+use crate::error::AppError;
+use crate::outcome_type::OutcomeType;
+use crate::payout::{Payout, PayoutFormula};
+use crate::repo::{BatchRepo, MarketFilter, MarketRepo, ReportRepo, SettlementRepo};
+use crate::resolution::{MarketRequirements, SettlementInputs, Strategy};
+use crate::state::AppState;
+use crate::types::{
+    BatchSummary, ChainSubmission, InclusionProof, Market, Provenance, Report, ReportFlag,
+    SettlementRevision, SettlementView,
+};
+
+use super::settlement::load_settlement;
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 200;
+
+// nested fields each cost a query per parent, so both are bounded
+const MAX_DEPTH: usize = 8;
+const MAX_COMPLEXITY: usize = 500;
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Built once per router; each request carries the state it reads from.
+pub fn schema() -> ApiSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+/// Read-only queries over markets, reports, settlements and batches, nested
+/// as far as a dashboard needs in one request. Accepts a single query or a
+/// batch of them.
+pub async fn execute(
+    State(state): State<AppState>,
+    Extension(schema): Extension<ApiSchema>,
+    Json(request): Json<BatchRequest>,
+) -> Json<BatchResponse> {
+    Json(schema.execute_batch(request.data(state)).await)
+}
+
+/// GraphiQL, for exploring the schema from a browser.
+pub async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+/// The same `code` REST clients branch on, under `extensions`.
+fn api_error(e: AppError) -> async_graphql::Error {
+    let code = e.code;
+    async_graphql::Error::new(e.message).extend_with(|_, ext| ext.set("code", code))
+}
+
+fn db_error(e: sqlx::Error) -> async_graphql::Error {
+    api_error(e.into())
+}
+
+fn page<T>(items: Vec<T>, limit: Option<usize>, offset: Option<usize>) -> Vec<T> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    items
+        .into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit)
+        .collect()
+}
+
+async fn settlement(
+    state: &AppState,
+    market_id: Uuid,
+) -> async_graphql::Result<Option<SettlementNode>> {
+    let settlement = state
+        .cache
+        .settlement(market_id, load_settlement(state, market_id))
+        .await
+        .map_err(api_error)?;
+    Ok(Option::clone(&settlement).map(SettlementNode))
+}
+
+async fn market(state: &AppState, id: Uuid) -> async_graphql::Result<Option<MarketNode>> {
+    let market = MarketRepo::get(&state.db, id, true, state.config.consensus.default_bps())
+        .await
+        .map_err(db_error)?;
+    Ok(market.map(MarketNode))
+}
+
+#[derive(InputObject, Default)]
+pub struct MarketFilterInput {
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    pub status: Option<String>,
+    /// Also match archived and deleted markets.
+    #[graphql(default)]
+    pub include_archived: bool,
+}
+
+pub struct QueryRoot;
+
+#[Object(name = "Query")]
+impl QueryRoot {
+    /// Newest first.
+    async fn markets(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: MarketFilterInput,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<Vec<MarketNode>> {
+        let state = ctx.data::<AppState>()?;
+        let filter = MarketFilter {
+            category: filter.category.map(|c| c.to_lowercase()),
+            tag: filter.tag,
+            status: filter.status.map(|s| s.to_uppercase()),
+            id: None,
+            include_archived: filter.include_archived,
+        };
+
+        let markets = state
+            .cache
+            .markets(
+                &filter,
+                MarketRepo::list(&state.db, &filter, state.config.consensus.default_bps()),
+            )
+            .await
+            .map_err(db_error)?;
+
+        Ok(page(Vec::clone(&markets), limit, offset)
+            .into_iter()
+            .map(MarketNode)
+            .collect())
+    }
+
+    /// Archived markets included.
+    async fn market(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+    ) -> async_graphql::Result<Option<MarketNode>> {
+        market(ctx.data::<AppState>()?, id).await
+    }
+
+    /// `None` until the market has resolved.
+    async fn settlement(
+        &self,
+        ctx: &Context<'_>,
+        market_id: Uuid,
+    ) -> async_graphql::Result<Option<SettlementNode>> {
+        settlement(ctx.data::<AppState>()?, market_id).await
+    }
+
+    /// Newest first; `status` is PENDING, SUBMITTED, CONFIRMED or FAILED.
+    async fn batches(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<Vec<BatchNode>> {
+        let state = ctx.data::<AppState>()?;
+        let status = status.map(|s| s.to_uppercase());
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as i64;
+        let offset = offset.unwrap_or(0) as i64;
+
+        let batches = BatchRepo::list(&state.db, status.as_deref(), limit, offset)
+            .await
+            .map_err(db_error)?;
+
+        Ok(batches.into_iter().map(BatchNode).collect())
+    }
+
+    async fn batch(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<BatchNode>> {
+        let state = ctx.data::<AppState>()?;
+        Ok(BatchRepo::summary(&state.db, id)
+            .await
+            .map_err(db_error)?
+            .map(BatchNode))
+    }
+}
+
+pub struct MarketNode(Market);
+
+#[Object(name = "Market")]
+impl MarketNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn question(&self) -> &str {
+        &self.0.question
+    }
+
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+
+    async fn opens_at(&self) -> Option<DateTime<Utc>> {
+        self.0.opens_at
+    }
+
+    async fn reporting_opens_at(&self) -> Option<DateTime<Utc>> {
+        self.0.reporting_opens_at
+    }
+
+    /// Null on a market that closes on an event, until it is closed.
+    async fn closes_at(&self) -> Option<DateTime<Utc>> {
+        self.0.closes_at
+    }
+
+    async fn resolve_deadline(&self) -> Option<DateTime<Utc>> {
+        self.0.resolve_deadline
+    }
+
+    async fn closed_reason(&self) -> Option<&str> {
+        self.0.closed_reason.as_deref()
+    }
+
+    /// As in the REST API: `{"kind": "SCALAR"}` and the like.
+    async fn outcome_type(&self) -> JsonValue<&OutcomeType> {
+        JsonValue(&self.0.outcome_type)
+    }
+
+    async fn value_type(&self) -> &str {
+        self.0.value_type.as_str()
+    }
+
+    async fn min_value(&self) -> Option<f64> {
+        self.0.min_value
+    }
+
+    async fn max_value(&self) -> Option<f64> {
+        self.0.max_value
+    }
+
+    async fn resolution(&self) -> JsonValue<&Strategy> {
+        JsonValue(&self.0.resolution)
+    }
+
+    async fn category(&self) -> Option<&str> {
+        self.0.category.as_deref()
+    }
+
+    async fn tags(&self) -> &[String] {
+        &self.0.tags
+    }
+
+    async fn chain_id(&self) -> Option<u64> {
+        self.0.chain_id
+    }
+
+    async fn anchor_on_chain(&self) -> bool {
+        self.0.anchor_on_chain
+    }
+
+    async fn close_conditions(&self) -> JsonValue<&[CloseCondition]> {
+        JsonValue(&self.0.close_conditions)
+    }
+
+    async fn requirements(&self) -> Option<JsonValue<&MarketRequirements>> {
+        self.0.requirements.as_ref().map(JsonValue)
+    }
+
+    async fn consensus_bps(&self) -> u32 {
+        self.0.consensus_bps
+    }
+
+    async fn priority(&self) -> i32 {
+        self.0.priority
+    }
+
+    async fn decimals(&self) -> Option<u32> {
+        self.0.decimals
+    }
+
+    async fn payout(&self) -> Option<JsonValue<&PayoutFormula>> {
+        self.0.payout.as_ref().map(JsonValue)
+    }
+
+    async fn group_id(&self) -> Option<Uuid> {
+        self.0.group_id
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn archived_at(&self) -> Option<DateTime<Utc>> {
+        self.0.archived_at
+    }
+
+    /// Oldest first, archived included.
+    async fn reports(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] include_retracted: bool,
+        #[graphql(default)] flagged_only: bool,
+        source: Option<String>,
+    ) -> async_graphql::Result<Vec<ReportNode>> {
+        let state = ctx.data::<AppState>()?;
+        let reports = ReportRepo::list(&state.db, self.0.id, true, include_retracted, flagged_only)
+            .await
+            .map_err(db_error)?;
+
+        Ok(reports
+            .into_iter()
+            .filter(|r| source.as_ref().is_none_or(|s| &r.source == s))
+            .map(ReportNode)
+            .collect())
+    }
+
+    async fn report_count(&self, ctx: &Context<'_>) -> async_graphql::Result<i64> {
+        let state = ctx.data::<AppState>()?;
+        ReportRepo::count(&state.db, self.0.id)
+            .await
+            .map_err(db_error)
+    }
+
+    async fn settlement(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<SettlementNode>> {
+        settlement(ctx.data::<AppState>()?, self.0.id).await
+    }
+
+    /// The batch holding the market's settlement; voided batches hold nothing.
+    async fn batch(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<BatchNode>> {
+        let state = ctx.data::<AppState>()?;
+        let Some(batch) = BatchRepo::for_market(&state.db, self.0.id)
+            .await
+            .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+        Ok(BatchRepo::summary(&state.db, batch.id)
+            .await
+            .map_err(db_error)?
+            .map(BatchNode))
+    }
+}
+
+pub struct ReportNode(Report);
+
+#[Object(name = "Report")]
+impl ReportNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn market_id(&self) -> Uuid {
+        self.0.market_id
+    }
+
+    async fn source(&self) -> &str {
+        &self.0.source
+    }
+
+    async fn value(&self) -> f64 {
+        self.0.value
+    }
+
+    async fn self_reported(&self) -> bool {
+        self.0.self_reported
+    }
+
+    async fn provenance(&self) -> Option<JsonValue<&Provenance>> {
+        self.0.provenance.as_ref().map(JsonValue)
+    }
+
+    async fn confidence(&self) -> Option<f64> {
+        self.0.confidence
+    }
+
+    async fn stake(&self) -> Option<f64> {
+        self.0.stake
+    }
+
+    async fn reporter_address(&self) -> Option<&str> {
+        self.0.reporter_address.as_deref()
+    }
+
+    async fn verified(&self) -> bool {
+        self.0.verified
+    }
+
+    async fn late(&self) -> bool {
+        self.0.late
+    }
+
+    /// Weight in the settled outcome; only on a settlement's reports.
+    async fn weight(&self) -> Option<f64> {
+        self.0.weight
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.0.updated_at
+    }
+
+    async fn retracted_at(&self) -> Option<DateTime<Utc>> {
+        self.0.retracted_at
+    }
+
+    async fn market(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<MarketNode>> {
+        market(ctx.data::<AppState>()?, self.0.market_id).await
+    }
+}
+
+pub struct SettlementNode(SettlementView);
+
+#[Object(name = "Settlement")]
+impl SettlementNode {
+    async fn market_id(&self) -> Uuid {
+        self.0.market_id
+    }
+
+    async fn outcome(&self) -> f64 {
+        self.0.outcome
+    }
+
+    /// outcome * 10^8, exact; a string since it can outgrow a GraphQL Int.
+    async fn outcome_e8(&self) -> String {
+        self.0.outcome_e8.to_string()
+    }
+
+    async fn winning_option(&self) -> Option<&str> {
+        self.0.winning_option.as_deref()
+    }
+
+    async fn decided_at(&self) -> DateTime<Utc> {
+        self.0.decided_at
+    }
+
+    async fn revision(&self) -> i32 {
+        self.0.revision
+    }
+
+    /// PROPOSED or FINAL.
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+
+    async fn finalized_at(&self) -> Option<DateTime<Utc>> {
+        self.0.finalized_at
+    }
+
+    async fn hash(&self) -> &str {
+        &self.0.hash
+    }
+
+    async fn hash_version(&self) -> u8 {
+        self.0.hash_version
+    }
+
+    async fn hash_algorithm(&self) -> &str {
+        self.0.hash_algorithm.as_str()
+    }
+
+    async fn decimals(&self) -> u32 {
+        self.0.decimals
+    }
+
+    async fn leaf(&self) -> &str {
+        &self.0.leaf
+    }
+
+    /// The reports it was settled from, with their weights.
+    async fn reports(&self) -> Vec<ReportNode> {
+        self.0.reports.iter().cloned().map(ReportNode).collect()
+    }
+
+    async fn excluded(&self) -> JsonValue<&[ReportFlag]> {
+        JsonValue(&self.0.excluded)
+    }
+
+    async fn inputs(&self) -> Option<JsonValue<&SettlementInputs>> {
+        self.0.inputs.as_ref().map(JsonValue)
+    }
+
+    async fn inclusion(&self) -> Option<JsonValue<&InclusionProof>> {
+        self.0.inclusion.as_ref().map(JsonValue)
+    }
+
+    async fn chain(&self) -> Option<JsonValue<&ChainSubmission>> {
+        self.0.chain.as_ref().map(JsonValue)
+    }
+
+    /// Each side's share of the payout, when the market defines a formula.
+    async fn payout(&self) -> Option<JsonValue<&Payout>> {
+        self.0.payout.as_ref().map(JsonValue)
+    }
+
+    /// Every revision, oldest first; the live one last.
+    async fn history(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<JsonValue<Vec<SettlementRevision>>> {
+        let state = ctx.data::<AppState>()?;
+        Ok(JsonValue(
+            SettlementRepo::history(&state.db, self.0.market_id)
+                .await
+                .map_err(db_error)?,
+        ))
+    }
+
+    async fn market(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<MarketNode>> {
+        market(ctx.data::<AppState>()?, self.0.market_id).await
+    }
+}
+
+pub struct BatchNode(BatchSummary);
+
+#[Object(name = "Batch")]
+impl BatchNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn merkle_root(&self) -> &str {
+        &self.0.merkle_root
+    }
+
+    async fn hash_algorithm(&self) -> &str {
+        self.0.hash_algorithm.as_str()
+    }
+
+    async fn size(&self) -> i64 {
+        self.0.size
+    }
+
+    /// PENDING, SUBMITTED, CONFIRMED or FAILED.
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+
+    async fn voided_at(&self) -> Option<DateTime<Utc>> {
+        self.0.voided_at
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    /// In leaf order.
+    async fn markets(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<MarketNode>> {
+        let state = ctx.data::<AppState>()?;
+        let mut markets = Vec::new();
+        for id in BatchRepo::market_ids(&state.db, self.0.id)
+            .await
+            .map_err(db_error)?
+        {
+            markets.extend(market(state, id).await?);
+        }
+        Ok(markets)
+    }
+}
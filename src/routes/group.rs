@@ -0,0 +1,165 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{SubsecRound, Utc};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::auth::RequireAdmin;
+use crate::error::{AppError, AppJson};
+use crate::state::AppState;
+use crate::types::{CreateMarketGroupRequest, MarketGroup};
+
+/// Links markets so the resolver settles them together (or not at all).
+#[utoipa::path(
+    post,
+    path = "/market-groups",
+    tag = "groups",
+    request_body = CreateMarketGroupRequest,
+    responses(
+        (status = 201, body = MarketGroup),
+        (status = 400, description = "Invalid members or invariant", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn create_market_group(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    AppJson(payload): AppJson<CreateMarketGroupRequest>,
+) -> Result<(StatusCode, Json<MarketGroup>), AppError> {
+    let mut market_ids = payload.market_ids.clone();
+    market_ids.sort();
+    market_ids.dedup();
+
+    if market_ids.len() < 2 {
+        return Err(AppError::bad_request(
+            "INVALID_GROUP",
+            "a market group needs at least two distinct markets",
+        ));
+    }
+
+    payload
+        .invariant
+        .validate()
+        .map_err(|e| AppError::bad_request("INVALID_INVARIANT", e))?;
+
+    let id = Uuid::new_v4();
+    let now = Utc::now().trunc_subsecs(6);
+
+    let mut tx = state.db.begin().await?;
+
+    let markets = sqlx::query!(
+        "SELECT id, status, group_id FROM markets WHERE id = ANY($1) FOR UPDATE",
+        &market_ids
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    if markets.len() != market_ids.len() {
+        return Err(AppError::not_found("MARKET_NOT_FOUND", "Market not found"));
+    }
+
+    if let Some(m) = markets.iter().find(|m| m.group_id.is_some()) {
+        return Err(AppError::conflict(
+            "MARKET_IN_GROUP",
+            format!("market {} already belongs to a group", m.id),
+        ));
+    }
+
+    if let Some(m) = markets
+        .iter()
+        .find(|m| m.status == "PROPOSED" || m.status == "RESOLVED")
+    {
+        return Err(AppError::conflict(
+            "MARKET_FINALIZED",
+            format!("market {} is already resolved", m.id),
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO market_groups (id, invariant, status, created_at)
+        VALUES ($1, $2, 'ACTIVE', $3)
+        "#,
+    )
+    .bind(id)
+    .bind(serde_json::to_value(&payload.invariant).unwrap())
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE markets SET group_id = $1 WHERE id = ANY($2)")
+        .bind(id)
+        .bind(&market_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market_group", id, "created", &admin.actor)
+            .transition(None, Some("ACTIVE"))
+            .details(serde_json::json!({ "market_ids": market_ids })),
+    )
+    .await?;
+
+    tx.commit().await?;
+    state.cache.invalidate_markets();
+
+    Ok((
+        StatusCode::CREATED,
+        Json(MarketGroup {
+            id,
+            invariant: payload.invariant,
+            status: "ACTIVE".to_string(),
+            blocked_reason: None,
+            market_ids,
+            created_at: now,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/market-groups/{id}",
+    tag = "groups",
+    params(("id" = Uuid, Path, description = "Group id")),
+    responses(
+        (status = 200, body = MarketGroup),
+        (status = 404, description = "Group not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_market_group(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MarketGroup>, AppError> {
+    let group = sqlx::query!(
+        r#"
+        SELECT g.id, g.invariant, g.status, g.blocked_reason, g.created_at,
+               ARRAY(
+                 SELECT m.id FROM markets m WHERE m.group_id = g.id
+                 UNION ALL
+                 SELECT a.id FROM markets_archive a WHERE a.group_id = g.id
+                 ORDER BY 1
+               ) AS "market_ids!"
+        FROM market_groups g
+        WHERE g.id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::not_found("GROUP_NOT_FOUND", "Market group not found"))?;
+
+    Ok(Json(MarketGroup {
+        id: group.id,
+        invariant: serde_json::from_value(group.invariant).unwrap_or_default(),
+        status: group.status,
+        blocked_reason: group.blocked_reason,
+        market_ids: group.market_ids,
+        created_at: group.created_at,
+    }))
+}
@@ -0,0 +1,66 @@
+use axum::{Json, extract::State};
+
+use crate::auth::RequireAdmin;
+use crate::error::AppError;
+use crate::loops::LoopStatus;
+use crate::state::AppState;
+use crate::types::{QueueDepths, SystemJobs};
+
+#[utoipa::path(
+    get,
+    path = "/admin/loops",
+    tag = "system",
+    responses(
+        (status = 200, body = Vec<LoopStatus>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn list_loops(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+) -> Json<Vec<LoopStatus>> {
+    Json(state.loops.snapshot())
+}
+
+/// The background loops alongside the work still waiting on them.
+#[utoipa::path(
+    get,
+    path = "/system/jobs",
+    tag = "system",
+    responses(
+        (status = 200, body = SystemJobs),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn system_jobs(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+) -> Result<Json<SystemJobs>, AppError> {
+    let queues = sqlx::query_as!(
+        QueueDepths,
+        r#"
+        SELECT
+          (SELECT COUNT(*) FROM markets WHERE status = 'CLOSED') AS "unresolved_markets!",
+          (
+            SELECT COUNT(*) FROM settlements s
+            JOIN markets m ON m.id = s.market_id
+            WHERE m.anchor_on_chain AND s.batch_id IS NULL
+          ) AS "unbatched_settlements!",
+          (SELECT COUNT(*) FROM outbox WHERE status IN ('PENDING', 'INTENT')) AS "pending_outbox!",
+          (SELECT COUNT(*) FROM settlements WHERE status = 'PROPOSED') AS "proposed_settlements!",
+          (SELECT COUNT(*) FROM batches WHERE status = 'FAILED' AND voided_at IS NULL) AS "failed_batches!"
+        "#
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(SystemJobs {
+        leader: state.leader.is_leader(),
+        loops: state.loops.snapshot(),
+        queues,
+    }))
+}
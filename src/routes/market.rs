@@ -1,62 +1,693 @@
-use axum::{extract::State, Json};
-use chrono::Utc;
-use sqlx::Row;
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+};
+use chrono::{SubsecRound, Utc};
 use uuid::Uuid;
 
+use crate::audit::{self, AuditEntry};
+use crate::auth::RequireAdmin;
+use crate::error::{AppError, AppJson};
+use crate::events::{self, Event};
+use crate::proof::{FIXED_POINT_DECIMALS, from_fixed};
+use crate::repo::{MarketFilter, MarketRepo, NewMarket, ReportRepo};
+use crate::resolution::{self, MarketRequirements, SelfReportPolicy, Strategy};
+use crate::resolver::{evaluate, load_held_values, load_source_values, outcome_fixed};
+use crate::routes::settlement::load_settlement;
 use crate::state::AppState;
-use crate::types::{CreateMarketRequest, Market};
+use crate::types::{
+    ArchivedQuery, CreateMarketRequest, Market, MarketDetail, MarketOutcomeFormat, MarketQuery,
+    MarketSettings, ResolutionPreview, ResolutionStatus,
+};
+use crate::validation::{MAX_IDEMPOTENCY_KEY_LEN, MAX_QUESTION_LEN, Validation};
+use crate::value_type::ValueType;
 
+// a 100% spread; anything wider accepts any set of reports
+const MAX_CONSENSUS_BPS: u32 = 10_000;
+
+#[utoipa::path(
+    post,
+    path = "/markets",
+    tag = "markets",
+    request_body = CreateMarketRequest,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Replays return the original market")),
+    responses(
+        (status = 201, body = Market),
+        (status = 200, description = "Existing market for the idempotency key", body = Market),
+        (status = 400, description = "Invalid market definition", body = ErrorResponse),
+        (status = 422, description = "VALIDATION_FAILED, with each offending field in details.errors", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
 pub async fn create_market(
     State(state): State<AppState>,
-    Json(payload): Json<CreateMarketRequest>,
-) -> Result<&'static str, (axum::http::StatusCode, String)> {
+    admin: RequireAdmin,
+    headers: HeaderMap,
+    AppJson(payload): AppJson<CreateMarketRequest>,
+) -> Result<(StatusCode, Json<Market>), AppError> {
+    let idempotency_key = match headers.get("idempotency-key") {
+        Some(v) => Some(
+            v.to_str()
+                .map_err(|_| {
+                    AppError::bad_request(
+                        "INVALID_IDEMPOTENCY_KEY",
+                        "Idempotency-Key must be visible ASCII",
+                    )
+                })?
+                .to_string(),
+        ),
+        None => payload.idempotency_key.clone(),
+    }
+    .filter(|k| !k.is_empty());
+
+    let (status, market) = insert_market(&state, &admin.actor, payload, idempotency_key).await?;
+
+    Ok((status, Json(market)))
+}
+
+/// Validates and stores a new market. A market already created under
+/// `idempotency_key` comes back with 200 instead.
+pub(crate) async fn insert_market(
+    state: &AppState,
+    actor: &str,
+    payload: CreateMarketRequest,
+    idempotency_key: Option<String>,
+) -> Result<(StatusCode, Market), AppError> {
     let id = Uuid::new_v4();
-    let now = Utc::now();
-
-    let closes_at = chrono::DateTime::parse_from_rfc3339(&payload.closes_at)
-        .map_err(|e| (axum::http::StatusCode::BAD_REQUEST, e.to_string()))?
-        .with_timezone(&Utc);
-
-    sqlx::query(
-        r#"
-        INSERT INTO markets (id, question, closes_at, status, created_at)
-        VALUES ($1, $2, $3, $4, $5)
-        "#,
-    )
-    .bind(id)
-    .bind(&payload.question)
-    .bind(closes_at)
-    .bind("OPEN")
-    .bind(now)
-    .execute(&state.db)
-    .await
-    .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-    Ok("Market created")
-}
-
-pub async fn list_markets(State(state): State<AppState>) -> Json<Vec<Market>> {
-    let rows = sqlx::query!(
-        r#"
-        SELECT id, question, closes_at, status, created_at
-        FROM markets
-        ORDER BY created_at DESC
-        "#
-    )
-    .fetch_all(&state.db)
-    .await
-    .unwrap();
-
-    let markets = rows
-        .into_iter()
-        .map(|row| Market {
-            id: row.id,
-            question: row.question,
-            closes_at: row.closes_at,
-            status: row.status,
-            created_at: row.created_at,
-        })
+    // microseconds, as Postgres stores it, so the response matches later reads
+    let now = Utc::now().trunc_subsecs(6);
+
+    if let Some(key) = &idempotency_key
+        && let Some(existing) = market_for_key(state, key).await?
+    {
+        return Ok((StatusCode::OK, existing));
+    }
+
+    // None: the market closes on an event, through POST /markets/{id}/close
+    let closes_at = match &payload.closes_at {
+        Some(raw) => Some(
+            chrono::DateTime::parse_from_rfc3339(raw)
+                .map_err(|e| {
+                    AppError::bad_request("INVALID_TIMESTAMP", format!("closes_at: {}", e))
+                })?
+                .with_timezone(&Utc),
+        ),
+        None => None,
+    };
+
+    let mut validation = Validation::new();
+    validation
+        .text("question", &payload.question, MAX_QUESTION_LEN)
+        .check(
+            closes_at.is_none_or(|c| c > now),
+            "closes_at",
+            "IN_PAST",
+            "closes_at must be in the future",
+        );
+    if let Some(key) = &idempotency_key {
+        validation.check(
+            key.chars().count() <= MAX_IDEMPOTENCY_KEY_LEN,
+            "idempotency_key",
+            "TOO_LONG",
+            format!(
+                "idempotency_key must be at most {} characters",
+                MAX_IDEMPOTENCY_KEY_LEN
+            ),
+        );
+    }
+    validation.finish()?;
+
+    let opens_at = match &payload.opens_at {
+        Some(raw) => Some(
+            chrono::DateTime::parse_from_rfc3339(raw)
+                .map_err(|e| {
+                    AppError::bad_request("INVALID_TIMESTAMP", format!("opens_at: {}", e))
+                })?
+                .with_timezone(&Utc),
+        ),
+        None => None,
+    };
+
+    let reporting_opens_at = match &payload.reporting_opens_at {
+        Some(raw) => Some(
+            chrono::DateTime::parse_from_rfc3339(raw)
+                .map_err(|e| {
+                    AppError::bad_request("INVALID_TIMESTAMP", format!("reporting_opens_at: {}", e))
+                })?
+                .with_timezone(&Utc),
+        ),
+        None => None,
+    };
+
+    // an event-closed market without one gets it when it closes
+    let resolve_deadline = match &payload.resolve_deadline {
+        Some(raw) => Some(
+            chrono::DateTime::parse_from_rfc3339(raw)
+                .map_err(|e| {
+                    AppError::bad_request("INVALID_TIMESTAMP", format!("resolve_deadline: {}", e))
+                })?
+                .with_timezone(&Utc),
+        ),
+        None => closes_at.map(|c| c + state.config.resolver.resolve_window()),
+    };
+
+    // an event-closed market can close any time after it opens
+    let earliest_close = closes_at.or(opens_at).unwrap_or(now);
+
+    if resolve_deadline.is_some_and(|d| d <= earliest_close) {
+        return Err(AppError::bad_request(
+            "INVALID_DEADLINE",
+            "resolve_deadline must be after closes_at",
+        ));
+    }
+
+    if let (Some(opens_at), Some(closes_at)) = (opens_at, closes_at)
+        && opens_at >= closes_at
+    {
+        return Err(AppError::bad_request(
+            "INVALID_OPENS_AT",
+            "opens_at must be before closes_at",
+        ));
+    }
+
+    if reporting_opens_at
+        .is_some_and(|r| closes_at.is_some_and(|c| r >= c) || opens_at.is_some_and(|o| r < o))
+    {
+        return Err(AppError::bad_request(
+            "INVALID_REPORTING_OPENS_AT",
+            "reporting_opens_at must be between opens_at and closes_at",
+        ));
+    }
+
+    let requirements = validate_settings(state, &payload.settings)?;
+    let settings = payload.settings;
+
+    let status = if opens_at.is_some_and(|o| o > now) {
+        "SCHEDULED"
+    } else {
+        "OPEN"
+    };
+
+    let outcome_type = serde_json::to_value(&settings.outcome_type).unwrap();
+    let resolution = serde_json::to_value(&settings.resolution).unwrap();
+    let close_conditions = serde_json::to_value(&settings.close_conditions).unwrap();
+    let self_report_policy = serde_json::to_value(&settings.self_report_policy).unwrap();
+    let late_report_policy = serde_json::to_value(&settings.late_report_policy).unwrap();
+    let payout = settings
+        .payout
+        .as_ref()
+        .map(|p| serde_json::to_value(p).unwrap());
+
+    let category = settings
+        .category
+        .as_deref()
+        .map(|c| c.trim().to_lowercase())
+        .filter(|c| !c.is_empty());
+
+    let mut tags: Vec<String> = settings
+        .tags
+        .iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
         .collect();
+    tags.sort();
+    tags.dedup();
+
+    let mut tx = state.db.begin().await?;
+
+    let inserted = MarketRepo::insert(
+        &mut tx,
+        &NewMarket {
+            id,
+            question: &payload.question,
+            opens_at,
+            reporting_opens_at,
+            closes_at,
+            resolve_deadline,
+            status,
+            outcome_type,
+            value_type: settings.value_type.as_str(),
+            min_value: settings.min_value,
+            max_value: settings.max_value,
+            resolution,
+            self_report_policy,
+            late_report_policy,
+            category: category.as_deref(),
+            tags: &tags,
+            chain_id: settings.chain_id.map(|c| c as i64),
+            anchor_on_chain: settings.anchor_on_chain.unwrap_or(true),
+            close_notice_secs: settings.close_notice_secs.as_deref(),
+            close_conditions,
+            requirements: requirements.as_ref(),
+            consensus_bps: settings.consensus_bps.map(|bps| bps as i32),
+            decimals: settings.decimals.map(|d| d as i16),
+            payout,
+            idempotency_key: idempotency_key.as_deref(),
+            created_at: now,
+        },
+    )
+    .await;
+
+    if let Err(e) = inserted {
+        // a concurrent retry with the same key won the insert
+        if let Some(db_err) = e.as_database_error()
+            && db_err.code().as_deref() == Some("23505")
+            && let Some(key) = &idempotency_key
+            && let Some(existing) = market_for_key(state, key).await?
+        {
+            return Ok((StatusCode::OK, existing));
+        }
+        return Err(e.into());
+    }
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market", id, "created", actor)
+            .transition(None, Some(status))
+            .details(serde_json::json!({ "question": payload.question, "closes_at": closes_at })),
+    )
+    .await?;
+
+    let event = Event::MarketCreated {
+        market_id: id,
+        question: payload.question.clone(),
+        closes_at,
+    };
+    events::append(&mut *tx, &event).await?;
+
+    tx.commit().await?;
+    state.cache.invalidate_markets();
+
+    let market = Market {
+        id,
+        question: payload.question,
+        opens_at,
+        reporting_opens_at,
+        closes_at,
+        resolve_deadline,
+        status: status.to_string(),
+        outcome_type: settings.outcome_type,
+        value_type: settings.value_type,
+        min_value: settings.min_value,
+        max_value: settings.max_value,
+        resolution: settings.resolution,
+        self_report_policy: settings.self_report_policy,
+        late_report_policy: settings.late_report_policy,
+        category,
+        tags,
+        chain_id: settings.chain_id,
+        anchor_on_chain: settings.anchor_on_chain.unwrap_or(true),
+        close_notice_secs: settings.close_notice_secs,
+        close_conditions: settings.close_conditions,
+        close_trigger: None,
+        closed_reason: None,
+        requirements,
+        consensus_bps: settings
+            .consensus_bps
+            .unwrap_or_else(|| state.config.consensus.default_bps()),
+        priority: 0,
+        decimals: settings.decimals,
+        payout: settings.payout,
+        group_id: None,
+        created_at: now,
+        archived_at: None,
+    };
+
+    Ok((StatusCode::CREATED, market))
+}
+
+/// Checks the parts of a market definition that don't depend on its
+/// schedule. Returns the normalized requirements.
+pub(crate) fn validate_settings(
+    state: &AppState,
+    settings: &MarketSettings,
+) -> Result<Option<MarketRequirements>, AppError> {
+    settings
+        .outcome_type
+        .validate()
+        .map_err(|e| AppError::bad_request("INVALID_OUTCOME_TYPE", e))?;
+
+    if settings.outcome_type.is_discrete()
+        && (settings.value_type != ValueType::Number
+            || settings.min_value.is_some()
+            || settings.max_value.is_some())
+    {
+        return Err(AppError::bad_request(
+            "INVALID_OUTCOME_TYPE",
+            "value_type and min_value/max_value only apply to NUMERIC markets",
+        ));
+    }
+
+    if settings.min_value.is_some_and(|v| !v.is_finite())
+        || settings.max_value.is_some_and(|v| !v.is_finite())
+    {
+        return Err(AppError::bad_request(
+            "INVALID_RANGE",
+            "min_value/max_value must be finite",
+        ));
+    }
+
+    if let (Some(min), Some(max)) = (settings.min_value, settings.max_value)
+        && min > max
+    {
+        return Err(AppError::bad_request(
+            "INVALID_RANGE",
+            "min_value must not exceed max_value",
+        ));
+    }
+
+    if let Strategy::AgreementMatrix {
+        min_pairs,
+        tolerance,
+    } = settings.resolution
+        && (min_pairs == 0 || !tolerance.is_finite() || tolerance < 0.0)
+    {
+        return Err(AppError::bad_request(
+            "INVALID_STRATEGY",
+            "AGREEMENT_MATRIX needs min_pairs >= 1 and a non-negative tolerance",
+        ));
+    }
+
+    if let Strategy::ConfidenceWeighted { min_reports: 0 } = settings.resolution {
+        return Err(AppError::bad_request(
+            "INVALID_STRATEGY",
+            "CONFIDENCE_WEIGHTED needs min_reports >= 1",
+        ));
+    }
+
+    if let Strategy::Twap { min_reports } = settings.resolution {
+        if min_reports == 0 {
+            return Err(AppError::bad_request(
+                "INVALID_STRATEGY",
+                "TWAP needs min_reports >= 1",
+            ));
+        }
+        if settings.outcome_type.is_discrete() {
+            return Err(AppError::bad_request(
+                "INVALID_STRATEGY",
+                "TWAP only applies to NUMERIC markets",
+            ));
+        }
+    }
+
+    if settings.decimals.is_some_and(|d| d > FIXED_POINT_DECIMALS) {
+        return Err(AppError::bad_request(
+            "INVALID_DECIMALS",
+            format!("decimals must be at most {}", FIXED_POINT_DECIMALS),
+        ));
+    }
+
+    if settings
+        .consensus_bps
+        .is_some_and(|bps| bps > MAX_CONSENSUS_BPS)
+    {
+        return Err(AppError::bad_request(
+            "INVALID_CONSENSUS",
+            format!("consensus_bps must be at most {}", MAX_CONSENSUS_BPS),
+        ));
+    }
+
+    if let SelfReportPolicy::DownWeight { weight } = settings.self_report_policy
+        && !(0.0..=1.0).contains(&weight)
+    {
+        return Err(AppError::bad_request(
+            "INVALID_SELF_REPORT_POLICY",
+            "DOWN_WEIGHT weight must be between 0 and 1",
+        ));
+    }
+
+    if let Some(chain_id) = settings.chain_id
+        && !state.has_chain(chain_id)
+    {
+        return Err(AppError::bad_request(
+            "UNKNOWN_CHAIN",
+            format!("chain {} is not configured", chain_id),
+        ));
+    }
+
+    if settings.chain_id.is_some() && settings.anchor_on_chain == Some(false) {
+        return Err(AppError::bad_request(
+            "INVALID_CHAIN",
+            "chain_id can't be set on a market that isn't anchored on chain",
+        ));
+    }
+
+    if settings
+        .close_notice_secs
+        .as_ref()
+        .is_some_and(|secs| secs.iter().any(|s| *s <= 0))
+    {
+        return Err(AppError::bad_request(
+            "INVALID_CLOSE_NOTICE",
+            "close_notice_secs must be positive",
+        ));
+    }
 
-    Json(markets)
-}
\ No newline at end of file
+    if let Some(payout) = &settings.payout {
+        if settings.outcome_type.is_discrete() {
+            return Err(AppError::bad_request(
+                "INVALID_PAYOUT",
+                "payout formulas only apply to NUMERIC markets",
+            ));
+        }
+        payout
+            .validate()
+            .map_err(|e| AppError::bad_request("INVALID_PAYOUT", e))?;
+    }
+
+    for condition in &settings.close_conditions {
+        condition
+            .validate()
+            .map_err(|e| AppError::bad_request("INVALID_CLOSE_CONDITION", e))?;
+    }
+
+    let requirements = settings
+        .requirements
+        .clone()
+        .map(|r| r.normalize())
+        .transpose()
+        .map_err(|e| AppError::bad_request("INVALID_REQUIREMENTS", e))?
+        .filter(|r| !r.is_empty());
+
+    Ok(requirements)
+}
+
+/// Market previously created under `key`, for idempotent retries.
+async fn market_for_key(state: &AppState, key: &str) -> Result<Option<Market>, AppError> {
+    let Some(id) = MarketRepo::id_for_idempotency_key(&state.db, key).await? else {
+        return Ok(None);
+    };
+
+    Ok(MarketRepo::get(&state.db, id, false, state.config.consensus.default_bps()).await?)
+}
+
+/// One market with its report count, a dry run of the resolver over its
+/// current reports and, once resolved, its settlement.
+#[utoipa::path(
+    get,
+    path = "/markets/{id}",
+    tag = "markets",
+    params(("id" = Uuid, Path, description = "Market id"), ArchivedQuery),
+    responses(
+        (status = 200, body = MarketDetail),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_market(
+    State(state): State<AppState>,
+    Path(market_id): Path<Uuid>,
+    Query(query): Query<ArchivedQuery>,
+) -> Result<Json<MarketDetail>, AppError> {
+    let include_archived = query.include_archived.unwrap_or(false);
+
+    let market = MarketRepo::get(
+        &state.db,
+        market_id,
+        include_archived,
+        state.config.consensus.default_bps(),
+    )
+    .await?
+    .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    let report_count = ReportRepo::count(&state.db, market_id).await?;
+
+    let settlement = load_settlement(&state, market_id).await?;
+
+    let preview = match settlement {
+        Some(_) => None,
+        None => Some(preview_resolution(&state, &market).await?),
+    };
+
+    Ok(Json(MarketDetail {
+        market,
+        report_count,
+        preview,
+        settlement,
+    }))
+}
+
+async fn preview_resolution(
+    state: &AppState,
+    market: &Market,
+) -> Result<ResolutionPreview, sqlx::Error> {
+    let consensus = state
+        .config
+        .consensus
+        .for_market(Some(market.consensus_bps as i32));
+    let mut reports = load_source_values(
+        state,
+        market.id,
+        market.min_value,
+        market.max_value,
+        &market.late_report_policy,
+    )
+    .await?;
+    if matches!(market.resolution, Strategy::Twap { .. }) {
+        let opens_at = market
+            .reporting_opens_at
+            .or(market.opens_at)
+            .unwrap_or(market.created_at);
+        // an open market's window so far
+        let closes_at = market.closes_at.map_or(Utc::now(), |c| c.min(Utc::now()));
+        load_held_values(state, market.id, opens_at, closes_at, &mut reports).await?;
+    }
+
+    let evaluation = evaluate(
+        &market.resolution,
+        &market.self_report_policy,
+        &consensus,
+        &market.outcome_type,
+        market.requirements.as_ref(),
+        reports,
+    );
+
+    let spread = if market.outcome_type.is_discrete() {
+        None
+    } else {
+        let values: Vec<f64> = evaluation.counted.iter().map(|r| r.value).collect();
+        resolution::relative_spread(&values)
+    };
+
+    Ok(ResolutionPreview {
+        counted: evaluation.counted.len(),
+        spread,
+        requirements_met: evaluation.requirements_met,
+        would_resolve: evaluation.outcome.is_some(),
+        // as the resolver would record it
+        outcome: evaluation
+            .outcome
+            .map(|o| from_fixed(outcome_fixed(o, &market.outcome_type, market.decimals))),
+        winning_option: evaluation
+            .outcome
+            .and_then(|o| market.outcome_type.option_label(o)),
+        outliers: evaluation.outliers.iter().map(|o| o.report_id).collect(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/markets",
+    tag = "markets",
+    params(MarketQuery),
+    responses((status = 200, body = Vec<Market>))
+)]
+pub async fn list_markets(
+    State(state): State<AppState>,
+    Query(query): Query<MarketQuery>,
+) -> Result<Json<Vec<Market>>, AppError> {
+    let filter = MarketFilter {
+        category: query.category.map(|c| c.to_lowercase()),
+        tag: query.tag,
+        status: query.status.map(|s| s.to_uppercase()),
+        id: None,
+        include_archived: query.include_archived.unwrap_or(false),
+    };
+
+    let markets = state
+        .cache
+        .markets(
+            &filter,
+            MarketRepo::list(&state.db, &filter, state.config.consensus.default_bps()),
+        )
+        .await?;
+
+    Ok(Json(Vec::clone(&markets)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/markets/{id}/outcome-format",
+    tag = "markets",
+    params(("id" = Uuid, Path, description = "Market id")),
+    responses(
+        (status = 200, body = MarketOutcomeFormat),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_outcome_format(
+    State(state): State<AppState>,
+    Path(market_id): Path<Uuid>,
+) -> Result<Json<MarketOutcomeFormat>, AppError> {
+    let (value_type, decimals) = MarketRepo::value_format(&state.db, market_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    let value_type = ValueType::parse(&value_type).unwrap_or_default();
+
+    Ok(Json(MarketOutcomeFormat {
+        market_id,
+        format: value_type.format_at(decimals.map(|d| d.max(0) as u32)),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/markets/{id}/resolution-status",
+    tag = "markets",
+    params(("id" = Uuid, Path, description = "Market id")),
+    responses(
+        (status = 200, body = ResolutionStatus),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_resolution_status(
+    State(state): State<AppState>,
+    Path(market_id): Path<Uuid>,
+) -> Result<Json<ResolutionStatus>, AppError> {
+    let market = MarketRepo::get(
+        &state.db,
+        market_id,
+        false,
+        state.config.consensus.default_bps(),
+    )
+    .await?
+    .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    let reports = load_source_values(
+        &state,
+        market_id,
+        market.min_value,
+        market.max_value,
+        &market.late_report_policy,
+    )
+    .await?;
+    let requirements = market.requirements;
+
+    let unmet = requirements
+        .as_ref()
+        .map(|r| r.unmet(&market.resolution, &market.self_report_policy, &reports))
+        .unwrap_or_default();
+
+    Ok(Json(ResolutionStatus {
+        market_id,
+        status: market.status,
+        reports: reports.len(),
+        requirements,
+        requirements_met: unmet.is_empty(),
+        unmet,
+    }))
+}
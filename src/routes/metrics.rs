@@ -0,0 +1,93 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use chrono::{Duration, Utc};
+
+use crate::auth::RequireAdmin;
+use crate::error::AppError;
+use crate::state::AppState;
+use crate::types::{CacheStats, MetricsHistoryQuery, MetricsSnapshot};
+
+#[utoipa::path(
+    get,
+    path = "/admin/metrics/history",
+    tag = "system",
+    params(MetricsHistoryQuery),
+    responses(
+        (status = 200, body = Vec<MetricsSnapshot>),
+        (status = 400, description = "Invalid window", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn metrics_history(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Query(query): Query<MetricsHistoryQuery>,
+) -> Result<Json<Vec<MetricsSnapshot>>, AppError> {
+    let window = match query.window.as_deref() {
+        Some(raw) => parse_window(raw).ok_or_else(|| {
+            AppError::bad_request("INVALID_WINDOW", format!("invalid window: {}", raw))
+        })?,
+        None => Duration::days(30),
+    };
+
+    let since = Utc::now() - window;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT captured_at, reports_24h, settlements_24h, batches_24h, avg_batch_size, db_size_bytes
+        FROM metrics_history
+        WHERE captured_at >= $1
+        ORDER BY captured_at ASC
+        "#,
+        since
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let snapshots = rows
+        .into_iter()
+        .map(|row| MetricsSnapshot {
+            captured_at: row.captured_at,
+            reports_24h: row.reports_24h,
+            settlements_24h: row.settlements_24h,
+            batches_24h: row.batches_24h,
+            avg_batch_size: row.avg_batch_size,
+            db_size_bytes: row.db_size_bytes,
+        })
+        .collect();
+
+    Ok(Json(snapshots))
+}
+
+/// Hit counts of this process's read caches since it started.
+#[utoipa::path(
+    get,
+    path = "/admin/metrics/cache",
+    tag = "system",
+    responses(
+        (status = 200, body = Vec<CacheStats>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn cache_stats(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+) -> Json<Vec<CacheStats>> {
+    Json(state.cache.stats().await)
+}
+
+fn parse_window(raw: &str) -> Option<Duration> {
+    if let Some(days) = raw.strip_suffix('d') {
+        return days.parse().ok().and_then(Duration::try_days);
+    }
+    if let Some(hours) = raw.strip_suffix('h') {
+        return hours.parse().ok().and_then(Duration::try_hours);
+    }
+    None
+}
@@ -1,33 +1,206 @@
+#[cfg(feature = "webhooks")]
+use axum::routing::delete;
 use axum::{
-    routing::{get, post},
-    Router,
+    Router, middleware,
+    routing::{get, post, put},
 };
+use tower_http::LatencyUnit;
 use tower_http::cors::{Any, CorsLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::{DefaultOnResponse, TraceLayer};
+use tracing::Level;
+use utoipa_swagger_ui::SwaggerUi;
 
+use crate::rate_limit;
+use crate::request_id::{self, REQUEST_ID_HEADER};
 use crate::state::AppState;
 
+pub mod admin;
+pub mod audit;
+pub mod auth;
+pub mod batch;
+#[cfg(feature = "eth")]
+pub mod chains;
+pub mod changes;
+pub mod export;
+#[cfg(feature = "feeds")]
+pub mod feed;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod group;
+pub mod loops;
 pub mod market;
+pub mod metrics;
+pub mod openapi;
+pub mod outbox;
 pub mod report;
 pub mod settlement;
+#[cfg(feature = "eth")]
+pub mod snapshot;
+pub mod source;
+pub mod template;
+pub mod verify;
+#[cfg(feature = "eth")]
+pub mod wallet;
+#[cfg(feature = "webhooks")]
+pub mod webhook;
+pub mod ws;
 
 pub fn router(state: AppState) -> Router {
-    Router::new()
+    let router = Router::new()
         .route("/health", get(health))
-        .route("/markets", post(market::create_market).get(market::list_markets))
+        .route("/auth/token", post(auth::issue_token))
+        .route("/auth/users", post(auth::create_user))
+        .route("/auth/users/:username/sources", put(auth::set_user_sources))
+        .route(
+            "/markets",
+            post(market::create_market)
+                .layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    rate_limit::limit_markets,
+                ))
+                .get(market::list_markets),
+        )
+        .route("/market-groups", post(group::create_market_group))
+        .route("/market-groups/:id", get(group::get_market_group))
         .route(
             "/markets/:id/reports",
-            post(report::create_report).get(report::list_reports),
+            post(report::create_report)
+                .layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    rate_limit::limit_reports,
+                ))
+                .get(report::list_reports),
+        )
+        .route(
+            "/markets/:id/reports/aggregate",
+            get(report::aggregate_reports),
+        )
+        .route("/markets/:id/reports/stream", get(report::stream_reports))
+        .route(
+            "/markets/:id/reports/:report_id",
+            put(report::update_report).delete(report::retract_report),
         )
         .route("/markets/:id/settlement", get(settlement::get_settlement))
+        .route(
+            "/markets/:id/settlement/history",
+            get(settlement::get_settlement_history),
+        )
+        .route("/settlements", get(settlement::list_settlements))
+        .route("/settlements/export", get(export::export_settlements))
+        .route("/sources/:name/alerts", get(source::list_source_alerts))
+        .route(
+            "/markets/:id/outcome-format",
+            get(market::get_outcome_format),
+        )
+        .route(
+            "/markets/:id/resolution-status",
+            get(market::get_resolution_status),
+        )
+        .route(
+            "/markets/:id",
+            get(market::get_market).delete(admin::delete_market),
+        )
+        .route("/markets/:id/cancel", post(admin::cancel_market))
+        .route("/markets/:id/close", post(admin::close_market))
+        .route("/markets/:id/extend", post(admin::extend_market))
+        .route("/markets/:id/priority", post(admin::set_market_priority))
+        .route(
+            "/markets/:id/force-resolve",
+            post(admin::force_resolve_market),
+        )
+        .route(
+            "/templates",
+            post(template::create_template).get(template::list_templates),
+        )
+        .route(
+            "/templates/:id",
+            get(template::get_template).delete(template::deactivate_template),
+        )
+        .route("/admin/loops", get(loops::list_loops))
+        .route("/system/jobs", get(loops::system_jobs))
+        .route("/admin/metrics/history", get(metrics::metrics_history))
+        .route("/admin/metrics/cache", get(metrics::cache_stats))
+        .route("/audit", get(audit::list_audit))
+        .route("/batches", get(batch::list_batches))
+        .route("/batches/flush", post(batch::flush_batches))
+        .route("/batches/:id", get(batch::get_batch))
+        .route("/batches/:id/void", post(batch::void_batch))
+        .route("/changes", get(changes::get_changes))
+        .route("/outbox", get(outbox::list_outbox))
+        .route(
+            "/outbox/:id",
+            get(outbox::get_outbox_job).delete(outbox::abandon_outbox_job),
+        )
+        .route("/outbox/:id/retry", post(outbox::retry_outbox_job))
+        .route("/outbox/:id/priority", post(outbox::set_outbox_priority))
+        .route("/verify", post(verify::verify_settlement_payload))
+        .route("/verify/settlements", post(verify::verify_settlements))
+        .route("/ws", get(ws::ws_handler))
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", openapi::spec()));
+
+    #[cfg(feature = "eth")]
+    let router = router
+        .route("/chains", get(chains::list_chains))
+        .route("/wallets", get(wallet::list_wallets))
+        .route("/outbox/:id/estimate", get(outbox::estimate_outbox_job))
+        .route("/markets/:id/snapshot", get(snapshot::get_market_snapshot));
+
+    #[cfg(feature = "feeds")]
+    let router = router.route(
+        "/markets/:id/feeds",
+        post(feed::create_feed).get(feed::list_feeds),
+    );
+
+    #[cfg(feature = "webhooks")]
+    let router = router
+        .route(
+            "/webhooks",
+            post(webhook::create_webhook).get(webhook::list_webhooks),
+        )
+        .route("/webhooks/:id", delete(webhook::delete_webhook))
+        .route("/webhooks/:id/deliveries", get(webhook::list_deliveries));
+
+    #[cfg(feature = "graphql")]
+    let router = router.route(
+        "/graphql",
+        get(graphql::graphiql)
+            .post(graphql::execute)
+            .layer(axum::Extension(graphql::schema())),
+    );
+
+    // each layer wraps those above it, so requests pass through them bottom
+    // up: the id is settled first, then the span opens around the rest
+    router
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
                 .allow_methods(Any)
-                .allow_headers(Any),
+                .allow_headers(Any)
+                .expose_headers([REQUEST_ID_HEADER]),
+        )
+        .layer(middleware::from_fn(request_id::scope))
+        .layer(PropagateRequestIdLayer::new(REQUEST_ID_HEADER))
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(request_id::make_span)
+                .on_response(
+                    DefaultOnResponse::new()
+                        .level(Level::INFO)
+                        .latency_unit(LatencyUnit::Millis),
+                ),
         )
+        .layer(SetRequestIdLayer::new(REQUEST_ID_HEADER, MakeRequestUuid))
+        .layer(middleware::from_fn(request_id::sanitize))
         .with_state(state)
 }
 
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "system",
+    responses((status = 200, body = String))
+)]
 async fn health() -> &'static str {
     "OK"
-}
\ No newline at end of file
+}
@@ -0,0 +1,250 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::auth::Role;
+use crate::close_condition::CloseCondition;
+use crate::error::ErrorResponse;
+use crate::linked::GroupInvariant;
+use crate::loops::LoopStatus;
+use crate::outcome_type::OutcomeType;
+use crate::payout::{Payout, PayoutFormula, PayoutSide};
+use crate::proof::HashAlgorithm;
+use crate::resolution::{
+    HeldValue, LateReportPolicy, MarketRequirements, SelfReportPolicy, SettlementInputs, Strategy,
+    UnmetRequirement,
+};
+use crate::types::*;
+use crate::validation::FieldError;
+use crate::value_type::{DisplayHints, OutcomeFormat, ValueType};
+
+use super::{
+    admin, audit, auth, batch, changes, export, group, loops, market, metrics, outbox, report,
+};
+use super::{settlement, source, template, verify, ws};
+
+/// Served at `/openapi.json` and browsable at `/docs`.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "OracleSettle API"),
+    paths(
+        super::health,
+        auth::issue_token,
+        auth::create_user,
+        auth::set_user_sources,
+        market::create_market,
+        market::list_markets,
+        market::get_market,
+        market::get_outcome_format,
+        market::get_resolution_status,
+        report::create_report,
+        report::update_report,
+        report::retract_report,
+        report::list_reports,
+        report::aggregate_reports,
+        report::stream_reports,
+        settlement::get_settlement,
+        settlement::get_settlement_history,
+        settlement::list_settlements,
+        export::export_settlements,
+        verify::verify_settlements,
+        verify::verify_settlement_payload,
+        source::list_source_alerts,
+        group::create_market_group,
+        group::get_market_group,
+        admin::delete_market,
+        admin::cancel_market,
+        admin::close_market,
+        admin::extend_market,
+        admin::set_market_priority,
+        admin::force_resolve_market,
+        template::create_template,
+        template::list_templates,
+        template::get_template,
+        template::deactivate_template,
+        batch::list_batches,
+        batch::flush_batches,
+        batch::get_batch,
+        batch::void_batch,
+        outbox::list_outbox,
+        outbox::get_outbox_job,
+        outbox::retry_outbox_job,
+        outbox::set_outbox_priority,
+        outbox::abandon_outbox_job,
+        changes::get_changes,
+        ws::ws_handler,
+        audit::list_audit,
+        loops::list_loops,
+        loops::system_jobs,
+        metrics::metrics_history,
+        metrics::cache_stats,
+    ),
+    components(schemas(
+        TokenRequest,
+        TokenResponse,
+        CreateUserRequest,
+        SetUserSourcesRequest,
+        User,
+        Role,
+        Market,
+        CreateMarketRequest,
+        MarketSettings,
+        MarketTemplate,
+        CreateTemplateRequest,
+        MarketOutcomeFormat,
+        ResolutionStatus,
+        MarketDetail,
+        ResolutionPreview,
+        MarketRequirements,
+        UnmetRequirement,
+        OutcomeFormat,
+        DisplayHints,
+        ValueType,
+        OutcomeType,
+        HashAlgorithm,
+        Strategy,
+        SelfReportPolicy,
+        LateReportPolicy,
+        CloseCondition,
+        PayoutFormula,
+        Report,
+        ReportBucket,
+        UpdateReportRequest,
+        CreateReportRequest,
+        Provenance,
+        SettlementView,
+        SettlementInputs,
+        HeldValue,
+        SettlementRevision,
+        ReportFlag,
+        SourceAlert,
+        ChainSubmission,
+        Payout,
+        PayoutSide,
+        SettlementExportRow,
+        SettlementListItem,
+        SettlementPage,
+        VerifySettlementsRequest,
+        VerifySettlementsSummary,
+        SettlementVerdict,
+        VerificationChecks,
+        VerifyPayloadRequest,
+        PayloadReport,
+        PayloadChecks,
+        PayloadVerdict,
+        InclusionProof,
+        MarketGroup,
+        CreateMarketGroupRequest,
+        GroupInvariant,
+        CancelMarketRequest,
+        CloseMarketRequest,
+        ExtendMarketRequest,
+        SetPriorityRequest,
+        ForceResolveRequest,
+        BatchSummary,
+        BatchDetail,
+        BatchItem,
+        OutboxJob,
+        ChangesPage,
+        ChangeEvent,
+        AuditRecord,
+        LoopStatus,
+        SystemJobs,
+        QueueDepths,
+        MetricsSnapshot,
+        CacheStats,
+        ErrorResponse,
+        FieldError,
+    )),
+    modifiers(&BearerToken),
+    tags(
+        (name = "auth", description = "Protected routes need `Authorization: Bearer <token>` from `/auth/token`"),
+        (name = "markets"),
+        (name = "reports"),
+        (name = "settlements"),
+        (name = "sources", description = "Reporting patterns flagged by the anomaly pass"),
+        (name = "groups"),
+        (name = "templates", description = "Recurring markets created on a schedule"),
+        (name = "admin", description = "Needs an admin token or `ADMIN_TOKEN`"),
+        (name = "batches"),
+        (name = "outbox"),
+        (name = "events"),
+        (name = "system"),
+    )
+)]
+struct ApiDoc;
+
+#[cfg(feature = "eth")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        super::chains::list_chains,
+        super::wallet::list_wallets,
+        super::outbox::estimate_outbox_job,
+        super::snapshot::get_market_snapshot,
+    ),
+    components(schemas(
+        crate::eth::chains::ChainSummary,
+        crate::eth::wallets::WalletHealth,
+        ChainWallets,
+        OutboxEstimate,
+        MarketSnapshot,
+        SnapshotSignature,
+        SignedMarketSnapshot,
+    )),
+    tags((name = "chains"))
+)]
+struct EthApiDoc;
+
+#[cfg(feature = "feeds")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(super::feed::create_feed, super::feed::list_feeds),
+    components(schemas(MarketFeed, CreateFeedRequest, crate::feeds::FeedSource)),
+    tags((name = "feeds"))
+)]
+struct FeedsApiDoc;
+
+#[cfg(feature = "webhooks")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        super::webhook::create_webhook,
+        super::webhook::list_webhooks,
+        super::webhook::delete_webhook,
+        super::webhook::list_deliveries,
+    ),
+    components(schemas(CreateWebhookRequest, Webhook, WebhookDelivery)),
+    tags((name = "webhooks", description = "Signed POSTs of lifecycle events to subscriber URLs"))
+)]
+struct WebhooksApiDoc;
+
+struct BearerToken;
+
+impl Modify for BearerToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_token",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+pub fn spec() -> utoipa::openapi::OpenApi {
+    #[allow(unused_mut)]
+    let mut doc = ApiDoc::openapi();
+
+    #[cfg(feature = "eth")]
+    doc.merge(EthApiDoc::openapi());
+    #[cfg(feature = "feeds")]
+    doc.merge(FeedsApiDoc::openapi());
+    #[cfg(feature = "webhooks")]
+    doc.merge(WebhooksApiDoc::openapi());
+
+    doc
+}
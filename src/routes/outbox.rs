@@ -0,0 +1,316 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::auth::RequireAdmin;
+use crate::error::{AppError, AppJson};
+use crate::repo::OutboxRepo;
+use crate::state::AppState;
+use crate::types::{OutboxJob, OutboxQuery, SetPriorityRequest};
+
+#[utoipa::path(
+    get,
+    path = "/outbox",
+    tag = "outbox",
+    params(OutboxQuery),
+    responses(
+        (status = 200, body = Vec<OutboxJob>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn list_outbox(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Query(query): Query<OutboxQuery>,
+) -> Result<Json<Vec<OutboxJob>>, AppError> {
+    let jobs = OutboxRepo::list(&state.db, query.status.as_deref(), 100).await?;
+
+    Ok(Json(jobs))
+}
+
+#[utoipa::path(
+    get,
+    path = "/outbox/{id}",
+    tag = "outbox",
+    params(("id" = Uuid, Path, description = "Outbox job id")),
+    responses(
+        (status = 200, body = OutboxJob),
+        (status = 404, description = "Outbox job not found", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn get_outbox_job(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+) -> Result<Json<OutboxJob>, AppError> {
+    let job = OutboxRepo::get(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("OUTBOX_JOB_NOT_FOUND", "Outbox job not found"))?;
+
+    Ok(Json(job))
+}
+
+/// Dry-runs the job's chain submission: estimates gas for the payload from a
+/// submitter wallet and prices it at the node's current fees, without
+/// signing or sending anything. A payload that would revert comes back with
+/// `reverts` set instead of an estimate.
+#[cfg(feature = "eth")]
+#[utoipa::path(
+    get,
+    path = "/outbox/{id}/estimate",
+    tag = "outbox",
+    params(("id" = Uuid, Path, description = "Outbox job id")),
+    responses(
+        (status = 200, body = OutboxEstimate),
+        (status = 404, description = "Outbox job not found", body = ErrorResponse),
+        (status = 422, description = "Job payload is malformed or its chain isn't configured", body = ErrorResponse),
+        (status = 502, description = "The chain's RPC node couldn't be reached", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn estimate_outbox_job(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+) -> Result<Json<crate::types::OutboxEstimate>, AppError> {
+    use crate::eth::submit::{SettlementEstimate, estimate_settlement};
+    use crate::models::outbox::SettlementPayload;
+    use crate::types::OutboxEstimate;
+
+    let job = OutboxRepo::get(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("OUTBOX_JOB_NOT_FOUND", "Outbox job not found"))?;
+
+    let payload: SettlementPayload = serde_json::from_value(job.payload).map_err(|e| {
+        AppError::unprocessable("INVALID_PAYLOAD", format!("bad payload json: {}", e))
+    })?;
+    let (market_hash, leaf) = payload
+        .words()
+        .map_err(|e| AppError::unprocessable("INVALID_PAYLOAD", e))?;
+    let target = state
+        .chains
+        .resolve(payload.chain_id)
+        .map_err(|e| AppError::unprocessable("UNKNOWN_CHAIN", e.to_string()))?;
+
+    let estimate = estimate_settlement(
+        &state.sender,
+        target,
+        market_hash,
+        leaf,
+        payload.outcome_u64,
+        payload.ts,
+    )
+    .await
+    .map_err(|e| {
+        // the error can carry the RPC url, key and all
+        tracing::warn!("gas estimate for outbox job {} failed: {}", id, e);
+        AppError::new(
+            axum::http::StatusCode::BAD_GATEWAY,
+            "CHAIN_UNAVAILABLE",
+            "The chain's RPC node could not produce an estimate",
+        )
+    })?;
+
+    let view = match estimate {
+        SettlementEstimate::Estimated {
+            from,
+            gas,
+            gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        } => OutboxEstimate {
+            outbox_id: id,
+            chain_id: target.config.chain_id,
+            from: format!("{:?}", from),
+            gas: Some(gas.to_string()),
+            gas_price: Some(gas_price.to_string()),
+            max_fee_per_gas: Some(max_fee_per_gas.to_string()),
+            max_priority_fee_per_gas: Some(max_priority_fee_per_gas.to_string()),
+            projected_cost_wei: Some(gas.saturating_mul(gas_price).to_string()),
+            max_cost_wei: Some(gas.saturating_mul(max_fee_per_gas).to_string()),
+            reverts: false,
+            revert_reason: None,
+        },
+        SettlementEstimate::Reverted { from, reason } => OutboxEstimate {
+            outbox_id: id,
+            chain_id: target.config.chain_id,
+            from: format!("{:?}", from),
+            gas: None,
+            gas_price: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            projected_cost_wei: None,
+            max_cost_wei: None,
+            reverts: true,
+            revert_reason: Some(reason),
+        },
+    };
+
+    Ok(Json(view))
+}
+
+/// Requeues a dead-lettered job with a fresh retry budget.
+#[utoipa::path(
+    post,
+    path = "/outbox/{id}/retry",
+    tag = "outbox",
+    params(("id" = Uuid, Path, description = "Outbox job id")),
+    responses(
+        (status = 200, description = "Job requeued", body = String),
+        (status = 404, description = "Outbox job not found", body = ErrorResponse),
+        (status = 409, description = "Job is not FAILED or ABANDONED", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn retry_outbox_job(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+) -> Result<&'static str, AppError> {
+    let status = OutboxRepo::status(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("OUTBOX_JOB_NOT_FOUND", "Outbox job not found"))?;
+
+    if status != "FAILED" && status != "ABANDONED" {
+        return Err(AppError::conflict(
+            "OUTBOX_JOB_NOT_RETRYABLE",
+            format!(
+                "Only FAILED or ABANDONED jobs can be retried (status is {})",
+                status
+            ),
+        ));
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    OutboxRepo::retry(&mut *tx, id).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("outbox", id, "retried", &admin.actor)
+            .transition(Some(&status), Some("PENDING")),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok("Outbox job requeued")
+}
+
+/// Moves a job within its chain's queue: the worker claims higher priorities
+/// first. A FAILED job keeps its priority when retried.
+#[utoipa::path(
+    post,
+    path = "/outbox/{id}/priority",
+    tag = "outbox",
+    params(("id" = Uuid, Path, description = "Outbox job id")),
+    request_body = SetPriorityRequest,
+    responses(
+        (status = 200, body = OutboxJob),
+        (status = 404, description = "Outbox job not found", body = ErrorResponse),
+        (status = 409, description = "Job was already sent on-chain or abandoned", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn set_outbox_priority(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+    AppJson(payload): AppJson<SetPriorityRequest>,
+) -> Result<Json<OutboxJob>, AppError> {
+    let job = OutboxRepo::get(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("OUTBOX_JOB_NOT_FOUND", "Outbox job not found"))?;
+
+    if !matches!(job.status.as_str(), "PENDING" | "INTENT" | "FAILED") {
+        return Err(AppError::conflict(
+            "OUTBOX_JOB_NOT_QUEUED",
+            format!(
+                "Only queued or FAILED jobs can be reprioritized (status is {})",
+                job.status
+            ),
+        ));
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    OutboxRepo::set_priority(&mut *tx, id, payload.priority).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("outbox", id, "reprioritized", &admin.actor).details(serde_json::json!({
+            "previous_priority": job.priority,
+            "priority": payload.priority,
+        })),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let job = OutboxRepo::get(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("OUTBOX_JOB_NOT_FOUND", "Outbox job not found"))?;
+
+    Ok(Json(job))
+}
+
+/// Permanently gives up on a job. The row is kept as ABANDONED for inspection.
+#[utoipa::path(
+    delete,
+    path = "/outbox/{id}",
+    tag = "outbox",
+    params(("id" = Uuid, Path, description = "Outbox job id")),
+    responses(
+        (status = 200, description = "Job abandoned", body = String),
+        (status = 404, description = "Outbox job not found", body = ErrorResponse),
+        (status = 409, description = "Job was already sent on-chain", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn abandon_outbox_job(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+) -> Result<&'static str, AppError> {
+    let status = OutboxRepo::status(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("OUTBOX_JOB_NOT_FOUND", "Outbox job not found"))?;
+
+    if status == "SENT" || status == "CONFIRMED" {
+        return Err(AppError::conflict(
+            "OUTBOX_JOB_SENT",
+            "Job was already sent on-chain",
+        ));
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    OutboxRepo::abandon(&mut *tx, id).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("outbox", id, "abandoned", &admin.actor)
+            .transition(Some(&status), Some("ABANDONED")),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok("Outbox job abandoned")
+}
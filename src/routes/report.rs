@@ -1,91 +1,908 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
 use axum::{
-    extract::{Path, State},
     Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::sse::{self, KeepAlive, Sse},
 };
-use chrono::Utc;
+use chrono::{DateTime, SubsecRound, Utc};
+use futures_util::stream::{self, Stream};
+use tokio::sync::broadcast::error::RecvError;
 use uuid::Uuid;
 
+use crate::audit::{self, AuditEntry};
+use crate::auth::{RequireReporter, Role};
+use crate::error::{AppError, AppJson};
+use crate::events::{self, Event};
+use crate::repo::{
+    ExistingReport, MarketRepo, NewReport, ReportRepo, ReportUpdate, SOURCE_UNIQUE_CONSTRAINT,
+    SettlementRepo,
+};
+use crate::resolution::{self, Outlier};
 use crate::state::AppState;
-use crate::types::{CreateReportRequest, Report};
+use crate::types::{
+    CreateReportRequest, Market, Provenance, Report, ReportAggregateQuery, ReportBucket,
+    ReportListQuery, UpdateReportRequest,
+};
+use crate::validation::{MAX_IDEMPOTENCY_KEY_LEN, MAX_SOURCE_LEN, Validation};
 
+// how far a signed timestamp may be from the server clock
+const MAX_SIGNATURE_SKEW_SECS: i64 = 300;
+// keeps idle report streams from being cut by proxies
+const STREAM_HEARTBEAT: Duration = Duration::from_secs(15);
+
+#[utoipa::path(
+    post,
+    path = "/markets/{id}/reports",
+    tag = "reports",
+    params(
+        ("id" = Uuid, Path, description = "Market id"),
+    ),
+    request_body = CreateReportRequest,
+    responses(
+        (status = 201, body = Report),
+        (status = 200, description = "Same source and value again within the dedup window; the original report", body = Report),
+        (status = 400, description = "Market not accepting reports, outside its reporting window, or invalid signature", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 409, description = "Duplicate idempotency key, or the source already has a report", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, value outside the market range, or too far from recent reports", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the reporter role or may not report for the source", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
 pub async fn create_report(
     State(state): State<AppState>,
+    reporter: RequireReporter,
     Path(market_id): Path<Uuid>,
-    Json(payload): Json<CreateReportRequest>,
-) -> Result<&'static str, (axum::http::StatusCode, String)> {
+    AppJson(payload): AppJson<CreateReportRequest>,
+) -> Result<(StatusCode, Json<Report>), AppError> {
     let id = Uuid::new_v4();
-    let now = Utc::now();
+    // microseconds, as Postgres stores it, so the response matches later reads
+    let now = Utc::now().trunc_subsecs(6);
+
+    let mut validation = Validation::new();
+    validation
+        .text("source", &payload.source, MAX_SOURCE_LEN)
+        .finite("value", payload.value)
+        .text(
+            "idempotency_key",
+            &payload.idempotency_key,
+            MAX_IDEMPOTENCY_KEY_LEN,
+        );
+    validation.finish()?;
 
-    let market = sqlx::query!("SELECT status FROM markets WHERE id = $1", market_id)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|_| (axum::http::StatusCode::NOT_FOUND, "Market not found".to_string()))?;
+    // a source's one report per market is only its assigned reporter's
+    reporter.check_source(&state.db, &payload.source).await?;
 
-    if market.status != "OPEN" {
-        return Err((
-            axum::http::StatusCode::BAD_REQUEST,
-            "Market is closed".to_string(),
+    let market = MarketRepo::get(
+        &state.db,
+        market_id,
+        false,
+        state.config.consensus.default_bps(),
+    )
+    .await?
+    .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    if let Some(opens_at) = market.opens_at
+        && now < opens_at
+    {
+        return Err(AppError::bad_request(
+            "MARKET_NOT_OPEN",
+            format!("Market opens at {}", opens_at.to_rfc3339()),
         ));
     }
 
-    let result = sqlx::query(
-        r#"
-        INSERT INTO reports (id, market_id, source, value, idempotency_key, created_at)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        "#,
+    if market.status == "VOID" {
+        return Err(AppError::bad_request("MARKET_VOID", "Market was cancelled"));
+    }
+
+    // reports still land for CLOSE_GRACE_SECS after closes_at, marked late;
+    // the resolver holds off until the grace period is over
+    // an event-closed market still OPEN has no closes_at yet
+    let grace_until = market
+        .closes_at
+        .map(|c| c + state.config.resolver.close_grace());
+    let late = market.closes_at.is_some_and(|c| now > c);
+
+    // the scheduler may not have flipped SCHEDULED -> OPEN yet
+    let accepting = match market.status.as_str() {
+        "OPEN" | "SCHEDULED" => true,
+        "CLOSED" => grace_until.is_some_and(|g| now <= g),
+        _ => false,
+    };
+    if !accepting {
+        return Err(AppError::bad_request("MARKET_CLOSED", "Market is closed"));
+    }
+
+    // closes_at may have passed before the scheduler closed the market
+    let window_opens_at = market
+        .reporting_opens_at
+        .or(market.opens_at)
+        .unwrap_or(market.created_at);
+    if now < window_opens_at || grace_until.is_some_and(|g| now > g) {
+        return Err(AppError::bad_request(
+            "OUTSIDE_REPORTING_WINDOW",
+            format!(
+                "Reports are accepted from {} until {}",
+                window_opens_at.to_rfc3339(),
+                grace_until.map_or("the market closes".to_string(), |g| g.to_rfc3339()),
+            ),
+        )
+        .details(serde_json::json!({
+            "reporting_opens_at": window_opens_at,
+            "closes_at": market.closes_at,
+            "grace_until": grace_until,
+        })));
+    }
+
+    if let Some(existing) = ReportRepo::by_source(&state.db, market_id, &payload.source).await? {
+        return existing_report(&state, market_id, &existing, &payload, now).await;
+    }
+
+    check_value(&market, payload.value)?;
+    check_weighting(
+        payload.provenance.as_ref(),
+        payload.confidence,
+        payload.stake,
+    )?;
+
+    let signed = check_signature(
+        market_id,
+        payload.value,
+        now,
+        payload.signature.as_deref(),
+        payload.reporter_address.as_deref(),
+        payload.timestamp,
+    )?;
+
+    let deviation = check_deviation(&state, &market, id, payload.value).await?;
+
+    let provenance = payload
+        .provenance
+        .as_ref()
+        .map(|p| serde_json::to_value(p).unwrap());
+
+    let mut tx = state.db.begin().await?;
+
+    let result = ReportRepo::insert(
+        &mut *tx,
+        &NewReport {
+            id,
+            market_id,
+            source: &payload.source,
+            value: payload.value,
+            idempotency_key: &payload.idempotency_key,
+            self_reported: false,
+            provenance,
+            confidence: payload.confidence,
+            stake: payload.stake,
+            reporter_address: signed.as_ref().map(|s| s.reporter.as_str()),
+            signature: signed.as_ref().map(|s| s.signature.as_str()),
+            signed_at: signed.as_ref().and_then(|s| s.signed_at),
+            verified: signed.is_some(),
+            late,
+            submitted_by: Some(&reporter.actor),
+            created_at: now,
+        },
     )
-    .bind(id)
-    .bind(market_id)
-    .bind(&payload.source)
-    .bind(payload.value)
-    .bind(&payload.idempotency_key)
-    .bind(now)
-    .execute(&state.db)
     .await;
 
-    match result {
-        Ok(_) => Ok("Report submitted"),
-        Err(e) => {
-            if let Some(db_err) = e.as_database_error() {
-                if db_err.code().as_deref() == Some("23505") {
-                    return Err((
-                        axum::http::StatusCode::CONFLICT,
-                        "Duplicate report or idempotency key".to_string(),
-                    ));
-                }
+    if let Err(e) = result {
+        if let Some(db_err) = e.as_database_error()
+            && db_err.code().as_deref() == Some("23505")
+        {
+            // lost a race with another report from the same source
+            if db_err.constraint() == Some(SOURCE_UNIQUE_CONSTRAINT)
+                && let Some(existing) =
+                    ReportRepo::by_source(&state.db, market_id, &payload.source).await?
+            {
+                return existing_report(&state, market_id, &existing, &payload, now).await;
             }
-            Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            return Err(AppError::conflict(
+                "DUPLICATE_IDEMPOTENCY_KEY",
+                "Duplicate report or idempotency key",
+            ));
         }
+        return Err(e.into());
+    }
+
+    if let Some(d) = &deviation {
+        SettlementRepo::flag_report(
+            &mut *tx,
+            id,
+            market_id,
+            "DEVIATION",
+            &serde_json::to_value(d).unwrap(),
+        )
+        .await?;
+        tracing::info!(
+            "Report {} on market {} flagged: {} vs recent median {}",
+            id,
+            market_id,
+            d.value,
+            d.median
+        );
+    }
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("report", id, "accepted", &reporter.actor).details(serde_json::json!({
+            "market_id": market_id,
+            "source": payload.source,
+            "value": payload.value,
+            "reporter_address": signed.as_ref().map(|s| &s.reporter),
+            "late": late,
+        })),
+    )
+    .await?;
+
+    let event = Event::ReportAccepted {
+        market_id,
+        report_id: id,
+        source: payload.source.clone(),
+        value: payload.value,
+        updated: false,
+    };
+    events::append(&mut *tx, &event).await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(Report {
+            id,
+            market_id,
+            source: payload.source,
+            value: payload.value,
+            self_reported: false,
+            provenance: payload.provenance,
+            confidence: payload.confidence,
+            stake: payload.stake,
+            verified: signed.is_some(),
+            reporter_address: signed.map(|s| s.reporter),
+            late,
+            weight: None,
+            created_at: now,
+            updated_at: None,
+            retracted_at: None,
+        }),
+    ))
+}
+
+/// Replaces the value of a source's report while the market is open. A
+/// signed report stays signed: its update must be signed by the same
+/// reporter address. Only the caller that submitted it (or an admin) may
+/// update it.
+#[utoipa::path(
+    put,
+    path = "/markets/{id}/reports/{report_id}",
+    tag = "reports",
+    params(
+        ("id" = Uuid, Path, description = "Market id"),
+        ("report_id" = Uuid, Path, description = "Report id"),
+    ),
+    request_body = UpdateReportRequest,
+    responses(
+        (status = 200, body = Report),
+        (status = 400, description = "Market closed, or invalid or missing signature", body = ErrorResponse),
+        (status = 404, description = "Market or report not found", body = ErrorResponse),
+        (status = 409, description = "Report comes from a feed", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, value outside the market range, or too far from recent reports", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the reporter role, didn't submit the report or may no longer report for its source", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn update_report(
+    State(state): State<AppState>,
+    reporter: RequireReporter,
+    Path((market_id, report_id)): Path<(Uuid, Uuid)>,
+    AppJson(payload): AppJson<UpdateReportRequest>,
+) -> Result<Json<Report>, AppError> {
+    let now = Utc::now().trunc_subsecs(6);
+
+    let mut validation = Validation::new();
+    validation.finite("value", payload.value);
+    validation.finish()?;
+
+    let market = MarketRepo::get(
+        &state.db,
+        market_id,
+        false,
+        state.config.consensus.default_bps(),
+    )
+    .await?
+    .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    // no updates in the close grace period: that is for reports in flight
+    if !matches!(market.status.as_str(), "OPEN" | "SCHEDULED")
+        || market.closes_at.is_some_and(|c| now > c)
+    {
+        return Err(AppError::bad_request(
+            "MARKET_CLOSED",
+            "Reports can only be updated before the market closes",
+        ));
+    }
+
+    check_value(&market, payload.value)?;
+    check_weighting(
+        payload.provenance.as_ref(),
+        payload.confidence,
+        payload.stake,
+    )?;
+
+    let signed = check_signature(
+        market_id,
+        payload.value,
+        now,
+        payload.signature.as_deref(),
+        payload.reporter_address.as_deref(),
+        payload.timestamp,
+    )?;
+
+    let deviation = check_deviation(&state, &market, report_id, payload.value).await?;
+
+    let mut tx = state.db.begin().await?;
+
+    let existing = ReportRepo::lock(&mut *tx, market_id, report_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("REPORT_NOT_FOUND", "Report not found"))?;
+
+    if existing.self_reported {
+        return Err(AppError::conflict(
+            "FEED_REPORT",
+            "Feed reports are updated by their feed",
+        ));
+    }
+
+    if reporter.role < Role::Admin
+        && existing.submitted_by.as_deref() != Some(reporter.actor.as_str())
+    {
+        return Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            "NOT_REPORT_OWNER",
+            "Only the caller that submitted a report may update it",
+        ));
+    }
+    reporter.check_source(&state.db, &existing.source).await?;
+
+    if existing.verified
+        && signed.as_ref().map(|s| &s.reporter) != existing.reporter_address.as_ref()
+    {
+        return Err(AppError::bad_request(
+            "SIGNATURE_REQUIRED",
+            "Report was signed; its update must be signed by the same reporter_address",
+        ));
+    }
+
+    let provenance = payload
+        .provenance
+        .as_ref()
+        .map(|p| serde_json::to_value(p).unwrap());
+
+    ReportRepo::update(
+        &mut *tx,
+        report_id,
+        &ReportUpdate {
+            value: payload.value,
+            provenance,
+            confidence: payload.confidence,
+            stake: payload.stake,
+            reporter_address: signed.as_ref().map(|s| s.reporter.as_str()),
+            signature: signed.as_ref().map(|s| s.signature.as_str()),
+            signed_at: signed.as_ref().and_then(|s| s.signed_at),
+            verified: signed.is_some(),
+            updated_at: now,
+        },
+    )
+    .await?;
+
+    // the old value's flag says nothing about the new one
+    SettlementRepo::unflag_report(&mut *tx, report_id, "DEVIATION").await?;
+    if let Some(d) = &deviation {
+        SettlementRepo::flag_report(
+            &mut *tx,
+            report_id,
+            market_id,
+            "DEVIATION",
+            &serde_json::to_value(d).unwrap(),
+        )
+        .await?;
+    }
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("report", report_id, "updated", &reporter.actor).details(
+            serde_json::json!({
+                "market_id": market_id,
+                "source": existing.source,
+                "from": existing.value,
+                "to": payload.value,
+            }),
+        ),
+    )
+    .await?;
+
+    let event = Event::ReportAccepted {
+        market_id,
+        report_id,
+        source: existing.source.clone(),
+        value: payload.value,
+        updated: true,
+    };
+    events::append(&mut *tx, &event).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(Report {
+        id: report_id,
+        market_id,
+        source: existing.source,
+        value: payload.value,
+        self_reported: false,
+        provenance: payload.provenance,
+        confidence: payload.confidence,
+        stake: payload.stake,
+        verified: signed.is_some(),
+        reporter_address: signed.map(|s| s.reporter),
+        late: existing.late,
+        weight: None,
+        created_at: existing.created_at,
+        updated_at: Some(now),
+        retracted_at: None,
+    }))
+}
+
+/// Takes back a report while the market is open, e.g. a fat-fingered value.
+/// The report is tombstoned rather than deleted: it stays in the audit trail
+/// and in listings with `include_retracted`, but no longer counts, and its
+/// source may report again. Only the caller that submitted it (or an admin)
+/// may retract it.
+#[utoipa::path(
+    delete,
+    path = "/markets/{id}/reports/{report_id}",
+    tag = "reports",
+    params(
+        ("id" = Uuid, Path, description = "Market id"),
+        ("report_id" = Uuid, Path, description = "Report id"),
+    ),
+    responses(
+        (status = 204, description = "Report retracted"),
+        (status = 400, description = "Market closed", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the reporter role or didn't submit the report", body = ErrorResponse),
+        (status = 404, description = "Market or report not found, or already retracted", body = ErrorResponse),
+        (status = 409, description = "Report comes from a feed", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn retract_report(
+    State(state): State<AppState>,
+    reporter: RequireReporter,
+    Path((market_id, report_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    let now = Utc::now().trunc_subsecs(6);
+
+    let market = MarketRepo::get(
+        &state.db,
+        market_id,
+        false,
+        state.config.consensus.default_bps(),
+    )
+    .await?
+    .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    if !matches!(market.status.as_str(), "OPEN" | "SCHEDULED")
+        || market.closes_at.is_some_and(|c| now > c)
+    {
+        return Err(AppError::bad_request(
+            "MARKET_CLOSED",
+            "Reports can only be retracted before the market closes",
+        ));
     }
+
+    let mut tx = state.db.begin().await?;
+
+    let existing = ReportRepo::lock(&mut *tx, market_id, report_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("REPORT_NOT_FOUND", "Report not found"))?;
+
+    if existing.self_reported {
+        return Err(AppError::conflict(
+            "FEED_REPORT",
+            "Feed reports are updated by their feed",
+        ));
+    }
+
+    if reporter.role < Role::Admin
+        && existing.submitted_by.as_deref() != Some(reporter.actor.as_str())
+    {
+        return Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            "NOT_REPORT_OWNER",
+            "Only the caller that submitted a report may retract it",
+        ));
+    }
+
+    ReportRepo::retract(&mut *tx, report_id, now).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("report", report_id, "retracted", &reporter.actor).details(
+            serde_json::json!({
+                "market_id": market_id,
+                "source": existing.source,
+                "value": existing.value,
+            }),
+        ),
+    )
+    .await?;
+
+    let event = Event::ReportRetracted {
+        market_id,
+        report_id,
+        source: existing.source,
+    };
+    events::append(&mut *tx, &event).await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    get,
+    path = "/markets/{id}/reports",
+    tag = "reports",
+    params(("id" = Uuid, Path, description = "Market id"), ReportListQuery),
+    responses((status = 200, body = Vec<Report>))
+)]
 pub async fn list_reports(
     State(state): State<AppState>,
     Path(market_id): Path<Uuid>,
-) -> Json<Vec<Report>> {
-    let rows = sqlx::query!(
-        r#"
-        SELECT id, market_id, source, value, created_at
-        FROM reports
-        WHERE market_id = $1
-        ORDER BY created_at ASC
-        "#,
-        market_id
+    Query(query): Query<ReportListQuery>,
+) -> Result<Json<Vec<Report>>, AppError> {
+    let reports = ReportRepo::list(
+        &state.db,
+        market_id,
+        query.include_archived.unwrap_or(false),
+        query.include_retracted.unwrap_or(false),
+        query.flagged.unwrap_or(false),
+    )
+    .await?;
+
+    Ok(Json(reports))
+}
+
+/// Server-Sent Events stream of the market's reports as they are accepted:
+/// one `report` event per new or updated report and one `retraction` event
+/// per retracted one, with a `heartbeat` comment while idle. Only changes
+/// after connecting are sent; fetch `GET /markets/{id}/reports` first for
+/// the backlog.
+#[utoipa::path(
+    get,
+    path = "/markets/{id}/reports/stream",
+    tag = "reports",
+    params(("id" = Uuid, Path, description = "Market id")),
+    responses(
+        (status = 200, description = "text/event-stream of report_accepted and report_retracted events as JSON"),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+    )
+)]
+pub async fn stream_reports(
+    State(state): State<AppState>,
+    Path(market_id): Path<Uuid>,
+) -> Result<Sse<impl Stream<Item = Result<sse::Event, Infallible>>>, AppError> {
+    // subscribed before the lookup so nothing accepted in between is missed
+    let rx = state.events.subscribe();
+
+    if MarketRepo::get(
+        &state.db,
+        market_id,
+        false,
+        state.config.consensus.default_bps(),
+    )
+    .await?
+    .is_none()
+    {
+        return Err(AppError::not_found("MARKET_NOT_FOUND", "Market not found"));
+    }
+
+    let reports = stream::unfold(rx, move |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event @ Event::ReportAccepted { market_id: id, .. }) if id == market_id => {
+                    let sse = sse::Event::default()
+                        .event("report")
+                        .json_data(&event)
+                        .unwrap();
+                    return Some((Ok(sse), rx));
+                }
+                Ok(event @ Event::ReportRetracted { market_id: id, .. }) if id == market_id => {
+                    let sse = sse::Event::default()
+                        .event("retraction")
+                        .json_data(&event)
+                        .unwrap();
+                    return Some((Ok(sse), rx));
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        "report stream for {} lagged, skipped {} events",
+                        market_id,
+                        skipped
+                    );
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Ok(Sse::new(reports).keep_alive(
+        KeepAlive::new()
+            .interval(STREAM_HEARTBEAT)
+            .text("heartbeat"),
+    ))
+}
+
+/// Bucketed min/max/mean/median of a market's report values, for charting
+/// without fetching every report.
+#[utoipa::path(
+    get,
+    path = "/markets/{id}/reports/aggregate",
+    tag = "reports",
+    params(("id" = Uuid, Path, description = "Market id"), ReportAggregateQuery),
+    responses(
+        (status = 200, body = Vec<ReportBucket>),
+        (status = 400, description = "Unsupported interval", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+    )
+)]
+pub async fn aggregate_reports(
+    State(state): State<AppState>,
+    Path(market_id): Path<Uuid>,
+    Query(query): Query<ReportAggregateQuery>,
+) -> Result<Json<Vec<ReportBucket>>, AppError> {
+    let bucket_secs = match query.interval.as_deref().unwrap_or("1m") {
+        "1m" => 60,
+        "5m" => 300,
+        "1h" => 3600,
+        other => {
+            return Err(AppError::bad_request(
+                "INVALID_INTERVAL",
+                format!("interval must be 1m, 5m or 1h, not {:?}", other),
+            ));
+        }
+    };
+
+    if MarketRepo::get(
+        &state.db,
+        market_id,
+        true,
+        state.config.consensus.default_bps(),
+    )
+    .await?
+    .is_none()
+    {
+        return Err(AppError::not_found("MARKET_NOT_FOUND", "Market not found"));
+    }
+
+    Ok(Json(
+        ReportRepo::aggregate(&state.db, market_id, bucket_secs).await?,
+    ))
+}
+
+/// Answers a report from a source that already has one on the market. The
+/// same value again within `dedup_window_secs` is taken for a client retry
+/// under a fresh idempotency key and gets the original report back.
+async fn existing_report(
+    state: &AppState,
+    market_id: Uuid,
+    existing: &ExistingReport,
+    payload: &CreateReportRequest,
+    now: DateTime<Utc>,
+) -> Result<(StatusCode, Json<Report>), AppError> {
+    if existing.idempotency_key == payload.idempotency_key {
+        return Err(AppError::conflict(
+            "DUPLICATE_IDEMPOTENCY_KEY",
+            "Duplicate report or idempotency key",
+        ));
+    }
+
+    let window = state.config.sanity.dedup_window_secs as i64;
+    let retry = window > 0
+        && existing.value == payload.value
+        && (now - existing.written_at).num_seconds() <= window;
+
+    if retry && let Some(report) = ReportRepo::get(&state.db, market_id, existing.id).await? {
+        tracing::info!(
+            "Report from {} on market {} deduplicated to {}",
+            payload.source,
+            market_id,
+            existing.id
+        );
+        return Ok((StatusCode::OK, Json(report)));
+    }
+
+    Err(source_already_reported(existing.id))
+}
+
+fn source_already_reported(report_id: Uuid) -> AppError {
+    AppError::conflict(
+        "SOURCE_ALREADY_REPORTED",
+        "Source has already reported on this market; update its report instead",
     )
-    .fetch_all(&state.db)
-    .await
-    .unwrap();
-
-    let reports = rows
-        .into_iter()
-        .map(|row| Report {
-            id: row.id,
-            market_id: row.market_id,
-            source: row.source,
-            value: row.value,
-            created_at: row.created_at,
-        })
-        .collect();
-
-    Json(reports)
-}
\ No newline at end of file
+    .details(serde_json::json!({ "report_id": report_id }))
+}
+
+/// The value is one the market can take.
+fn check_value(market: &Market, value: f64) -> Result<(), AppError> {
+    let outcome_type = &market.outcome_type;
+    if outcome_type.is_discrete() && outcome_type.option_index(value).is_none() {
+        return Err(AppError::unprocessable(
+            "INVALID_OPTION",
+            format!(
+                "Value must be an option index between 0 and {}",
+                outcome_type.options().len() - 1
+            ),
+        ));
+    }
+
+    if market.min_value.is_some_and(|min| value < min)
+        || market.max_value.is_some_and(|max| value > max)
+    {
+        return Err(AppError::unprocessable(
+            "VALUE_OUT_OF_RANGE",
+            format!(
+                "Value {} outside market range [{}, {}]",
+                value,
+                market
+                    .min_value
+                    .map_or("-inf".to_string(), |v| v.to_string()),
+                market
+                    .max_value
+                    .map_or("inf".to_string(), |v| v.to_string()),
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn check_weighting(
+    provenance: Option<&Provenance>,
+    confidence: Option<f64>,
+    stake: Option<f64>,
+) -> Result<(), AppError> {
+    if let Some(p) = provenance {
+        let url_ok = p.source_url.starts_with("https://") || p.source_url.starts_with("http://");
+        let hash_ok = p.response_sha256.len() == 64
+            && p.response_sha256.chars().all(|c| c.is_ascii_hexdigit());
+
+        if !url_ok || !hash_ok {
+            return Err(AppError::bad_request(
+                "INVALID_PROVENANCE",
+                "provenance needs an http(s) source_url and a hex sha256 response_sha256",
+            ));
+        }
+    }
+
+    if confidence.is_some_and(|c| !(0.0..=1.0).contains(&c))
+        || stake.is_some_and(|s| !s.is_finite() || s < 0.0)
+    {
+        return Err(AppError::bad_request(
+            "INVALID_WEIGHT",
+            "confidence must be between 0 and 1 and stake non-negative",
+        ));
+    }
+
+    Ok(())
+}
+
+/// A report signature that checked out.
+struct Signed {
+    // lowercase hex address the signature recovers to
+    reporter: String,
+    signature: String,
+    signed_at: Option<DateTime<Utc>>,
+}
+
+/// `Some` when the report is signed and the signature checks out.
+fn check_signature(
+    market_id: Uuid,
+    value: f64,
+    now: DateTime<Utc>,
+    signature: Option<&str>,
+    reporter: Option<&str>,
+    timestamp: Option<i64>,
+) -> Result<Option<Signed>, AppError> {
+    match (signature, reporter, timestamp) {
+        (None, None, None) => Ok(None),
+        (Some(signature), Some(reporter), Some(timestamp)) => {
+            let skew = (now.timestamp() - timestamp).abs();
+            if skew > MAX_SIGNATURE_SKEW_SECS {
+                return Err(AppError::bad_request(
+                    "SIGNATURE_EXPIRED",
+                    format!("timestamp is {}s away from server time", skew),
+                ));
+            }
+
+            let reporter = verify_signature(market_id, value, timestamp, reporter, signature)
+                .map_err(|e| AppError::bad_request("INVALID_SIGNATURE", e))?;
+
+            Ok(Some(Signed {
+                reporter,
+                signature: signature.to_string(),
+                signed_at: DateTime::from_timestamp(timestamp, 0),
+            }))
+        }
+        _ => Err(AppError::bad_request(
+            "INCOMPLETE_SIGNATURE",
+            "signature, reporter_address and timestamp must be sent together",
+        )),
+    }
+}
+
+/// Judged against the reports before it, so a run of bad values can't
+/// vouch for itself. Errors only when SANITY_REJECT is set.
+async fn check_deviation(
+    state: &AppState,
+    market: &Market,
+    report_id: Uuid,
+    value: f64,
+) -> Result<Option<Outlier>, AppError> {
+    if market.outcome_type.is_discrete() {
+        return Ok(None);
+    }
+
+    let history =
+        ReportRepo::recent_values(&state.db, market.id, state.config.sanity.window).await?;
+    let consensus = state
+        .config
+        .consensus
+        .for_market(Some(market.consensus_bps as i32));
+    let deviation = resolution::deviation(
+        report_id,
+        value,
+        &history,
+        state.config.sanity.max_deviation,
+        &consensus,
+    );
+
+    if let Some(d) = &deviation
+        && state.config.sanity.reject
+    {
+        return Err(AppError::unprocessable(
+            "VALUE_DEVIATES",
+            format!(
+                "Value {} is more than {} from the recent median {}",
+                d.value, d.threshold, d.median
+            ),
+        ));
+    }
+
+    Ok(deviation)
+}
+
+/// Lowercase hex reporter address when the signature checks out.
+#[cfg(feature = "eth")]
+fn verify_signature(
+    market_id: Uuid,
+    value: f64,
+    timestamp: i64,
+    reporter: &str,
+    signature: &str,
+) -> Result<String, String> {
+    crate::eth::verify::verify_report(market_id, value, timestamp, reporter, signature)
+        .map(|address| format!("{:?}", address))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "eth"))]
+fn verify_signature(_: Uuid, _: f64, _: i64, _: &str, _: &str) -> Result<String, String> {
+    Err("signed reports need the eth feature".to_string())
+}
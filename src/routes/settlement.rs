@@ -1,84 +1,298 @@
 use axum::{
-    extract::{Path, State},
     Json,
+    extract::{Path, Query, State},
 };
 use chrono::{DateTime, Utc};
-use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use crate::error::AppError;
+use crate::proof::{self, EncodedReport, HashAlgorithm, SETTLEMENT_ENCODING_VERSION};
+use crate::repo::{BatchRepo, MarketRepo, ReportRepo, SettlementRepo};
+use crate::resolution::{SourceValue, report_weight};
 use crate::state::AppState;
-use crate::types::{Report, SettlementView};
+use crate::types::{
+    Report, SettlementListQuery, SettlementPage, SettlementRevision, SettlementView,
+};
+use crate::verify::inclusion_proof;
 
+#[utoipa::path(
+    get,
+    path = "/markets/{id}/settlement",
+    tag = "settlements",
+    params(("id" = Uuid, Path, description = "Market id")),
+    responses(
+        (status = 200, body = SettlementView),
+        (status = 404, description = "Market not settled, or its settlement is still PROPOSED", body = ErrorResponse),
+    )
+)]
 pub async fn get_settlement(
     State(state): State<AppState>,
     Path(market_id): Path<Uuid>,
-) -> Result<Json<SettlementView>, axum::http::StatusCode> {
-    let settlement = sqlx::query!(
-        r#"
-        SELECT outcome, decided_at
-        FROM settlements
-        WHERE market_id = $1
-        "#,
-        market_id
+) -> Result<Json<SettlementView>, AppError> {
+    let settlement = state
+        .cache
+        .settlement(market_id, load_settlement(&state, market_id))
+        .await?;
+    let settlement = Option::clone(&settlement)
+        .ok_or_else(|| AppError::not_found("SETTLEMENT_NOT_FOUND", "Market not settled"))?;
+
+    // only final settlements are served here; the market detail shows a
+    // proposal with its status
+    if settlement.status != "FINAL" {
+        return Err(AppError::not_found(
+            "SETTLEMENT_NOT_FINAL",
+            "Settlement is proposed and awaiting chain confirmation",
+        )
+        .details(serde_json::json!({
+            "status": settlement.status,
+            "outcome": settlement.outcome,
+            "decided_at": settlement.decided_at,
+            "revision": settlement.revision,
+        })));
+    }
+
+    Ok(Json(settlement))
+}
+
+/// The market's settlements, oldest first, including those an operator
+/// override superseded.
+#[utoipa::path(
+    get,
+    path = "/markets/{id}/settlement/history",
+    tag = "settlements",
+    params(("id" = Uuid, Path, description = "Market id")),
+    responses(
+        (status = 200, body = Vec<SettlementRevision>),
+        (status = 404, description = "Market not settled", body = ErrorResponse),
+    )
+)]
+pub async fn get_settlement_history(
+    State(state): State<AppState>,
+    Path(market_id): Path<Uuid>,
+) -> Result<Json<Vec<SettlementRevision>>, AppError> {
+    let history = SettlementRepo::history(&state.db, market_id).await?;
+    if history.is_empty() {
+        return Err(AppError::not_found(
+            "SETTLEMENT_NOT_FOUND",
+            "Market not settled",
+        ));
+    }
+
+    Ok(Json(history))
+}
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+
+/// Settlements across all markets, archived ones included, oldest decided
+/// first, with the market's question, the batch holding each and where it
+/// stands on chain. Pass `next_cursor` back as `cursor` for the next page.
+#[utoipa::path(
+    get,
+    path = "/settlements",
+    tag = "settlements",
+    params(SettlementListQuery),
+    responses(
+        (status = 200, body = SettlementPage),
+        (status = 400, description = "Malformed cursor, or from after to", body = ErrorResponse),
     )
-    .fetch_optional(&state.db)
-    .await
-    .unwrap()
-    .ok_or(axum::http::StatusCode::NOT_FOUND)?;
-
-    let reports_rows = sqlx::query!(
-        r#"
-        SELECT id, market_id, source, value, created_at
-        FROM reports
-        WHERE market_id = $1
-        ORDER BY created_at ASC
-        "#,
-        market_id
+)]
+pub async fn list_settlements(
+    State(state): State<AppState>,
+    Query(query): Query<SettlementListQuery>,
+) -> Result<Json<SettlementPage>, AppError> {
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return Err(AppError::bad_request(
+            "INVALID_RANGE",
+            "from must not be after to",
+        ));
+    }
+
+    let after = query
+        .cursor
+        .as_deref()
+        .map(|c| {
+            parse_cursor(c)
+                .ok_or_else(|| AppError::bad_request("INVALID_CURSOR", "cursor is malformed"))
+        })
+        .transpose()?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let settlements = SettlementRepo::list(&state.db, query.from, query.to, after, limit).await?;
+
+    let next_cursor = match settlements.last() {
+        Some(last) if settlements.len() as i64 == limit => {
+            Some(cursor(last.decided_at, last.market_id))
+        }
+        _ => None,
+    };
+
+    Ok(Json(SettlementPage {
+        settlements,
+        next_cursor,
+    }))
+}
+
+// the last row's (decided_at, market_id), as microseconds and uuid
+fn cursor(decided_at: DateTime<Utc>, market_id: Uuid) -> String {
+    format!("{}_{}", decided_at.timestamp_micros(), market_id)
+}
+
+fn parse_cursor(cursor: &str) -> Option<(DateTime<Utc>, Uuid)> {
+    let (micros, id) = cursor.split_once('_')?;
+    Some((
+        DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+        id.parse().ok()?,
+    ))
+}
+
+/// The settlement view for `market_id`, or `None` if it hasn't resolved.
+pub(crate) async fn load_settlement(
+    state: &AppState,
+    market_id: Uuid,
+) -> Result<Option<SettlementView>, AppError> {
+    let Some(settlement) = SettlementRepo::get(&state.db, market_id).await? else {
+        return Ok(None);
+    };
+
+    let mut reports = ReportRepo::list(&state.db, market_id, true, false, false).await?;
+    let inputs = settlement.inputs();
+
+    let hash_algorithm = settlement.hash_algorithm();
+    let hash = settlement_hash(
+        hash_algorithm,
+        market_id,
+        settlement.outcome_e8,
+        settlement.decided_at,
+        &reports,
+    );
+
+    let market = MarketRepo::get(
+        &state.db,
+        market_id,
+        true,
+        state.config.consensus.default_bps(),
     )
-    .fetch_all(&state.db)
-    .await
-    .unwrap();
+    .await?
+    .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
 
-    let reports: Vec<Report> = reports_rows
-        .into_iter()
-        .map(|r| Report {
+    // weigh reports as of the decision, not as the market stands now
+    let strategy = inputs.as_ref().map_or(&market.resolution, |i| &i.strategy);
+    let policy = &market.self_report_policy;
+    let outcome_type = market.outcome_type;
+    let excluded = SettlementRepo::flags(&state.db, market_id).await?;
+
+    for r in &mut reports {
+        let flagged = excluded.iter().any(|f| f.report_id == r.id);
+        let counted = market.late_report_policy.counts(r.late)
+            && inputs.as_ref().is_none_or(|i| i.report_ids.contains(&r.id));
+        let in_bounds = market.min_value.is_none_or(|min| r.value >= min)
+            && market.max_value.is_none_or(|max| r.value <= max)
+            && (!outcome_type.is_discrete() || outcome_type.option_index(r.value).is_some());
+
+        let source = SourceValue {
             id: r.id,
-            market_id: r.market_id,
-            source: r.source,
+            source: r.source.clone(),
             value: r.value,
-            created_at: r.created_at,
-        })
-        .collect();
+            self_reported: r.self_reported,
+            confidence: r.confidence,
+            stake: r.stake,
+            late: r.late,
+            submitted_by: None,
+            held: Vec::new(),
+        };
+
+        r.weight = Some(if in_bounds && !flagged && counted {
+            report_weight(strategy, policy, &source)
+        } else {
+            0.0
+        });
+    }
 
-    let hash = settlement_hash(market_id, settlement.outcome, settlement.decided_at, &reports);
+    let leaf = hex::encode(proof::settlement_leaf(
+        hash_algorithm,
+        market_id,
+        settlement.outcome_e8,
+        settlement.decided_at,
+    ));
+
+    let inclusion = match BatchRepo::for_market(&state.db, market_id).await? {
+        Some(batch) => inclusion_proof(&state.db, &batch, market_id)
+            .await?
+            .filter(|p| {
+                // legacy batches hold the leaf in the encoding they were cut with
+                p.leaf
+                    == hex::encode(proof::versioned_leaf(
+                        p.leaf_version,
+                        hash_algorithm,
+                        market_id,
+                        settlement.outcome,
+                        settlement.outcome_e8,
+                        settlement.decided_at,
+                    ))
+            }),
+        None => None,
+    };
+
+    let chain = SettlementRepo::chain_submission(&state.db, market_id).await?;
+
+    // settlements from before decimals were recorded anchored at the value
+    // type's; discrete ones anchor an option index
+    let decimals = match settlement.decimals {
+        Some(places) => places.max(0) as u32,
+        None if outcome_type.is_discrete() => 0,
+        None => market.value_type.default_decimals(),
+    };
 
-    Ok(Json(SettlementView {
+    let payout = market.payout.as_ref().map(|f| f.payout(settlement.outcome));
+
+    Ok(Some(SettlementView {
         market_id,
         outcome: settlement.outcome,
+        outcome_e8: settlement.outcome_e8,
+        winning_option: outcome_type.option_label(settlement.outcome),
+        outcome_type,
         decided_at: settlement.decided_at,
+        revision: settlement.revision,
+        status: settlement.status,
+        finalized_at: settlement.finalized_at,
         reports,
+        excluded,
+        inputs,
         hash,
+        hash_version: SETTLEMENT_ENCODING_VERSION,
+        hash_algorithm,
+        decimals,
+        leaf,
+        inclusion,
+        chain,
+        payout,
     }))
 }
 
-fn settlement_hash(
+/// Hex settlement hash over `reports` as listed by `ReportRepo::list` with
+/// archived reports included; see
+/// `proof::encode_settlement` for the byte layout.
+pub fn settlement_hash(
+    alg: HashAlgorithm,
     market_id: Uuid,
-    outcome: f64,
+    outcome_e8: i64,
     decided_at: DateTime<Utc>,
     reports: &[Report],
 ) -> String {
-    let mut hasher = Sha256::new();
-
-    hasher.update(market_id.as_bytes());
-    hasher.update(outcome.to_string().as_bytes());
-    hasher.update(decided_at.to_rfc3339().as_bytes());
-
-    for r in reports {
-        hasher.update(r.id.as_bytes());
-        hasher.update(r.source.as_bytes());
-        hasher.update(r.value.to_string().as_bytes());
-        hasher.update(r.created_at.to_rfc3339().as_bytes());
-    }
+    let encoded: Vec<EncodedReport> = reports
+        .iter()
+        .map(|r| EncodedReport {
+            id: r.id,
+            source: &r.source,
+            value: r.value,
+            created_at: r.created_at,
+        })
+        .collect();
 
-    hex::encode(hasher.finalize())
-}
\ No newline at end of file
+    hex::encode(proof::settlement_hash(
+        alg, market_id, outcome_e8, decided_at, &encoded,
+    ))
+}
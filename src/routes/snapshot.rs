@@ -0,0 +1,102 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{SubsecRound, Utc};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::proof::HashAlgorithm;
+use crate::repo::{MarketRepo, ReportRepo};
+use crate::state::AppState;
+use crate::types::{BatchSummary, MarketSnapshot, SignedMarketSnapshot};
+
+use super::settlement::load_settlement;
+
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// The market's full state as one document signed with the server's
+/// attestation key: the market, every report, the settlement with its
+/// Merkle proof and chain transaction, and the batch holding it. Verify by
+/// hashing `snapshot` as compact JSON with sorted keys (keccak256) and
+/// recovering the EIP-191 signature over that digest to `signer`.
+#[utoipa::path(
+    get,
+    path = "/markets/{id}/snapshot",
+    tag = "markets",
+    params(("id" = Uuid, Path, description = "Market id")),
+    responses(
+        (status = 200, body = SignedMarketSnapshot),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 503, description = "No snapshot signing key is configured", body = ErrorResponse),
+    )
+)]
+pub async fn get_market_snapshot(
+    State(state): State<AppState>,
+    Path(market_id): Path<Uuid>,
+) -> Result<Json<SignedMarketSnapshot>, AppError> {
+    let Some(attestor) = state.attestor.clone() else {
+        return Err(AppError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "SNAPSHOTS_DISABLED",
+            "Market snapshots are off: no signing key is configured",
+        ));
+    };
+
+    // taken before the reads so nothing in the document postdates it
+    let generated_at = Utc::now().trunc_subsecs(6);
+
+    let market = MarketRepo::get(
+        &state.db,
+        market_id,
+        true,
+        state.config.consensus.default_bps(),
+    )
+    .await?
+    .ok_or_else(|| AppError::not_found("MARKET_NOT_FOUND", "Market not found"))?;
+
+    let reports = ReportRepo::list(&state.db, market_id, true, true, false).await?;
+    let settlement = load_settlement(&state, market_id).await?;
+
+    let batch = sqlx::query!(
+        r#"
+        SELECT b.id, b.merkle_root, b.hash_algorithm, b.status, b.voided_at, b.created_at,
+               (SELECT COUNT(*) FROM batch_items i WHERE i.batch_id = b.id) AS "size!"
+        FROM batch_items bi
+        JOIN batches b ON b.id = bi.batch_id
+        WHERE bi.market_id = $1 AND b.voided_at IS NULL
+        "#,
+        market_id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .map(|row| BatchSummary {
+        id: row.id,
+        merkle_root: row.merkle_root,
+        hash_algorithm: HashAlgorithm::parse(&row.hash_algorithm).unwrap_or_default(),
+        size: row.size,
+        status: row.status,
+        voided_at: row.voided_at,
+        created_at: row.created_at,
+    });
+
+    let snapshot = MarketSnapshot {
+        version: SNAPSHOT_VERSION,
+        generated_at,
+        market,
+        reports,
+        settlement,
+        batch,
+    };
+
+    let signature = attestor.sign(&snapshot).await.map_err(|e| {
+        tracing::error!("signing snapshot of market {} failed: {}", market_id, e);
+        AppError::internal("Snapshot could not be signed")
+    })?;
+
+    Ok(Json(SignedMarketSnapshot {
+        snapshot,
+        signature,
+    }))
+}
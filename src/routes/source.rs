@@ -0,0 +1,59 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::auth::RequireAdmin;
+use crate::error::AppError;
+use crate::state::AppState;
+use crate::types::SourceAlert;
+
+/// What the anomaly pass has flagged for a source, newest first; cleared
+/// alerts stay listed with `cleared_at` set.
+#[utoipa::path(
+    get,
+    path = "/sources/{name}/alerts",
+    tag = "sources",
+    params(("name" = String, Path, description = "Reporting source")),
+    responses(
+        (status = 200, body = Vec<SourceAlert>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn list_source_alerts(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<SourceAlert>>, AppError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, source, kind, related_source, markets, details,
+               detected_at, last_detected_at, cleared_at
+        FROM source_alerts
+        WHERE source = $1
+        ORDER BY detected_at DESC, id
+        "#,
+        name
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let alerts = rows
+        .into_iter()
+        .map(|row| SourceAlert {
+            id: row.id,
+            source: row.source,
+            kind: row.kind,
+            related_source: Some(row.related_source).filter(|s| !s.is_empty()),
+            markets: row.markets,
+            details: row.details,
+            detected_at: row.detected_at,
+            last_detected_at: row.last_detected_at,
+            cleared_at: row.cleared_at,
+        })
+        .collect();
+
+    Ok(Json(alerts))
+}
@@ -0,0 +1,206 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{SubsecRound, Utc};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::auth::RequireAdmin;
+use crate::error::{AppError, AppJson};
+use crate::repo::{NewTemplate, TemplateRepo};
+use crate::routes::market::validate_settings;
+use crate::state::AppState;
+use crate::templates::next_live_slot;
+use crate::types::{CreateTemplateRequest, MarketTemplate};
+
+const MIN_CADENCE_SECS: i32 = 60;
+
+/// Defines a recurring market. The templates loop creates one market per
+/// slot, from `starts_at` every `cadence_secs`; slots already over are
+/// skipped.
+#[utoipa::path(
+    post,
+    path = "/templates",
+    tag = "templates",
+    request_body = CreateTemplateRequest,
+    responses(
+        (status = 201, body = MarketTemplate),
+        (status = 400, description = "Invalid schedule or market settings", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn create_template(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    AppJson(payload): AppJson<CreateTemplateRequest>,
+) -> Result<(StatusCode, Json<MarketTemplate>), AppError> {
+    let name = payload.name.trim();
+    if name.is_empty() || payload.question_pattern.trim().is_empty() {
+        return Err(AppError::bad_request(
+            "INVALID_TEMPLATE",
+            "name and question_pattern must not be empty",
+        ));
+    }
+
+    let starts_at = chrono::DateTime::parse_from_rfc3339(&payload.starts_at)
+        .map_err(|e| AppError::bad_request("INVALID_TIMESTAMP", format!("starts_at: {}", e)))?
+        .with_timezone(&Utc)
+        .trunc_subsecs(6);
+
+    if payload.cadence_secs < MIN_CADENCE_SECS {
+        return Err(AppError::bad_request(
+            "INVALID_CADENCE",
+            format!("cadence_secs must be at least {}", MIN_CADENCE_SECS),
+        ));
+    }
+
+    if payload.duration_secs <= 0 || payload.resolve_window_secs.is_some_and(|s| s <= 0) {
+        return Err(AppError::bad_request(
+            "INVALID_DURATION",
+            "duration_secs and resolve_window_secs must be positive",
+        ));
+    }
+
+    let lead_secs = payload.lead_secs.unwrap_or(0);
+    if lead_secs < 0 {
+        return Err(AppError::bad_request(
+            "INVALID_LEAD",
+            "lead_secs must not be negative",
+        ));
+    }
+
+    validate_settings(&state, &payload.settings)?;
+
+    let id = Uuid::new_v4();
+    let now = Utc::now().trunc_subsecs(6);
+    let next_run_at = next_live_slot(starts_at, payload.cadence_secs, payload.duration_secs, now);
+
+    let mut tx = state.db.begin().await?;
+
+    TemplateRepo::insert(
+        &mut *tx,
+        &NewTemplate {
+            id,
+            name,
+            question_pattern: &payload.question_pattern,
+            starts_at,
+            cadence_secs: payload.cadence_secs,
+            duration_secs: payload.duration_secs,
+            resolve_window_secs: payload.resolve_window_secs,
+            lead_secs,
+            settings: serde_json::to_value(&payload.settings).unwrap(),
+            next_run_at,
+            created_at: now,
+        },
+    )
+    .await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market_template", id, "created", &admin.actor).details(
+            serde_json::json!({
+                "question_pattern": payload.question_pattern,
+                "starts_at": starts_at,
+                "cadence_secs": payload.cadence_secs,
+            }),
+        ),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(MarketTemplate {
+            id,
+            name: name.to_string(),
+            question_pattern: payload.question_pattern,
+            starts_at,
+            cadence_secs: payload.cadence_secs,
+            duration_secs: payload.duration_secs,
+            resolve_window_secs: payload.resolve_window_secs,
+            lead_secs,
+            settings: payload.settings,
+            next_run_at,
+            active: true,
+            last_market_id: None,
+            created_at: now,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/templates",
+    tag = "templates",
+    responses((status = 200, body = Vec<MarketTemplate>))
+)]
+pub async fn list_templates(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<MarketTemplate>>, AppError> {
+    Ok(Json(TemplateRepo::list(&state.db).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/templates/{id}",
+    tag = "templates",
+    params(("id" = Uuid, Path, description = "Template id")),
+    responses(
+        (status = 200, body = MarketTemplate),
+        (status = 404, description = "Template not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_template(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MarketTemplate>, AppError> {
+    TemplateRepo::get(&state.db, id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::not_found("TEMPLATE_NOT_FOUND", "Template not found"))
+}
+
+/// Stops creating markets from a template. Markets it already created are
+/// left alone.
+#[utoipa::path(
+    delete,
+    path = "/templates/{id}",
+    tag = "templates",
+    params(("id" = Uuid, Path, description = "Template id")),
+    responses(
+        (status = 204, description = "Template deactivated"),
+        (status = 404, description = "Template not found or already inactive", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn deactivate_template(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let mut tx = state.db.begin().await?;
+
+    if !TemplateRepo::deactivate(&mut *tx, id).await? {
+        return Err(AppError::not_found(
+            "TEMPLATE_NOT_FOUND",
+            "Template not found",
+        ));
+    }
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("market_template", id, "deactivated", &admin.actor),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
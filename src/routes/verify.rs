@@ -0,0 +1,91 @@
+use axum::{Json, extract::State};
+
+use crate::error::{AppError, AppJson};
+use crate::state::AppState;
+use crate::types::{
+    PayloadVerdict, VerifyPayloadRequest, VerifySettlementsRequest, VerifySettlementsSummary,
+};
+use crate::verify::{verify_market, verify_payload};
+
+const MAX_MARKETS: i64 = 1000;
+
+#[utoipa::path(
+    post,
+    path = "/verify/settlements",
+    tag = "settlements",
+    request_body = VerifySettlementsRequest,
+    responses(
+        (status = 200, body = VerifySettlementsSummary),
+        (status = 400, description = "Too many markets, or no selection", body = ErrorResponse),
+    )
+)]
+pub async fn verify_settlements(
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<VerifySettlementsRequest>,
+) -> Result<Json<VerifySettlementsSummary>, AppError> {
+    let market_ids = match (payload.market_ids, payload.from, payload.to) {
+        (Some(ids), _, _) => {
+            if ids.len() as i64 > MAX_MARKETS {
+                return Err(AppError::bad_request(
+                    "TOO_MANY_MARKETS",
+                    format!("at most {} market ids per request", MAX_MARKETS),
+                ));
+            }
+            ids
+        }
+        (None, Some(from), Some(to)) => {
+            sqlx::query_scalar!(
+                r#"
+            SELECT market_id
+            FROM settlements
+            WHERE decided_at >= $1 AND decided_at <= $2
+            ORDER BY decided_at ASC
+            LIMIT $3
+            "#,
+                from,
+                to,
+                MAX_MARKETS
+            )
+            .fetch_all(&state.db)
+            .await?
+        }
+        _ => {
+            return Err(AppError::bad_request(
+                "INVALID_RANGE",
+                "provide market_ids or both from and to",
+            ));
+        }
+    };
+
+    let mut results = Vec::with_capacity(market_ids.len());
+    for market_id in market_ids {
+        let verdict = verify_market(&state, market_id, payload.check_chain).await?;
+        results.push(verdict);
+    }
+
+    let count = |v: &str| results.iter().filter(|r| r.verdict == v).count();
+
+    Ok(Json(VerifySettlementsSummary {
+        total: results.len(),
+        ok: count("OK"),
+        mismatched: count("MISMATCH"),
+        not_settled: count("NOT_SETTLED"),
+        results,
+    }))
+}
+
+/// Checks a settlement payload published elsewhere against what the oracle
+/// produced: its hash, the stored settlement and the batch Merkle proof.
+#[utoipa::path(
+    post,
+    path = "/verify",
+    tag = "settlements",
+    request_body = VerifyPayloadRequest,
+    responses((status = 200, body = PayloadVerdict))
+)]
+pub async fn verify_settlement_payload(
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<VerifyPayloadRequest>,
+) -> Result<Json<PayloadVerdict>, AppError> {
+    Ok(Json(verify_payload(&state, &payload).await?))
+}
@@ -0,0 +1,32 @@
+use axum::{Json, extract::State};
+
+use crate::auth::RequireAdmin;
+use crate::state::AppState;
+use crate::types::ChainWallets;
+
+#[utoipa::path(
+    get,
+    path = "/wallets",
+    tag = "chains",
+    responses(
+        (status = 200, body = Vec<ChainWallets>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn list_wallets(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+) -> Json<Vec<ChainWallets>> {
+    let wallets = state
+        .chains
+        .targets()
+        .map(|t| ChainWallets {
+            chain_id: t.config.chain_id,
+            wallets: t.wallets.health(),
+        })
+        .collect();
+
+    Json(wallets)
+}
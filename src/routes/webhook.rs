@@ -0,0 +1,276 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use chrono::{SubsecRound, Utc};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::auth::RequireAdmin;
+use crate::error::{AppError, AppJson};
+use crate::events::Event;
+use crate::state::AppState;
+use crate::types::{CreateWebhookRequest, Webhook, WebhookDelivery, WebhookDeliveryQuery};
+
+const MIN_SECRET_LEN: usize = 16;
+const DEFAULT_DELIVERY_LIMIT: i64 = 100;
+
+/// Subscribes a URL to lifecycle events. Each matching event is POSTed as
+/// JSON signed with the secret; see `webhooks::sign`.
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, body = Webhook),
+        (status = 400, description = "Invalid URL, secret or event type", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    AppJson(payload): AppJson<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<Webhook>), AppError> {
+    let url = reqwest::Url::parse(&payload.url)
+        .map_err(|e| AppError::bad_request("INVALID_URL", format!("invalid url: {}", e)))?;
+
+    if url.scheme() != "https" && url.scheme() != "http" {
+        return Err(AppError::bad_request(
+            "INVALID_URL",
+            "url must be http or https",
+        ));
+    }
+
+    if payload.secret.len() < MIN_SECRET_LEN {
+        return Err(AppError::bad_request(
+            "INVALID_SECRET",
+            format!("secret must be at least {} bytes", MIN_SECRET_LEN),
+        ));
+    }
+
+    let mut event_types: Vec<String> = payload
+        .event_types
+        .iter()
+        .map(|t| t.to_lowercase())
+        .collect();
+    event_types.sort();
+    event_types.dedup();
+
+    if event_types.is_empty() {
+        return Err(AppError::bad_request(
+            "INVALID_EVENT_TYPE",
+            "event_types must not be empty",
+        ));
+    }
+
+    if let Some(unknown) = event_types
+        .iter()
+        .find(|t| !Event::KINDS.contains(&t.as_str()))
+    {
+        return Err(AppError::bad_request(
+            "INVALID_EVENT_TYPE",
+            format!("unknown event type {}", unknown),
+        )
+        .details(serde_json::json!({ "allowed": Event::KINDS })));
+    }
+
+    let id = Uuid::new_v4();
+    let now = Utc::now().trunc_subsecs(6);
+
+    let mut tx = state.db.begin().await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO webhooks (id, url, secret, event_types, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        id,
+        url.as_str(),
+        payload.secret,
+        &event_types,
+        now
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("webhook", id, "created", &admin.actor)
+            .details(serde_json::json!({ "url": url.as_str(), "event_types": event_types })),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(Webhook {
+            id,
+            url: url.to_string(),
+            event_types,
+            created_at: now,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, body = Vec<Webhook>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+) -> Result<Json<Vec<Webhook>>, AppError> {
+    let rows = sqlx::query!(
+        "SELECT id, url, event_types, created_at FROM webhooks ORDER BY created_at ASC"
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let webhooks = rows
+        .into_iter()
+        .map(|row| Webhook {
+            id: row.id,
+            url: row.url,
+            event_types: row.event_types,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(Json(webhooks))
+}
+
+/// Unsubscribes and drops the webhook's delivery log.
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    responses(
+        (status = 204, description = "Webhook removed"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let mut tx = state.db.begin().await?;
+
+    let deleted = sqlx::query!("DELETE FROM webhooks WHERE id = $1", id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+        > 0;
+
+    if !deleted {
+        return Err(AppError::not_found(
+            "WEBHOOK_NOT_FOUND",
+            "Webhook not found",
+        ));
+    }
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("webhook", id, "deleted", &admin.actor),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delivery log for one webhook, newest first.
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id"), WebhookDeliveryQuery),
+    responses(
+        (status = 200, body = Vec<WebhookDelivery>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+    ),
+    security(("bearer_token" = []))
+)]
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+    Query(query): Query<WebhookDeliveryQuery>,
+) -> Result<Json<Vec<WebhookDelivery>>, AppError> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM webhooks WHERE id = $1) AS "exists!""#,
+        id
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    if !exists {
+        return Err(AppError::not_found(
+            "WEBHOOK_NOT_FOUND",
+            "Webhook not found",
+        ));
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+        .clamp(1, DEFAULT_DELIVERY_LIMIT);
+    let status = query.status.map(|s| s.to_uppercase());
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, webhook_id, event_id, event_type, payload, status, attempts, next_attempt_at,
+               last_status_code, last_error, delivered_at, created_at
+        FROM webhook_deliveries
+        WHERE webhook_id = $1
+          AND ($2::TEXT IS NULL OR status = $2)
+        ORDER BY created_at DESC, event_id DESC
+        LIMIT $3
+        "#,
+        id,
+        status,
+        limit
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let deliveries = rows
+        .into_iter()
+        .map(|row| WebhookDelivery {
+            id: row.id,
+            webhook_id: row.webhook_id,
+            event_id: row.event_id,
+            event_type: row.event_type,
+            payload: row.payload,
+            status: row.status,
+            attempts: row.attempts,
+            next_attempt_at: row.next_attempt_at,
+            last_status_code: row.last_status_code,
+            last_error: row.last_error,
+            delivered_at: row.delivered_at,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(Json(deliveries))
+}
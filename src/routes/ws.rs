@@ -0,0 +1,45 @@
+use axum::{
+    extract::{
+        State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::Response,
+};
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+
+use crate::events::Event;
+use crate::state::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "events",
+    responses((status = 101, description = "WebSocket stream of lifecycle events as JSON"))
+)]
+pub async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    let rx = state.events.subscribe();
+    ws.on_upgrade(move |socket| stream_events(socket, rx))
+}
+
+async fn stream_events(mut socket: WebSocket, mut rx: Receiver<Event>) {
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(event) => {
+                    let text = serde_json::to_string(&event).unwrap();
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("ws subscriber lagged, skipped {} events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow};
+use axum::extract::ConnectInfo;
+use axum::{Extension, Router};
+use hyper::server::conn::http1;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::{Certificate, PrivateKey, ServerConfig};
+
+use crate::config::AppConfig;
+
+/// Binds `config.listen_addr()` and serves `app`, over HTTPS when
+/// `config.tls` names a certificate and key.
+pub async fn serve(app: Router, config: &AppConfig) -> Result<()> {
+    let addr = config.listen_addr();
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("binding {}", addr))?;
+
+    let Some((cert_path, key_path)) = config.tls.paths() else {
+        tracing::info!("Listening on http://{}", addr);
+        // peer addresses feed the per-IP rate limits
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let acceptor = acceptor(cert_path, key_path)?;
+    tracing::info!("Listening on https://{}", addr);
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                tracing::warn!("accept failed: {}", e);
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let app = app.clone().layer(Extension(ConnectInfo(peer)));
+
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::debug!("TLS handshake with {} failed: {}", peer, e);
+                    return;
+                }
+            };
+
+            let served = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), TowerToHyperService::new(app))
+                .with_upgrades()
+                .await;
+
+            if let Err(e) = served {
+                tracing::debug!("connection from {} ended: {}", peer, e);
+            }
+        });
+    }
+}
+
+fn acceptor(cert_path: &Path, key_path: &Path) -> Result<TlsAcceptor> {
+    let certs = rustls_pemfile::certs(&mut reader(cert_path)?)
+        .with_context(|| format!("parsing {}", cert_path.display()))?;
+    if certs.is_empty() {
+        return Err(anyhow!("no certificates in {}", cert_path.display()));
+    }
+
+    let key = private_key(key_path)?;
+
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs.into_iter().map(Certificate).collect(), key)
+        .context("building TLS config")?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+fn private_key(path: &Path) -> Result<PrivateKey> {
+    let mut reader = reader(path)?;
+
+    while let Some(item) = rustls_pemfile::read_one(&mut reader)
+        .with_context(|| format!("parsing {}", path.display()))?
+    {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => return Ok(PrivateKey(key)),
+            _ => continue,
+        }
+    }
+
+    Err(anyhow!("no private key in {}", path.display()))
+}
+
+fn reader(path: &Path) -> Result<BufReader<File>> {
+    let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    Ok(BufReader::new(file))
+}
@@ -1,6 +1,58 @@
+use std::sync::Arc;
+
 use sqlx::PgPool;
 
+use crate::auth::AuthConfig;
+use crate::cache::ReadCache;
+use crate::config::AppConfig;
+#[cfg(feature = "eth")]
+use crate::eth::attest::Attestor;
+#[cfg(feature = "eth")]
+use crate::eth::chains::ChainRegistry;
+#[cfg(feature = "eth")]
+use crate::eth::sender::EthSender;
+use crate::events::EventBus;
+use crate::leader::Leadership;
+use crate::loops::LoopRegistry;
+use crate::rate_limit::RateLimiter;
+
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
-}
\ No newline at end of file
+    pub config: Arc<AppConfig>,
+    #[cfg(feature = "eth")]
+    pub chains: Arc<ChainRegistry>,
+    #[cfg(feature = "eth")]
+    pub sender: Arc<EthSender>,
+    // signs market snapshots; None leaves them off
+    #[cfg(feature = "eth")]
+    pub attestor: Option<Arc<Attestor>>,
+    pub events: EventBus,
+    pub loops: LoopRegistry,
+    pub leader: Leadership,
+    pub auth: AuthConfig,
+    pub rate_limiter: RateLimiter,
+    pub cache: Arc<ReadCache>,
+}
+
+impl AppState {
+    /// Chain new settlements are pinned to when the market names none.
+    pub fn default_chain_id(&self) -> Option<u64> {
+        #[cfg(feature = "eth")]
+        return self.chains.default_chain_id();
+
+        #[cfg(not(feature = "eth"))]
+        None
+    }
+
+    pub fn has_chain(&self, chain_id: u64) -> bool {
+        #[cfg(feature = "eth")]
+        return self.chains.get(chain_id).is_some();
+
+        #[cfg(not(feature = "eth"))]
+        {
+            let _ = chain_id;
+            false
+        }
+    }
+}
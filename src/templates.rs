@@ -0,0 +1,145 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::repo::TemplateRepo;
+use crate::routes::market::insert_market;
+use crate::state::AppState;
+use crate::types::{CreateMarketRequest, MarketTemplate};
+
+// templates handled per pass
+const BATCH_SIZE: i64 = 50;
+
+/// Creates the next market for every template whose slot has come within its
+/// lead time. Each instance is keyed `template:<id>:<slot>`, so a pass that
+/// overlaps another (or a restart mid-pass) can't create a slot twice.
+pub async fn templates_loop(state: AppState) {
+    let interval = state.config.templates.interval();
+
+    loop {
+        let run = state.loops.start("templates", interval);
+
+        match instantiate_due(&state).await {
+            Ok(created) => run.finish(created),
+            Err(e) => {
+                tracing::error!("template pass failed: {}", e);
+                run.fail(e);
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Returns the number of markets created.
+async fn instantiate_due(state: &AppState) -> Result<usize, sqlx::Error> {
+    let now = Utc::now();
+    let templates = TemplateRepo::due(&state.db, now, BATCH_SIZE).await?;
+
+    let mut created = 0;
+
+    for template in templates {
+        let cadence = Duration::seconds(template.cadence_secs.into());
+        let lead = Duration::seconds(template.lead_secs.into());
+
+        // slots missed while the service was down are skipped, not back-filled
+        let slot = next_live_slot(
+            template.next_run_at,
+            template.cadence_secs,
+            template.duration_secs,
+            now,
+        );
+
+        if slot - lead > now {
+            if slot != template.next_run_at {
+                tracing::warn!("template {} skipped slots up to {}", template.id, slot);
+            }
+            TemplateRepo::advance(&state.db, template.id, template.next_run_at, slot, None).await?;
+            continue;
+        }
+
+        let market_id = match instantiate(state, &template, slot).await {
+            Ok(id) => {
+                created += 1;
+                Some(id)
+            }
+            Err(e) => {
+                // a definition that stopped validating (a chain removed, say)
+                // would otherwise fail every pass
+                tracing::error!("template {} slot {} not created: {}", template.id, slot, e);
+                None
+            }
+        };
+
+        TemplateRepo::advance(
+            &state.db,
+            template.id,
+            template.next_run_at,
+            slot + cadence,
+            market_id,
+        )
+        .await?;
+    }
+
+    Ok(created)
+}
+
+async fn instantiate(
+    state: &AppState,
+    template: &MarketTemplate,
+    slot: DateTime<Utc>,
+) -> Result<Uuid, String> {
+    let closes_at = slot + Duration::seconds(template.duration_secs.into());
+    let resolve_deadline = template
+        .resolve_window_secs
+        .map(|secs| closes_at + Duration::seconds(secs.into()));
+
+    let request = CreateMarketRequest {
+        question: render_question(&template.question_pattern, slot),
+        opens_at: Some(slot.to_rfc3339()),
+        reporting_opens_at: None,
+        closes_at: Some(closes_at.to_rfc3339()),
+        resolve_deadline: resolve_deadline.map(|d| d.to_rfc3339()),
+        settings: template.settings.clone(),
+        idempotency_key: None,
+    };
+
+    let key = format!("template:{}:{}", template.id, slot.timestamp());
+
+    let (_, market) = insert_market(state, "templates", request, Some(key))
+        .await
+        .map_err(|e| format!("{}: {}", e.code, e.message))?;
+
+    tracing::info!(
+        "Template {} created market {} for {}",
+        template.id,
+        market.id,
+        slot
+    );
+
+    Ok(market.id)
+}
+
+/// The first slot from `slot` on whose market would still be open at `now`.
+pub fn next_live_slot(
+    slot: DateTime<Utc>,
+    cadence_secs: i32,
+    duration_secs: i32,
+    now: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let closes_at = slot + Duration::seconds(duration_secs.into());
+    if closes_at > now {
+        return slot;
+    }
+
+    let cadence = i64::from(cadence_secs.max(1));
+    let behind = (now - closes_at).num_seconds() / cadence + 1;
+
+    slot + Duration::seconds(behind * cadence)
+}
+
+/// Fills `{date}` (YYYY-MM-DD) and `{time}` (HH:MM) from the slot, in UTC.
+pub fn render_question(pattern: &str, slot: DateTime<Utc>) -> String {
+    pattern
+        .replace("{date}", &slot.format("%Y-%m-%d").to_string())
+        .replace("{time}", &slot.format("%H:%M").to_string())
+}
@@ -1,44 +1,980 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-#[derive(Serialize)]
+use crate::auth::Role;
+use crate::close_condition::CloseCondition;
+#[cfg(feature = "eth")]
+use crate::eth::wallets::WalletHealth;
+#[cfg(feature = "feeds")]
+use crate::feeds::FeedSource;
+use crate::linked::GroupInvariant;
+use crate::loops::LoopStatus;
+use crate::outcome_type::OutcomeType;
+use crate::payout::{Payout, PayoutFormula};
+use crate::proof::HashAlgorithm;
+use crate::resolution::{
+    LateReportPolicy, MarketRequirements, SelfReportPolicy, SettlementInputs, Strategy,
+    UnmetRequirement,
+};
+use crate::value_type::{OutcomeFormat, ValueType};
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct Market {
     pub id: Uuid,
     pub question: String,
-    pub closes_at: DateTime<Utc>,
+    pub opens_at: Option<DateTime<Utc>>,
+    // reports are accepted from here (or opens_at) until closes_at
+    pub reporting_opens_at: Option<DateTime<Utc>>,
+    // None on a market that closes on an event, until it is closed
+    pub closes_at: Option<DateTime<Utc>>,
+    // None on an event-closed market that didn't set one, until it is closed
+    pub resolve_deadline: Option<DateTime<Utc>>,
     pub status: String,
+    pub outcome_type: OutcomeType,
+    pub value_type: ValueType,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub resolution: Strategy,
+    pub self_report_policy: SelfReportPolicy,
+    pub late_report_policy: LateReportPolicy,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub chain_id: Option<u64>,
+    // false when the settlement stays off chain: never batched nor submitted
+    pub anchor_on_chain: bool,
+    pub close_notice_secs: Option<Vec<i32>>,
+    pub close_conditions: Vec<CloseCondition>,
+    pub close_trigger: Option<CloseCondition>,
+    // given when an operator closed the market through /markets/{id}/close
+    pub closed_reason: Option<String>,
+    pub requirements: Option<MarketRequirements>,
+    // SPREAD tolerance in basis points: the market's own or the server default
+    pub consensus_bps: u32,
+    // set by admins; once closed, higher priorities resolve first
+    pub priority: i32,
+    // places the outcome is rounded to and anchored at; None keeps it at 8
+    // and anchors at the value type's
+    pub decimals: Option<u32>,
+    // splits the settled outcome into payouts per side
+    pub payout: Option<PayoutFormula>,
+    pub group_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
+    // set once the market has moved to markets_archive
+    pub archived_at: Option<DateTime<Utc>>,
+}
+
+/// `GET /markets/{id}`: the market with what its reports currently add up to.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MarketDetail {
+    #[serde(flatten)]
+    pub market: Market,
+    pub report_count: i64,
+    // dry run of the resolver; absent once settled
+    pub preview: Option<ResolutionPreview>,
+    pub settlement: Option<SettlementView>,
+}
+
+/// What the resolver would decide from the reports as they stand. Timing
+/// (closes_at, close conditions) isn't considered.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ResolutionPreview {
+    // in-bounds reports left after outlier filtering
+    pub counted: usize,
+    // (max - min) / max(|min|, |max|) over counted values; scalar markets only
+    pub spread: Option<f64>,
+    pub requirements_met: bool,
+    pub would_resolve: bool,
+    pub outcome: Option<f64>,
+    pub winning_option: Option<String>,
+    // reports that would be flagged as outliers
+    pub outliers: Vec<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MarketQuery {
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    pub status: Option<String>,
+    /// Also list archived and deleted markets.
+    pub include_archived: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ArchivedQuery {
+    /// Also look in the archive tables.
+    pub include_archived: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportListQuery {
+    /// Also look in the archive tables.
+    pub include_archived: Option<bool>,
+    /// Only reports flagged as outliers or deviations.
+    pub flagged: Option<bool>,
+    /// Also list reports their source retracted.
+    pub include_retracted: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportAggregateQuery {
+    /// Bucket width: 1m (default), 5m or 1h.
+    pub interval: Option<String>,
 }
 
-#[derive(Serialize, Clone)]
+/// Report values received within one bucket, live and archived alike.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ReportBucket {
+    pub bucket_start: DateTime<Utc>,
+    pub count: i64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct Report {
     pub id: Uuid,
     pub market_id: Uuid,
     pub source: String,
     pub value: f64,
+    // submitted by one of our own feed adapters rather than an external reporter
+    pub self_reported: bool,
+    pub provenance: Option<Provenance>,
+    pub confidence: Option<f64>,
+    pub stake: Option<f64>,
+    pub reporter_address: Option<String>,
+    // signature over the report recovered to reporter_address
+    pub verified: bool,
+    // accepted after closes_at, during the close grace period
+    pub late: bool,
+    // weight this report carried in the settled outcome; settlement views only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
     pub created_at: DateTime<Utc>,
+    // last PUT, or the last feed reading that replaced the value
+    pub updated_at: Option<DateTime<Utc>>,
+    // set once the source took the report back; it no longer counts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retracted_at: Option<DateTime<Utc>>,
+}
+
+/// `PUT /markets/{id}/reports/{report_id}`: a new value for a source's
+/// report, with the same optional weighting and signature fields as a new
+/// report.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct UpdateReportRequest {
+    pub value: f64,
+    pub provenance: Option<Provenance>,
+    pub confidence: Option<f64>,
+    pub stake: Option<f64>,
+    pub signature: Option<String>,
+    pub reporter_address: Option<String>,
+    pub timestamp: Option<i64>,
+}
+
+/// Enough about the upstream fetch for an auditor to re-request the same URL
+/// and compare what comes back.
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct Provenance {
+    pub source_url: String,
+    pub http_status: Option<u16>,
+    // hex sha256 of the raw response body
+    pub response_sha256: String,
+    pub fetched_at: DateTime<Utc>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct CreateReportRequest {
     pub source: String,
     pub value: f64,
     pub idempotency_key: String,
+    pub provenance: Option<Provenance>,
+    // reporter's own confidence in the value, 0..1
+    pub confidence: Option<f64>,
+    // amount the reporter has at stake on this value
+    pub stake: Option<f64>,
+    // EIP-191 signature by reporter_address over (market_id, value, timestamp);
+    // see eth::verify::report_message
+    pub signature: Option<String>,
+    pub reporter_address: Option<String>,
+    // unix seconds the reporter signed at
+    pub timestamp: Option<i64>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct CreateMarketRequest {
     pub question: String,
-    // RFC3339 string from client
-    pub closes_at: String,
+    // RFC3339 strings from client; opens_at in the future schedules the market
+    pub opens_at: Option<String>,
+    // no reports before this; must fall between opens_at and closes_at
+    pub reporting_opens_at: Option<String>,
+    // omitted for a market that closes on an event: the auto-closer leaves it
+    // OPEN until POST /markets/{id}/close
+    pub closes_at: Option<String>,
+    // still unresolved by then means UNRESOLVED; defaults to resolver.resolve_window_secs
+    // after the market closes
+    pub resolve_deadline: Option<String>,
+    #[serde(flatten)]
+    pub settings: MarketSettings,
+    // same as the Idempotency-Key header; the header wins if both are sent
+    pub idempotency_key: Option<String>,
+}
+
+/// Everything about a market but its question and schedule.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct MarketSettings {
+    // BINARY/CATEGORICAL reports submit an option index as their value
+    #[serde(default)]
+    pub outcome_type: OutcomeType,
+    #[serde(default)]
+    pub value_type: ValueType,
+    // inclusive bounds on accepted report values
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    #[serde(default)]
+    pub resolution: Strategy,
+    #[serde(default)]
+    pub self_report_policy: SelfReportPolicy,
+    // whether reports taken during the close grace period count
+    #[serde(default)]
+    pub late_report_policy: LateReportPolicy,
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // chain the settlement is anchored on; omitted uses the default chain
+    pub chain_id: Option<u64>,
+    // false skips batching and chain submission; the settlement hash is
+    // still served. Omitted means true
+    pub anchor_on_chain: Option<bool>,
+    // seconds before closes_at to announce "closing soon"; omitted uses the server default
+    pub close_notice_secs: Option<Vec<i32>>,
+    // any one of these being met closes the market before closes_at
+    #[serde(default)]
+    pub close_conditions: Vec<CloseCondition>,
+    // sources and report count that must be present before the strategy runs
+    pub requirements: Option<MarketRequirements>,
+    // SPREAD tolerance in basis points (100 = 1%); omitted uses the server default
+    pub consensus_bps: Option<u32>,
+    // 0-8 places the outcome is rounded to and anchored on chain at, e.g. 2
+    // for prices; omitted keeps 8 and anchors at the value type's
+    pub decimals: Option<u32>,
+    // NUMERIC markets only; the settlement then carries each side's payout
+    pub payout: Option<PayoutFormula>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct ReportFlag {
+    pub report_id: Uuid,
+    // OUTLIER, or DEVIATION when it strayed from the reports before it
+    pub flag: String,
+    // value, median and the threshold it exceeded
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct SettlementView {
     pub market_id: Uuid,
     pub outcome: f64,
+    // outcome * 10^8, exact
+    pub outcome_e8: i64,
+    pub outcome_type: OutcomeType,
+    // label of the winning option on BINARY/CATEGORICAL markets, whose
+    // outcome is that option's index
+    pub winning_option: Option<String>,
     pub decided_at: DateTime<Utc>,
+    // 1 unless an operator has overridden the outcome; see /settlement/history
+    pub revision: i32,
+    // PROPOSED until the anchoring transaction has enough confirmations,
+    // then FINAL
+    pub status: String,
+    pub finalized_at: Option<DateTime<Utc>>,
     pub reports: Vec<Report>,
+    // reports the resolver left out of the outcome, e.g. as outliers
+    pub excluded: Vec<ReportFlag>,
+    // the reports and strategy the outcome was decided from, as of
+    // decided_at; absent for operator outcomes
+    pub inputs: Option<SettlementInputs>,
+    pub hash: String,
+    // proof::SETTLEMENT_ENCODING_VERSION the hash was computed with
+    pub hash_version: u8,
+    // of both the hash and the leaf
+    pub hash_algorithm: HashAlgorithm,
+    // places the outcome was anchored on chain at; markets that set their own
+    // decimals were also rounded to them
+    pub decimals: u32,
+    // hex leaf anchored on chain and in the batch tree
+    pub leaf: String,
+    // path from leaf to its batch root; absent until batched, when the
+    // market isn't anchored on chain, or when the batch holds an earlier
+    // revision
+    pub inclusion: Option<InclusionProof>,
+    pub chain: Option<ChainSubmission>,
+    // per-side payout fractions, when the market defines a formula
+    pub payout: Option<Payout>,
+}
+
+/// One entry of `GET /markets/{id}/settlement/history`.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SettlementRevision {
+    pub revision: i32,
+    pub outcome: f64,
+    pub outcome_e8: i64,
+    pub decided_at: DateTime<Utc>,
+    // unset if it was superseded while still PROPOSED
+    pub finalized_at: Option<DateTime<Utc>>,
+    // unset on the live settlement
+    pub superseded_at: Option<DateTime<Utc>>,
+    pub superseded_by: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SettlementExportQuery {
+    // csv (default) or jsonl
+    pub format: Option<String>,
+    // inclusive bounds on decided_at
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// One line of `/settlements/export`.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SettlementExportRow {
+    pub market_id: Uuid,
+    pub outcome: f64,
+    pub outcome_e8: i64,
+    pub decided_at: DateTime<Utc>,
+    pub leaf: String,
+    pub batch_id: Option<Uuid>,
+    pub merkle_root: Option<String>,
+    pub tx_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub hash_algorithm: HashAlgorithm,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SettlementListQuery {
+    // inclusive bounds on decided_at
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    // next_cursor of the previous page
+    pub cursor: Option<String>,
+}
+
+/// One settlement of `GET /settlements`.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SettlementListItem {
+    pub market_id: Uuid,
+    pub question: String,
+    pub outcome: f64,
+    pub outcome_e8: i64,
+    pub decided_at: DateTime<Utc>,
+    pub revision: i32,
+    // PROPOSED or FINAL
+    pub status: String,
+    pub batch_id: Option<Uuid>,
+    // OFF_CHAIN, UNBATCHED, BATCHED until its outbox job exists, then that
+    // job's status (PENDING, INTENT, SENT, CONFIRMED, FAILED, ABANDONED)
+    pub chain_status: String,
+    pub tx_hash: Option<String>,
+    pub block_number: Option<i64>,
+    // set once the market has moved to markets_archive
+    pub archived: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SettlementPage {
+    // oldest decided first
+    pub settlements: Vec<SettlementListItem>,
+    // absent on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct ChainSubmission {
+    pub tx_hash: String,
+    pub block_number: Option<i64>,
+    pub gas_used: Option<i64>,
+    pub submitter: String,
+    pub submitted_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct OutboxJob {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub retries: i32,
+    pub last_error: Option<String>,
+    pub intent_tx_hash: Option<String>,
+    pub claimed_by: Option<String>,
+    pub claimed_at: Option<DateTime<Utc>>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    // claimed highest first within its chain's queue
+    pub priority: i32,
+    // None: the default chain
+    pub chain_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OutboxQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChangesQuery {
+    pub cursor: Option<i64>,
+    // e.g. "30s", "500ms" or plain seconds
+    pub wait: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ChangeEvent {
+    pub id: i64,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ChangesPage {
+    pub events: Vec<ChangeEvent>,
+    pub next_cursor: i64,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MetricsHistoryQuery {
+    // e.g. "90d" or "12h"
+    pub window: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MetricsSnapshot {
+    pub captured_at: DateTime<Utc>,
+    pub reports_24h: i64,
+    pub settlements_24h: i64,
+    pub batches_24h: i64,
+    pub avg_batch_size: Option<f64>,
+    pub db_size_bytes: i64,
+}
+
+/// One of the in-process read caches; counters are since startup.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CacheStats {
+    pub name: String,
+    // false when its TTL is 0
+    pub enabled: bool,
+    pub ttl_secs: u64,
+    pub entries: u64,
+    pub hits: u64,
+    pub misses: u64,
+    // hits / (hits + misses); absent before the first lookup
+    pub hit_rate: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Default, ToSchema)]
+pub struct CancelMarketRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CloseMarketRequest {
+    pub closed_reason: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ExtendMarketRequest {
+    pub closes_at: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SetPriorityRequest {
+    // 0 is the default; negative values queue behind everything else
+    pub priority: i32,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ForceResolveRequest {
+    pub outcome: f64,
+    pub reason: Option<String>,
+}
+
+#[cfg(feature = "feeds")]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateFeedRequest {
+    pub source: FeedSource,
+    pub interval_secs: Option<i32>,
+}
+
+#[cfg(feature = "feeds")]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MarketFeed {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub source: FeedSource,
+    pub interval_secs: i32,
+    pub last_fetched_at: Option<DateTime<Utc>>,
+    pub last_value: Option<f64>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateMarketGroupRequest {
+    pub market_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub invariant: GroupInvariant,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MarketGroup {
+    pub id: Uuid,
+    pub invariant: GroupInvariant,
+    pub status: String,
+    pub blocked_reason: Option<String>,
+    pub market_ids: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BatchQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BatchSummary {
+    pub id: Uuid,
+    pub merkle_root: String,
+    // nodes of the tree; each leaf is hashed as its settlement says
+    pub hash_algorithm: HashAlgorithm,
+    pub size: i64,
+    // PENDING, SUBMITTED, CONFIRMED or FAILED
+    pub status: String,
+    // set once a FAILED batch's settlements were released for re-batching
+    pub voided_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BatchItem {
+    pub market_id: Uuid,
+    pub outcome: f64,
+    pub outcome_e8: i64,
+    pub decided_at: DateTime<Utc>,
+    pub leaf_index: Option<i32>,
+    // hex leaf hash and the hex bytes it hashes, as stored at batching
+    pub leaf_hash: Option<String>,
+    pub leaf_data: Option<String>,
+    pub outbox_status: Option<String>,
+    pub tx_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BatchDetail {
+    pub id: Uuid,
+    pub merkle_root: String,
+    pub hash_algorithm: HashAlgorithm,
+    pub created_at: DateTime<Utc>,
+    // PENDING, SUBMITTED, CONFIRMED or FAILED, as of the last batcher pass
+    pub status: String,
+    pub voided_at: Option<DateTime<Utc>>,
+    // ANCHORED when every item is on-chain, PARTIAL when some are, else PENDING
+    pub chain_status: String,
+    pub items: Vec<BatchItem>,
+}
+
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    pub entity_id: Option<Uuid>,
+    pub entity_type: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AuditRecord {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub action: String,
+    pub actor: String,
+    pub before_state: Option<String>,
+    pub after_state: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A suspicious reporting pattern the anomaly pass found for a source.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SourceAlert {
+    pub id: Uuid,
+    pub source: String,
+    // ALWAYS_LAST, COPYING or BIASED
+    pub kind: String,
+    // the source whose values it repeats, for COPYING
+    pub related_source: Option<String>,
+    // markets the finding is based on
+    pub markets: i32,
+    // the shares and deviation behind it
+    pub details: serde_json::Value,
+    pub detected_at: DateTime<Utc>,
+    // the latest pass that still saw it
+    pub last_detected_at: DateTime<Utc>,
+    // set once a pass no longer sees it
+    pub cleared_at: Option<DateTime<Utc>>,
+}
+
+/// Why a CLOSED market has or hasn't resolved yet, as far as its
+/// requirements go; the strategy itself may still be waiting on agreement.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ResolutionStatus {
+    pub market_id: Uuid,
+    pub status: String,
+    // reports within the market bounds
+    pub reports: usize,
+    pub requirements: Option<MarketRequirements>,
+    pub unmet: Vec<UnmetRequirement>,
+    pub requirements_met: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MarketOutcomeFormat {
+    pub market_id: Uuid,
+    #[serde(flatten)]
+    pub format: OutcomeFormat,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct VerifySettlementsRequest {
+    pub market_ids: Option<Vec<Uuid>>,
+    // alternatively every settlement decided in [from, to]
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    // also read the contract for each market (slow, needs RPC_URL)
+    #[serde(default)]
+    pub check_chain: bool,
+}
+
+#[derive(Serialize, Deserialize, Default, ToSchema)]
+pub struct VerificationChecks {
+    pub leaf_matches_outbox: Option<bool>,
+    pub batch_id: Option<Uuid>,
+    // the leaf stored at batching against today's encoding of the settlement
+    pub leaf_matches_batch: Option<bool>,
+    pub batch_root_matches: Option<bool>,
+    pub anchored: Option<bool>,
+    pub on_chain_matches: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SettlementVerdict {
+    pub market_id: Uuid,
+    // OK, MISMATCH or NOT_SETTLED
+    pub verdict: String,
+    pub settlement_hash: Option<String>,
+    pub leaf: Option<String>,
+    pub checks: VerificationChecks,
+    pub issues: Vec<String>,
+}
+
+/// A settlement as published, e.g. copied from `GET /markets/{id}/settlement`.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct VerifyPayloadRequest {
+    pub market_id: Uuid,
+    pub outcome: f64,
+    pub decided_at: DateTime<Utc>,
+    // in the order they were hashed, archived reports included
+    pub reports: Vec<PayloadReport>,
+    // hex settlement hash, 0x optional
     pub hash: String,
-}
\ No newline at end of file
+    // defaults to the stored settlement's
+    pub hash_algorithm: Option<HashAlgorithm>,
+}
+
+/// The report fields the settlement hash covers; others are ignored.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PayloadReport {
+    pub id: Uuid,
+    pub source: String,
+    pub value: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Default, ToSchema)]
+pub struct PayloadChecks {
+    // the payload's own fields hash to its hash
+    pub hash_matches_payload: bool,
+    // against the stored settlement; None until the market settles
+    pub outcome_matches: Option<bool>,
+    pub decided_at_matches: Option<bool>,
+    pub hash_matches_oracle: Option<bool>,
+    // the payload's leaf proves into the stored batch root; None until batched
+    pub batch_proof_valid: Option<bool>,
+}
+
+/// Merkle path from a settlement's leaf to its batch root, built from the
+/// leaves stored when the batch was cut.
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct InclusionProof {
+    pub batch_id: Uuid,
+    pub merkle_root: String,
+    // what each pair of nodes is hashed with on the way up
+    pub hash_algorithm: HashAlgorithm,
+    pub leaf_index: usize,
+    pub leaf: String,
+    // encoding the leaf was built with: 1 for batches cut before leaves
+    // were stored, else the current one
+    pub leaf_version: u8,
+    // sibling hashes from the leaf up; an odd node out is its own sibling
+    pub siblings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PayloadVerdict {
+    pub market_id: Uuid,
+    // OK, MISMATCH or NOT_SETTLED
+    pub verdict: String,
+    pub computed_hash: String,
+    pub leaf: String,
+    pub checks: PayloadChecks,
+    pub inclusion: Option<InclusionProof>,
+    pub issues: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct VerifySettlementsSummary {
+    pub total: usize,
+    pub ok: usize,
+    pub mismatched: usize,
+    pub not_settled: usize,
+    pub results: Vec<SettlementVerdict>,
+}
+
+/// `GET /outbox/{id}/estimate`: a dry run of the job's chain submission.
+/// Amounts are decimal wei strings; the gas and cost fields are absent when
+/// the call would revert.
+#[cfg(feature = "eth")]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct OutboxEstimate {
+    pub outbox_id: Uuid,
+    pub chain_id: u64,
+    // submitter wallet the estimate was run from
+    pub from: String,
+    pub gas: Option<String>,
+    pub gas_price: Option<String>,
+    pub max_fee_per_gas: Option<String>,
+    pub max_priority_fee_per_gas: Option<String>,
+    // gas * gas_price
+    pub projected_cost_wei: Option<String>,
+    // gas * max_fee_per_gas, the most the transaction can cost
+    pub max_cost_wei: Option<String>,
+    pub reverts: bool,
+    pub revert_reason: Option<String>,
+}
+
+/// Everything known about a market at `generated_at`: the signed part of
+/// `GET /markets/{id}/snapshot`.
+#[cfg(feature = "eth")]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MarketSnapshot {
+    // bumped whenever the document's shape changes
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub market: Market,
+    // every report, retracted ones included
+    pub reports: Vec<Report>,
+    // with its inclusion proof and chain submission, once decided
+    pub settlement: Option<SettlementView>,
+    // the batch holding the settlement
+    pub batch: Option<BatchSummary>,
+}
+
+#[cfg(feature = "eth")]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SnapshotSignature {
+    // eip191-keccak256-json: see eth::attest
+    pub scheme: String,
+    // address of the server's attestation key
+    pub signer: String,
+    // keccak256 of the snapshot as compact JSON with sorted keys
+    pub digest: String,
+    // 65-byte r || s || v over the digest, EIP-191 prefixed
+    pub signature: String,
+}
+
+#[cfg(feature = "eth")]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SignedMarketSnapshot {
+    pub snapshot: MarketSnapshot,
+    pub signature: SnapshotSignature,
+}
+
+#[cfg(feature = "eth")]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ChainWallets {
+    pub chain_id: u64,
+    pub wallets: Vec<WalletHealth>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct TokenRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct TokenResponse {
+    pub access_token: String,
+    // always "Bearer"
+    pub token_type: String,
+    pub expires_at: DateTime<Utc>,
+    pub role: Role,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateUserRequest {
+    pub username: String,
+    pub password: String,
+    pub role: Role,
+    // sources a reporter may submit reports for; each belongs to one user
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SetUserSourcesRequest {
+    // replaces the user's sources
+    pub sources: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct User {
+    pub id: Uuid,
+    pub username: String,
+    pub role: Role,
+    pub sources: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    // HMAC-SHA256 key for the X-OracleSettle-Signature header; never returned
+    pub secret: String,
+    // event kinds such as settlement_decided or tx_confirmed
+    pub event_types: Vec<String>,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WebhookDeliveryQuery {
+    // PENDING, DELIVERED or FAILED
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_id: i64,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateTemplateRequest {
+    pub name: String,
+    // {date} and {time} are replaced with each slot's start (UTC), e.g.
+    // "BTC/USD close price on {date}"
+    pub question_pattern: String,
+    // RFC3339; the first slot, later ones follow every cadence_secs
+    pub starts_at: String,
+    pub cadence_secs: i32,
+    // each instance opens at its slot and closes this long after
+    pub duration_secs: i32,
+    // resolve_deadline offset from closes_at; omitted uses resolver.resolve_window_secs
+    pub resolve_window_secs: Option<i32>,
+    // create instances this long before their slot, as SCHEDULED markets
+    pub lead_secs: Option<i32>,
+    #[serde(flatten)]
+    pub settings: MarketSettings,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MarketTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub question_pattern: String,
+    pub starts_at: DateTime<Utc>,
+    pub cadence_secs: i32,
+    pub duration_secs: i32,
+    pub resolve_window_secs: Option<i32>,
+    pub lead_secs: i32,
+    pub settings: MarketSettings,
+    pub next_run_at: DateTime<Utc>,
+    pub active: bool,
+    pub last_market_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// `GET /system/jobs`.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SystemJobs {
+    // whether this replica runs the resolver and batcher
+    pub leader: bool,
+    pub loops: Vec<LoopStatus>,
+    pub queues: QueueDepths,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct QueueDepths {
+    // CLOSED markets the resolver has yet to settle
+    pub unresolved_markets: i64,
+    pub unbatched_settlements: i64,
+    // PENDING or INTENT, i.e. not yet broadcast
+    pub pending_outbox: i64,
+    // waiting for their transaction's confirmations
+    pub proposed_settlements: i64,
+    // holding their settlements until voided
+    pub failed_batches: i64,
+}
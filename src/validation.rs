@@ -0,0 +1,93 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+
+pub const MAX_QUESTION_LEN: usize = 500;
+pub const MAX_SOURCE_LEN: usize = 128;
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 128;
+pub const MAX_REASON_LEN: usize = 500;
+
+/// One problem with one field of a request body.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: &'static str,
+    #[schema(example = "TOO_LONG")]
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Collects every field-level problem in a request body so they come back
+/// together as one 422 VALIDATION_FAILED, listed under `details.errors`.
+#[derive(Debug, Default)]
+pub struct Validation {
+    errors: Vec<FieldError>,
+}
+
+impl Validation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `message` against `field` unless `ok`.
+    pub fn check(
+        &mut self,
+        ok: bool,
+        field: &'static str,
+        code: &'static str,
+        message: impl Into<String>,
+    ) -> &mut Self {
+        if !ok {
+            self.errors.push(FieldError {
+                field,
+                code,
+                message: message.into(),
+            });
+        }
+        self
+    }
+
+    /// Not blank, and at most `max` characters.
+    pub fn text(&mut self, field: &'static str, value: &str, max: usize) -> &mut Self {
+        if value.trim().is_empty() {
+            return self.check(
+                false,
+                field,
+                "REQUIRED",
+                format!("{} must not be empty", field),
+            );
+        }
+        let len = value.chars().count();
+        self.check(
+            len <= max,
+            field,
+            "TOO_LONG",
+            format!("{} is {} characters; the limit is {}", field, len, max),
+        )
+    }
+
+    pub fn finite(&mut self, field: &'static str, value: f64) -> &mut Self {
+        self.check(
+            value.is_finite(),
+            field,
+            "NOT_FINITE",
+            format!("{} must be a finite number", field),
+        )
+    }
+
+    pub fn finish(self) -> Result<(), AppError> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+
+        let summary = self
+            .errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        Err(AppError::unprocessable("VALIDATION_FAILED", summary)
+            .details(serde_json::json!({ "errors": self.errors })))
+    }
+}
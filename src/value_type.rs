@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::proof::{FIXED_POINT_DECIMALS, rescale_fixed, to_fixed};
+
+/// What a market's outcome measures. Drives both the on-chain integer
+/// encoding and how UIs should render the settled number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ValueType {
+    #[default]
+    Number,
+    Integer,
+    PriceUsd,
+    Percentage,
+    TemperatureC,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DisplayHints {
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    pub thousands_separator: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OutcomeFormat {
+    pub value_type: ValueType,
+    pub decimals: u32,
+    pub unit: Option<String>,
+    /// On-chain value = round(outcome * scaling_factor)
+    pub scaling_factor: u64,
+    pub display: DisplayHints,
+}
+
+impl ValueType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValueType::Number => "NUMBER",
+            ValueType::Integer => "INTEGER",
+            ValueType::PriceUsd => "PRICE_USD",
+            ValueType::Percentage => "PERCENTAGE",
+            ValueType::TemperatureC => "TEMPERATURE_C",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "NUMBER" => Some(ValueType::Number),
+            "INTEGER" => Some(ValueType::Integer),
+            "PRICE_USD" => Some(ValueType::PriceUsd),
+            "PERCENTAGE" => Some(ValueType::Percentage),
+            "TEMPERATURE_C" => Some(ValueType::TemperatureC),
+            _ => None,
+        }
+    }
+
+    pub fn format(&self) -> OutcomeFormat {
+        self.format_at(None)
+    }
+
+    /// Places this type rounds and anchors at unless a market sets its own.
+    pub fn default_decimals(&self) -> u32 {
+        self.format().decimals
+    }
+
+    /// The format for a market with its own `decimals`, or the type's when
+    /// it has none.
+    pub fn format_at(&self, decimals: Option<u32>) -> OutcomeFormat {
+        let (default_decimals, unit, prefix, suffix, thousands_separator) = match self {
+            ValueType::Number => (0, None, None, None, false),
+            ValueType::Integer => (0, None, None, None, true),
+            ValueType::PriceUsd => (2, Some("USD"), Some("$"), None, true),
+            ValueType::Percentage => (2, Some("percent"), None, Some("%"), false),
+            ValueType::TemperatureC => (1, Some("celsius"), None, Some("°C"), false),
+        };
+        let decimals = decimals
+            .unwrap_or(default_decimals)
+            .min(FIXED_POINT_DECIMALS);
+
+        OutcomeFormat {
+            value_type: *self,
+            decimals,
+            unit: unit.map(str::to_string),
+            scaling_factor: 10u64.pow(decimals),
+            display: DisplayHints {
+                prefix: prefix.map(str::to_string),
+                suffix: suffix.map(str::to_string),
+                thousands_separator,
+            },
+        }
+    }
+
+    /// Integer the contract stores for this outcome.
+    pub fn encode_outcome(&self, outcome: f64) -> u64 {
+        self.encode_fixed(to_fixed(outcome))
+    }
+
+    /// Integer the contract stores for a fixed-point outcome at this type's
+    /// decimals; see `encode_fixed_at`.
+    pub fn encode_fixed(&self, outcome_e8: i64) -> u64 {
+        encode_fixed_at(outcome_e8, self.default_decimals())
+    }
+}
+
+/// Integer the contract stores for a fixed-point outcome, rescaled from
+/// `FIXED_POINT_DECIMALS` to `decimals`. Rounds half away from zero; the
+/// contract takes no negatives, so those clamp to 0.
+pub fn encode_fixed_at(outcome_e8: i64, decimals: u32) -> u64 {
+    rescale_fixed(outcome_e8, decimals).max(0) as u64
+}
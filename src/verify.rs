@@ -0,0 +1,345 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[cfg(feature = "eth")]
+use crate::eth::read;
+use crate::models::outbox::SettlementPayload;
+use crate::proof::{
+    self, EncodedReport, market_hash, merkle_proof, settlement_leaf, verify_merkle_proof,
+    versioned_leaf,
+};
+use crate::repo::{BatchRef, BatchRepo, OutboxRepo, ReportRepo, SettlementRepo};
+use crate::routes::settlement::settlement_hash;
+use crate::state::AppState;
+use crate::types::{
+    InclusionProof, PayloadChecks, PayloadVerdict, SettlementVerdict, VerificationChecks,
+    VerifyPayloadRequest,
+};
+
+/// Re-derives everything we can for one market from stored data (and
+/// optionally the contract) and reports where it disagrees.
+pub async fn verify_market(
+    state: &AppState,
+    market_id: Uuid,
+    check_chain: bool,
+) -> Result<SettlementVerdict, sqlx::Error> {
+    let settlement = SettlementRepo::get(&state.db, market_id).await?;
+
+    let Some(settlement) = settlement else {
+        return Ok(SettlementVerdict {
+            market_id,
+            verdict: "NOT_SETTLED".to_string(),
+            settlement_hash: None,
+            leaf: None,
+            checks: VerificationChecks::default(),
+            issues: Vec::new(),
+        });
+    };
+
+    let mut checks = VerificationChecks::default();
+    let mut issues = Vec::new();
+
+    let alg = settlement.hash_algorithm();
+    let reports = ReportRepo::list(&state.db, market_id, true, false, false).await?;
+    let hash = settlement_hash(
+        alg,
+        market_id,
+        settlement.outcome_e8,
+        settlement.decided_at,
+        &reports,
+    );
+    let leaf = settlement_leaf(alg, market_id, settlement.outcome_e8, settlement.decided_at);
+    let leaf_hex = hex::encode(leaf);
+
+    let payload: Option<SettlementPayload> = OutboxRepo::latest_payload(&state.db, market_id)
+        .await?
+        .and_then(|p| serde_json::from_value(p).ok());
+
+    if let Some(p) = &payload {
+        let matches =
+            p.leaf_hex == leaf_hex && p.market_hash_hex == hex::encode(market_hash(market_id));
+        if !matches {
+            issues
+                .push("outbox payload leaf/market hash differs from recomputed values".to_string());
+        }
+        checks.leaf_matches_outbox = Some(matches);
+    }
+
+    if let Some(batch) = BatchRepo::for_market(&state.db, market_id).await? {
+        // proven from the leaves stored at batching, not recomputed ones
+        let proof = inclusion_proof(&state.db, &batch, market_id).await?;
+        let proven = proof
+            .as_ref()
+            .is_some_and(|p| decode_hash(&p.leaf).is_some_and(|l| proof_holds(p, l)));
+        if !proven {
+            issues.push(format!(
+                "batch {} stored leaves do not prove into its merkle root",
+                batch.id
+            ));
+        }
+
+        if let Some(proof) = &proof {
+            let matches = proof.leaf
+                == hex::encode(versioned_leaf(
+                    proof.leaf_version,
+                    alg,
+                    market_id,
+                    settlement.outcome,
+                    settlement.outcome_e8,
+                    settlement.decided_at,
+                ));
+            if !matches {
+                issues.push(format!(
+                    "leaf stored in batch {} differs from recomputed leaf",
+                    batch.id
+                ));
+            }
+            checks.leaf_matches_batch = Some(matches);
+        }
+
+        checks.batch_id = Some(batch.id);
+        checks.batch_root_matches = Some(proven);
+    }
+
+    let anchored = OutboxRepo::anchored(&state.db, market_id).await?;
+    checks.anchored = Some(anchored);
+
+    if check_chain && anchored {
+        let chain_id = payload.as_ref().and_then(|p| p.chain_id);
+        match read_on_chain(state, chain_id, market_id).await {
+            Ok((root, outcome, exists)) => {
+                let matches = exists
+                    && root == leaf
+                    && payload.as_ref().is_some_and(|p| p.outcome_u64 == outcome);
+                if !matches {
+                    issues.push(
+                        "on-chain settlement differs from recomputed leaf/outcome".to_string(),
+                    );
+                }
+                checks.on_chain_matches = Some(matches);
+            }
+            Err(e) => issues.push(format!("chain read failed: {}", e)),
+        }
+    }
+
+    let mismatch = [
+        checks.leaf_matches_outbox,
+        checks.leaf_matches_batch,
+        checks.batch_root_matches,
+        checks.on_chain_matches,
+    ]
+    .contains(&Some(false));
+
+    Ok(SettlementVerdict {
+        market_id,
+        verdict: if mismatch { "MISMATCH" } else { "OK" }.to_string(),
+        settlement_hash: Some(hash),
+        leaf: Some(leaf_hex),
+        checks,
+        issues,
+    })
+}
+
+/// Checks a published settlement payload: that its fields hash to the hash
+/// it claims, that it matches what the oracle settled, and (once batched)
+/// that its leaf proves into the stored batch root.
+pub async fn verify_payload(
+    state: &AppState,
+    payload: &VerifyPayloadRequest,
+) -> Result<PayloadVerdict, sqlx::Error> {
+    let market_id = payload.market_id;
+    let mut checks = PayloadChecks::default();
+    let mut issues = Vec::new();
+
+    let settlement = SettlementRepo::get(&state.db, market_id).await?;
+    let alg = payload
+        .hash_algorithm
+        .or(settlement.as_ref().map(|s| s.hash_algorithm()))
+        .unwrap_or(state.config.proof.hash_algorithm);
+
+    let encoded: Vec<EncodedReport> = payload
+        .reports
+        .iter()
+        .map(|r| EncodedReport {
+            id: r.id,
+            source: &r.source,
+            value: r.value,
+            created_at: r.created_at,
+        })
+        .collect();
+    let outcome_e8 = proof::to_fixed(payload.outcome);
+    let computed = hex::encode(proof::settlement_hash(
+        alg,
+        market_id,
+        outcome_e8,
+        payload.decided_at,
+        &encoded,
+    ));
+
+    let claimed = payload.hash.trim_start_matches("0x").to_lowercase();
+    checks.hash_matches_payload = computed == claimed;
+    if !checks.hash_matches_payload {
+        issues.push("payload fields do not hash to the given hash".to_string());
+    }
+
+    let leaf = settlement_leaf(alg, market_id, outcome_e8, payload.decided_at);
+
+    let Some(settlement) = settlement else {
+        return Ok(PayloadVerdict {
+            market_id,
+            verdict: "NOT_SETTLED".to_string(),
+            computed_hash: computed,
+            leaf: hex::encode(leaf),
+            checks,
+            inclusion: None,
+            issues,
+        });
+    };
+
+    let outcome_matches = settlement.outcome == payload.outcome;
+    let decided_at_matches = settlement.decided_at == payload.decided_at;
+    if !outcome_matches || !decided_at_matches {
+        issues.push("outcome or decided_at differs from the stored settlement".to_string());
+    }
+    checks.outcome_matches = Some(outcome_matches);
+    checks.decided_at_matches = Some(decided_at_matches);
+
+    let reports = ReportRepo::list(&state.db, market_id, true, false, false).await?;
+    let stored_hash = settlement_hash(
+        settlement.hash_algorithm(),
+        market_id,
+        settlement.outcome_e8,
+        settlement.decided_at,
+        &reports,
+    );
+    let hash_matches_oracle = stored_hash == claimed;
+    if !hash_matches_oracle {
+        issues
+            .push("hash differs from the one the oracle computes from its own records".to_string());
+    }
+    checks.hash_matches_oracle = Some(hash_matches_oracle);
+
+    let mut inclusion = None;
+    if let Some(batch) = BatchRepo::for_market(&state.db, market_id).await? {
+        inclusion = inclusion_proof(&state.db, &batch, market_id).await?;
+
+        // the payload's own leaf against the stored path, so a payload with
+        // a different outcome can't borrow the stored leaf's proof
+        let valid = inclusion.as_ref().is_some_and(|p| {
+            let leaf = versioned_leaf(
+                p.leaf_version,
+                alg,
+                market_id,
+                payload.outcome,
+                outcome_e8,
+                payload.decided_at,
+            );
+            proof_holds(p, leaf)
+        });
+        if !valid {
+            issues.push(format!(
+                "payload leaf does not prove into batch {} root",
+                batch.id
+            ));
+        }
+        checks.batch_proof_valid = Some(valid);
+    }
+
+    let mismatch = !checks.hash_matches_payload
+        || [
+            checks.outcome_matches,
+            checks.decided_at_matches,
+            checks.hash_matches_oracle,
+            checks.batch_proof_valid,
+        ]
+        .contains(&Some(false));
+
+    Ok(PayloadVerdict {
+        market_id,
+        verdict: if mismatch { "MISMATCH" } else { "OK" }.to_string(),
+        computed_hash: computed,
+        leaf: hex::encode(leaf),
+        checks,
+        inclusion,
+        issues,
+    })
+}
+
+/// The market's leaf and Merkle path as stored when `batch` was cut. `None`
+/// when the batch's stored leaves don't form a complete tree.
+pub async fn inclusion_proof(
+    db: &PgPool,
+    batch: &BatchRef,
+    market_id: Uuid,
+) -> Result<Option<InclusionProof>, sqlx::Error> {
+    let leaves = BatchRepo::leaves(db, batch.id).await?;
+
+    let contiguous = leaves
+        .iter()
+        .enumerate()
+        .all(|(i, l)| l.leaf_index as usize == i);
+    let hashes: Option<Vec<[u8; 32]>> = leaves.iter().map(|l| decode_hash(&l.leaf_hash)).collect();
+    let (true, Some(hashes)) = (contiguous, hashes) else {
+        return Ok(None);
+    };
+
+    let Some(own) = leaves.iter().find(|l| l.market_id == market_id) else {
+        return Ok(None);
+    };
+    let index = own.leaf_index as usize;
+    let alg = batch.hash_algorithm();
+
+    Ok(Some(InclusionProof {
+        batch_id: batch.id,
+        merkle_root: batch.merkle_root.clone(),
+        hash_algorithm: alg,
+        leaf_index: index,
+        leaf: own.leaf_hash.clone(),
+        leaf_version: own.leaf_version(),
+        siblings: merkle_proof(alg, &hashes, index)
+            .iter()
+            .map(hex::encode)
+            .collect(),
+    }))
+}
+
+// whether `leaf` walks up `proof`'s siblings to its root
+fn proof_holds(proof: &InclusionProof, leaf: [u8; 32]) -> bool {
+    let siblings: Option<Vec<[u8; 32]>> = proof.siblings.iter().map(|s| decode_hash(s)).collect();
+
+    match (siblings, decode_hash(&proof.merkle_root)) {
+        (Some(siblings), Some(root)) => verify_merkle_proof(
+            proof.hash_algorithm,
+            leaf,
+            proof.leaf_index,
+            &siblings,
+            root,
+        ),
+        _ => false,
+    }
+}
+
+fn decode_hash(hex_str: &str) -> Option<[u8; 32]> {
+    hex::decode(hex_str).ok()?.try_into().ok()
+}
+
+#[cfg(feature = "eth")]
+async fn read_on_chain(
+    state: &AppState,
+    chain_id: Option<u64>,
+    market_id: Uuid,
+) -> anyhow::Result<([u8; 32], u64, bool)> {
+    let target = state.chains.resolve(chain_id)?;
+    let on_chain = read::settlement(&target.config, market_hash(market_id)).await?;
+
+    Ok((on_chain.root, on_chain.outcome, on_chain.exists))
+}
+
+#[cfg(not(feature = "eth"))]
+async fn read_on_chain(
+    _state: &AppState,
+    _chain_id: Option<u64>,
+    _market_id: Uuid,
+) -> anyhow::Result<([u8; 32], u64, bool)> {
+    Err(anyhow::anyhow!("built without the `eth` feature"))
+}
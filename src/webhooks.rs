@@ -0,0 +1,239 @@
+use chrono::Utc;
+use futures_util::{StreamExt, stream};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+// delay before attempt n + 1; the last entry repeats
+const BACKOFF_SECS: [f64; 6] = [10.0, 30.0, 120.0, 600.0, 1800.0, 3600.0];
+
+// deliveries POSTed at once per pass
+const CONCURRENCY: usize = 8;
+
+pub const SIGNATURE_HEADER: &str = "X-OracleSettle-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-OracleSettle-Timestamp";
+
+/// Queues `event` for every webhook subscribed to its kind. Called by the
+/// event dispatcher inside the transaction that marks the event dispatched.
+pub async fn enqueue<'e, E: PgExecutor<'e>>(
+    db: E,
+    event_id: i64,
+    kind: &str,
+    payload: &Value,
+) -> Result<u64, sqlx::Error> {
+    let queued = sqlx::query!(
+        r#"
+        INSERT INTO webhook_deliveries (id, webhook_id, event_id, event_type, payload)
+        SELECT gen_random_uuid(), w.id, $1, $2,
+               jsonb_build_object('event_id', $1::BIGINT, 'type', $2::TEXT, 'created_at', now(), 'data', $3::JSONB)
+        FROM webhooks w
+        WHERE $2 = ANY(w.event_types)
+        ON CONFLICT (webhook_id, event_id) DO NOTHING
+        "#,
+        event_id,
+        kind,
+        payload
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    Ok(queued)
+}
+
+pub async fn delivery_loop(state: AppState) {
+    let config = &state.config.webhooks;
+
+    let client = reqwest::Client::builder()
+        .timeout(config.timeout())
+        .build()
+        .expect("Failed to build webhook HTTP client");
+
+    let interval = config.interval();
+
+    loop {
+        let run = state.loops.start("webhooks", interval);
+
+        match deliver_due(&state, &client).await {
+            Ok(n) => run.finish(n),
+            Err(e) => {
+                tracing::error!("webhook delivery pass failed: {}", e);
+                run.fail(e);
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+struct DueDelivery {
+    id: Uuid,
+    event_type: String,
+    payload: Value,
+    attempts: i32,
+    url: String,
+    secret: String,
+}
+
+/// Claims due PENDING deliveries and POSTs them. Claiming pushes
+/// `next_attempt_at` past the request timeout so a second instance doesn't
+/// send the same delivery while this one is waiting on the subscriber.
+async fn deliver_due(state: &AppState, client: &reqwest::Client) -> Result<usize, sqlx::Error> {
+    let config = &state.config.webhooks;
+    let lease_secs = (config.timeout_secs * 2 + 30) as f64;
+
+    let due = sqlx::query!(
+        r#"
+        UPDATE webhook_deliveries d
+        SET next_attempt_at = now() + make_interval(secs => $2)
+        FROM webhooks w
+        WHERE w.id = d.webhook_id
+          AND d.id IN (
+                SELECT id FROM webhook_deliveries
+                WHERE status = 'PENDING' AND next_attempt_at <= now()
+                ORDER BY next_attempt_at ASC
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+              )
+        RETURNING d.id, d.event_type, d.payload, d.attempts, w.url, w.secret
+        "#,
+        config.batch_size,
+        lease_secs
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let count = due.len();
+
+    let due = due.into_iter().map(|d| DueDelivery {
+        id: d.id,
+        event_type: d.event_type,
+        payload: d.payload,
+        attempts: d.attempts,
+        url: d.url,
+        secret: d.secret,
+    });
+
+    stream::iter(due)
+        .for_each_concurrent(CONCURRENCY, |delivery| async move {
+            let result = send(client, &delivery).await;
+
+            if let Err(e) = record_attempt(state, &delivery, result).await {
+                tracing::error!("failed to record webhook delivery {}: {}", delivery.id, e);
+            }
+        })
+        .await;
+
+    Ok(count)
+}
+
+/// Status code of a completed request, or the transport error.
+async fn send(client: &reqwest::Client, delivery: &DueDelivery) -> Result<u16, String> {
+    let body = serde_json::to_vec(&delivery.payload).map_err(|e| e.to_string())?;
+    let timestamp = Utc::now().timestamp();
+
+    let response = client
+        .post(&delivery.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-OracleSettle-Event", &delivery.event_type)
+        .header("X-OracleSettle-Delivery", delivery.id.to_string())
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(
+            SIGNATURE_HEADER,
+            format!("sha256={}", sign(&delivery.secret, timestamp, &body)),
+        )
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(response.status().as_u16())
+}
+
+async fn record_attempt(
+    state: &AppState,
+    delivery: &DueDelivery,
+    result: Result<u16, String>,
+) -> Result<(), sqlx::Error> {
+    let attempts = delivery.attempts + 1;
+
+    let (status_code, error) = match result {
+        Ok(code) if (200..300).contains(&code) => {
+            sqlx::query!(
+                r#"
+                UPDATE webhook_deliveries
+                SET status = 'DELIVERED',
+                    attempts = $1,
+                    last_status_code = $2,
+                    last_error = NULL,
+                    delivered_at = now()
+                WHERE id = $3
+                "#,
+                attempts,
+                code as i32,
+                delivery.id
+            )
+            .execute(&state.db)
+            .await?;
+
+            return Ok(());
+        }
+        Ok(code) => (Some(code as i32), format!("subscriber returned {}", code)),
+        Err(e) => (None, e),
+    };
+
+    let gave_up = attempts >= state.config.webhooks.max_attempts;
+
+    if gave_up {
+        tracing::warn!(
+            "webhook delivery {} failed after {} attempts: {}",
+            delivery.id,
+            attempts,
+            error
+        );
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = CASE WHEN $1 THEN 'FAILED' ELSE 'PENDING' END,
+            attempts = $2,
+            last_status_code = $3,
+            last_error = $4,
+            next_attempt_at = now() + make_interval(secs => $5)
+        WHERE id = $6
+        "#,
+        gave_up,
+        attempts,
+        status_code,
+        error,
+        backoff_secs(attempts),
+        delivery.id
+    )
+    .execute(&state.db)
+    .await?;
+
+    Ok(())
+}
+
+fn backoff_secs(attempts: i32) -> f64 {
+    let step = (attempts.max(1) as usize - 1).min(BACKOFF_SECS.len() - 1);
+    BACKOFF_SECS[step]
+}
+
+/// Hex HMAC-SHA256 of `"{timestamp}.{body}"`. Subscribers recompute it with
+/// their secret and the `X-OracleSettle-Timestamp` header, and should reject
+/// stale timestamps to stop replays.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    hex::encode(mac.finalize().into_bytes())
+}
@@ -1,158 +1,675 @@
 use crate::AppState;
-use crate::eth::submit::submit_settlement;
+use crate::audit::{self, AuditEntry};
+use crate::eth::chains::ChainTarget;
+use crate::eth::read::{self, TxState};
+use crate::eth::revert;
+use crate::eth::submit::{
+    IntentStatus, SignedSettlement, SubmissionReceipt, resume_intent, submit_settlement,
+};
+use crate::events::{self, Event};
 use crate::models::outbox::SettlementPayload;
+use crate::proof::settlement_leaf;
+use crate::repo::{ClaimedJob, MarketRepo, NewSubmission, OutboxRepo, SettlementRepo};
 
-use sqlx::Row;
+use chrono::{SubsecRound, Utc};
+use ethers::types::TxHash;
+use futures_util::{StreamExt, stream};
+use rand::Rng;
 use uuid::Uuid;
 
-pub async fn run_worker(state: AppState) {
+// a claim this old belongs to a worker that died mid-job; a job that was
+// broadcast has its intent recorded, so taking it over can't double-submit
+const CLAIM_TTL_SECS: f64 = 300.0;
+
+// delay before retry n (1-based); later retries reuse the last step
+const BACKOFF_SECS: [f64; 4] = [30.0, 120.0, 600.0, 3600.0];
+
+/// Spawns a worker per configured chain, each claiming only its own chain's
+/// queue, so a congested chain doesn't hold up submissions to the others.
+pub fn spawn_workers(state: &AppState) {
+    let chains: Vec<i64> = state
+        .chains
+        .targets()
+        .map(|t| t.config.chain_id as i64)
+        .collect();
+    if chains.is_empty() {
+        tracing::warn!("No chains configured; outbox jobs wait until one is");
+        return;
+    }
+
+    let default_chain = state.default_chain_id().map(|c| c as i64);
+
+    for &chain_id in &chains {
+        // jobs with no usable chain go to the default chain's queue, where
+        // they fail instead of waiting forever
+        let others = (Some(chain_id) == default_chain).then(|| {
+            chains
+                .iter()
+                .copied()
+                .filter(|c| *c != chain_id)
+                .collect::<Vec<_>>()
+        });
+
+        let worker_state = state.clone();
+        state
+            .loops
+            .spawn(&format!("worker-chain-{}", chain_id), async move {
+                run_worker(worker_state, chain_id, others).await
+            });
+    }
+}
+
+async fn run_worker(state: AppState, chain_id: i64, others: Option<Vec<i64>>) {
+    let worker_id = state.config.worker.id();
+    tracing::info!("outbox worker {} started for chain {}", worker_id, chain_id);
+
+    let name = format!("worker-chain-{}", chain_id);
+    let interval = state.config.worker.interval();
+    let batch_size = state.config.worker.batch_size;
+
     loop {
-        let rows = sqlx::query(
-            r#"
-            SELECT id, payload, retries
-            FROM outbox
-            WHERE status = 'PENDING'
-            ORDER BY created_at ASC
-            LIMIT 10
-            "#
+        let run = state.loops.start(&name, interval);
+
+        let claimed = OutboxRepo::claim(
+            &state.db,
+            &worker_id,
+            chain_id,
+            others.as_deref(),
+            CLAIM_TTL_SECS,
+            batch_size,
         )
-        .fetch_all(&state.db)
-        .await
-        .unwrap();
-
-        for row in rows {
-            let job_id: Uuid = row.get("id");
-            let payload_json: serde_json::Value = row.get("payload");
-            let retries: i32 = row.get("retries");
-
-            let payload: SettlementPayload = match serde_json::from_value(payload_json) {
-                Ok(p) => p,
-                Err(e) => {
-                    sqlx::query(
-                        r#"
-                        UPDATE outbox
-                        SET status = 'FAILED',
-                            last_error = $1,
-                            updated_at = now()
-                        WHERE id = $2
-                        "#
-                    )
-                    .bind(format!("bad payload json: {}", e))
-                    .bind(job_id)
-                    .execute(&state.db)
-                    .await
-                    .unwrap();
-                    continue;
+        .await;
+        let jobs = match claimed {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                tracing::error!("outbox claim for chain {} failed: {}", chain_id, e);
+                run.fail(e);
+                tokio::time::sleep(interval).await;
+                continue;
+            }
+        };
+
+        let processed = jobs.len();
+
+        // each job holds an in-flight permit from the sender while it submits
+        stream::iter(jobs)
+            .for_each_concurrent(None, |job| async {
+                let job_id = job.id;
+                // the claim expires, so the job is picked up again once the
+                // database is back
+                if let Err(e) = process_job(&state, job).await {
+                    tracing::error!("outbox {} not updated: {}", job_id, e);
                 }
-            };
-
-            let market_hash_vec = match hex::decode(&payload.market_hash_hex) {
-                Ok(v) => v,
-                Err(e) => {
-                    sqlx::query(
-                        r#"
-                        UPDATE outbox
-                        SET status = 'FAILED',
-                            last_error = $1,
-                            updated_at = now()
-                        WHERE id = $2
-                        "#
+            })
+            .await;
+
+        run.finish(processed);
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn process_job(state: &AppState, job: ClaimedJob) -> Result<(), sqlx::Error> {
+    let job_id = job.id;
+    let market_id = job.market_id;
+    let status = job.status.clone();
+    let retries = job.retries;
+
+    let payload: SettlementPayload = match serde_json::from_value(job.payload.clone()) {
+        Ok(p) => p,
+        Err(e) => {
+            return mark_failed(state, job_id, &status, &format!("bad payload json: {}", e)).await;
+        }
+    };
+
+    let target = match state.chains.resolve(payload.chain_id) {
+        Ok(t) => t,
+        Err(e) => {
+            return mark_failed(state, job_id, &status, &e.to_string()).await;
+        }
+    };
+
+    if let Some(signed) = stored_intent(&job) {
+        return resume_job(state, target, job_id, market_id, &status, retries, &signed).await;
+    }
+
+    let (market_hash, leaf) = match payload.words() {
+        Ok(words) => words,
+        Err(e) => {
+            return mark_failed(state, job_id, &status, &e).await;
+        }
+    };
+
+    // the job may have been sent before without its intent surviving (an
+    // intent requeued as dropped that was mined after all, a job retried by
+    // hand): the contract has the final say, so never settle twice
+    match prior_submission(state, target, market_hash, leaf).await {
+        Ok(None) => {}
+        Ok(Some(receipt)) => {
+            tracing::info!(
+                "outbox {} already settled on chain by {:?}",
+                job_id,
+                receipt.tx_hash
+            );
+            return mark_sent(
+                state,
+                job_id,
+                market_id,
+                &status,
+                Some(receipt),
+                "found_on_chain",
+            )
+            .await;
+        }
+        Err(e) => {
+            return record_failure(state, job_id, &status, retries, &revert::describe(&e)).await;
+        }
+    }
+
+    let db = state.db.clone();
+    let before = status.clone();
+    let record_intent = move |signed: SignedSettlement| {
+        let db = db.clone();
+        let before = before.clone();
+        async move {
+            let mut tx = db.begin().await?;
+
+            OutboxRepo::record_intent(
+                &mut *tx,
+                job_id,
+                &format!("{:?}", signed.tx_hash),
+                &hex::encode(&signed.raw),
+                &format!("{:?}", signed.submitter),
+            )
+            .await?;
+
+            audit::record(
+                &mut *tx,
+                AuditEntry::new("outbox", job_id, "intent_recorded", "worker")
+                    .transition(Some(&before), Some("INTENT"))
+                    .details(serde_json::json!({
+                        "tx_hash": format!("{:?}", signed.tx_hash),
+                        "submitter": format!("{:?}", signed.submitter),
+                    })),
+            )
+            .await?;
+
+            tx.commit().await?;
+            anyhow::Ok(())
+        }
+    };
+
+    match submit_settlement(
+        &state.sender,
+        target,
+        market_hash,
+        leaf,
+        payload.outcome_u64,
+        payload.ts,
+        record_intent,
+    )
+    .await
+    {
+        Ok(receipt) => mark_sent(state, job_id, market_id, &status, receipt, "sent").await,
+        Err(e) => record_failure(state, job_id, &status, retries, &revert::describe(&e)).await,
+    }
+}
+
+async fn release_claim(state: &AppState, job_id: Uuid) -> Result<(), sqlx::Error> {
+    OutboxRepo::release_claim(&state.db, job_id).await
+}
+
+/// The tx that already put this leaf on chain, if the contract holds it. An
+/// on-chain settlement whose tx is past the lookback is an error rather than
+/// a reason to submit again.
+async fn prior_submission(
+    state: &AppState,
+    target: &ChainTarget,
+    market_hash: [u8; 32],
+    leaf: [u8; 32],
+) -> anyhow::Result<Option<SubmissionReceipt>> {
+    let on_chain = read::settlement(&target.config, market_hash).await?;
+    if !on_chain.exists || on_chain.root != leaf {
+        return Ok(None);
+    }
+
+    let lookback = state.config.worker.replay_lookback_blocks;
+    match read::settlement_tx(&target.config, market_hash, leaf, lookback).await? {
+        Some(receipt) => Ok(Some(receipt)),
+        None => Err(anyhow::anyhow!(
+            "settlement already on chain but its tx is not in the last {} blocks",
+            lookback
+        )),
+    }
+}
+
+fn stored_intent(job: &ClaimedJob) -> Option<SignedSettlement> {
+    Some(SignedSettlement {
+        tx_hash: job.intent_tx_hash.as_ref()?.parse().ok()?,
+        raw: hex::decode(job.intent_raw_tx.as_ref()?).ok()?.into(),
+        submitter: job.intent_submitter.as_ref()?.parse().ok()?,
+    })
+}
+
+/// A previous pass signed (and possibly broadcast) a tx for this job. Ask the
+/// chain about that exact hash rather than signing a new one.
+async fn resume_job(
+    state: &AppState,
+    target: &ChainTarget,
+    job_id: Uuid,
+    market_id: Uuid,
+    status: &str,
+    retries: i32,
+    signed: &SignedSettlement,
+) -> Result<(), sqlx::Error> {
+    let provider = match state.sender.provider(target.config.chain_id) {
+        Ok(p) => p,
+        Err(e) => return record_failure(state, job_id, status, retries, &e.to_string()).await,
+    };
+
+    match resume_intent(provider, signed).await {
+        Ok(IntentStatus::Mined(receipt)) => {
+            mark_sent(state, job_id, market_id, status, Some(receipt), "sent").await
+        }
+        Ok(IntentStatus::Pending) => {
+            tracing::info!("outbox {} waiting on tx {:?}", job_id, signed.tx_hash);
+            release_claim(state, job_id).await
+        }
+        Ok(IntentStatus::Dropped) => {
+            tracing::warn!(
+                "outbox {} tx {:?} dropped, will re-sign",
+                job_id,
+                signed.tx_hash
+            );
+
+            let mut tx = state.db.begin().await?;
+
+            OutboxRepo::requeue(&mut *tx, job_id, status).await?;
+
+            audit::record(
+                &mut *tx,
+                AuditEntry::new("outbox", job_id, "intent_dropped", "worker")
+                    .transition(Some(status), Some("PENDING"))
+                    .details(serde_json::json!({ "tx_hash": format!("{:?}", signed.tx_hash) })),
+            )
+            .await?;
+
+            tx.commit().await
+        }
+        Err(e) => record_failure(state, job_id, status, retries, &e.to_string()).await,
+    }
+}
+
+/// `action` names the audit entry: `sent`, or `found_on_chain` when the
+/// settlement was already there.
+async fn mark_sent(
+    state: &AppState,
+    job_id: Uuid,
+    market_id: Uuid,
+    status: &str,
+    receipt: Option<SubmissionReceipt>,
+    action: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = state.db.begin().await?;
+
+    OutboxRepo::mark_sent(&mut *tx, job_id).await?;
+
+    if let Some(receipt) = &receipt {
+        OutboxRepo::record_submission(
+            &mut *tx,
+            &NewSubmission {
+                outbox_id: job_id,
+                market_id,
+                tx_hash: &format!("{:?}", receipt.tx_hash),
+                block_number: receipt.block_number.map(|b| b as i64),
+                gas_used: receipt.gas_used.map(|g| g as i64),
+                submitter: &format!("{:?}", receipt.submitter),
+            },
+        )
+        .await?;
+    }
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("outbox", job_id, action, "worker")
+            .transition(Some(status), Some("SENT"))
+            .details(serde_json::json!({
+                "tx_hash": receipt.as_ref().map(|r| format!("{:?}", r.tx_hash)),
+            })),
+    )
+    .await?;
+
+    tx.commit().await
+}
+
+/// Jobs that already recorded an intent stay in INTENT so the next pass
+/// checks the chain first; everything else goes back to PENDING.
+async fn record_failure(
+    state: &AppState,
+    job_id: Uuid,
+    status: &str,
+    retries: i32,
+    error: &str,
+) -> Result<(), sqlx::Error> {
+    let next_retries = retries + 1;
+    let delay = backoff_secs(next_retries);
+    let mut tx = state.db.begin().await?;
+
+    let after = OutboxRepo::record_failure(
+        &mut *tx,
+        job_id,
+        next_retries,
+        error,
+        delay,
+        state.config.worker.max_retries,
+    )
+    .await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("outbox", job_id, "attempt_failed", "worker")
+            .transition(Some(status), Some(&after))
+            .details(serde_json::json!({
+                "retries": next_retries,
+                "error": error,
+                "retry_in_secs": delay.round(),
+            })),
+    )
+    .await?;
+
+    tx.commit().await
+}
+
+/// Exponential backoff with +/-20% jitter so jobs that failed together (an
+/// RPC outage, say) don't all retry in the same poll.
+fn backoff_secs(retries: i32) -> f64 {
+    let step = (retries.max(1) as usize - 1).min(BACKOFF_SECS.len() - 1);
+    BACKOFF_SECS[step] * rand::thread_rng().gen_range(0.8..=1.2)
+}
+
+async fn mark_failed(
+    state: &AppState,
+    job_id: Uuid,
+    status: &str,
+    error: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = state.db.begin().await?;
+
+    OutboxRepo::mark_failed(&mut *tx, job_id, error).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("outbox", job_id, "failed", "worker")
+            .transition(Some(status), Some("FAILED"))
+            .details(serde_json::json!({ "error": error })),
+    )
+    .await?;
+
+    tx.commit().await
+}
+
+/// Follows SENT jobs until their settlement is buried under
+/// `reconciler.confirmations` blocks, then marks them CONFIRMED. A tx that
+/// vanished (dropped, or reorged out and evicted) sends its job back to
+/// PENDING so the submit loop signs a fresh one. Runs on the leader replica
+/// only, so a confirmation is recorded and announced once.
+pub async fn reconcile_loop(state: AppState) {
+    let interval = state.config.reconciler.interval();
+
+    loop {
+        state.leader.wait().await;
+
+        let run = state.loops.start("reconciler", interval);
+
+        match reconcile(&state).await {
+            Ok(checked) => run.finish(checked),
+            Err(e) => {
+                tracing::error!("reconcile pass failed: {}", e);
+                run.fail(e);
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Returns the number of jobs checked.
+async fn reconcile(state: &AppState) -> Result<usize, sqlx::Error> {
+    let jobs = OutboxRepo::sent(&state.db, state.config.reconciler.batch_size).await?;
+
+    let checked = jobs.len();
+
+    for job in jobs {
+        let payload: SettlementPayload = match serde_json::from_value(job.payload) {
+            Ok(p) => p,
+            Err(e) => {
+                mark_failed(state, job.id, "SENT", &format!("bad payload json: {}", e)).await?;
+                continue;
+            }
+        };
+
+        let Some(tx_hash) = job.tx_hash.and_then(|h| h.parse::<TxHash>().ok()) else {
+            tracing::warn!("outbox {} is SENT without a tx hash, skipping", job.id);
+            continue;
+        };
+
+        let target = match state.chains.resolve(payload.chain_id) {
+            Ok(t) => t,
+            Err(e) => {
+                tracing::warn!("outbox {}: {}", job.id, e);
+                continue;
+            }
+        };
+
+        let tx_state = match read::tx_state(&target.config, tx_hash).await {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("outbox {} reconcile failed: {}", job.id, e);
+                continue;
+            }
+        };
+
+        match tx_state {
+            TxState::Mined {
+                block_number,
+                confirmations,
+                success,
+            } => {
+                // a reorg can re-include the tx in a different block
+                if job.block_number != Some(block_number as i64) {
+                    OutboxRepo::move_submission(
+                        &state.db,
+                        &format!("{:?}", tx_hash),
+                        block_number as i64,
                     )
-                    .bind(format!("bad market_hash hex: {}", e))
-                    .bind(job_id)
-                    .execute(&state.db)
-                    .await
-                    .unwrap();
+                    .await?;
+                }
+
+                if confirmations < state.config.reconciler.confirmations {
                     continue;
                 }
-            };
-
-            let leaf_vec = match hex::decode(&payload.leaf_hex) {
-                Ok(v) => v,
-                Err(e) => {
-                    sqlx::query(
-                        r#"
-                        UPDATE outbox
-                        SET status = 'FAILED',
-                            last_error = $1,
-                            updated_at = now()
-                        WHERE id = $2
-                        "#
-                    )
-                    .bind(format!("bad leaf hex: {}", e))
-                    .bind(job_id)
-                    .execute(&state.db)
-                    .await
-                    .unwrap();
+
+                if !success {
+                    mark_failed(state, job.id, "SENT", "settlement tx reverted").await?;
                     continue;
                 }
-            };
-
-            if market_hash_vec.len() != 32 || leaf_vec.len() != 32 {
-                sqlx::query(
-                    r#"
-                    UPDATE outbox
-                    SET status = 'FAILED',
-                        last_error = $1,
-                        updated_at = now()
-                    WHERE id = $2
-                    "#
-                )
-                .bind("hash/leaf wrong length (expected 32 bytes)")
-                .bind(job_id)
-                .execute(&state.db)
-                .await
-                .unwrap();
-                continue;
-            }
 
-            let mut market_hash = [0u8; 32];
-            market_hash.copy_from_slice(&market_hash_vec);
-
-            let mut leaf = [0u8; 32];
-            leaf.copy_from_slice(&leaf_vec);
-
-            match submit_settlement(market_hash, leaf, payload.outcome_u64, payload.ts).await {
-                Ok(_) => {
-                    sqlx::query(
-                        r#"
-                        UPDATE outbox
-                        SET status = 'SENT',
-                            updated_at = now(),
-                            last_error = NULL
-                        WHERE id = $1
-                        "#
+                let on_chain = match read_settlement(target, &payload).await {
+                    Ok(s) => s,
+                    Err(e) => {
+                        tracing::warn!("outbox {} settlement read failed: {}", job.id, e);
+                        continue;
+                    }
+                };
+
+                if on_chain.exists && hex::encode(on_chain.root) == payload.leaf_hex {
+                    mark_confirmed(
+                        state,
+                        job.id,
+                        job.market_id,
+                        &payload,
+                        tx_hash,
+                        block_number,
+                        confirmations,
                     )
-                    .bind(job_id)
-                    .execute(&state.db)
-                    .await
-                    .unwrap();
-                }
-                Err(e) => {
-                    let next_retries = retries + 1;
-                    let next_status = if next_retries > 5 { "FAILED" } else { "PENDING" };
-
-                    sqlx::query(
-                        r#"
-                        UPDATE outbox
-                        SET retries = $1,
-                            last_error = $2,
-                            status = $3,
-                            updated_at = now()
-                        WHERE id = $4
-                        "#
+                    .await?;
+                } else {
+                    mark_failed(
+                        state,
+                        job.id,
+                        "SENT",
+                        "tx mined but settlement not found on-chain",
                     )
-                    .bind(next_retries)
-                    .bind(e.to_string())
-                    .bind(next_status)
-                    .bind(job_id)
-                    .execute(&state.db)
-                    .await
-                    .unwrap();
+                    .await?;
                 }
             }
+            TxState::Pending => {
+                tracing::info!("outbox {} tx {:?} back in the mempool", job.id, tx_hash);
+            }
+            TxState::Unknown => requeue_dropped(state, job.id, job.market_id, tx_hash).await?,
         }
+    }
 
-        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    Ok(checked)
+}
+
+async fn read_settlement(
+    target: &ChainTarget,
+    payload: &SettlementPayload,
+) -> anyhow::Result<read::OnChainSettlement> {
+    let bytes = hex::decode(&payload.market_hash_hex)?;
+    let market_hash: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("market_hash wrong length (expected 32 bytes)"))?;
+
+    read::settlement(&target.config, market_hash).await
+}
+
+/// Also finalizes the market's settlement when the confirmed leaf is that of
+/// its live PROPOSED settlement; a job for a superseded revision confirms
+/// without touching the market. A job no longer SENT is left alone.
+async fn mark_confirmed(
+    state: &AppState,
+    job_id: Uuid,
+    market_id: Uuid,
+    payload: &SettlementPayload,
+    tx_hash: TxHash,
+    block_number: u64,
+    confirmations: u64,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now().trunc_subsecs(6);
+    let mut tx = state.db.begin().await?;
+
+    // another pass got there first; it emitted the events
+    if !OutboxRepo::mark_confirmed(&mut *tx, job_id, block_number as i64).await? {
+        return Ok(());
     }
-}
\ No newline at end of file
+
+    let mut decided = None;
+    if let Some(s) = SettlementRepo::get(&mut *tx, market_id).await?
+        && s.status == "PROPOSED"
+        && hex::encode(settlement_leaf(
+            s.hash_algorithm(),
+            market_id,
+            s.outcome_e8,
+            s.decided_at,
+        )) == payload.leaf_hex
+        && SettlementRepo::finalize(&mut *tx, market_id, s.decided_at, now).await?
+    {
+        MarketRepo::mark_resolved(&mut *tx, market_id).await?;
+
+        audit::record(
+            &mut *tx,
+            AuditEntry::new("market", market_id, "resolved", "reconciler")
+                .transition(Some("PROPOSED"), Some("RESOLVED"))
+                .details(serde_json::json!({
+                    "revision": s.revision,
+                    "outbox_id": job_id,
+                    "tx_hash": format!("{:?}", tx_hash),
+                    "confirmations": confirmations,
+                })),
+        )
+        .await?;
+
+        decided = Some(Event::SettlementDecided {
+            market_id,
+            outcome: s.outcome,
+            decided_at: s.decided_at,
+        });
+    }
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("outbox", job_id, "confirmed", "reconciler")
+            .transition(Some("SENT"), Some("CONFIRMED"))
+            .details(serde_json::json!({
+                "tx_hash": format!("{:?}", tx_hash),
+                "block_number": block_number,
+                "confirmations": confirmations,
+            })),
+    )
+    .await?;
+
+    let confirmed = Event::TxConfirmed {
+        outbox_id: job_id,
+        market_id: market_id.to_string(),
+        tx_hash: format!("{:?}", tx_hash),
+    };
+    events::append(&mut *tx, &confirmed).await?;
+
+    if let Some(event) = &decided {
+        events::append(&mut *tx, event).await?;
+    }
+
+    tx.commit().await?;
+
+    if decided.is_some() {
+        state.cache.invalidate_markets();
+        state.cache.invalidate_settlement(market_id).await;
+        tracing::info!(
+            "Settlement of market {} final after {} confirmations",
+            market_id,
+            confirmations
+        );
+    }
+
+    Ok(())
+}
+
+/// The submission no longer exists anywhere the node can see. Its
+/// `chain_submissions` row goes (the audit entry keeps the hash) and the job
+/// is re-signed on the next worker pass.
+async fn requeue_dropped(
+    state: &AppState,
+    job_id: Uuid,
+    market_id: Uuid,
+    tx_hash: TxHash,
+) -> Result<(), sqlx::Error> {
+    tracing::warn!(
+        "outbox {} tx {:?} no longer on-chain, requeueing",
+        job_id,
+        tx_hash
+    );
+
+    let tx_hash = format!("{:?}", tx_hash);
+    let mut tx = state.db.begin().await?;
+
+    OutboxRepo::requeue(&mut *tx, job_id, "SENT").await?;
+    OutboxRepo::delete_submission(&mut *tx, &tx_hash).await?;
+
+    audit::record(
+        &mut *tx,
+        AuditEntry::new("outbox", job_id, "tx_dropped", "reconciler")
+            .transition(Some("SENT"), Some("PENDING"))
+            .details(serde_json::json!({ "tx_hash": tx_hash })),
+    )
+    .await?;
+
+    let event = Event::TxDropped {
+        outbox_id: job_id,
+        market_id: market_id.to_string(),
+        tx_hash,
+    };
+    events::append(&mut *tx, &event).await?;
+
+    tx.commit().await
+}